@@ -0,0 +1,145 @@
+use once_cell::sync::{Lazy, OnceCell};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::db;
+
+/// A pending write is flushed this long after it first became dirty, so rapid
+/// edits coalesce into at most one DB write per overlay per interval.
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(2);
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+// ---------------------------------------------------------------------------
+// Pending saves
+// ---------------------------------------------------------------------------
+
+struct PendingSave {
+    name: String,
+    config: String,
+    /// The `updated_at` the editor last loaded or saved; `None` saves blindly.
+    expected_updated_at: Option<String>,
+    dirty_since: Instant,
+}
+
+/// What a pending save came to, sent to the editor (as `autosaved`) so it can
+/// queue later edits against the new `updated_at`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum Autosaved {
+    Saved { id: String, updated_at: String },
+    /// Not written: the overlay was modified after the `updated_at` it was queued with.
+    Conflict { id: String, stored_updated_at: String },
+}
+
+static PENDING: Lazy<Mutex<HashMap<String, PendingSave>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Held from taking saves out of `PENDING` until they're written, and around
+/// explicit saves and profile switches, so a save taken by the background
+/// thread can't land after (or past) them.
+static WRITING: Mutex<()> = Mutex::new(());
+
+type Listener = Box<dyn Fn(&Autosaved) + Send + Sync>;
+
+static LISTENER: OnceCell<Listener> = OnceCell::new();
+
+/// Register the callback run after every autosave write or refused write.
+/// Only one listener is kept; later registrations are ignored.
+pub fn on_write(listener: impl Fn(&Autosaved) + Send + Sync + 'static) {
+    if LISTENER.set(Box::new(listener)).is_err() {
+        log::warn!("Autosave listener was already registered");
+    }
+}
+
+/// Queue (or replace) the pending autosave for an overlay.
+pub fn queue(id: &str, name: &str, config_json: String, expected_updated_at: Option<String>) {
+    let mut pending = PENDING.lock().unwrap();
+    let dirty_since = pending.get(id).map(|p| p.dirty_since).unwrap_or_else(Instant::now);
+    pending.insert(
+        id.to_string(),
+        PendingSave { name: name.to_string(), config: config_json, expected_updated_at, dirty_since },
+    );
+}
+
+/// Run an explicit save of overlay `id` in place of its pending autosave: the
+/// autosave is dropped, and one already being written lands first.
+pub fn save_instead<T>(id: &str, save: impl FnOnce() -> T) -> T {
+    let _writing = WRITING.lock().unwrap();
+    PENDING.lock().unwrap().remove(id);
+    save()
+}
+
+fn take_due(force: bool) -> Vec<(String, PendingSave)> {
+    let mut pending = PENDING.lock().unwrap();
+    let due: Vec<String> = pending
+        .iter()
+        .filter(|(_, p)| force || p.dirty_since.elapsed() >= AUTOSAVE_INTERVAL)
+        .map(|(id, _)| id.clone())
+        .collect();
+    due.into_iter().filter_map(|id| pending.remove_entry(&id)).collect()
+}
+
+/// Write taken saves; the caller holds `WRITING`.
+fn write(saves: Vec<(String, PendingSave)>) -> usize {
+    let mut written = 0;
+    for (id, save) in saves {
+        let result = match &save.expected_updated_at {
+            Some(expected) => db::update_overlay_if_unmodified(&id, &save.name, &save.config, expected),
+            None => db::upsert_overlay(&id, &save.name, &save.config).map(|updated_at| db::SaveOutcome::Saved { updated_at }),
+        };
+        let autosaved = match result {
+            Ok(db::SaveOutcome::Saved { updated_at }) => {
+                written += 1;
+                Autosaved::Saved { id, updated_at }
+            }
+            Ok(db::SaveOutcome::Conflict { stored_updated_at }) => {
+                log::warn!("Autosave of overlay {id} skipped: it was modified at {stored_updated_at}, after it was loaded");
+                Autosaved::Conflict { id, stored_updated_at }
+            }
+            Err(e) => {
+                log::error!("Autosave of overlay {id} failed: {e}");
+                // Put it back for the next pass unless a newer edit already replaced it
+                PENDING.lock().unwrap().entry(id).or_insert(save);
+                continue;
+            }
+        };
+        if let Some(listener) = LISTENER.get() {
+            listener(&autosaved);
+        }
+    }
+    written
+}
+
+/// Write an overlay's pending autosave now, if it has one, so a change applied
+/// to the stored config (a patch) builds on it instead of being overwritten by it.
+pub fn flush(id: &str) -> usize {
+    let _writing = WRITING.lock().unwrap();
+    let save = PENDING.lock().unwrap().remove_entry(id);
+    write(save.into_iter().collect())
+}
+
+/// Write every pending autosave immediately (used on app exit).
+pub fn flush_all() -> usize {
+    flush_all_then(|| ()).0
+}
+
+/// Write every pending autosave, then run `then` before any other can be
+/// written (switching profiles, so none lands in the new profile's database).
+pub fn flush_all_then<T>(then: impl FnOnce() -> T) -> (usize, T) {
+    let _writing = WRITING.lock().unwrap();
+    let flushed = write(take_due(true));
+    (flushed, then())
+}
+
+/// Start the background thread that flushes due autosaves.
+pub fn start() {
+    std::thread::Builder::new()
+        .name("autosave".into())
+        .spawn(|| loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let _writing = WRITING.lock().unwrap();
+            write(take_due(false));
+        })
+        .expect("Failed to start autosave thread");
+}
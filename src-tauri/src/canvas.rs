@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// ---------------------------------------------------------------------------
+// Workspace canvas: the output resolution a workspace is designed against,
+// plus optional title-safe margins (`canvasWidth`/`canvasHeight`/`safeArea`)
+// ---------------------------------------------------------------------------
+
+const DEFAULT_WIDTH: u64 = 1920;
+const DEFAULT_HEIGHT: u64 = 1080;
+/// Same bounds the editor puts on widget sizes (8K).
+const MAX_WIDTH: u64 = 7680;
+const MAX_HEIGHT: u64 = 4320;
+
+/// Margins in canvas pixels that titles and important content should stay inside.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SafeArea {
+    pub top: u64,
+    pub right: u64,
+    pub bottom: u64,
+    pub left: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct WorkspaceCanvas {
+    pub width: u64,
+    pub height: u64,
+    pub safe_area: Option<SafeArea>,
+}
+
+impl Default for WorkspaceCanvas {
+    fn default() -> Self {
+        Self { width: DEFAULT_WIDTH, height: DEFAULT_HEIGHT, safe_area: None }
+    }
+}
+
+fn dimension(config: &Value, key: &str, default: u64, max: u64) -> Result<u64, String> {
+    match config.get(key) {
+        None | Some(Value::Null) => Ok(default),
+        Some(v) => v
+            .as_u64()
+            .filter(|n| (1..=max).contains(n))
+            .ok_or_else(|| format!("{key} must be a whole number of pixels from 1 to {max}")),
+    }
+}
+
+impl WorkspaceCanvas {
+    /// Read the canvas from a workspace config; a missing size is 1920×1080.
+    pub fn from_config(config: &Value) -> Result<Self, String> {
+        let width = dimension(config, "canvasWidth", DEFAULT_WIDTH, MAX_WIDTH)?;
+        let height = dimension(config, "canvasHeight", DEFAULT_HEIGHT, MAX_HEIGHT)?;
+        let safe_area = match config.get("safeArea") {
+            None | Some(Value::Null) => None,
+            Some(v) => {
+                let area: SafeArea = serde_json::from_value(v.clone()).map_err(|_| {
+                    "safeArea must have whole-pixel top, right, bottom and left margins".to_string()
+                })?;
+                if area.left.saturating_add(area.right) >= width || area.top.saturating_add(area.bottom) >= height {
+                    return Err(format!("safeArea margins leave no room on the {width}×{height} canvas"));
+                }
+                Some(area)
+            }
+        };
+        Ok(Self { width, height, safe_area })
+    }
+}
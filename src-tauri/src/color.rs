@@ -0,0 +1,176 @@
+// ---------------------------------------------------------------------------
+// Widget background colours: `transparent`, hex (alpha included), rgb()/rgba()
+// or a basic CSS colour name, normalized to a plain `rgba(...)` string, and
+// the optional background image laid over them
+// ---------------------------------------------------------------------------
+
+const NAMED: &[(&str, [u8; 3])] = &[
+    ("black", [0, 0, 0]),
+    ("white", [255, 255, 255]),
+    ("gray", [128, 128, 128]),
+    ("grey", [128, 128, 128]),
+    ("silver", [192, 192, 192]),
+    ("red", [255, 0, 0]),
+    ("maroon", [128, 0, 0]),
+    ("orange", [255, 165, 0]),
+    ("yellow", [255, 255, 0]),
+    ("olive", [128, 128, 0]),
+    ("lime", [0, 255, 0]),
+    ("green", [0, 128, 0]),
+    ("aqua", [0, 255, 255]),
+    ("cyan", [0, 255, 255]),
+    ("teal", [0, 128, 128]),
+    ("blue", [0, 0, 255]),
+    ("navy", [0, 0, 128]),
+    ("fuchsia", [255, 0, 255]),
+    ("magenta", [255, 0, 255]),
+    ("purple", [128, 0, 128]),
+];
+
+fn rgba(rgb: [u8; 3], alpha: f64) -> String {
+    let alpha = (alpha.clamp(0.0, 1.0) * 1000.0).round() / 1000.0;
+    format!("rgba({}, {}, {}, {alpha})", rgb[0], rgb[1], rgb[2])
+}
+
+fn parse_hex(hex: &str) -> Option<String> {
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    // #rgb / #rgba expand each digit; #rrggbb / #rrggbbaa are pairs
+    let digits: Vec<u8> = match hex.len() {
+        3 | 4 => hex.chars().map(|c| u8::from_str_radix(&c.to_string().repeat(2), 16).ok()).collect::<Option<_>>()?,
+        6 | 8 => (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok()).collect::<Option<_>>()?,
+        _ => return None,
+    };
+    let alpha = digits.get(3).map_or(1.0, |a| f64::from(*a) / 255.0);
+    Some(rgba([digits[0], digits[1], digits[2]], alpha))
+}
+
+/// A channel (`0`–`255` or a percentage) or the alpha (`0`–`1` or a percentage).
+fn parse_component(part: &str, max: f64) -> Option<f64> {
+    let value = match part.strip_suffix('%') {
+        Some(pct) => pct.trim().parse::<f64>().ok()? / 100.0 * max,
+        None => part.parse::<f64>().ok()?,
+    };
+    (value.is_finite() && (0.0..=max).contains(&value)).then_some(value)
+}
+
+/// `rgb(1, 2, 3)`, `rgba(1, 2, 3, 0.5)` and the space syntax `rgb(1 2 3 / 50%)`.
+fn parse_rgb(args: &str) -> Option<String> {
+    let (channels, alpha) = match args.split_once('/') {
+        Some((channels, alpha)) => (channels, Some(alpha.trim())),
+        None => (args, None),
+    };
+    let mut parts: Vec<&str> = channels.split(|c: char| c == ',' || c.is_whitespace()).filter(|p| !p.is_empty()).collect();
+    let alpha = match (alpha, parts.len()) {
+        (Some(a), 3) => Some(a),
+        (None, 4) => parts.pop(),
+        (None, 3) => None,
+        _ => return None,
+    };
+    let mut rgb = [0u8; 3];
+    for (slot, part) in rgb.iter_mut().zip(&parts) {
+        *slot = parse_component(part, 255.0)?.round() as u8;
+    }
+    let alpha = match alpha {
+        Some(a) => parse_component(a, 1.0)?,
+        None => 1.0,
+    };
+    Some(rgba(rgb, alpha))
+}
+
+/// Parse a widget background into a normalized CSS colour, or explain why it
+/// isn't one.
+pub fn parse_background(value: &str) -> Result<String, String> {
+    let trimmed = value.trim();
+    let lower = trimmed.to_ascii_lowercase();
+    let parsed = if lower.is_empty() || lower == "transparent" {
+        Some("transparent".to_string())
+    } else if let Some(hex) = lower.strip_prefix('#') {
+        parse_hex(hex)
+    } else if let Some(args) = lower.strip_prefix("rgba(").or_else(|| lower.strip_prefix("rgb(")) {
+        args.strip_suffix(')').and_then(parse_rgb)
+    } else {
+        NAMED.iter().find(|(name, _)| *name == lower).map(|(_, rgb)| rgba(*rgb, 1.0))
+    };
+    parsed.ok_or_else(|| {
+        format!("'{trimmed}' is not a valid background; use transparent, #RRGGBB[AA], rgb()/rgba() or a basic colour name")
+    })
+}
+
+/// How a widget background image fills the page; `stretch` ignores its aspect ratio.
+const IMAGE_SIZES: &[(&str, &str)] = &[("cover", "cover"), ("contain", "contain"), ("stretch", "100% 100%"), ("auto", "auto")];
+const IMAGE_POSITIONS: &[&str] =
+    &["center", "top", "bottom", "left", "right", "top left", "top right", "bottom left", "bottom right"];
+
+/// A widget's `background`, ready for CSS: the colour, plus an image painted
+/// over it when the background is an object with a `src`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WidgetBackground {
+    pub color: String,
+    pub image: Option<BackgroundImage>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct BackgroundImage {
+    pub src: String,
+    /// CSS `background-size`.
+    pub size: String,
+    /// CSS `background-position`.
+    pub position: String,
+}
+
+/// Parse a widget `background`: a colour string as before, or
+/// `{ color?, src?, size?, position? }` where `size` is cover (the default),
+/// contain, stretch or auto and `position` a keyword like `top left`.
+pub fn parse_widget_background(value: &serde_json::Value) -> Result<WidgetBackground, String> {
+    let Some(obj) = value.as_object() else {
+        let color = parse_background(value.as_str().ok_or("background must be a colour string or an object")?)?;
+        return Ok(WidgetBackground { color, image: None });
+    };
+    let text = |key: &str| match obj.get(key) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(v) => v.as_str().map(|s| Some(s.trim())).ok_or_else(|| format!("background {key} must be a string")),
+    };
+    let color = parse_background(text("color")?.unwrap_or("transparent"))?;
+    let Some(src) = text("src")?.filter(|s| !s.is_empty()) else {
+        return Ok(WidgetBackground { color, image: None });
+    };
+    let size_name = text("size")?.unwrap_or("cover");
+    let size = IMAGE_SIZES.iter().find(|(name, _)| *name == size_name).map(|(_, css)| css.to_string()).ok_or_else(|| {
+        format!("'{size_name}' is not a valid background size; use cover, contain, stretch or auto")
+    })?;
+    let position = text("position")?.unwrap_or("center");
+    if !IMAGE_POSITIONS.contains(&position) {
+        return Err(format!("'{position}' is not a valid background position; use center, top, bottom left, etc."));
+    }
+    Ok(WidgetBackground {
+        color,
+        image: Some(BackgroundImage { src: src.to_string(), size, position: position.to_string() }),
+    })
+}
+
+/// WCAG relative luminance of an sRGB colour.
+fn luminance(rgb: [f64; 3]) -> f64 {
+    let linear = |c: f64| {
+        let c = c / 255.0;
+        if c <= 0.03928 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+    };
+    0.2126 * linear(rgb[0]) + 0.7152 * linear(rgb[1]) + 0.0722 * linear(rgb[2])
+}
+
+/// Black or white, whichever contrasts more with `background` (any colour
+/// [`parse_background`] takes). `None` when it isn't an opaque colour: what
+/// shows through a transparent or translucent one isn't known here.
+pub fn contrasting_text(background: &str) -> Option<&'static str> {
+    let normalized = parse_background(background).ok()?;
+    let args = normalized.strip_prefix("rgba(")?.strip_suffix(')')?;
+    let parts: Vec<f64> = args.split(',').map(|p| p.trim().parse().ok()).collect::<Option<_>>()?;
+    let [r, g, b, alpha] = parts[..] else { return None };
+    if alpha < 1.0 {
+        return None;
+    }
+    let l = luminance([r, g, b]);
+    // Contrast ratios against white (1.05 / (l + 0.05)) and black ((l + 0.05) / 0.05)
+    Some(if 1.05 / (l + 0.05) >= (l + 0.05) / 0.05 { "#ffffff" } else { "#000000" })
+}
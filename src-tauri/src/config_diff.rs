@@ -0,0 +1,214 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeSet, HashMap};
+
+// ---------------------------------------------------------------------------
+// Structured diff between two workspace configs: widgets and elements are
+// matched by id (elements across group nesting), and each change lists the
+// top-level properties that differ
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Change {
+    Added,
+    Removed,
+    Changed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ElementDiff {
+    pub id: String,
+    pub name: String,
+    pub change: Change,
+    /// Properties that differ; `parent` when the element moved in or out of a group.
+    /// Empty for added and removed elements.
+    pub properties: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WidgetDiff {
+    pub id: String,
+    pub name: String,
+    pub change: Change,
+    /// Widget-level properties that differ (its elements are listed separately).
+    pub properties: Vec<String>,
+    pub elements: Vec<ElementDiff>,
+}
+
+/// Counts for a one-line description ("3 elements moved, 1 text changed").
+/// An element can count towards several of moved/resized/text_changed.
+#[derive(Debug, Default, Serialize)]
+pub struct DiffSummary {
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+    pub moved: usize,
+    pub resized: usize,
+    pub text_changed: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigDiff {
+    /// Workspace-level properties that differ (`canvasWidth`, `safeArea`…).
+    pub properties: Vec<String>,
+    /// Only widgets that were added, removed or changed.
+    pub widgets: Vec<WidgetDiff>,
+    /// Element counts across all widgets.
+    pub summary: DiffSummary,
+}
+
+const MOVE_KEYS: &[&str] = &["x", "y", "parent"];
+const SIZE_KEYS: &[&str] = &["width", "height"];
+const TEXT_KEYS: &[&str] = &["content", "segments"];
+
+fn str_field<'a>(value: &'a Value, key: &str) -> &'a str {
+    value.get(key).and_then(|v| v.as_str()).unwrap_or("")
+}
+
+/// Keys whose values differ, skipping `skip` (nested lists diffed on their own).
+fn changed_properties(from: &Value, to: &Value, skip: &str) -> Vec<String> {
+    let empty = Map::new();
+    let from = from.as_object().unwrap_or(&empty);
+    let to = to.as_object().unwrap_or(&empty);
+    let keys: BTreeSet<&String> = from.keys().chain(to.keys()).collect();
+    keys.into_iter().filter(|key| key.as_str() != skip && from.get(*key) != to.get(*key)).cloned().collect()
+}
+
+/// Every element of a widget by id along with its parent group's id, in tree order.
+fn flatten_elements<'a>(
+    elements: &'a Value,
+    parent: Option<&'a str>,
+    out: &mut Vec<(&'a str, Option<&'a str>, &'a Value)>,
+) {
+    for element in elements.as_array().into_iter().flatten() {
+        let id = str_field(element, "id");
+        out.push((id, parent, element));
+        if let Some(children) = element.get("children") {
+            flatten_elements(children, Some(id), out);
+        }
+    }
+}
+
+fn diff_elements(from: &Value, to: &Value) -> Vec<ElementDiff> {
+    let (mut old, mut new) = (Vec::new(), Vec::new());
+    flatten_elements(from.get("elements").unwrap_or(&Value::Null), None, &mut old);
+    flatten_elements(to.get("elements").unwrap_or(&Value::Null), None, &mut new);
+    let old_by_id: HashMap<&str, (Option<&str>, &Value)> = old.iter().map(|(id, p, e)| (*id, (*p, *e))).collect();
+    let new_by_id: HashMap<&str, (Option<&str>, &Value)> = new.iter().map(|(id, p, e)| (*id, (*p, *e))).collect();
+
+    let mut diffs = Vec::new();
+    for (id, parent, element) in &new {
+        let change = match old_by_id.get(id) {
+            None => ElementDiff {
+                id: id.to_string(),
+                name: str_field(element, "name").to_string(),
+                change: Change::Added,
+                properties: Vec::new(),
+            },
+            Some((old_parent, old_element)) => {
+                let mut properties = changed_properties(old_element, element, "children");
+                if old_parent != parent {
+                    properties.push("parent".to_string());
+                }
+                if properties.is_empty() {
+                    continue;
+                }
+                ElementDiff {
+                    id: id.to_string(),
+                    name: str_field(element, "name").to_string(),
+                    change: Change::Changed,
+                    properties,
+                }
+            }
+        };
+        diffs.push(change);
+    }
+    for (id, _, element) in &old {
+        if !new_by_id.contains_key(id) {
+            diffs.push(ElementDiff {
+                id: id.to_string(),
+                name: str_field(element, "name").to_string(),
+                change: Change::Removed,
+                properties: Vec::new(),
+            });
+        }
+    }
+    diffs
+}
+
+fn widget_list(config: &Value) -> Vec<&Value> {
+    config.get("widgets").and_then(|w| w.as_array()).into_iter().flatten().collect()
+}
+
+/// Elements of a widget that only exists on one side, all marked `change`.
+fn whole_widget(widget: &Value, change: Change) -> WidgetDiff {
+    let mut elements = Vec::new();
+    flatten_elements(widget.get("elements").unwrap_or(&Value::Null), None, &mut elements);
+    WidgetDiff {
+        id: str_field(widget, "id").to_string(),
+        name: str_field(widget, "name").to_string(),
+        change,
+        properties: Vec::new(),
+        elements: elements
+            .into_iter()
+            .map(|(id, _, e)| ElementDiff {
+                id: id.to_string(),
+                name: str_field(e, "name").to_string(),
+                change,
+                properties: Vec::new(),
+            })
+            .collect(),
+    }
+}
+
+/// Diff `from` → `to`. A changed widget is listed under its new name.
+pub fn diff(from: &Value, to: &Value) -> ConfigDiff {
+    let old = widget_list(from);
+    let new = widget_list(to);
+    let old_by_id: HashMap<&str, &Value> = old.iter().map(|w| (str_field(w, "id"), *w)).collect();
+    let new_ids: BTreeSet<&str> = new.iter().map(|w| str_field(w, "id")).collect();
+
+    let mut widgets = Vec::new();
+    for widget in &new {
+        let id = str_field(widget, "id");
+        match old_by_id.get(id) {
+            None => widgets.push(whole_widget(widget, Change::Added)),
+            Some(old_widget) => {
+                let properties = changed_properties(old_widget, widget, "elements");
+                let elements = diff_elements(old_widget, widget);
+                if !properties.is_empty() || !elements.is_empty() {
+                    widgets.push(WidgetDiff {
+                        id: id.to_string(),
+                        name: str_field(widget, "name").to_string(),
+                        change: Change::Changed,
+                        properties,
+                        elements,
+                    });
+                }
+            }
+        }
+    }
+    for widget in &old {
+        if !new_ids.contains(str_field(widget, "id")) {
+            widgets.push(whole_widget(widget, Change::Removed));
+        }
+    }
+
+    let mut summary = DiffSummary::default();
+    for element in widgets.iter().flat_map(|w| &w.elements) {
+        let touches = |keys: &[&str]| element.properties.iter().any(|p| keys.contains(&p.as_str()));
+        match element.change {
+            Change::Added => summary.added += 1,
+            Change::Removed => summary.removed += 1,
+            Change::Changed => {
+                summary.changed += 1;
+                summary.moved += touches(MOVE_KEYS) as usize;
+                summary.resized += touches(SIZE_KEYS) as usize;
+                summary.text_changed += touches(TEXT_KEYS) as usize;
+            }
+        }
+    }
+
+    ConfigDiff { properties: changed_properties(from, to, "widgets"), widgets, summary }
+}
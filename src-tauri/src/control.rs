@@ -0,0 +1,227 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+
+// ---------------------------------------------------------------------------
+// Control channel: server -> widget page messages (triggers etc.)
+// ---------------------------------------------------------------------------
+
+const CHANNEL_CAPACITY: usize = 256;
+/// Shared start times are set this far ahead so every page has the trigger before it's due.
+const START_LEAD_MS: u64 = 150;
+
+#[derive(Debug, Clone)]
+pub struct ControlMessage {
+    pub widget_id: String,
+    /// JSON object sent verbatim to the page, e.g. `{"action":"trigger"}`.
+    pub payload: Value,
+}
+
+static CHANNEL: Lazy<broadcast::Sender<ControlMessage>> =
+    Lazy::new(|| broadcast::channel(CHANNEL_CAPACITY).0);
+
+/// Open control connections per widget id.
+static CONNECTIONS: Lazy<Mutex<HashMap<String, usize>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Event a widget page publishes for integrators, e.g. `{"event":"time","t":1.2}`.
+#[derive(Debug, Clone)]
+pub struct PageEvent {
+    pub widget_id: String,
+    pub payload: Value,
+}
+
+static EVENTS: Lazy<broadcast::Sender<PageEvent>> = Lazy::new(|| broadcast::channel(CHANNEL_CAPACITY).0);
+
+/// Open integrator connections listening to page events, per widget id.
+static LISTENERS: Lazy<Mutex<HashMap<String, usize>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+static NEXT_CONNECTION: AtomicU64 = AtomicU64::new(1);
+
+/// Alert queue length last reported by each open page: connection -> (widget id, length).
+static QUEUES: Lazy<Mutex<HashMap<u64, (String, usize)>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn subscribe() -> broadcast::Receiver<ControlMessage> {
+    CHANNEL.subscribe()
+}
+
+/// Send a message to every open page of a widget. Returns how many pages are
+/// connected to receive it.
+pub fn send(widget_id: &str, payload: Value) -> usize {
+    let _ = CHANNEL.send(ControlMessage { widget_id: widget_id.to_string(), payload });
+    connection_count(widget_id)
+}
+
+/// Unix time in milliseconds; shared start times (`at`) are on this clock.
+pub fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+/// Start time for a trigger sent now. Give several widgets the same one to
+/// play them in lockstep.
+pub fn start_epoch() -> u64 {
+    now_ms() + START_LEAD_MS
+}
+
+/// Tells a page the server's clock so it can convert `at` to its own.
+pub fn clock_message() -> Value {
+    serde_json::json!({ "action": "clock", "now": now_ms() })
+}
+
+/// Restart a widget's timeline on every open page. `payload` must be a JSON
+/// object; its fields are forwarded alongside `"action": "trigger"`, with a
+/// fresh start time as `at` unless it already has one.
+pub fn trigger(widget_id: &str, mut payload: Value) -> usize {
+    payload["action"] = Value::from("trigger");
+    if !payload.get("at").is_some_and(Value::is_u64) {
+        payload["at"] = Value::from(start_epoch());
+    }
+    send(widget_id, payload)
+}
+
+/// Record a new page connection; returns its id for later reports.
+pub fn connection_opened(widget_id: &str) -> u64 {
+    *CONNECTIONS.lock().unwrap().entry(widget_id.to_string()).or_default() += 1;
+    let conn = NEXT_CONNECTION.fetch_add(1, Ordering::Relaxed);
+    QUEUES.lock().unwrap().insert(conn, (widget_id.to_string(), 0));
+    conn
+}
+
+pub fn connection_closed(widget_id: &str, conn: u64) {
+    QUEUES.lock().unwrap().remove(&conn);
+    let mut conns = CONNECTIONS.lock().unwrap();
+    if let Some(n) = conns.get_mut(widget_id) {
+        *n = n.saturating_sub(1);
+        if *n == 0 {
+            conns.remove(widget_id);
+        }
+    }
+}
+
+pub fn connection_count(widget_id: &str) -> usize {
+    CONNECTIONS.lock().unwrap().get(widget_id).copied().unwrap_or(0)
+}
+
+pub fn total_connections() -> usize {
+    CONNECTIONS.lock().unwrap().values().sum()
+}
+
+pub fn set_queue_length(conn: u64, length: usize) {
+    if let Some(entry) = QUEUES.lock().unwrap().get_mut(&conn) {
+        entry.1 = length;
+    }
+}
+
+/// Longest alert queue among a widget's open pages (as last reported by them).
+pub fn queue_length(widget_id: &str) -> usize {
+    QUEUES.lock().unwrap().values().filter(|(id, _)| id == widget_id).map(|(_, n)| *n).max().unwrap_or(0)
+}
+
+// ---------------------------------------------------------------------------
+// Page events: widget page -> integrators (timeline time etc.)
+// ---------------------------------------------------------------------------
+
+pub fn subscribe_events() -> broadcast::Receiver<PageEvent> {
+    EVENTS.subscribe()
+}
+
+pub fn publish_event(widget_id: &str, payload: Value) {
+    let _ = EVENTS.send(PageEvent { widget_id: widget_id.to_string(), payload });
+}
+
+/// Message telling pages to play their exit animations and hide.
+pub fn hide_message() -> Value {
+    serde_json::json!({ "action": "hide" })
+}
+
+/// Message telling pages whether to emit time events.
+pub fn time_updates_message(enabled: bool) -> Value {
+    serde_json::json!({ "action": "time_updates", "enabled": enabled })
+}
+
+/// Register a listener; the first one for a widget switches its pages' time events on.
+pub fn listener_opened(widget_id: &str) {
+    let mut listeners = LISTENERS.lock().unwrap();
+    let n = listeners.entry(widget_id.to_string()).or_default();
+    *n += 1;
+    if *n == 1 {
+        send(widget_id, time_updates_message(true));
+    }
+}
+
+/// Unregister a listener; when the last one leaves, pages stop emitting.
+pub fn listener_closed(widget_id: &str) {
+    let mut listeners = LISTENERS.lock().unwrap();
+    if let Some(n) = listeners.get_mut(widget_id) {
+        *n = n.saturating_sub(1);
+        if *n == 0 {
+            listeners.remove(widget_id);
+            send(widget_id, time_updates_message(false));
+        }
+    }
+}
+
+pub fn listener_count(widget_id: &str) -> usize {
+    LISTENERS.lock().unwrap().get(widget_id).copied().unwrap_or(0)
+}
+
+// ---------------------------------------------------------------------------
+// Script errors reported by widget pages
+// ---------------------------------------------------------------------------
+
+/// Errors kept per widget; older ones are dropped first.
+const ERRORS_PER_WIDGET: usize = 20;
+/// Longest message/stack kept, in characters.
+const ERROR_TEXT_MAX: usize = 4000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageError {
+    pub message: String,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub line: Option<u32>,
+    #[serde(default)]
+    pub column: Option<u32>,
+    #[serde(default)]
+    pub stack: Option<String>,
+    /// Unix time in milliseconds the error was received.
+    #[serde(default)]
+    pub at: u64,
+}
+
+static ERRORS: Lazy<Mutex<HashMap<String, VecDeque<PageError>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn truncate(text: &mut String) {
+    if let Some((i, _)) = text.char_indices().nth(ERROR_TEXT_MAX) {
+        text.truncate(i);
+    }
+}
+
+pub fn record_error(widget_id: &str, mut error: PageError) {
+    truncate(&mut error.message);
+    if let Some(stack) = error.stack.as_mut() {
+        truncate(stack);
+    }
+    error.at = now_ms();
+    log::warn!("Widget {widget_id} error: {}", error.message);
+    let mut errors = ERRORS.lock().unwrap();
+    let list = errors.entry(widget_id.to_string()).or_default();
+    if list.len() >= ERRORS_PER_WIDGET {
+        list.pop_front();
+    }
+    list.push_back(error);
+}
+
+/// Errors reported for a widget, oldest first.
+pub fn errors(widget_id: &str) -> Vec<PageError> {
+    ERRORS.lock().unwrap().get(widget_id).map(|list| list.iter().cloned().collect()).unwrap_or_default()
+}
+
+pub fn clear_errors(widget_id: &str) {
+    ERRORS.lock().unwrap().remove(widget_id);
+}
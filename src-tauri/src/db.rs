@@ -1,7 +1,14 @@
-use rusqlite::{Connection, Result, params};
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, ErrorCode, OpenFlags, OptionalExtension, Result, params};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
-use once_cell::sync::Lazy;
+use std::time::Duration;
+use once_cell::sync::{Lazy, OnceCell};
+use tokio::sync::broadcast;
+
+use crate::error::CommandError;
 
 // ---------------------------------------------------------------------------
 // Types
@@ -11,6 +18,7 @@ use once_cell::sync::Lazy;
 pub struct OverlaySummary {
     pub id: String,
     pub name: String,
+    /// UTC, ISO-8601 with milliseconds (`2024-05-01T18:30:00.123Z`).
     pub updated_at: String,
 }
 
@@ -19,49 +27,417 @@ pub struct OverlayRow {
     pub id: String,
     pub name: String,
     pub config: String, // raw JSON string of OverlayConfig
+    /// UTC, ISO-8601 with milliseconds (`2024-05-01T18:30:00.123Z`).
     pub updated_at: String,
 }
 
+/// Result of a save that checks the client's last-seen `updated_at`.
+#[derive(Debug, Clone)]
+pub enum SaveOutcome {
+    Saved { updated_at: String },
+    /// The stored row was modified after the client loaded it.
+    Conflict { stored_updated_at: String },
+}
+
+/// An overlay row whose config can't be loaded.
+#[derive(Debug, Serialize, Clone)]
+pub struct CorruptRow {
+    pub id: String,
+    pub name: String,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct IntegrityReport {
+    /// True when SQLite reports no problems and every config parses.
+    pub ok: bool,
+    /// `PRAGMA integrity_check` output (`["ok"]` when the file is healthy).
+    pub integrity: Vec<String>,
+    pub overlays_checked: usize,
+    pub corrupt_rows: Vec<CorruptRow>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RepairReport {
+    /// Rows moved from `overlays` to `overlays_quarantine`.
+    pub quarantined: Vec<CorruptRow>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CompactReport {
+    pub before_bytes: u64,
+    pub after_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Created,
+    Updated,
+    Deleted,
+    /// Only elements' `srcNonce`s were bumped; the `updated_at` is unchanged.
+    Refreshed,
+}
+
+/// Payload of the `overlay-changed` event: one overlay row actually changed.
+#[derive(Debug, Serialize, Clone)]
+pub struct OverlayChange {
+    pub id: String,
+    pub kind: ChangeKind,
+    /// The row's new timestamp (`None` for deletions).
+    pub updated_at: Option<String>,
+}
+
+/// A separate overlay library with its own database file.
+#[derive(Debug, Serialize, Clone)]
+pub struct ProfileInfo {
+    pub name: String,
+    pub active: bool,
+    pub path: String,
+}
+
 // ---------------------------------------------------------------------------
 // Global DB connection (Mutex-protected)
 // ---------------------------------------------------------------------------
 
+const DB_FILE_NAME: &str = "overlays.db";
+
+/// Resolved by `init_db_path` during app setup, before `DB` is first touched.
+/// This is the default profile's database; other profiles live beside it.
+static DB_PATH: OnceCell<PathBuf> = OnceCell::new();
+
+/// Connection opened by `open` at startup, handed to `DB` on first use.
+static OPENED: Mutex<Option<Connection>> = Mutex::new(None);
+
 pub static DB: Lazy<Mutex<Connection>> = Lazy::new(|| {
-    let conn = open_or_create_db().expect("Failed to open database");
+    let opened = OPENED.lock().unwrap().take();
+    let conn = opened.map_or_else(|| open_db(&get_db_path()), Ok).expect("Failed to open database");
     Mutex::new(conn)
 });
 
-fn get_db_path() -> std::path::PathBuf {
-    // Store the database in the user's local app data directory
-    let mut path = dirs_path();
-    path.push("overlays.db");
-    path
+/// Open the active profile's database. Called once during setup, before
+/// anything touches `DB`, so a failure can be explained instead of panicking.
+pub fn open() -> std::result::Result<(), String> {
+    let path = get_db_path();
+    let conn = open_db(&path).map_err(|e| {
+        if is_busy(&e) {
+            format!(
+                "Another program is holding the database open ({}). Close any other copy of Open Overlay, \
+                 or wait for your backup or antivirus tool to finish with the file, then start the app again.",
+                path.display()
+            )
+        } else {
+            format!("Could not open the database at {}: {e}", path.display())
+        }
+    })?;
+    *OPENED.lock().unwrap() = Some(conn);
+    Lazy::force(&DB);
+    Ok(())
+}
+
+/// Whether `e` means another connection or process holds a lock on the file.
+pub fn is_busy(e: &rusqlite::Error) -> bool {
+    matches!(e.sqlite_error_code(), Some(ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked))
+}
+
+fn get_db_path() -> PathBuf {
+    profile_db_path(&ACTIVE_PROFILE.lock().unwrap())
 }
 
-fn dirs_path() -> std::path::PathBuf {
-    // Use the same dir as the executable for portability
+fn default_db_path() -> PathBuf {
+    DB_PATH.get().cloned().unwrap_or_else(|| exe_dir().join(DB_FILE_NAME))
+}
+
+fn exe_dir() -> PathBuf {
+    // Directory of the executable, used for portable installs
     if let Ok(exe) = std::env::current_exe() {
         if let Some(parent) = exe.parent() {
             return parent.to_path_buf();
         }
     }
-    std::path::PathBuf::from(".")
+    PathBuf::from(".")
 }
 
-fn open_or_create_db() -> Result<Connection> {
-    let path = get_db_path();
-    let conn = Connection::open(&path)?;
+/// Make sure the database file at `path` can be created and written.
+fn ensure_writable(path: &Path) -> std::result::Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Cannot create database directory '{}': {e}", parent.display()))?;
+    }
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map(|_| ())
+        .map_err(|e| format!("Database location '{}' is not writable: {e}", path.display()))
+}
+
+/// Decide where `overlays.db` lives. In order of preference:
+/// 1. the `OPEN_OVERLAY_DB` environment variable (a file path),
+/// 2. the platform app-data directory (`app_data_dir`, from Tauri's path API),
+/// 3. the executable's directory, as a last resort for portable installs.
+///
+/// A library left next to the executable by older versions is copied into the
+/// app-data directory the first time it is used.
+pub fn init_db_path(app_data_dir: Option<PathBuf>) -> std::result::Result<PathBuf, String> {
+    let legacy = exe_dir().join(DB_FILE_NAME);
+
+    let path = if let Some(env_path) = std::env::var_os("OPEN_OVERLAY_DB") {
+        // An explicit location is never second-guessed
+        let path = PathBuf::from(env_path);
+        ensure_writable(&path)?;
+        path
+    } else {
+        match app_data_dir.map(|dir| dir.join(DB_FILE_NAME)) {
+            Some(path) => {
+                if !path.exists() && legacy.exists() {
+                    if let Some(parent) = path.parent() {
+                        let _ = std::fs::create_dir_all(parent);
+                    }
+                    match std::fs::copy(&legacy, &path) {
+                        Ok(_) => log::info!("Migrated database from {} to {}", legacy.display(), path.display()),
+                        Err(e) => log::warn!("Could not migrate database from {}: {e}", legacy.display()),
+                    }
+                }
+                match ensure_writable(&path) {
+                    Ok(()) => path,
+                    Err(e) => {
+                        log::warn!("{e}; falling back to the executable directory");
+                        ensure_writable(&legacy)?;
+                        legacy
+                    }
+                }
+            }
+            None => {
+                ensure_writable(&legacy)?;
+                legacy
+            }
+        }
+    };
+
+    DB_PATH
+        .set(path)
+        .map_err(|_| "Database path was already initialized".to_string())?;
+    load_active_profile();
+    Ok(get_db_path())
+}
+
+/// How long a statement waits on a lock held elsewhere (antivirus, a backup
+/// tool, a second copy of the app) before failing as busy.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(3);
+/// Opening a locked database is tried this many times, a second apart.
+const OPEN_ATTEMPTS: u32 = 3;
+
+fn open_db(path: &Path) -> Result<Connection> {
+    let mut attempt = 1;
+    loop {
+        match try_open_db(path) {
+            Err(e) if is_busy(&e) && attempt < OPEN_ATTEMPTS => {
+                log::warn!("Database {} is locked (attempt {attempt} of {OPEN_ATTEMPTS}); retrying", path.display());
+                std::thread::sleep(Duration::from_secs(1));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn try_open_db(path: &Path) -> Result<Connection> {
+    let conn = Connection::open(path)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
     conn.execute_batch(
         "CREATE TABLE IF NOT EXISTS overlays (
             id          TEXT PRIMARY KEY,
             name        TEXT NOT NULL,
             config      TEXT NOT NULL,
-            updated_at  DATETIME DEFAULT CURRENT_TIMESTAMP
-         );",
+            updated_at  DATETIME DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+         );
+         CREATE TABLE IF NOT EXISTS settings (
+            key    TEXT PRIMARY KEY,
+            value  TEXT NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS overlays_quarantine (
+            id              TEXT,
+            name            TEXT,
+            config          BLOB,
+            updated_at      DATETIME,
+            error           TEXT NOT NULL,
+            quarantined_at  DATETIME DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+         );
+         -- Rows written before timestamps were ISO-8601 say `YYYY-MM-DD HH:MM:SS` (also UTC),
+         -- and before they had milliseconds `YYYY-MM-DDTHH:MM:SSZ`
+         UPDATE overlays SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', updated_at)
+          WHERE updated_at NOT LIKE '%.___Z' AND strftime('%Y-%m-%dT%H:%M:%fZ', updated_at) IS NOT NULL;
+         UPDATE overlays_quarantine SET
+           updated_at = coalesce(strftime('%Y-%m-%dT%H:%M:%SZ', updated_at), updated_at),
+           quarantined_at = coalesce(strftime('%Y-%m-%dT%H:%M:%SZ', quarantined_at), quarantined_at)
+          WHERE updated_at NOT LIKE '%Z' OR quarantined_at NOT LIKE '%Z';",
     )?;
     Ok(conn)
 }
 
+// ---------------------------------------------------------------------------
+// Profiles: `profiles/<name>/overlays.db` next to the default database, with
+// the active one's name kept in `profiles/active_profile`
+// ---------------------------------------------------------------------------
+
+pub const DEFAULT_PROFILE: &str = "default";
+const PROFILES_DIR: &str = "profiles";
+const ACTIVE_PROFILE_FILE: &str = "active_profile";
+const PROFILE_NAME_MAX_LEN: usize = 64;
+
+static ACTIVE_PROFILE: Lazy<Mutex<String>> = Lazy::new(|| Mutex::new(DEFAULT_PROFILE.to_string()));
+
+fn profiles_dir() -> PathBuf {
+    let default = default_db_path();
+    default.parent().unwrap_or(Path::new(".")).join(PROFILES_DIR)
+}
+
+fn profile_db_path(name: &str) -> PathBuf {
+    if name == DEFAULT_PROFILE {
+        default_db_path()
+    } else {
+        profiles_dir().join(name).join(DB_FILE_NAME)
+    }
+}
+
+/// Profile names double as directory names, so keep them portable.
+fn validate_profile_name(name: &str) -> std::result::Result<(), String> {
+    if name.is_empty() || name.len() > PROFILE_NAME_MAX_LEN {
+        return Err(format!("Profile names must be 1-{PROFILE_NAME_MAX_LEN} characters"));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("'{name}' is not a valid profile name; use letters, digits, '-' and '_'"));
+    }
+    Ok(())
+}
+
+fn profile_names() -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(profiles_dir())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().join(DB_FILE_NAME).is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name != DEFAULT_PROFILE && validate_profile_name(name).is_ok())
+        .collect();
+    names.sort();
+    names.insert(0, DEFAULT_PROFILE.to_string());
+    names
+}
+
+/// Pick up the profile that was active when the app last closed; one whose
+/// database has gone missing falls back to the default.
+fn load_active_profile() {
+    let Ok(saved) = std::fs::read_to_string(profiles_dir().join(ACTIVE_PROFILE_FILE)) else {
+        return;
+    };
+    let name = saved.trim();
+    if profile_names().iter().any(|n| n == name) {
+        *ACTIVE_PROFILE.lock().unwrap() = name.to_string();
+    } else {
+        log::warn!("Active profile '{name}' no longer exists; using the default profile");
+    }
+}
+
+pub fn active_profile() -> String {
+    ACTIVE_PROFILE.lock().unwrap().clone()
+}
+
+pub fn list_profiles() -> Vec<ProfileInfo> {
+    let active = active_profile();
+    profile_names()
+        .into_iter()
+        .map(|name| ProfileInfo {
+            active: name == active,
+            path: profile_db_path(&name).to_string_lossy().into_owned(),
+            name,
+        })
+        .collect()
+}
+
+/// Create an empty profile. Names are compared case-insensitively so two
+/// profiles can't collide on a case-insensitive filesystem.
+pub fn create_profile(name: &str) -> std::result::Result<ProfileInfo, CommandError> {
+    let name = name.trim();
+    validate_profile_name(name)?;
+    if profile_names().iter().any(|n| n.eq_ignore_ascii_case(name)) {
+        return Err(CommandError::Conflict { message: format!("a profile named '{name}' already exists") });
+    }
+    let path = profile_db_path(name);
+    ensure_writable(&path)?;
+    open_db(&path).map_err(|e| format!("Cannot create profile '{name}': {e}"))?;
+    Ok(ProfileInfo { name: name.to_string(), active: false, path: path.to_string_lossy().into_owned() })
+}
+
+/// Point `DB` at another profile's database. The new file is opened before
+/// the swap, so a failure leaves the current profile in place; the swap
+/// itself happens under the `DB` lock, between statements.
+pub fn switch_profile(name: &str) -> std::result::Result<ProfileInfo, CommandError> {
+    if !profile_names().iter().any(|n| n == name) {
+        return Err(CommandError::not_found(format!("Profile '{name}' does not exist")));
+    }
+    let path = profile_db_path(name);
+    let conn = open_db(&path).map_err(|e| format!("Cannot open profile '{name}': {e}"))?;
+    {
+        let mut db = DB.lock().unwrap();
+        *db = conn;
+        *ACTIVE_PROFILE.lock().unwrap() = name.to_string();
+    }
+
+    let marker = profiles_dir().join(ACTIVE_PROFILE_FILE);
+    let saved = if name == DEFAULT_PROFILE {
+        match std::fs::remove_file(&marker) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    } else {
+        std::fs::create_dir_all(profiles_dir()).and_then(|_| std::fs::write(&marker, name))
+    };
+    if let Err(e) = saved {
+        log::warn!("Could not remember the active profile: {e}");
+    }
+    log::info!("Switched to profile '{name}' ({})", path.display());
+    Ok(ProfileInfo { name: name.to_string(), active: true, path: path.to_string_lossy().into_owned() })
+}
+
+// ---------------------------------------------------------------------------
+// Change notifications (the app forwards them to the UI as `overlay-changed`,
+// the OBS server to `/ws/events` subscribers)
+// ---------------------------------------------------------------------------
+
+type ChangeListener = Box<dyn Fn(&OverlayChange) + Send + Sync>;
+
+static CHANGE_LISTENER: OnceCell<ChangeListener> = OnceCell::new();
+
+/// Changes nobody has received yet are dropped past this many; a subscriber
+/// that falls that far behind is told to resync.
+const CHANGE_CHANNEL_CAPACITY: usize = 256;
+
+static CHANGES: Lazy<broadcast::Sender<OverlayChange>> = Lazy::new(|| broadcast::channel(CHANGE_CHANNEL_CAPACITY).0);
+
+/// Every overlay change from now on, for any number of subscribers.
+pub fn subscribe_changes() -> broadcast::Receiver<OverlayChange> {
+    CHANGES.subscribe()
+}
+
+/// Register the callback run after every write that changed an overlay row.
+/// Only one listener is kept; later registrations are ignored.
+pub fn on_change(listener: impl Fn(&OverlayChange) + Send + Sync + 'static) {
+    if CHANGE_LISTENER.set(Box::new(listener)).is_err() {
+        log::warn!("Overlay change listener was already registered");
+    }
+}
+
+/// Call once the `DB` lock is released, so a listener may read the database.
+fn notify(id: &str, kind: ChangeKind, updated_at: Option<String>) {
+    let change = OverlayChange { id: id.to_string(), kind, updated_at };
+    if let Some(listener) = CHANGE_LISTENER.get() {
+        listener(&change);
+    }
+    let _ = CHANGES.send(change);
+}
+
 // ---------------------------------------------------------------------------
 // CRUD helpers
 // ---------------------------------------------------------------------------
@@ -69,7 +445,7 @@ fn open_or_create_db() -> Result<Connection> {
 pub fn list_overlays() -> Result<Vec<OverlaySummary>> {
     let conn = DB.lock().unwrap();
     let mut stmt = conn.prepare(
-        "SELECT id, name, updated_at FROM overlays ORDER BY updated_at DESC",
+        "SELECT id, name, updated_at FROM overlays ORDER BY updated_at DESC, rowid DESC",
     )?;
     let rows = stmt.query_map([], |row| {
         Ok(OverlaySummary {
@@ -100,21 +476,429 @@ pub fn get_overlay(id: &str) -> Result<Option<OverlayRow>> {
     }
 }
 
-pub fn upsert_overlay(id: &str, name: &str, config_json: &str) -> Result<()> {
+pub fn upsert_overlay(id: &str, name: &str, config_json: &str) -> Result<String> {
+    let conn = DB.lock().unwrap();
+    let (updated_at, change) = upsert_locked(&conn, id, name, config_json)?;
+    drop(conn);
+    if let Some(kind) = change {
+        notify(id, kind, Some(updated_at.clone()));
+    }
+    Ok(updated_at)
+}
+
+/// Upsert on an already-locked connection, returning the `updated_at` and what
+/// changed. Elements keep a stored `srcNonce` newer than the incoming one.
+/// Writing the stored name and config again is a no-op that keeps the old
+/// timestamp. A change always gets a later timestamp than the stored one,
+/// a millisecond later if the clock hasn't moved on, so a timestamp names
+/// exactly one version of an overlay.
+fn upsert_locked(
+    conn: &Connection,
+    id: &str,
+    name: &str,
+    config_json: &str,
+) -> Result<(String, Option<ChangeKind>)> {
+    let stored: Option<(String, String)> = conn
+        .query_row("SELECT updated_at, config FROM overlays WHERE id = ?1", params![id], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .optional()?;
+    let kept = stored.as_ref().and_then(|(_, config)| crate::src_refresh::keep_nonces(config, config_json));
+    let config_json = kept.as_deref().unwrap_or(config_json);
+    let stored = stored.map(|(updated_at, _)| updated_at);
+    let stamp: String = conn.query_row(
+        "SELECT CASE WHEN ?1 >= now THEN strftime('%Y-%m-%dT%H:%M:%fZ', ?1, '+0.001 seconds') ELSE now END
+         FROM (SELECT strftime('%Y-%m-%dT%H:%M:%fZ', 'now') AS now)",
+        params![stored],
+        |row| row.get(0),
+    )?;
+    let written: Option<String> = conn
+        .query_row(
+            "INSERT INTO overlays (id, name, config, updated_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(id) DO UPDATE SET
+               name       = excluded.name,
+               config     = excluded.config,
+               updated_at = excluded.updated_at
+             WHERE overlays.name IS NOT excluded.name OR overlays.config IS NOT excluded.config
+             RETURNING updated_at",
+            params![id, name, config_json, stamp],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(match (written, stored) {
+        (Some(updated_at), None) => (updated_at, Some(ChangeKind::Created)),
+        (Some(updated_at), Some(_)) => (updated_at, Some(ChangeKind::Updated)),
+        (None, Some(stored)) => (stored, None),
+        (None, None) => return Err(rusqlite::Error::QueryReturnedNoRows),
+    })
+}
+
+/// Save an overlay unless it was modified since `expected_updated_at` (the
+/// timestamp the client loaded). An overlay that doesn't exist yet is inserted.
+pub fn update_overlay_if_unmodified(
+    id: &str,
+    name: &str,
+    config_json: &str,
+    expected_updated_at: &str,
+) -> Result<SaveOutcome> {
+    let conn = DB.lock().unwrap();
+    let (outcome, change) = save_if_unmodified_locked(&conn, id, name, config_json, expected_updated_at)?;
+    drop(conn);
+    if let (Some(kind), SaveOutcome::Saved { updated_at }) = (change, &outcome) {
+        notify(id, kind, Some(updated_at.clone()));
+    }
+    Ok(outcome)
+}
+
+fn save_if_unmodified_locked(
+    conn: &Connection,
+    id: &str,
+    name: &str,
+    config_json: &str,
+    expected_updated_at: &str,
+) -> Result<(SaveOutcome, Option<ChangeKind>)> {
+    let stored: Option<(String, bool)> = conn
+        .query_row(
+            // Any other timestamp is another version. One in an older format compares after normalizing
+            "SELECT updated_at, updated_at = coalesce(strftime('%Y-%m-%dT%H:%M:%fZ', ?2), ?2)
+             FROM overlays WHERE id = ?1",
+            params![id, expected_updated_at],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    if let Some((stored_updated_at, false)) = stored {
+        return Ok((SaveOutcome::Conflict { stored_updated_at }, None));
+    }
+    // Unmodified since the client loaded it (or new): the lock keeps it that way
+    let (updated_at, change) = upsert_locked(conn, id, name, config_json)?;
+    Ok((SaveOutcome::Saved { updated_at }, change))
+}
+
+/// Store `config_json` (patched from `base_config`) unless the overlay's config
+/// stopped being `base_config` meanwhile, checked and written in one
+/// transaction. `None` when the overlay no longer exists.
+pub fn replace_config_if_unchanged(
+    id: &str,
+    name: &str,
+    base_config: &str,
+    config_json: &str,
+) -> Result<Option<SaveOutcome>> {
+    let mut conn = DB.lock().unwrap();
+    let tx = conn.transaction()?;
+    let stored: Option<(String, String)> = tx
+        .query_row("SELECT config, updated_at FROM overlays WHERE id = ?1", params![id], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .optional()?;
+    let Some((stored_config, stored_updated_at)) = stored else { return Ok(None) };
+    if stored_config != base_config {
+        return Ok(Some(SaveOutcome::Conflict { stored_updated_at }));
+    }
+    let (updated_at, change) = upsert_locked(&tx, id, name, config_json)?;
+    tx.commit()?;
+    drop(conn);
+    if let Some(kind) = change {
+        notify(id, kind, Some(updated_at.clone()));
+    }
+    Ok(Some(SaveOutcome::Saved { updated_at }))
+}
+
+/// Store `config_json` over `base_config` like `replace_config_if_unchanged`,
+/// but as the same version: the `updated_at` stays, and the change is announced
+/// as `Refreshed`. Only for changes later saves carry over (`srcNonce` bumps).
+pub fn refresh_config_if_unchanged(id: &str, base_config: &str, config_json: &str) -> Result<Option<SaveOutcome>> {
+    let mut conn = DB.lock().unwrap();
+    let tx = conn.transaction()?;
+    let stored: Option<(String, String)> = tx
+        .query_row("SELECT config, updated_at FROM overlays WHERE id = ?1", params![id], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .optional()?;
+    let Some((stored_config, updated_at)) = stored else { return Ok(None) };
+    if stored_config != base_config {
+        return Ok(Some(SaveOutcome::Conflict { stored_updated_at: updated_at }));
+    }
+    tx.execute("UPDATE overlays SET config = ?2 WHERE id = ?1", params![id, config_json])?;
+    tx.commit()?;
+    drop(conn);
+    notify(id, ChangeKind::Refreshed, Some(updated_at.clone()));
+    Ok(Some(SaveOutcome::Saved { updated_at }))
+}
+
+pub fn delete_overlay(id: &str) -> Result<()> {
+    let conn = DB.lock().unwrap();
+    let deleted = conn.execute("DELETE FROM overlays WHERE id = ?1", params![id])?;
+    drop(conn);
+    if deleted > 0 {
+        notify(id, ChangeKind::Deleted, None);
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Settings (key/value)
+// ---------------------------------------------------------------------------
+
+pub fn get_setting(key: &str) -> Result<Option<String>> {
+    let conn = DB.lock().unwrap();
+    conn.query_row("SELECT value FROM settings WHERE key = ?1", params![key], |row| row.get(0))
+        .optional()
+}
+
+pub fn set_setting(key: &str, value: &str) -> Result<()> {
     let conn = DB.lock().unwrap();
     conn.execute(
-        "INSERT INTO overlays (id, name, config) VALUES (?1, ?2, ?3)
-         ON CONFLICT(id) DO UPDATE SET
-           name       = excluded.name,
-           config     = excluded.config,
-           updated_at = CURRENT_TIMESTAMP",
-        params![id, name, config_json],
+        "INSERT INTO settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value],
     )?;
     Ok(())
 }
 
-pub fn delete_overlay(id: &str) -> Result<()> {
+// ---------------------------------------------------------------------------
+// Integrity check / repair
+// ---------------------------------------------------------------------------
+
+/// Why a stored config can't be loaded, or `None` if it's a JSON object.
+fn config_error(raw: ValueRef<'_>) -> Option<String> {
+    let bytes = match raw {
+        ValueRef::Text(b) | ValueRef::Blob(b) => b,
+        ValueRef::Null => return Some("config is NULL".to_string()),
+        _ => return Some("config is not text".to_string()),
+    };
+    let text = match std::str::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(e) => return Some(format!("config is not valid UTF-8: {e}")),
+    };
+    match serde_json::from_str::<serde_json::Value>(text) {
+        Ok(serde_json::Value::Object(_)) => None,
+        Ok(_) => Some("config is not a JSON object".to_string()),
+        Err(e) => Some(format!("config is not valid JSON: {e}")),
+    }
+}
+
+fn lossy_text(value: ValueRef<'_>) -> String {
+    match value {
+        ValueRef::Text(b) | ValueRef::Blob(b) => String::from_utf8_lossy(b).into_owned(),
+        ValueRef::Null => String::new(),
+        ValueRef::Integer(i) => i.to_string(),
+        ValueRef::Real(f) => f.to_string(),
+    }
+}
+
+/// Scan every overlay row; returns the row count and `(rowid, row)` for each corrupt one.
+fn scan_overlays(conn: &Connection) -> Result<(usize, Vec<(i64, CorruptRow)>)> {
+    let mut stmt = conn.prepare("SELECT rowid, id, name, config FROM overlays")?;
+    let mut rows = stmt.query([])?;
+    let (mut checked, mut corrupt) = (0, Vec::new());
+    while let Some(row) = rows.next()? {
+        checked += 1;
+        if let Some(error) = config_error(row.get_ref(3)?) {
+            let id = lossy_text(row.get_ref(1)?);
+            let name = lossy_text(row.get_ref(2)?);
+            corrupt.push((row.get(0)?, CorruptRow { id, name, error }));
+        }
+    }
+    Ok((checked, corrupt))
+}
+
+/// Run `PRAGMA integrity_check` and try to parse every overlay's config.
+pub fn check_database() -> Result<IntegrityReport> {
+    let conn = DB.lock().unwrap();
+    let integrity = conn
+        .prepare("PRAGMA integrity_check")?
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>>>()?;
+    let (overlays_checked, corrupt) = scan_overlays(&conn)?;
+    let corrupt_rows: Vec<CorruptRow> = corrupt.into_iter().map(|(_, row)| row).collect();
+    Ok(IntegrityReport {
+        ok: integrity == ["ok"] && corrupt_rows.is_empty(),
+        integrity,
+        overlays_checked,
+        corrupt_rows,
+    })
+}
+
+/// Move overlays whose config can't be parsed into `overlays_quarantine`, so
+/// the rest of the library loads. Nothing is deleted outright.
+pub fn repair_database() -> Result<RepairReport> {
+    let mut conn = DB.lock().unwrap();
+    let tx = conn.transaction()?;
+    let (_, corrupt) = scan_overlays(&tx)?;
+    for (rowid, row) in &corrupt {
+        tx.execute(
+            "INSERT INTO overlays_quarantine (id, name, config, updated_at, error, quarantined_at)
+             SELECT id, name, config, updated_at, ?2, strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+             FROM overlays WHERE rowid = ?1",
+            params![rowid, row.error],
+        )?;
+        tx.execute("DELETE FROM overlays WHERE rowid = ?1", params![rowid])?;
+    }
+    tx.commit()?;
+    drop(conn);
+    for (_, row) in &corrupt {
+        notify(&row.id, ChangeKind::Deleted, None);
+    }
+    Ok(RepairReport { quarantined: corrupt.into_iter().map(|(_, row)| row).collect() })
+}
+
+/// Rebuild the database file with `VACUUM` to reclaim space left by deleted rows.
+/// Every write goes through `DB`, so holding the lock guarantees no other
+/// transaction is open; autosaves that come due meanwhile simply wait.
+pub fn compact_database() -> Result<CompactReport> {
+    let conn = DB.lock().unwrap();
+    let path = get_db_path();
+    let file_size = || std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    let before_bytes = file_size();
+    conn.execute_batch("VACUUM")?;
+    Ok(CompactReport { before_bytes, after_bytes: file_size() })
+}
+
+/// Every overlay config in every profile, quarantined ones included, as
+/// `(profile, overlay id, raw config)`, for clean-ups that must see every use
+/// of the files kept beside the databases. `f` runs with the active database
+/// still locked, so no save lands until it returns; inactive profiles are
+/// only read, and nothing writes to them.
+pub fn with_every_config_locked<T>(f: impl FnOnce(Vec<(String, String, String)>) -> T) -> Result<T> {
     let conn = DB.lock().unwrap();
-    conn.execute("DELETE FROM overlays WHERE id = ?1", params![id])?;
+    let active = active_profile();
+    let mut configs = Vec::new();
+    for name in profile_names() {
+        if name == active {
+            read_configs(&conn, &name, &mut configs)?;
+        } else {
+            let other = Connection::open_with_flags(profile_db_path(&name), OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+            read_configs(&other, &name, &mut configs)?;
+        }
+    }
+    Ok(f(configs))
+}
+
+fn read_configs(conn: &Connection, profile: &str, out: &mut Vec<(String, String, String)>) -> Result<()> {
+    for table in ["overlays", "overlays_quarantine"] {
+        // A profile last opened by an older version may not have the quarantine yet
+        let exists: bool =
+            conn.query_row("SELECT count(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = ?1", params![table], |row| row.get(0))?;
+        if !exists {
+            continue;
+        }
+        let mut stmt = conn.prepare(&format!("SELECT id, config FROM {table}"))?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            out.push((profile.to_string(), lossy_text(row.get_ref(0)?), lossy_text(row.get_ref(1)?)));
+        }
+    }
     Ok(())
 }
+
+// ---------------------------------------------------------------------------
+// Whole-library import (see `library`)
+// ---------------------------------------------------------------------------
+
+/// Folder the default database lives in; restored assets and fonts go beside it.
+pub fn data_dir() -> PathBuf {
+    default_db_path().parent().map(Path::to_path_buf).unwrap_or_else(|| PathBuf::from("."))
+}
+
+pub fn all_settings() -> Result<BTreeMap<String, String>> {
+    let conn = DB.lock().unwrap();
+    let mut stmt = conn.prepare("SELECT key, value FROM settings")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// Snapshot the active database to `overlays.db.<UTC timestamp>.bak` beside
+/// it. `VACUUM INTO` writes a consistent copy while the connection stays open.
+pub fn backup_database() -> Result<PathBuf> {
+    let conn = DB.lock().unwrap();
+    let stamp: String = conn.query_row("SELECT strftime('%Y%m%d-%H%M%S', 'now')", [], |row| row.get(0))?;
+    let path = get_db_path().with_extension(format!("db.{stamp}.bak"));
+    conn.execute("VACUUM INTO ?1", params![path.to_string_lossy()])?;
+    Ok(path)
+}
+
+/// Write imported overlays (id, name, config JSON) and settings in one
+/// transaction. With `replace` every overlay and setting is deleted first;
+/// otherwise settings that already exist keep their current value.
+pub fn import_library(overlays: &[(String, String, String)], settings: &BTreeMap<String, String>, replace: bool) -> Result<()> {
+    let mut conn = DB.lock().unwrap();
+    let tx = conn.transaction()?;
+    let mut removed = HashSet::new();
+    if replace {
+        let mut stmt = tx.prepare("SELECT id FROM overlays")?;
+        removed = stmt.query_map([], |row| row.get::<_, String>(0))?.collect::<Result<_>>()?;
+        drop(stmt);
+        tx.execute_batch("DELETE FROM overlays; DELETE FROM settings;")?;
+    }
+    let mut changes = Vec::new();
+    for (id, name, config) in overlays {
+        let (updated_at, change) = upsert_locked(&tx, id, name, config)?;
+        // A replaced overlay that comes back under the same id was updated, not recreated
+        let change = if removed.remove(id) { Some(ChangeKind::Updated) } else { change };
+        changes.extend(change.map(|kind| (id.clone(), kind, Some(updated_at))));
+    }
+    for (key, value) in settings {
+        tx.execute(
+            "INSERT INTO settings (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO NOTHING",
+            params![key, value],
+        )?;
+    }
+    tx.commit()?;
+    drop(conn);
+    changes.extend(removed.into_iter().map(|id| (id, ChangeKind::Deleted, None)));
+    for (id, kind, updated_at) in changes {
+        notify(&id, kind, updated_at);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn second_save_from_the_same_version_conflicts_within_one_second() {
+        let conn = try_open_db(Path::new(":memory:")).unwrap();
+        let (loaded, _) = upsert_locked(&conn, "o", "Overlay", r#"{"v":0}"#).unwrap();
+
+        let (first, _) = save_if_unmodified_locked(&conn, "o", "Overlay", r#"{"v":1}"#, &loaded).unwrap();
+        let SaveOutcome::Saved { updated_at } = first else { panic!("first save conflicted: {first:?}") };
+        assert_ne!(updated_at, loaded);
+
+        let (second, _) = save_if_unmodified_locked(&conn, "o", "Overlay", r#"{"v":2}"#, &loaded).unwrap();
+        assert!(matches!(second, SaveOutcome::Conflict { stored_updated_at } if stored_updated_at == updated_at));
+    }
+
+    #[test]
+    fn timestamps_from_before_milliseconds_still_match() {
+        let path = std::env::temp_dir().join(format!("open-overlay-test-{}.db", uuid::Uuid::new_v4()));
+        let conn = try_open_db(&path).unwrap();
+        conn.execute("INSERT INTO overlays (id, name, config, updated_at) VALUES ('o', 'Overlay', '{}', '2024-05-01 18:30:00')", [])
+            .unwrap();
+        drop(conn);
+        // Reopening migrates the row; a client that loaded it before still holds the old format
+        let conn = try_open_db(&path).unwrap();
+        let (outcome, _) = save_if_unmodified_locked(&conn, "o", "Overlay", r#"{"v":1}"#, "2024-05-01T18:30:00Z").unwrap();
+        drop(conn);
+        let _ = std::fs::remove_file(&path);
+        assert!(matches!(outcome, SaveOutcome::Saved { .. }), "{outcome:?}");
+    }
+
+    #[test]
+    fn a_save_from_before_a_src_refresh_keeps_the_nonce() {
+        let conn = try_open_db(Path::new(":memory:")).unwrap();
+        let element = |nonce: Option<u64>| {
+            let mut el = serde_json::json!({ "id": "art", "type": "image", "src": "https://example.com/a.png" });
+            if let Some(nonce) = nonce {
+                el["srcNonce"] = nonce.into();
+            }
+            serde_json::json!({ "widgets": [{ "id": "w", "elements": [el] }] }).to_string()
+        };
+        upsert_locked(&conn, "o", "Overlay", &element(Some(2))).unwrap();
+        let (_, change) = upsert_locked(&conn, "o", "Overlay", &element(None)).unwrap();
+        assert_eq!(change, None);
+        let stored: String = conn.query_row("SELECT config FROM overlays WHERE id = 'o'", [], |row| row.get(0)).unwrap();
+        assert_eq!(stored, element(Some(2)));
+    }
+}
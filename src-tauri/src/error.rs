@@ -0,0 +1,70 @@
+use serde::Serialize;
+use std::fmt;
+
+// ---------------------------------------------------------------------------
+// Errors Tauri commands return, serialized as `{"kind": "...", ...}` so the
+// frontend can tell a missing overlay from a conflict or a locked database
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CommandError {
+    /// No overlay, widget or profile by that id or name.
+    NotFound { message: String },
+    /// Changed by someone else since it was loaded, or the name/hotkey is taken.
+    Conflict { message: String },
+    /// A save-time check rejected the config; `details` says which and why.
+    InvalidConfig { details: String },
+    /// The config is over the configured size, element or nesting limits.
+    Limit { message: String },
+    /// Another program (a second copy of the app, a backup or antivirus tool)
+    /// held the database past the busy timeout; try again in a moment.
+    Busy { message: String },
+    /// SQLite failed otherwise (unreadable file, a bad statement).
+    Database { message: String },
+    /// Anything else: file I/O, exports, the clipboard.
+    Other { message: String },
+}
+
+impl CommandError {
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::NotFound { message: message.into() }
+    }
+
+    pub fn other(error: impl fmt::Display) -> Self {
+        Self::Other { message: error.to_string() }
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound { message }
+            | Self::Conflict { message }
+            | Self::Limit { message }
+            | Self::Busy { message }
+            | Self::Database { message }
+            | Self::Other { message } => f.write_str(message),
+            Self::InvalidConfig { details } => f.write_str(details),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for CommandError {
+    fn from(e: rusqlite::Error) -> Self {
+        if crate::db::is_busy(&e) {
+            log::warn!("Database busy: {e}");
+            return Self::Busy { message: "The database is busy (another program has it open); try again in a moment".to_string() };
+        }
+        log::error!("Database error: {e}");
+        Self::Database { message: e.to_string() }
+    }
+}
+
+/// Other modules report plain errors as strings. Those that need another
+/// kind (a conflict, something missing, a limit) return a `CommandError`.
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        Self::Other { message }
+    }
+}
@@ -0,0 +1,457 @@
+use base64::Engine;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::CommandError;
+use crate::{library, obs_server, validation};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FrameExportReport {
+    pub frames: usize,
+    pub fps: u32,
+    pub out_dir: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct StandaloneExportReport {
+    pub path: String,
+    pub bytes: usize,
+    /// Local files embedded as data URIs.
+    pub inlined: usize,
+    /// http(s) URLs left as-is; the page needs network access for them.
+    pub remote: Vec<String>,
+    /// Font files embedded for the families elements ask for first.
+    pub fonts: usize,
+    /// Families no font file was found for; the viewer needs them installed.
+    pub missing_fonts: Vec<String>,
+    /// References that only work with this app running, left as-is: relative
+    /// paths and URLs of this PC's own server (`localhost`, `127.0.0.1`).
+    pub unresolved: Vec<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Frame timing
+// ---------------------------------------------------------------------------
+
+/// Timeline times (in seconds) to capture for a widget at `fps`.
+///
+/// Frames are spaced `1 / fps` seconds of wall-clock time apart, so the
+/// timeline's `speed` stretches or compresses the sequence: a 4s timeline at
+/// speed 2 plays for 2s and yields `2 * fps` frames. A looping timeline
+/// exports exactly one loop (the frame at `duration` is skipped because it is
+/// identical to the frame at 0); a one-shot timeline includes its final pose.
+/// A widget without keyframes exports a single frame.
+fn frame_times(timeline: Option<&Value>, fps: u32) -> Vec<f64> {
+    let Some(tl) = timeline.filter(|t| {
+        t.get("keyframes").and_then(|k| k.as_array()).is_some_and(|k| !k.is_empty())
+    }) else {
+        return vec![0.0];
+    };
+    let duration = tl.get("duration").and_then(|v| v.as_f64()).unwrap_or(0.0).max(0.0);
+    let speed = tl.get("speed").and_then(|v| v.as_f64()).filter(|s| *s > 0.0).unwrap_or(1.0);
+    let looping = tl.get("loop").and_then(|v| v.as_bool()).unwrap_or(false);
+
+    let wall_duration = duration / speed;
+    let step = speed / fps as f64;
+    let count = if looping {
+        ((wall_duration * fps as f64).round() as usize).max(1)
+    } else {
+        (wall_duration * fps as f64).floor() as usize + 1
+    };
+    (0..count).map(|i| (i as f64 * step).min(duration)).collect()
+}
+
+// ---------------------------------------------------------------------------
+// Headless browser
+// ---------------------------------------------------------------------------
+
+/// Locate a Chromium-based browser for headless capture. `OPEN_OVERLAY_BROWSER`
+/// wins; otherwise the usual Chrome / Edge / Chromium install locations are tried.
+fn find_browser() -> Option<PathBuf> {
+    if let Ok(p) = std::env::var("OPEN_OVERLAY_BROWSER") {
+        return Some(PathBuf::from(p));
+    }
+
+    let fixed: &[&str] = if cfg!(target_os = "windows") {
+        &[
+            r"C:\Program Files\Google\Chrome\Application\chrome.exe",
+            r"C:\Program Files (x86)\Google\Chrome\Application\chrome.exe",
+            r"C:\Program Files (x86)\Microsoft\Edge\Application\msedge.exe",
+            r"C:\Program Files\Microsoft\Edge\Application\msedge.exe",
+        ]
+    } else if cfg!(target_os = "macos") {
+        &[
+            "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+            "/Applications/Chromium.app/Contents/MacOS/Chromium",
+            "/Applications/Microsoft Edge.app/Contents/MacOS/Microsoft Edge",
+        ]
+    } else {
+        &[]
+    };
+    if let Some(p) = fixed.iter().map(PathBuf::from).find(|p| p.exists()) {
+        return Some(p);
+    }
+
+    // Fall back to searching PATH (the common case on Linux)
+    let names = ["google-chrome", "google-chrome-stable", "chromium", "chromium-browser", "microsoft-edge"];
+    let path_var = std::env::var_os("PATH")?;
+    std::env::split_paths(&path_var)
+        .flat_map(|dir| names.iter().map(move |n| dir.join(n)))
+        .find(|p| p.exists())
+}
+
+fn capture_frame(browser: &Path, url: &str, w: u64, h: u64, out: &Path) -> Result<(), String> {
+    let status = Command::new(browser)
+        .arg("--headless=new")
+        .arg("--disable-gpu")
+        .arg("--hide-scrollbars")
+        .arg("--default-background-color=00000000")
+        .arg("--virtual-time-budget=1000")
+        .arg(format!("--window-size={w},{h}"))
+        .arg(format!("--screenshot={}", out.display()))
+        .arg(url)
+        .status()
+        .map_err(|e| format!("Failed to launch headless browser: {e}"))?;
+    if !status.success() || !out.exists() {
+        return Err(format!("Headless browser failed to capture {url}"));
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Export
+// ---------------------------------------------------------------------------
+
+/// Render a widget's timeline to a transparent PNG sequence in `out_dir`
+/// (`frame_00000.png`, `frame_00001.png`, ...). Each frame is captured from the
+/// running OBS server using the `?t=` seek parameter. Turn the sequence into a
+/// transparent WebM with e.g.
+/// `ffmpeg -framerate <fps> -i frame_%05d.png -c:v libvpx-vp9 -pix_fmt yuva420p out.webm`.
+pub fn export_frames(id: &str, fps: u32, out_dir: &str) -> Result<FrameExportReport, CommandError> {
+    if !(1..=120).contains(&fps) {
+        return Err(CommandError::other(format!("fps must be between 1 and 120 (got {fps})")));
+    }
+    let widget = obs_server::find_widget(id).ok_or_else(|| CommandError::not_found(format!("Widget '{id}' not found")))?;
+    let browser = find_browser().ok_or_else(|| {
+        "No Chromium-based browser found; install Chrome/Edge or set OPEN_OVERLAY_BROWSER".to_string()
+    })?;
+
+    let w = widget.get("width").and_then(|v| v.as_u64()).unwrap_or(400);
+    let h = widget.get("height").and_then(|v| v.as_u64()).unwrap_or(300);
+    let times = frame_times(widget.get("animationTimeline"), fps);
+
+    let dir = PathBuf::from(out_dir);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Cannot create '{out_dir}': {e}"))?;
+
+    for (i, t) in times.iter().enumerate() {
+        let url = format!("http://127.0.0.1:{}/widget/{}?t={}", obs_server::OBS_HTTP_PORT, id, t);
+        capture_frame(&browser, &url, w, h, &dir.join(format!("frame_{i:05}.png")))?;
+    }
+
+    Ok(FrameExportReport { frames: times.len(), fps, out_dir: out_dir.to_string() })
+}
+
+// ---------------------------------------------------------------------------
+// Standalone HTML
+// ---------------------------------------------------------------------------
+
+/// Element fields (at any depth, segments included) that reference an asset.
+/// Every walk over asset references goes through `element_assets` or
+/// `for_each_asset`, so these are the only list of them.
+const ASSET_KEYS: &[&str] = &["src", "poster", "maskImageSrc"];
+/// Element fields holding a list of asset references (sprite frames).
+const ASSET_LIST_KEYS: &[&str] = &["frames"];
+
+fn own_assets<'a>(value: &'a Value, field: Option<&'static str>, out: &mut Vec<(&'static str, &'a str)>) {
+    for &key in ASSET_KEYS {
+        out.extend(value.get(key).and_then(|v| v.as_str()).map(|r| (field.unwrap_or(key), r)));
+    }
+    for &key in ASSET_LIST_KEYS {
+        let items = value.get(key).and_then(|v| v.as_array()).into_iter().flatten();
+        out.extend(items.filter_map(|v| v.as_str()).map(|r| (field.unwrap_or(key), r)));
+    }
+}
+
+/// Asset references of one element (not its children), each with the field
+/// holding it; references in its text segments count as `segments`.
+pub(crate) fn element_assets(element: &Value) -> Vec<(&'static str, &str)> {
+    let mut out = Vec::new();
+    own_assets(element, None, &mut out);
+    for segment in element.get("segments").and_then(|v| v.as_array()).into_iter().flatten() {
+        own_assets(segment, Some("segments"), &mut out);
+    }
+    out
+}
+
+/// Font stacks of one element and its text segments, fielded like `element_assets`.
+pub(crate) fn element_fonts(element: &Value) -> Vec<(&'static str, &str)> {
+    let segments = element.get("segments").and_then(|v| v.as_array()).into_iter().flatten();
+    let segment_fonts = segments.filter_map(|s| s.get("fontFamily")?.as_str()).map(|f| ("segments", f));
+    element.get("fontFamily").and_then(|v| v.as_str()).map(|f| ("fontFamily", f)).into_iter().chain(segment_fonts).collect()
+}
+
+fn mime_for(path: &Path) -> &'static str {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase();
+    match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "bmp" => "image/bmp",
+        "avif" => "image/avif",
+        "webm" => "video/webm",
+        "mp4" => "video/mp4",
+        "woff2" => "font/woff2",
+        "woff" => "font/woff",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Local file a reference points at (`file://` URL or absolute path), if any.
+pub(crate) fn local_asset_path(reference: &str) -> Option<PathBuf> {
+    let path = match reference.strip_prefix("file://") {
+        // file:///C:/x on Windows, file:///home/x elsewhere
+        Some(rest) if cfg!(windows) => PathBuf::from(rest.trim_start_matches('/')),
+        Some(rest) => PathBuf::from(rest),
+        None => PathBuf::from(reference),
+    };
+    path.is_absolute().then_some(path)
+}
+
+/// Whether an http(s) URL points at this PC (the OBS server, say), which a
+/// page opened elsewhere can't reach.
+fn is_this_pc(url: &str) -> bool {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = match host.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or(""),
+        None => host.split(':').next().unwrap_or(""),
+    };
+    host.eq_ignore_ascii_case("localhost") || host.starts_with("127.") || host == "::1" || host == "0.0.0.0"
+}
+
+/// Replace one local asset reference with a data URI, or note it as remote
+/// or unresolved.
+fn inline_asset(reference: &mut String, report: &mut StandaloneExportReport) -> Result<(), String> {
+    if reference.is_empty() || reference.starts_with("data:") {
+        return Ok(());
+    }
+    if reference.starts_with("http://") || reference.starts_with("https://") {
+        let list = if is_this_pc(reference) { &mut report.unresolved } else { &mut report.remote };
+        list.push(reference.clone());
+    } else if let Some(path) = local_asset_path(reference) {
+        let bytes = std::fs::read(&path).map_err(|e| format!("Cannot read asset '{}': {e}", path.display()))?;
+        let data = base64::engine::general_purpose::STANDARD.encode(bytes);
+        *reference = format!("data:{};base64,{data}", mime_for(&path));
+        report.inlined += 1;
+    } else {
+        report.unresolved.push(reference.clone());
+    }
+    Ok(())
+}
+
+/// `font-weight` and `font-style` guessed from a font file's name
+/// (`Inter-BoldItalic.ttf`), since the files aren't read.
+fn face_descriptors(family: &str, path: &Path) -> (u16, &'static str) {
+    let stem = path.file_stem().and_then(|n| n.to_str()).map(validation::font_key).unwrap_or_default();
+    let family_key = validation::font_key(family);
+    let style = stem.strip_prefix(&family_key).unwrap_or(&stem);
+    const WEIGHTS: &[(&str, u16)] = &[
+        ("extralight", 200), ("ultralight", 200), ("thin", 100), ("hairline", 100), ("light", 300),
+        ("semibold", 600), ("demibold", 600), ("extrabold", 800), ("ultrabold", 800), ("bold", 700),
+        ("black", 900), ("heavy", 900), ("medium", 500),
+    ];
+    let weight = WEIGHTS.iter().find(|(name, _)| style.contains(name)).map_or(400, |(_, weight)| *weight);
+    let italic = style.contains("italic") || style.contains("oblique");
+    (weight, if italic { "italic" } else { "normal" })
+}
+
+/// `@font-face` rules embedding the files `font_files` finds for every family
+/// `widget` asks for first. Families without a usable file are reported.
+fn embed_fonts(
+    widget: &Value,
+    font_files: &dyn Fn(&str) -> Vec<PathBuf>,
+    report: &mut StandaloneExportReport,
+) -> Result<String, String> {
+    let mut families = std::collections::BTreeSet::new();
+    library::collect_font_families(widget, &mut families);
+    let mut css = String::new();
+    for family in families {
+        let mut files = font_files(&family);
+        files.sort();
+        files.dedup_by(|a, b| a.file_name() == b.file_name());
+        // Browsers can't load font collections (.ttc) or bitmap fonts
+        files.retain(|file| matches!(mime_for(file), "font/ttf" | "font/otf" | "font/woff" | "font/woff2"));
+        if files.is_empty() {
+            report.missing_fonts.push(family);
+            continue;
+        }
+        for file in files {
+            let bytes = std::fs::read(&file).map_err(|e| format!("Cannot read font '{}': {e}", file.display()))?;
+            let data = base64::engine::general_purpose::STANDARD.encode(bytes);
+            let (weight, style) = face_descriptors(&family, &file);
+            let name = serde_json::to_string(&family).unwrap_or_default();
+            css.push_str(&format!(
+                "@font-face {{ font-family:{name}; font-weight:{weight}; font-style:{style}; src:url(data:{};base64,{data}); }}\n",
+                mime_for(&file)
+            ));
+            report.fonts += 1;
+        }
+    }
+    Ok(css)
+}
+
+/// Installed files for `family`, and those a library import restored for it.
+fn installed_or_restored_fonts(family: &str) -> Vec<PathBuf> {
+    let mut files = validation::font_files(family);
+    files.extend(library::restored_font_files(family));
+    files
+}
+
+/// Inline `widget`'s local files in place and return the `@font-face` rules
+/// for its fonts, recording both in `report`.
+fn make_standalone(
+    widget: &mut Value,
+    font_files: &dyn Fn(&str) -> Vec<PathBuf>,
+    report: &mut StandaloneExportReport,
+) -> Result<String, String> {
+    for_each_asset(widget, &mut |reference| inline_asset(reference, report))?;
+    for list in [&mut report.remote, &mut report.unresolved] {
+        list.sort();
+        list.dedup();
+    }
+    embed_fonts(widget, font_files, report)
+}
+
+/// Run `f` on every asset reference in `value` (element fields and sprite frames, at any depth).
+pub(crate) fn for_each_asset(value: &mut Value, f: &mut dyn FnMut(&mut String) -> Result<(), String>) -> Result<(), String> {
+    match value {
+        Value::Object(obj) => {
+            for (key, v) in obj.iter_mut() {
+                match v {
+                    Value::String(reference) if ASSET_KEYS.contains(&key.as_str()) => f(reference)?,
+                    Value::Array(items) if ASSET_LIST_KEYS.contains(&key.as_str()) => {
+                        for item in items {
+                            if let Value::String(reference) = item {
+                                f(reference)?;
+                            }
+                        }
+                    }
+                    _ => for_each_asset(v, f)?,
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                for_each_asset(item, f)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Write a widget as a single self-contained `.html` file that renders like
+/// the OBS page without this app: local images/videos and the font files for
+/// its families are embedded as data URIs, and the control socket, reload
+/// polling and error reporting are left out.
+pub fn export_standalone_html(id: &str, path: &str) -> Result<StandaloneExportReport, CommandError> {
+    let mut widget = obs_server::find_widget(id).ok_or_else(|| CommandError::not_found(format!("Widget '{id}' not found")))?;
+    let mut report = StandaloneExportReport {
+        path: path.to_string(),
+        bytes: 0,
+        inlined: 0,
+        remote: Vec::new(),
+        fonts: 0,
+        missing_fonts: Vec::new(),
+        unresolved: Vec::new(),
+    };
+    let font_faces = make_standalone(&mut widget, &installed_or_restored_fonts, &mut report)?;
+
+    let mut html = obs_server::render_standalone_html(&widget);
+    if !font_faces.is_empty() {
+        html = html.replacen("</head>", &format!("<style>\n{font_faces}</style>\n</head>"), 1);
+    }
+    std::fs::write(path, &html).map_err(|e| format!("Cannot write '{path}': {e}"))?;
+    report.bytes = html.len();
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_report() -> StandaloneExportReport {
+        StandaloneExportReport {
+            path: String::new(),
+            bytes: 0,
+            inlined: 0,
+            remote: Vec::new(),
+            fonts: 0,
+            missing_fonts: Vec::new(),
+            unresolved: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn fonts_are_embedded_and_local_server_links_reported() {
+        let dir = std::env::temp_dir().join(format!("open-overlay-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let font = dir.join("TestFace-BoldItalic.ttf");
+        std::fs::write(&font, b"not really a font").unwrap();
+        let mut widget = serde_json::json!({
+            "id": "w",
+            "elements": [
+                { "id": "title", "type": "text", "text": "Hi", "fontFamily": "\"TestFace\", sans-serif" },
+                { "id": "other", "type": "text", "text": "Yo", "fontFamily": "Nowhere Sans" },
+                { "id": "logo", "type": "image", "src": "images/logo.png" },
+                { "id": "art", "type": "image", "src": "http://127.0.0.1:7878/art.png" },
+                { "id": "cdn", "type": "image", "src": "https://cdn.example.com/a.png" }
+            ]
+        });
+        let files_for = |family: &str| if family == "TestFace" { vec![font.clone()] } else { Vec::new() };
+        let mut report = empty_report();
+        let css = make_standalone(&mut widget, &files_for, &mut report).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(report.fonts, 1);
+        assert!(css.contains(r#"font-family:"TestFace"; font-weight:700; font-style:italic; src:url(data:font/ttf;base64,"#), "{css}");
+        assert_eq!(report.missing_fonts, ["Nowhere Sans"]);
+        assert_eq!(report.unresolved, ["http://127.0.0.1:7878/art.png", "images/logo.png"]);
+        assert_eq!(report.remote, ["https://cdn.example.com/a.png"]);
+    }
+
+    #[test]
+    fn only_this_pc_counts_as_local() {
+        assert!(is_this_pc("http://localhost:7878/widget/a"));
+        assert!(is_this_pc("http://[::1]:7878/x"));
+        assert!(is_this_pc("https://user@127.0.0.2/x"));
+        assert!(!is_this_pc("https://localhost.example.com/x"));
+    }
+
+    #[test]
+    fn element_assets_include_frames_and_segments() {
+        let element = serde_json::json!({
+            "src": "/a.png",
+            "frames": ["/f1.png", "/f2.png"],
+            "fontFamily": "Inter, sans-serif",
+            "segments": [{ "src": "/s.png", "fontFamily": "Lobster" }],
+            "children": [{ "src": "/child.png" }]
+        });
+        assert_eq!(
+            element_assets(&element),
+            [("src", "/a.png"), ("frames", "/f1.png"), ("frames", "/f2.png"), ("segments", "/s.png")]
+        );
+        assert_eq!(element_fonts(&element), [("fontFamily", "Inter, sans-serif"), ("segments", "Lobster")]);
+    }
+}
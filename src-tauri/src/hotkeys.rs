@@ -0,0 +1,102 @@
+use once_cell::sync::Lazy;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use std::sync::Mutex;
+use tauri::AppHandle;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+
+use crate::error::CommandError;
+use crate::{control, settings};
+
+// ---------------------------------------------------------------------------
+// Global hotkeys: accelerator -> "trigger widget X"
+// ---------------------------------------------------------------------------
+
+/// Registered shortcut id -> widget id it triggers.
+static BOUND: Lazy<Mutex<HashMap<u32, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn parse(accelerator: &str) -> Result<Shortcut, String> {
+    Shortcut::from_str(accelerator).map_err(|e| format!("Invalid accelerator '{accelerator}': {e}"))
+}
+
+/// Global shortcut plugin handler: fire the bound widget's trigger on key press.
+pub fn handle(_app: &AppHandle, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state != ShortcutState::Pressed {
+        return;
+    }
+    let widget_id = BOUND.lock().unwrap().get(&shortcut.id()).cloned();
+    if let Some(widget_id) = widget_id {
+        let delivered = control::trigger(&widget_id, serde_json::json!({}));
+        log::info!("Hotkey triggered widget {widget_id} ({delivered} page(s))");
+    }
+}
+
+/// Register every hotkey saved in settings. Failures are logged, not fatal, so
+/// one accelerator taken by another app doesn't block the rest.
+pub fn register_saved(app: &AppHandle) {
+    for (widget_id, accelerator) in settings::hotkeys() {
+        let result = parse(&accelerator).and_then(|shortcut| {
+            app.global_shortcut().register(shortcut).map_err(|e| e.to_string())?;
+            BOUND.lock().unwrap().insert(shortcut.id(), widget_id.clone());
+            Ok(())
+        });
+        if let Err(e) = result {
+            log::warn!("Could not register hotkey {accelerator} for widget {widget_id}: {e}");
+        }
+    }
+}
+
+/// Release every bound hotkey (before switching to a profile with its own set).
+pub fn unregister_all(app: &AppHandle) {
+    if let Err(e) = app.global_shortcut().unregister_all() {
+        log::warn!("Failed to unregister hotkeys: {e}");
+    }
+    BOUND.lock().unwrap().clear();
+}
+
+pub fn list() -> BTreeMap<String, String> {
+    settings::hotkeys()
+}
+
+/// Bind (or with `None`, unbind) the hotkey that triggers `widget_id`.
+/// An accelerator already bound to another widget, or held by another
+/// application, is rejected with a `Conflict` error; the previous binding
+/// is left in place.
+pub fn set(app: &AppHandle, widget_id: &str, accelerator: Option<&str>) -> Result<(), CommandError> {
+    let new = accelerator.map(parse).transpose()?;
+    let mut saved = settings::hotkeys();
+
+    if let (Some(new), Some(accelerator)) = (new, accelerator) {
+        let owner = BOUND.lock().unwrap().get(&new.id()).cloned();
+        if let Some(owner) = owner.filter(|owner| owner != widget_id) {
+            return Err(CommandError::Conflict { message: format!("{accelerator} is already bound to widget '{owner}'") });
+        }
+    }
+
+    let shortcuts = app.global_shortcut();
+    let old = saved.get(widget_id).and_then(|a| parse(a).ok());
+    if let Some(old) = old {
+        if let Err(e) = shortcuts.unregister(old) {
+            log::warn!("Failed to unregister previous hotkey for widget {widget_id}: {e}");
+        }
+        BOUND.lock().unwrap().remove(&old.id());
+    }
+
+    if let (Some(new), Some(accelerator)) = (new, accelerator) {
+        if let Err(e) = shortcuts.register(new) {
+            // Put the previous binding back so a failed change is a no-op
+            if let Some(old) = old {
+                if shortcuts.register(old).is_ok() {
+                    BOUND.lock().unwrap().insert(old.id(), widget_id.to_string());
+                }
+            }
+            return Err(CommandError::Conflict { message: format!("could not register {accelerator}: {e}") });
+        }
+        BOUND.lock().unwrap().insert(new.id(), widget_id.to_string());
+        saved.insert(widget_id.to_string(), accelerator.to_string());
+    } else {
+        saved.remove(widget_id);
+    }
+
+    Ok(settings::set_hotkeys(&saved)?)
+}
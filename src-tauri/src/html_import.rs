@@ -0,0 +1,645 @@
+use kuchikiki::traits::TendrilSink;
+use kuchikiki::{NodeRef, Selectors};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+// ---------------------------------------------------------------------------
+// Importing hand-written overlay HTML (the kind of page people point an OBS
+// browser source at). Absolutely positioned text, images, videos and boxes
+// become elements of one widget; anything else is listed as unconverted
+// ---------------------------------------------------------------------------
+
+/// Size a widget gets when nothing in the page says how big it is.
+const DEFAULT_SIZE: (f64, f64) = (1920.0, 1080.0);
+/// Properties that cascade from parent to child text.
+const INHERITED: &[&str] =
+    &["color", "font-family", "font-size", "font-weight", "text-align", "line-height", "letter-spacing", "text-shadow", "text-transform"];
+/// Properties the importer has no element field for; named in the report when set.
+const UNSUPPORTED: &[&str] = &[
+    "animation",
+    "animation-name",
+    "transition",
+    "filter",
+    "box-shadow",
+    "background-image",
+    "clip-path",
+    "mask",
+    "mask-image",
+];
+
+#[derive(Debug, Serialize)]
+pub struct HtmlImportReport {
+    pub overlay_id: String,
+    pub name: String,
+    /// Elements created in the new overlay's one widget.
+    pub elements: usize,
+    /// What wasn't carried over, or only partly, one line each; finish these in the editor.
+    pub unconverted: Vec<String>,
+}
+
+struct Rule {
+    selectors: Selectors,
+    declarations: Vec<(String, String, bool)>,
+    order: usize,
+}
+
+type Style = HashMap<String, String>;
+
+/// Read the overlay HTML at `path` and convert it into a new overlay config
+/// (fresh ids, named after the file), with the report of what was left out.
+pub fn import(path: &str) -> Result<(Value, HtmlImportReport), String> {
+    let path = Path::new(path);
+    let html = std::fs::read_to_string(path).map_err(|e| format!("Cannot read '{}': {e}", path.display()))?;
+    let name = path.file_stem().and_then(|s| s.to_str()).filter(|s| !s.is_empty()).unwrap_or("Imported overlay");
+    Ok(convert(&html, path.parent().unwrap_or(Path::new("")), name))
+}
+
+/// Convert overlay HTML; relative image and video paths resolve against `base_dir`.
+pub fn convert(html: &str, base_dir: &Path, name: &str) -> (Value, HtmlImportReport) {
+    let document = kuchikiki::parse_html().one(html).document_node;
+    let mut importer = Importer { base_dir, rules: Vec::new(), elements: Vec::new(), unconverted: Vec::new(), scripts: 0 };
+    for style in document.select("style").into_iter().flatten() {
+        importer.read_stylesheet(&style.text_contents());
+    }
+    for link in document.select("link[rel~=stylesheet]").into_iter().flatten() {
+        let href = link.attributes.borrow().get("href").unwrap_or("").to_string();
+        importer.note(format!("stylesheet '{href}': only <style> blocks in the page are read"));
+    }
+
+    let mut root = Style::new();
+    let mut size = (None, None);
+    let mut background = None;
+    for tag in ["html", "body"] {
+        let Ok(el) = document.select_first(tag) else { continue };
+        root = importer.computed(el.as_node(), &inherited(&root));
+        size = (px(root.get("width"), None, 16.0).or(size.0), px(root.get("height"), None, 16.0).or(size.1));
+        if let Some(bg) = root.remove("background-color") {
+            background = Some(bg);
+        }
+        if let Some(image) = root.remove("background-image") {
+            importer.note(format!("page background '{image}': set it as the widget's background image in the editor"));
+        }
+    }
+    if let Ok(body) = document.select_first("body") {
+        importer.walk(body.as_node(), (0.0, 0.0), size, &inherited_from(&root), false);
+    }
+    if importer.scripts > 0 {
+        importer.note(format!(
+            "{} script(s): scripts don't carry over; use keyframes, triggers or text tokens for what they did",
+            importer.scripts
+        ));
+    }
+
+    let extent = |axis: &str, len: &str| {
+        importer.elements.iter().map(|e| num(e, axis) + num(e, len)).fold(0.0_f64, f64::max).ceil()
+    };
+    let width = size.0.unwrap_or_else(|| Some(extent("x", "width")).filter(|w| *w > 0.0).unwrap_or(DEFAULT_SIZE.0));
+    let height = size.1.unwrap_or_else(|| Some(extent("y", "height")).filter(|h| *h > 0.0).unwrap_or(DEFAULT_SIZE.1));
+    let background = match background {
+        Some(bg) if crate::color::parse_background(&bg).is_ok() => bg,
+        Some(bg) => {
+            importer.note(format!("page background '{bg}': only a plain colour carries over; the widget is transparent"));
+            "transparent".to_string()
+        }
+        None => "transparent".to_string(),
+    };
+
+    let overlay_id = uuid::Uuid::new_v4().to_string();
+    let report = HtmlImportReport {
+        overlay_id: overlay_id.clone(),
+        name: name.to_string(),
+        elements: importer.elements.len(),
+        unconverted: importer.unconverted,
+    };
+    let config = json!({
+        "id": overlay_id,
+        "name": name,
+        "widgets": [{
+            "id": uuid::Uuid::new_v4().to_string(),
+            "name": name,
+            "widgetType": "custom",
+            "width": width.round(),
+            "height": height.round(),
+            "background": background,
+            "artboardX": 60,
+            "artboardY": 60,
+            "elements": importer.elements,
+        }],
+    });
+    (config, report)
+}
+
+struct Importer<'a> {
+    base_dir: &'a Path,
+    rules: Vec<Rule>,
+    elements: Vec<Value>,
+    unconverted: Vec<String>,
+    scripts: usize,
+}
+
+impl Importer<'_> {
+    fn note(&mut self, message: String) {
+        if !self.unconverted.contains(&message) {
+            self.unconverted.push(message);
+        }
+    }
+
+    /// Plain rules from a `<style>` block; at-rules (`@media`, `@keyframes`...) are skipped.
+    fn read_stylesheet(&mut self, css: &str) {
+        let css = strip_comments(css);
+        let mut rest = css.as_str();
+        while let Some(open) = rest.find('{') {
+            let prelude = rest[..open].trim();
+            let (body, after) = block(&rest[open + 1..]);
+            rest = after;
+            // `@import url(x);` and the like end at a semicolon, before the next block
+            for import in prelude.split(';').filter_map(|p| p.trim().strip_prefix("@import")) {
+                self.note(format!("stylesheet {}: only <style> blocks in the page are read", import.trim()));
+            }
+            let prelude = prelude.rsplit(';').next().unwrap_or(prelude).trim();
+            if let Some(at_rule) = prelude.strip_prefix('@') {
+                let kind = at_rule.split(|c: char| !c.is_alphanumeric() && c != '-').next().unwrap_or("");
+                self.note(match kind {
+                    "keyframes" => "CSS animations (@keyframes): recreate them on the timeline".to_string(),
+                    "font-face" => "web fonts (@font-face): install the font, then pick it in the editor".to_string(),
+                    other => format!("@{other} rules: not read"),
+                });
+                continue;
+            }
+            match Selectors::compile(prelude) {
+                Ok(selectors) => {
+                    let order = self.rules.len();
+                    self.rules.push(Rule { selectors, declarations: declarations(body), order });
+                }
+                Err(()) => self.note(format!("CSS rule '{prelude}': selector not understood")),
+            }
+        }
+    }
+
+    /// The element's own declarations over what it inherits: matching rules by
+    /// specificity and source order, then its `style` attribute, `!important` last.
+    fn computed(&self, node: &NodeRef, inherited: &Style) -> Style {
+        let mut style = inherited.clone();
+        let Some(el) = node.clone().into_element_ref() else { return style };
+        let mut matched: Vec<_> = self
+            .rules
+            .iter()
+            .filter_map(|rule| {
+                let specificity = rule.selectors.0.iter().filter(|s| s.matches(&el)).map(|s| s.specificity()).max()?;
+                Some((specificity, rule.order, &rule.declarations))
+            })
+            .collect();
+        matched.sort_by_key(|(specificity, order, _)| (*specificity, *order));
+        let inline = el.attributes.borrow().get("style").map(declarations).unwrap_or_default();
+        for important in [false, true] {
+            let layers = matched.iter().map(|(_, _, d)| d.as_slice()).chain([inline.as_slice()]);
+            for (key, value, _) in layers.flatten().filter(|(_, _, i)| *i == important) {
+                expand(&mut style, key, value);
+            }
+        }
+        style
+    }
+
+    /// Convert `node`'s children. `origin` is the page position of the nearest
+    /// positioned ancestor and `container` its size, when known.
+    fn walk(&mut self, node: &NodeRef, origin: (f64, f64), container: (Option<f64>, Option<f64>), inherited: &Style, hidden: bool) {
+        for child in node.children() {
+            let Some(el) = child.as_element() else { continue };
+            let tag = el.name.local.to_string();
+            match tag.as_str() {
+                "script" => {
+                    self.scripts += 1;
+                    continue;
+                }
+                "style" | "link" | "meta" | "title" | "head" | "noscript" | "template" | "br" => continue,
+                _ => {}
+            }
+            let style = self.computed(&child, inherited);
+            let label = label(&child, &tag);
+            let hidden = hidden || style.get("display").is_some_and(|d| d == "none") || style.get("visibility").is_some_and(|v| v == "hidden");
+            let font_size = font_size(&style, inherited);
+            let position = style.get("position").map(String::as_str).unwrap_or("static");
+            let text = inline_text(&child);
+            let leaf = !child.children().any(|c| c.as_element().is_some_and(|e| !is_inline(&e.name.local)));
+            // What a box anchored by `right` or `bottom` without a size is measured by
+            let natural = (leaf && !text.is_empty()).then(|| text_size(&text, font_size, &style));
+
+            let placed = match position {
+                "absolute" | "fixed" => {
+                    let (origin, container) = if position == "fixed" { ((0.0, 0.0), (None, None)) } else { (origin, container) };
+                    let width = length(&style, "width", container.0, font_size)
+                        .or_else(|| Some(container.0? - length(&style, "left", container.0, font_size)? - length(&style, "right", container.0, font_size)?));
+                    let height = length(&style, "height", container.1, font_size)
+                        .or_else(|| Some(container.1? - length(&style, "top", container.1, font_size)? - length(&style, "bottom", container.1, font_size)?));
+                    let left = length(&style, "left", container.0, font_size)
+                        .or_else(|| Some(container.0? - length(&style, "right", container.0, font_size)? - width.or(natural.map(|n| n.0))?))
+                        .unwrap_or(0.0);
+                    let top = length(&style, "top", container.1, font_size)
+                        .or_else(|| Some(container.1? - length(&style, "bottom", container.1, font_size)? - height.or(natural.map(|n| n.1))?))
+                        .unwrap_or(0.0);
+                    Some((origin.0 + left, origin.1 + top, width, height))
+                }
+                // Taken to sit at its container's corner: where the flow puts it isn't worked out
+                "relative" | "sticky" => Some((
+                    origin.0 + length(&style, "left", container.0, font_size).unwrap_or(0.0),
+                    origin.1 + length(&style, "top", container.1, font_size).unwrap_or(0.0),
+                    length(&style, "width", container.0, font_size),
+                    length(&style, "height", container.1, font_size),
+                )),
+                _ => None,
+            };
+
+            let Some((x, y, width, height)) = placed else {
+                if matches!(tag.as_str(), "img" | "video") || (leaf && !text.is_empty()) {
+                    self.note(format!("{label}{}: not absolutely positioned, so where it sits can't be worked out", snippet(&text)));
+                } else {
+                    self.walk(&child, origin, container, &inherited_from(&style), hidden);
+                }
+                continue;
+            };
+
+            let mut unsupported: Vec<&str> = UNSUPPORTED.iter().copied().filter(|p| style.contains_key(*p)).collect();
+            let rotation = match style.get("transform").map(String::as_str) {
+                None | Some("none") => 0.0,
+                Some(t) => t.strip_prefix("rotate(").and_then(|r| r.strip_suffix("deg)")).and_then(|d| d.trim().parse().ok()).unwrap_or_else(|| {
+                    unsupported.push("transform");
+                    0.0
+                }),
+            };
+            let base = |kind: &str, w: f64, h: f64| {
+                json!({
+                    "id": uuid::Uuid::new_v4().to_string(),
+                    "type": kind,
+                    "name": label.clone(),
+                    "x": round(x), "y": round(y), "width": round(w), "height": round(h),
+                    "zIndex": style.get("z-index").and_then(|z| z.parse::<i64>().ok()).unwrap_or(0),
+                    "visible": !hidden, "locked": false,
+                    "opacity": style.get("opacity").and_then(|o| o.parse::<f64>().ok()).unwrap_or(1.0).clamp(0.0, 1.0),
+                    "rotation": rotation,
+                })
+            };
+
+            match tag.as_str() {
+                "img" | "video" => {
+                    let attrs = el.attributes.borrow();
+                    let attr_len = |name: &str| attrs.get(name).and_then(|v| v.trim_end_matches("px").parse::<f64>().ok());
+                    let (w, h) = (width.or_else(|| attr_len("width")), height.or_else(|| attr_len("height")));
+                    let source = attrs.get("src").map(str::to_string).or_else(|| {
+                        child.select_first("source").ok().and_then(|s| s.attributes.borrow().get("src").map(str::to_string))
+                    });
+                    let mut element = base(if tag == "img" { "image" } else { "video" }, w.unwrap_or(200.0), h.unwrap_or(200.0));
+                    if w.is_none() || h.is_none() {
+                        self.note(format!("{label}: no size set; given 200 × 200"));
+                    }
+                    match source.as_deref().map(|s| self.resolve(s)) {
+                        Some(Ok(src)) => element["src"] = src.into(),
+                        Some(Err(message)) => self.note(format!("{label}: {message}")),
+                        None => self.note(format!("{label}: has no src")),
+                    }
+                    if let Some(fit) = style.get("object-fit").filter(|f| matches!(f.as_str(), "contain" | "cover" | "fill")) {
+                        element["objectFit"] = fit.as_str().into();
+                    }
+                    if tag == "video" {
+                        element["loop"] = attrs.contains("loop").into();
+                        element["muted"] = attrs.contains("muted").into();
+                    }
+                    self.push(element, &label, &unsupported);
+                }
+                "svg" | "canvas" | "iframe" | "object" | "embed" => {
+                    self.note(format!("{label}: <{tag}> has no matching element type; rebuild it in the editor"));
+                }
+                _ => {
+                    if has_box(&style) {
+                        match width.zip(height).or_else(|| (!text.is_empty()).then(|| text_size(&text, font_size, &style))) {
+                            Some((w, h)) => {
+                                let mut panel = panel(&style, base("shape", w, h), font_size);
+                                panel["name"] = format!("{label} background").into();
+                                self.push(panel, &label, &[]);
+                            }
+                            None => self.note(format!("{label}: background box has no size; not converted")),
+                        }
+                    }
+                    if leaf && !text.is_empty() {
+                        let mut element = text_element(&style, font_size, &text, width, height, &base);
+                        if width.is_none() || height.is_none() {
+                            self.note(format!("{label}: no size set; estimated from the text"));
+                        }
+                        if child.descendants().filter_map(|d| d.into_element_ref()).any(|e| {
+                            let attrs = e.attributes.borrow();
+                            attrs.contains("style") || attrs.contains("class")
+                        }) {
+                            self.note(format!("{label}: styled parts of the text were given the element's one style"));
+                        }
+                        if let Some(transform) = style.get("text-transform") {
+                            let content = element["content"].as_str().unwrap_or("").to_string();
+                            match transform.as_str() {
+                                "uppercase" => element["content"] = content.to_uppercase().into(),
+                                "lowercase" => element["content"] = content.to_lowercase().into(),
+                                _ => {}
+                            }
+                        }
+                        self.push(element, &label, &unsupported);
+                    } else {
+                        if !unsupported.is_empty() {
+                            self.note(format!("{label}: {} not converted", unsupported.join(", ")));
+                        }
+                        self.walk(&child, (x, y), (width, height), &inherited_from(&style), hidden);
+                    }
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, element: Value, label: &str, unsupported: &[&str]) {
+        if !unsupported.is_empty() {
+            self.note(format!("{label}: {} not converted", unsupported.join(", ")));
+        }
+        self.elements.push(element);
+    }
+
+    /// A `src` as the editor stores it: web and data URLs as they are, local
+    /// files as absolute paths (relative ones resolved against the page's folder).
+    fn resolve(&self, src: &str) -> Result<String, String> {
+        if src.starts_with("http://") || src.starts_with("https://") || src.starts_with("data:") {
+            return Ok(src.to_string());
+        }
+        let path = match src.strip_prefix("file://") {
+            Some(rest) => PathBuf::from(if cfg!(windows) { rest.trim_start_matches('/') } else { rest }),
+            None => self.base_dir.join(src.split(['?', '#']).next().unwrap_or(src)),
+        };
+        let path = path.canonicalize().map_err(|_| format!("'{src}' wasn't found next to the page; pick the file in the editor"))?;
+        // canonicalize gives `\\?\C:\...` on Windows
+        Ok(path.to_string_lossy().trim_start_matches(r"\\?\").to_string())
+    }
+}
+
+/// Whether a box draws a background colour or border of its own.
+fn has_box(style: &Style) -> bool {
+    style.get("background-color").is_some_and(|c| usable_colour(c))
+        || style.get("border").is_some_and(|b| b.as_str() != "none" && b.as_str() != "0")
+}
+
+/// A rectangle for a box's background colour and border, drawn under its contents.
+fn panel(style: &Style, mut panel: Value, font_size: f64) -> Value {
+    let fill = style.get("background-color").filter(|c| usable_colour(c));
+    panel["shapeType"] = "rectangle".into();
+    panel["fill"] = fill.map(String::as_str).unwrap_or("transparent").into();
+    panel["borderRadius"] = round(length(style, "border-radius", None, font_size).unwrap_or(0.0)).into();
+    if let Some(border) = style.get("border").filter(|b| b.as_str() != "none" && b.as_str() != "0") {
+        let is_length = |p: &str| px(Some(&p.to_string()), None, font_size).is_some();
+        let width = border.split_whitespace().find_map(|p| px(Some(&p.to_string()), None, font_size));
+        if let Some(colour) = border.split_whitespace().find(|p| usable_colour(p) && !is_length(p) && !is_border_style(p)) {
+            panel["strokeColor"] = colour.into();
+        }
+        panel["strokeWidth"] = round(width.unwrap_or(1.0)).into();
+    }
+    panel
+}
+
+fn text_element(
+    style: &Style,
+    font_size: f64,
+    text: &str,
+    width: Option<f64>,
+    height: Option<f64>,
+    base: &dyn Fn(&str, f64, f64) -> Value,
+) -> Value {
+    let (w, h) = text_size(text, font_size, style);
+    let mut element = base("text", width.unwrap_or(w), height.unwrap_or(h));
+    element["content"] = text.into();
+    element["fontSize"] = round(font_size).into();
+    element["color"] = style.get("color").filter(|c| usable_colour(c)).map(String::as_str).unwrap_or("#000000").into();
+    if let Some(family) = style.get("font-family").and_then(|f| f.split(',').next()) {
+        element["fontFamily"] = family.trim().trim_matches(['"', '\'']).into();
+    }
+    element["fontWeight"] = match style.get("font-weight").map(String::as_str) {
+        Some("bold" | "bolder") => "700".to_string(),
+        Some("lighter") => "300".to_string(),
+        Some(w) if w.parse::<u32>().is_ok() => w.to_string(),
+        _ => "400".to_string(),
+    }
+    .into();
+    element["textAlign"] = match style.get("text-align").map(String::as_str) {
+        Some("center") => "center",
+        Some("right" | "end") => "right",
+        _ => "left",
+    }
+    .into();
+    if let Some(line_height) = line_height(style, font_size) {
+        element["lineHeight"] = line_height.into();
+    }
+    if let Some(spacing) = length(style, "letter-spacing", None, font_size) {
+        element["letterSpacing"] = round(spacing).into();
+    }
+    if let Some(shadow) = style.get("text-shadow").filter(|s| s.as_str() != "none" && !s.contains("var(")) {
+        element["textShadow"] = shadow.as_str().into();
+    }
+    element
+}
+
+/// Rough box for text with no size set: average glyphs at 0.6 em, one line per `\n`.
+fn text_size(text: &str, font_size: f64, style: &Style) -> (f64, f64) {
+    let lines = text.lines().count().max(1) as f64;
+    let longest = text.lines().map(|l| l.chars().count()).max().unwrap_or(0) as f64;
+    let line_height = line_height(style, font_size).unwrap_or(1.2);
+    ((longest * font_size * 0.6).ceil().max(1.0), (lines * font_size * line_height).ceil())
+}
+
+/// `line-height` as a multiple of the font size.
+fn line_height(style: &Style, font_size: f64) -> Option<f64> {
+    let value = style.get("line-height")?;
+    let ratio = match value.parse::<f64>() {
+        Ok(ratio) => ratio,
+        Err(_) => px(Some(value), Some(font_size), font_size)? / font_size,
+    };
+    Some((ratio * 100.0).round() / 100.0)
+}
+
+fn font_size(style: &Style, inherited: &Style) -> f64 {
+    let parent = inherited.get("font-size").and_then(|f| px(Some(f), None, 16.0)).unwrap_or(16.0);
+    match style.get("font-size") {
+        Some(own) if Some(own) != inherited.get("font-size") => px(Some(own), Some(parent), parent).unwrap_or(parent),
+        _ => parent,
+    }
+}
+
+/// The inheritable part of `style`, font size resolved to px so children's `em` use it.
+fn inherited_from(style: &Style) -> Style {
+    let mut out = inherited(style);
+    let size = font_size(style, &Style::new());
+    out.insert("font-size".to_string(), format!("{size}px"));
+    out
+}
+
+fn inherited(style: &Style) -> Style {
+    style.iter().filter(|(k, _)| INHERITED.contains(&k.as_str())).map(|(k, v)| (k.clone(), v.clone())).collect()
+}
+
+fn length(style: &Style, key: &str, relative_to: Option<f64>, font_size: f64) -> Option<f64> {
+    px(style.get(key), relative_to, font_size)
+}
+
+/// A CSS length in px: px, pt, em, rem, % (of `relative_to`) or a bare number.
+/// `auto`, `calc()`, viewport units and variables give `None`.
+fn px(value: Option<&String>, relative_to: Option<f64>, font_size: f64) -> Option<f64> {
+    let value = value?.trim();
+    let split = value.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+')).unwrap_or(value.len());
+    let number: f64 = value[..split].parse().ok()?;
+    let px = match &value[split..] {
+        "" | "px" => number,
+        "pt" => number * 4.0 / 3.0,
+        "em" => number * font_size,
+        "rem" => number * 16.0,
+        "%" => number / 100.0 * relative_to?,
+        _ => return None,
+    };
+    px.is_finite().then_some(px)
+}
+
+fn round(n: f64) -> f64 {
+    (n * 100.0).round() / 100.0
+}
+
+fn num(element: &Value, key: &str) -> f64 {
+    element.get(key).and_then(|v| v.as_f64()).unwrap_or(0.0)
+}
+
+fn usable_colour(value: &str) -> bool {
+    !value.contains("var(") && !value.contains("gradient(") && !value.contains("url(") && value != "transparent" && value != "none"
+}
+
+fn is_border_style(word: &str) -> bool {
+    matches!(word, "solid" | "dashed" | "dotted" | "double" | "groove" | "ridge" | "inset" | "outset" | "none" | "hidden")
+}
+
+fn is_inline(tag: &str) -> bool {
+    matches!(tag, "span" | "b" | "strong" | "i" | "em" | "u" | "small" | "big" | "sub" | "sup" | "a" | "font" | "mark" | "br" | "label" | "code")
+}
+
+/// Readable text of an element: whitespace collapsed per line, `<br>` as a line break.
+fn inline_text(node: &NodeRef) -> String {
+    let mut raw = String::new();
+    for d in node.descendants() {
+        if let Some(text) = d.as_text() {
+            raw.push_str(&text.borrow());
+        } else if d.as_element().is_some_and(|e| &*e.name.local == "br") {
+            raw.push('\n');
+        }
+    }
+    let lines: Vec<String> = raw.split('\n').map(|l| l.split_whitespace().collect::<Vec<_>>().join(" ")).collect();
+    lines.join("\n").trim().to_string()
+}
+
+/// `div#title.big` style name for an element.
+fn label(node: &NodeRef, tag: &str) -> String {
+    let Some(el) = node.as_element() else { return tag.to_string() };
+    let attrs = el.attributes.borrow();
+    let mut label = tag.to_string();
+    if let Some(id) = attrs.get("id").filter(|id| !id.is_empty()) {
+        label.push('#');
+        label.push_str(id);
+    }
+    for class in attrs.get("class").unwrap_or("").split_whitespace().take(2) {
+        label.push('.');
+        label.push_str(class);
+    }
+    label
+}
+
+fn snippet(text: &str) -> String {
+    if text.is_empty() {
+        return String::new();
+    }
+    let short: String = text.chars().take(30).collect();
+    let more = if text.chars().count() > 30 { "…" } else { "" };
+    format!(" ('{}{more}')", short.replace('\n', " "))
+}
+
+/// Set a declaration, spreading the `inset` and `background` shorthands over
+/// the longhands the importer reads.
+fn expand(style: &mut Style, key: &str, value: &str) {
+    match key {
+        "inset" => {
+            let parts: Vec<&str> = value.split_whitespace().collect();
+            let pick = |i: usize| match parts.len() {
+                1 => parts[0],
+                2 => parts[i % 2],
+                3 => parts[if i == 3 { 1 } else { i }],
+                _ => parts.get(i).copied().unwrap_or("auto"),
+            };
+            for (i, side) in ["top", "right", "bottom", "left"].into_iter().enumerate() {
+                style.insert(side.to_string(), pick(i).to_string());
+            }
+        }
+        "background" if !value.contains("url(") && !value.contains("gradient(") => {
+            style.insert("background-color".to_string(), value.to_string());
+        }
+        "background" => {
+            style.insert("background-image".to_string(), value.to_string());
+        }
+        _ => {
+            style.insert(key.to_string(), value.to_string());
+        }
+    }
+}
+
+fn strip_comments(css: &str) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        out.push_str(&rest[..start]);
+        rest = rest[start + 2..].find("*/").map_or("", |end| &rest[start + 2 + end + 2..]);
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The body of a block whose `{` was just consumed, and what follows its closing brace.
+fn block(css: &str) -> (&str, &str) {
+    let mut depth = 0;
+    for (i, c) in css.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => return (&css[..i], &css[i + 1..]),
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    (css, "")
+}
+
+/// `key: value` pairs of a declaration block, lowercased keys, with whether each is `!important`.
+/// Semicolons inside quotes or parentheses (`url(data:...;base64,...)`) don't split.
+fn declarations(block: &str) -> Vec<(String, String, bool)> {
+    let mut parts = Vec::new();
+    let (mut depth, mut quote, mut start) = (0, None, 0);
+    for (i, c) in block.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('(', None) => depth += 1,
+            (')', None) => depth -= 1,
+            (';', None) if depth <= 0 => {
+                parts.push(&block[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&block[start..]);
+    parts
+        .into_iter()
+        .filter_map(|part| {
+            let (key, value) = part.split_once(':')?;
+            let value = value.trim();
+            let (value, important) = match value.strip_suffix("!important") {
+                Some(v) => (v.trim(), true),
+                None => (value, false),
+            };
+            let key = key.trim().to_ascii_lowercase();
+            (!key.is_empty() && !value.is_empty()).then(|| (key, value.to_string(), important))
+        })
+        .collect()
+}
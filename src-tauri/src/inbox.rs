@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+
+use crate::{control, obs_server};
+
+// ---------------------------------------------------------------------------
+// Webhook inbox: chat bots POST `{"type": ..., "payload": ...}` to `/inbox`,
+// and every widget whose `inbox` rules name that type reacts to it
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize)]
+pub struct InboxEvent {
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Forwarded to the page with the trigger; any JSON. An object's fields
+    /// also fill the widget's `{name}` text tokens.
+    #[serde(default)]
+    pub payload: Value,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InboxAction {
+    /// Play the widget like `POST /api/widget/{id}/trigger`.
+    #[default]
+    Trigger,
+    /// Drop queued alerts like `POST /api/widget/{id}/queue/clear`.
+    ClearQueue,
+    /// Hide the widget like `POST /api/widget/{id}/hide`.
+    Hide,
+}
+
+/// One entry of a widget's `inbox` list, e.g. `{"event": "!hydrate"}`.
+#[derive(Debug, Deserialize)]
+struct InboxRule {
+    event: String,
+    #[serde(default)]
+    action: InboxAction,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Delivery {
+    pub widget_id: String,
+    pub action: InboxAction,
+    /// Open pages of the widget the action reached.
+    pub pages: usize,
+}
+
+fn rules(widget: &Value) -> Result<Vec<InboxRule>, String> {
+    match widget.get("inbox") {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(v) => serde_json::from_value(v.clone()).map_err(|e| format!("invalid inbox rules: {e}")),
+    }
+}
+
+/// Event types match case-insensitively and ignore surrounding spaces, so a
+/// bot relaying `!Hydrate ` from chat still hits a `!hydrate` rule.
+fn matches(rule: &InboxRule, kind: &str) -> bool {
+    rule.event.trim().eq_ignore_ascii_case(kind.trim())
+}
+
+/// Event types a widget reacts to, in rule order without repeats (bad rules count as none).
+pub fn event_types(widget: &Value) -> Vec<String> {
+    let mut types: Vec<String> = Vec::new();
+    for rule in rules(widget).unwrap_or_default() {
+        let kind = rule.event.trim();
+        if !types.iter().any(|t| t.eq_ignore_ascii_case(kind)) {
+            types.push(kind.to_string());
+        }
+    }
+    types
+}
+
+/// Every widget's inbox rules must parse and name an event type.
+pub fn validate(config: &Value) -> Result<(), String> {
+    let widgets = config.get("widgets").and_then(|w| w.as_array()).into_iter().flatten();
+    for widget in widgets {
+        let name = widget.get("name").and_then(|v| v.as_str()).unwrap_or("unnamed");
+        let rules = rules(widget).map_err(|e| format!("Widget '{name}': {e}"))?;
+        if rules.iter().any(|rule| rule.event.trim().is_empty()) {
+            return Err(format!("Widget '{name}': inbox rules need an event type"));
+        }
+    }
+    Ok(())
+}
+
+/// Run the matching actions of every served widget (the newest copy of a
+/// duplicated id, as OBS sees it). Triggered widgets share one start time.
+pub fn deliver(event: &InboxEvent) -> Vec<Delivery> {
+    let at = control::start_epoch();
+    let values = if event.payload.is_object() { event.payload.clone() } else { Value::Null };
+    let mut seen = HashSet::new();
+    let mut deliveries = Vec::new();
+    for (_, widget) in obs_server::all_widgets() {
+        let Some(id) = widget.get("id").and_then(|v| v.as_str()) else { continue };
+        if !seen.insert(id.to_string()) {
+            continue;
+        }
+        let actions: Vec<InboxAction> = match rules(&widget) {
+            Ok(rules) => rules.iter().filter(|rule| matches(rule, &event.kind)).map(|rule| rule.action).collect(),
+            Err(e) => {
+                log::warn!("Skipping widget {id} for inbox event: {e}");
+                continue;
+            }
+        };
+        for action in actions {
+            let pages = match action {
+                InboxAction::Trigger => control::trigger(
+                    id,
+                    serde_json::json!({ "event": event.kind, "payload": event.payload, "values": values, "at": at }),
+                ),
+                InboxAction::ClearQueue => control::send(id, serde_json::json!({ "action": "clear_queue" })),
+                InboxAction::Hide => control::send(id, control::hide_message()),
+            };
+            deliveries.push(Delivery { widget_id: id.to_string(), action, pages });
+        }
+    }
+    deliveries
+}
+
+/// Compare a presented token without leaking how much of it matched.
+pub fn token_matches(presented: &str, expected: &str) -> bool {
+    presented.len() == expected.len()
+        && presented.bytes().zip(expected.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
@@ -0,0 +1,118 @@
+use serde_json::Value;
+
+// ---------------------------------------------------------------------------
+// RFC 6902 JSON Patch (`[{"op": "replace", "path": "/widgets/0/elements/2/x",
+// "value": 40}]`), for editing a stored config without resending all of it
+// ---------------------------------------------------------------------------
+
+/// Most operations one patch may carry.
+const MAX_OPS: usize = 1000;
+
+/// Apply every operation in `patch` to `doc`, in order. All or nothing: when
+/// one fails (a path that doesn't exist, a `test` that doesn't match) `doc` is
+/// left as it was and the error says which operation and why.
+pub fn apply(doc: &mut Value, patch: &Value) -> Result<(), String> {
+    let ops = patch.as_array().ok_or("A patch must be a list of operations")?;
+    if ops.len() > MAX_OPS {
+        return Err(format!("A patch can carry at most {MAX_OPS} operations, this one has {}", ops.len()));
+    }
+    let mut patched = doc.clone();
+    for (i, op) in ops.iter().enumerate() {
+        apply_op(&mut patched, op).map_err(|e| format!("Patch operation {i}: {e}"))?;
+    }
+    *doc = patched;
+    Ok(())
+}
+
+fn apply_op(doc: &mut Value, op: &Value) -> Result<(), String> {
+    let field = |key: &str| op.get(key).and_then(|v| v.as_str()).ok_or_else(|| format!("needs a string `{key}`"));
+    let value = || op.get("value").cloned().ok_or_else(|| "needs a `value`".to_string());
+    let path = field("path")?;
+    match field("op")? {
+        "add" => add(doc, path, value()?),
+        "remove" => remove(doc, path).map(drop),
+        "replace" => {
+            *doc.pointer_mut(path).ok_or_else(|| missing(path))? = value()?;
+            Ok(())
+        }
+        "move" => {
+            let from = field("from")?;
+            if path.strip_prefix(from).is_some_and(|rest| rest.starts_with('/')) {
+                return Err(format!("can't move {from} into itself"));
+            }
+            let moved = remove(doc, from)?;
+            add(doc, path, moved)
+        }
+        "copy" => {
+            let from = field("from")?;
+            let copied = doc.pointer(from).ok_or_else(|| missing(from))?.clone();
+            add(doc, path, copied)
+        }
+        "test" => match doc.pointer(path) {
+            Some(current) if *current == value()? => Ok(()),
+            Some(_) => Err(format!("test failed: {path} has changed")),
+            None => Err(missing(path)),
+        },
+        other => Err(format!("unknown op '{other}'; use add, remove, replace, move, copy or test")),
+    }
+}
+
+fn missing(path: &str) -> String {
+    format!("nothing at {path}")
+}
+
+/// The parent's pointer and the last reference token, unescaped (`~1` is `/`, `~0` is `~`).
+fn split(path: &str) -> Result<(&str, String), String> {
+    let (parent, token) = path.rsplit_once('/').ok_or_else(|| format!("'{path}' isn't a JSON pointer"))?;
+    if !parent.is_empty() && !parent.starts_with('/') {
+        return Err(format!("'{path}' isn't a JSON pointer"));
+    }
+    Ok((parent, token.replace("~1", "/").replace("~0", "~")))
+}
+
+/// An array index token: digits without leading zeros.
+fn index(token: &str, path: &str) -> Result<usize, String> {
+    let digits = !token.is_empty() && token.chars().all(|c| c.is_ascii_digit());
+    if !digits || (token.len() > 1 && token.starts_with('0')) {
+        return Err(format!("{path}: '{token}' isn't an array index"));
+    }
+    token.parse().map_err(|_| format!("{path}: '{token}' isn't an array index"))
+}
+
+fn add(doc: &mut Value, path: &str, value: Value) -> Result<(), String> {
+    if path.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+    let (parent, token) = split(path)?;
+    match doc.pointer_mut(parent).ok_or_else(|| missing(parent))? {
+        Value::Object(map) => {
+            map.insert(token, value);
+        }
+        Value::Array(list) if token == "-" => list.push(value),
+        Value::Array(list) => {
+            let i = index(&token, path)?;
+            if i > list.len() {
+                return Err(format!("{path}: the list only has {} items", list.len()));
+            }
+            list.insert(i, value);
+        }
+        _ => return Err(format!("{parent} is neither an object nor a list")),
+    }
+    Ok(())
+}
+
+fn remove(doc: &mut Value, path: &str) -> Result<Value, String> {
+    if path.is_empty() {
+        return Err("can't remove the whole config".to_string());
+    }
+    let (parent, token) = split(path)?;
+    match doc.pointer_mut(parent).ok_or_else(|| missing(parent))? {
+        Value::Object(map) => map.remove(&token).ok_or_else(|| missing(path)),
+        Value::Array(list) => {
+            let i = index(&token, path)?;
+            (i < list.len()).then(|| list.remove(i)).ok_or_else(|| missing(path))
+        }
+        _ => Err(missing(path)),
+    }
+}
@@ -1,7 +1,73 @@
+mod autosave;
+mod canvas;
+mod color;
+mod config_diff;
+mod control;
 mod db;
+mod error;
+mod export;
+mod hotkeys;
+mod html_import;
+mod inbox;
+mod json_patch;
+mod library;
+mod limits;
+mod normalize;
 mod obs_server;
+mod playback;
+mod rate_limit;
+mod segments;
+mod settings;
+mod src_refresh;
+mod style_presets;
+mod timeline_check;
+mod tray;
+mod validation;
+mod window_state;
 
+use error::CommandError;
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+use tauri_plugin_log::{RotationStrategy, Target, TargetKind};
+
+const LOG_FILE_NAME: &str = "open-overlay";
+const LOG_MAX_FILE_SIZE: u128 = 5 * 1024 * 1024;
+const LOG_KEEP_FILES: usize = 5;
+
+/// Event sent to the frontend with a `db::OverlayChange` whenever a saved overlay changes.
+const OVERLAY_CHANGED_EVENT: &str = "overlay-changed";
+const SERVER_STATUS_EVENT: &str = "server-status";
+/// Event sent with an `autosave::Autosaved` after each autosave write (or refused write).
+const AUTOSAVED_EVENT: &str = "autosaved";
+
+/// Launch flag (or environment variable, set to anything but `0`) that opens
+/// the editor and database without starting the OBS server.
+const SAFE_MODE_ARG: &str = "--safe-mode";
+const SAFE_MODE_ENV: &str = "OPEN_OVERLAY_SAFE_MODE";
+
+fn safe_mode_requested() -> bool {
+    std::env::args().any(|arg| arg == SAFE_MODE_ARG) || std::env::var(SAFE_MODE_ENV).is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Tokio runtime hosting the OBS HTTP server; lives for the whole app lifetime.
+static SERVER_RUNTIME: OnceCell<tokio::runtime::Runtime> = OnceCell::new();
+
+/// Restart the OBS server on its runtime (e.g. after LAN mode changed).
+pub(crate) fn restart_obs_server() {
+    match SERVER_RUNTIME.get() {
+        Some(rt) => {
+            rt.spawn(obs_server::restart_obs_server());
+        }
+        None => log::warn!("OBS server runtime not started; restart skipped"),
+    }
+}
+
+fn log_dir(app: &tauri::AppHandle) -> Result<std::path::PathBuf, tauri::Error> {
+    Ok(app.path().app_data_dir()?.join("logs"))
+}
 
 // ---------------------------------------------------------------------------
 // Tauri Commands (called from frontend via invoke())
@@ -12,16 +78,20 @@ pub struct SaveOverlayArgs {
     pub id: String,
     pub name: String,
     pub config: serde_json::Value,
+    /// `updated_at` of the overlay as the client last loaded it. When present the
+    /// save is rejected with a `conflict` error if the stored row is newer.
+    #[serde(default)]
+    pub updated_at: Option<String>,
 }
 
 #[tauri::command]
-fn list_overlays() -> Result<Vec<db::OverlaySummary>, String> {
-    db::list_overlays().map_err(|e| e.to_string())
+fn list_overlays() -> Result<Vec<db::OverlaySummary>, CommandError> {
+    Ok(db::list_overlays()?)
 }
 
 #[tauri::command]
-fn get_overlay(id: String) -> Result<Option<serde_json::Value>, String> {
-    match db::get_overlay(&id).map_err(|e| e.to_string())? {
+fn get_overlay(id: String) -> Result<Option<serde_json::Value>, CommandError> {
+    match db::get_overlay(&id)? {
         Some(row) => {
             let config_val: serde_json::Value =
                 serde_json::from_str(&row.config).unwrap_or(serde_json::Value::Null);
@@ -36,20 +106,608 @@ fn get_overlay(id: String) -> Result<Option<serde_json::Value>, String> {
     }
 }
 
+/// Every widget background must be a colour (or image settings) the renderer understands.
+fn check_backgrounds(config: &serde_json::Value) -> Result<(), String> {
+    let widgets = config.get("widgets").and_then(|w| w.as_array()).into_iter().flatten();
+    for widget in widgets {
+        if let Some(bg) = widget.get("background").filter(|v| !v.is_null()) {
+            let name = widget.get("name").and_then(|v| v.as_str()).unwrap_or("unnamed");
+            color::parse_widget_background(bg).map_err(|e| format!("Widget '{name}': {e}"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Slugs must be valid and used once within the config; with `overlay_id`,
+/// also not by a widget of another stored overlay.
+fn check_slugs(overlay_id: Option<&str>, config: &serde_json::Value) -> Result<(), String> {
+    let widgets = config.get("widgets").and_then(|w| w.as_array()).into_iter().flatten();
+    let mut slugs = std::collections::HashSet::new();
+    for slug in widgets.filter_map(obs_server::widget_slug) {
+        obs_server::validate_slug(slug)?;
+        if !slugs.insert(slug) {
+            return Err(format!("Slug '{slug}' is used by more than one widget"));
+        }
+    }
+    let Some(overlay_id) = overlay_id.filter(|_| !slugs.is_empty()) else { return Ok(()) };
+    for (summary, widget) in obs_server::all_widgets() {
+        match obs_server::widget_slug(&widget) {
+            Some(slug) if summary.id != overlay_id && slugs.contains(slug) => {
+                return Err(format!("Slug '{slug}' is already used by a widget in '{}'", summary.name));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Outcome of every check a save of overlay `id` runs, in order. Without an
+/// `id` (a library being replaced) slugs aren't checked against the stored
+/// overlays.
+fn config_checks(id: Option<&str>, config: &serde_json::Value, config_str: &str) -> Vec<Result<(), CommandError>> {
+    let invalid = |details| CommandError::InvalidConfig { details };
+    vec![
+        settings::config_limits().check(config, config_str),
+        check_backgrounds(config).map_err(invalid),
+        check_slugs(id, config).map_err(invalid),
+        canvas::WorkspaceCanvas::from_config(config).map(|_| ()).map_err(invalid),
+        inbox::validate(config).map_err(invalid),
+        style_presets::validate(config).map_err(invalid),
+        obs_server::validate_audio_track(config).map_err(invalid),
+        segments::validate(config).map_err(invalid),
+    ]
+}
+
+/// Serialize a config for storage, rejecting it with a `Limit` error if it's
+/// over the configured size, element count or nesting depth, or `InvalidConfig` if a widget
+/// background, slug or inbox rule, the workspace canvas, a style preset, the
+/// audio track settings or a timeline segment are invalid.
+fn checked_config(id: &str, config: &serde_json::Value) -> Result<String, CommandError> {
+    let config_str = serde_json::to_string(config).map_err(CommandError::other)?;
+    config_checks(Some(id), config, &config_str)
+        .into_iter()
+        .collect::<Result<(), CommandError>>()
+        .inspect_err(|e| log::warn!("Rejected save of overlay {id}: {e}"))?;
+    Ok(config_str)
+}
+
+#[tauri::command]
+fn save_overlay(args: SaveOverlayArgs) -> Result<String, CommandError> {
+    let config_str = checked_config(&args.id, &args.config)?;
+    autosave::save_instead(&args.id, || {
+        let Some(expected) = args.updated_at.as_deref() else {
+            return Ok(db::upsert_overlay(&args.id, &args.name, &config_str)?);
+        };
+        match db::update_overlay_if_unmodified(&args.id, &args.name, &config_str, expected)? {
+            db::SaveOutcome::Saved { updated_at } => Ok(updated_at),
+            db::SaveOutcome::Conflict { stored_updated_at } => Err(CommandError::Conflict {
+                message: format!("overlay '{}' was modified at {stored_updated_at}, after it was loaded", args.id),
+            }),
+        }
+    })
+}
+
+/// Tries at a patch that keeps losing the race with other saves before it gives up.
+const PATCH_ATTEMPTS: usize = 3;
+
+/// Apply an RFC 6902 JSON Patch to the stored config of overlay `id`, for small
+/// edits without resending the whole config. The result goes through every
+/// save check. A save landing meanwhile makes it re-apply to the newer config;
+/// add `test` operations to refuse edits made since it was loaded instead.
+/// Returns the new `updated_at`.
+#[tauri::command]
+fn patch_overlay(id: String, patch: serde_json::Value) -> Result<String, CommandError> {
+    autosave::flush(&id);
+    for _ in 0..PATCH_ATTEMPTS {
+        let row = db::get_overlay(&id)?.ok_or_else(|| CommandError::not_found(format!("Overlay '{id}' not found")))?;
+        let mut config: serde_json::Value = serde_json::from_str(&row.config).map_err(CommandError::other)?;
+        json_patch::apply(&mut config, &patch).map_err(|details| CommandError::InvalidConfig { details })?;
+        if !config.is_object() {
+            return Err(CommandError::InvalidConfig { details: "Overlay config must be a JSON object".to_string() });
+        }
+        if config.get("id").and_then(|v| v.as_str()).is_some_and(|patched| patched != id) {
+            return Err(CommandError::InvalidConfig { details: "A patch can't change the overlay id".to_string() });
+        }
+        let name = config.get("name").and_then(|v| v.as_str()).unwrap_or(&row.name).to_string();
+        let config_str = checked_config(&id, &config)?;
+        match db::replace_config_if_unchanged(&id, &name, &row.config, &config_str)? {
+            Some(db::SaveOutcome::Saved { updated_at }) => return Ok(updated_at),
+            Some(db::SaveOutcome::Conflict { .. }) => continue,
+            None => return Err(CommandError::not_found(format!("Overlay '{id}' not found"))),
+        }
+    }
+    Err(CommandError::Conflict { message: format!("overlay '{id}' kept changing while the patch was applied") })
+}
+
+/// Dry run of a save for previewing an import: every save-time check plus
+/// element checks (ids, geometry, types, asset files), reported without writing.
+#[tauri::command]
+fn validate_config(config: serde_json::Value) -> Result<validation::ValidationReport, CommandError> {
+    if !config.is_object() {
+        return Err(CommandError::InvalidConfig { details: "Overlay config must be a JSON object".to_string() });
+    }
+    let config_str = serde_json::to_string(&config).map_err(CommandError::other)?;
+    let id = config.get("id").and_then(|v| v.as_str()).unwrap_or("");
+    let save_errors = config_checks(Some(id), &config, &config_str).into_iter().filter_map(Result::err).map(|e| e.to_string()).collect();
+    Ok(validation::report(&config, save_errors))
+}
+
+/// Save the editor's current state as a brand-new overlay, leaving the one it
+/// was opened from untouched. Widgets get fresh ids (and lose their slugs) so
+/// their OBS URLs don't collide with the original's. Returns the new overlay id.
+#[tauri::command]
+fn save_overlay_as(name: String, mut config: serde_json::Value) -> Result<String, CommandError> {
+    let Some(obj) = config.as_object_mut() else {
+        return Err(CommandError::InvalidConfig { details: "Overlay config must be a JSON object".to_string() });
+    };
+    let id = uuid::Uuid::new_v4().to_string();
+    obj.insert("id".into(), id.clone().into());
+    obj.insert("name".into(), name.clone().into());
+    if let Some(widgets) = obj.get_mut("widgets").and_then(|w| w.as_array_mut()) {
+        for widget in widgets.iter_mut().filter_map(|w| w.as_object_mut()) {
+            widget.insert("id".into(), uuid::Uuid::new_v4().to_string().into());
+            // Slugs are unique per install; the original keeps them
+            widget.remove("slug");
+        }
+    }
+    let config_str = checked_config(&id, &config)?;
+    db::upsert_overlay(&id, &name, &config_str)?;
+    log::info!("Saved copy as new overlay {id} ({name})");
+    Ok(id)
+}
+
+/// Queue a debounced save; rapid calls for the same overlay coalesce into one
+/// write. With `updated_at` it's skipped if the overlay was modified since;
+/// either way the outcome arrives as an `autosaved` event.
+#[tauri::command]
+fn queue_autosave(args: SaveOverlayArgs) -> Result<(), CommandError> {
+    let config_str = checked_config(&args.id, &args.config)?;
+    autosave::queue(&args.id, &args.name, config_str, args.updated_at);
+    Ok(())
+}
+
+#[tauri::command]
+fn delete_overlay(id: String) -> Result<(), CommandError> {
+    Ok(autosave::save_instead(&id, || db::delete_overlay(&id))?)
+}
+
+/// What changed between two overlay configs (e.g. before restoring an older copy).
+#[tauri::command]
+fn diff_configs(from: serde_json::Value, to: serde_json::Value) -> config_diff::ConfigDiff {
+    config_diff::diff(&from, &to)
+}
+
+/// Verify the database file and every overlay's config after a crash.
+#[tauri::command]
+fn check_database() -> Result<db::IntegrityReport, CommandError> {
+    Ok(db::check_database()?)
+}
+
+/// Quarantine overlays whose config can't be parsed so the rest still load.
+#[tauri::command]
+fn repair_database() -> Result<db::RepairReport, CommandError> {
+    let report = db::repair_database()?;
+    for row in &report.quarantined {
+        log::warn!("Quarantined overlay {} ({}): {}", row.id, row.name, row.error);
+    }
+    Ok(report)
+}
+
+/// Flush pending autosaves, then `VACUUM` the database; reports the file size
+/// before and after.
+#[tauri::command]
+async fn compact_database() -> Result<db::CompactReport, CommandError> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let flushed = autosave::flush_all();
+        if flushed > 0 {
+            log::info!("Flushed {flushed} pending autosave(s) before compacting");
+        }
+        let report = db::compact_database()?;
+        log::info!("Compacted database: {} -> {} bytes", report.before_bytes, report.after_bytes);
+        Ok(report)
+    })
+    .await
+    .map_err(CommandError::other)?
+}
+
+fn stored_config(id: &str) -> Result<(db::OverlayRow, serde_json::Value), CommandError> {
+    let row = db::get_overlay(id)?.ok_or_else(|| CommandError::not_found(format!("Overlay '{id}' not found")))?;
+    let config = serde_json::from_str(&row.config).map_err(|e| CommandError::InvalidConfig {
+        details: format!("Overlay '{id}' has an unreadable config: {e}"),
+    })?;
+    Ok((row, config))
+}
+
+/// Give every element of a saved overlay a fresh unique id (and its widgets,
+/// where theirs are missing or shared), moving keyframe states and mask links
+/// to the new ids, then re-save it. Pending autosaves are flushed first so
+/// they can't overwrite the result.
+#[tauri::command]
+fn normalize_overlay(id: String) -> Result<normalize::NormalizeReport, CommandError> {
+    autosave::flush_all();
+    let (row, mut config) = stored_config(&id)?;
+    let taken: std::collections::HashSet<String> = obs_server::all_widgets()
+        .into_iter()
+        .filter(|(summary, _)| summary.id != id)
+        .filter_map(|(_, widget)| widget.get("id").and_then(|v| v.as_str()).map(str::to_string))
+        .collect();
+    let mut report = normalize::normalize(&mut config, &taken);
+    let config_str = checked_config(&id, &config)?;
+    match db::update_overlay_if_unmodified(&id, &row.name, &config_str, &row.updated_at)? {
+        db::SaveOutcome::Saved { updated_at } => report.updated_at = updated_at,
+        db::SaveOutcome::Conflict { stored_updated_at } => {
+            return Err(CommandError::Conflict {
+                message: format!("overlay '{id}' was modified at {stored_updated_at}, while normalizing"),
+            })
+        }
+    }
+    log::info!(
+        "Normalized overlay {id}: {} element ids, {} widget ids replaced",
+        report.elements,
+        report.widgets.len()
+    );
+    Ok(report)
+}
+
+/// List keyframe states in a saved overlay that name elements it doesn't
+/// have, and elements its keyframes never mention. Pending autosaves are
+/// flushed first, so the check sees the latest edits.
+#[tauri::command]
+fn check_timeline_consistency(id: String) -> Result<timeline_check::TimelineReport, CommandError> {
+    autosave::flush(&id);
+    let (_, config) = stored_config(&id)?;
+    Ok(timeline_check::check(&config))
+}
+
+/// Remove the keyframe states `check_timeline_consistency` reports as
+/// orphaned and re-save the overlay (left as it is when there are none).
+#[tauri::command]
+fn fix_timeline_consistency(id: String) -> Result<timeline_check::TimelineReport, CommandError> {
+    autosave::flush(&id);
+    let (row, mut config) = stored_config(&id)?;
+    let mut report = timeline_check::prune(&mut config);
+    if report.pruned == 0 {
+        return Ok(report);
+    }
+    let config_str = checked_config(&id, &config)?;
+    match db::update_overlay_if_unmodified(&id, &row.name, &config_str, &row.updated_at)? {
+        db::SaveOutcome::Saved { updated_at } => report.updated_at = Some(updated_at),
+        db::SaveOutcome::Conflict { stored_updated_at } => {
+            return Err(CommandError::Conflict {
+                message: format!("overlay '{id}' was modified at {stored_updated_at}, while fixing its timeline"),
+            })
+        }
+    }
+    log::info!("Pruned {} orphaned keyframe states from overlay {id}", report.pruned);
+    Ok(report)
+}
+
+/// Render a widget's OBS page here and report the likely reasons it would
+/// show up blank: missing files or fonts, empty or off-canvas content,
+/// keyframes for elements that don't exist, an oversized page.
+#[tauri::command]
+fn test_render(id: String) -> Result<validation::RenderReport, CommandError> {
+    let widget = obs_server::find_widget(&id).ok_or_else(|| CommandError::not_found(format!("Widget '{id}' not found")))?;
+    // Asked for after fixing things, so fonts installed just now count
+    validation::forget_installed_fonts();
+    Ok(validation::render_report(&widget))
+}
+
+/// Measure what makes a widget's page heavy: element, filter and keyframe
+/// counts, group nesting and how long its HTML takes to build.
+#[tauri::command]
+fn profile_widget(id: String) -> Result<validation::WidgetProfile, CommandError> {
+    let widget = obs_server::find_widget(&id).ok_or_else(|| CommandError::not_found(format!("Widget '{id}' not found")))?;
+    Ok(validation::profile_widget(&widget))
+}
+
+/// Widget ids shared by several workspaces; only the newest copy is served.
+#[tauri::command]
+fn check_duplicate_widget_ids() -> Vec<obs_server::DuplicateWidget> {
+    obs_server::duplicate_widget_ids()
+}
+
+/// Elements across all workspaces that reference an image, video or font;
+/// check before deleting the file.
+#[tauri::command]
+fn find_asset_usages(asset: String) -> Vec<obs_server::AssetUsage> {
+    obs_server::find_asset_usages(&asset)
+}
+
+/// Elements of one type (`path`, `sprite`, ...) across all workspaces,
+/// grouped by workspace; check before retiring a feature.
+#[tauri::command]
+fn find_elements_by_type(kind: String) -> Vec<obs_server::OverlayElementHits> {
+    obs_server::find_elements_by_type(&kind)
+}
+
+/// Elements across all workspaces that use a font family, grouped by workspace.
+#[tauri::command]
+fn find_elements_using_font(family: String) -> Vec<obs_server::OverlayElementHits> {
+    obs_server::find_elements_using_font(&family)
+}
+
+#[tauri::command]
+fn get_config_limits() -> limits::ConfigLimits {
+    settings::config_limits()
+}
+
+#[tauri::command]
+fn set_config_limits(limits: limits::ConfigLimits) -> Result<(), CommandError> {
+    Ok(settings::set_config_limits(&limits)?)
+}
+
+#[tauri::command]
+fn get_rate_limit() -> rate_limit::RateLimit {
+    settings::rate_limit()
+}
+
+/// Save the widget request limit; the running server picks it up immediately.
+#[tauri::command]
+fn set_rate_limit(limit: rate_limit::RateLimit) -> Result<(), CommandError> {
+    settings::set_rate_limit(&limit)?;
+    obs_server::configure_rate_limit(limit);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_playback_defaults() -> playback::PlaybackDefaults {
+    settings::playback_defaults()
+}
+
+/// Save the `autoplay`/`loop` defaults; open widget pages pick them up on their
+/// next reload check, since they change the rendered widgets' hashes.
+#[tauri::command]
+fn set_playback_defaults(defaults: playback::PlaybackDefaults) -> Result<(), CommandError> {
+    Ok(settings::set_playback_defaults(&defaults)?)
+}
+
+/// The overlay open when the editor last closed, or the most recently updated
+/// one if that was deleted (`None` with no overlays at all).
+#[tauri::command]
+fn get_last_overlay() -> Result<Option<String>, CommandError> {
+    if let Some(id) = settings::last_overlay() {
+        if db::get_overlay(&id)?.is_some() {
+            return Ok(Some(id));
+        }
+    }
+    Ok(db::list_overlays()?.into_iter().next().map(|overlay| overlay.id))
+}
+
+#[tauri::command]
+fn set_last_overlay(id: String) -> Result<(), CommandError> {
+    Ok(settings::set_last_overlay(&id)?)
+}
+
+/// Overlay libraries, each with its own database; the default one is always listed first.
+#[tauri::command]
+fn list_profiles() -> Vec<db::ProfileInfo> {
+    db::list_profiles()
+}
+
+#[tauri::command]
+fn create_profile(name: String) -> Result<db::ProfileInfo, CommandError> {
+    db::create_profile(&name)
+}
+
+/// Switch the whole app (editor, OBS server, hotkeys) to another profile.
+/// Pending autosaves are written to the old profile first. Settings live in
+/// each profile's database, so the server restarts to pick up its LAN mode
+/// and rate limit.
+#[tauri::command]
+fn switch_profile(app: tauri::AppHandle, name: String) -> Result<db::ProfileInfo, CommandError> {
+    if name == db::active_profile() {
+        return db::list_profiles()
+            .into_iter()
+            .find(|p| p.active)
+            .ok_or_else(|| CommandError::not_found(format!("Profile '{name}' does not exist")));
+    }
+    hotkeys::unregister_all(&app);
+    let (_, profile) = autosave::flush_all_then(|| db::switch_profile(&name));
+    hotkeys::register_saved(&app);
+    let profile = profile?;
+    obs_server::profile_changed();
+    restart_obs_server();
+    Ok(profile)
+}
+
+/// Token bots must send to `POST /inbox`, created on first request.
 #[tauri::command]
-fn save_overlay(args: SaveOverlayArgs) -> Result<(), String> {
-    let config_str = serde_json::to_string(&args.config).map_err(|e| e.to_string())?;
-    db::upsert_overlay(&args.id, &args.name, &config_str).map_err(|e| e.to_string())
+fn get_inbox_token() -> Result<String, CommandError> {
+    Ok(settings::inbox_token()?)
 }
 
 #[tauri::command]
-fn delete_overlay(id: String) -> Result<(), String> {
-    db::delete_overlay(&id).map_err(|e| e.to_string())
+fn regenerate_inbox_token() -> Result<String, CommandError> {
+    Ok(settings::regenerate_inbox_token()?)
 }
 
 #[tauri::command]
 fn get_obs_url(id: String) -> String {
-    format!("http://localhost:{}/widget/{}", obs_server::OBS_HTTP_PORT, id)
+    obs_server::saved_widget_url(&obs_server::public_host(), &id)
+}
+
+/// Copy a widget's OBS URL (the LAN URL in LAN mode) to the clipboard and
+/// return it for the confirmation toast.
+#[tauri::command]
+fn copy_obs_url(app: tauri::AppHandle, id: String) -> Result<String, CommandError> {
+    let url = obs_server::saved_widget_url(&obs_server::public_host(), &id);
+    app.clipboard().write_text(url.clone()).map_err(CommandError::other)?;
+    Ok(url)
+}
+
+/// Script errors widget pages reported for `widget_id`, oldest first.
+#[tauri::command]
+fn get_widget_errors(widget_id: String) -> Vec<control::PageError> {
+    control::errors(&widget_id)
+}
+
+#[tauri::command]
+fn clear_widget_errors(widget_id: String) {
+    control::clear_errors(&widget_id);
+    // Missing files and fonts are noted again on the next page load
+    obs_server::forget_asset_check(&widget_id);
+}
+
+/// Whether the OBS server is up, where it's bound and why it last failed.
+#[tauri::command]
+fn get_server_status() -> obs_server::ServerStatus {
+    obs_server::status()
+}
+
+/// Start the OBS server, or restart it if it's running: after a safe-mode
+/// launch, or to retry once whatever held the port has let go.
+#[tauri::command]
+fn start_obs_server() -> Result<(), CommandError> {
+    let rt = SERVER_RUNTIME.get().ok_or_else(|| CommandError::other("The OBS server runtime isn't running"))?;
+    rt.spawn(obs_server::start_by_hand());
+    Ok(())
+}
+
+#[tauri::command]
+fn get_lan_mode() -> bool {
+    settings::lan_enabled()
+}
+
+/// Toggle LAN mode and restart the server on the new bind address.
+#[tauri::command]
+fn set_lan_mode(enabled: bool) -> Result<(), CommandError> {
+    settings::set_lan_enabled(enabled)?;
+    restart_obs_server();
+    Ok(())
+}
+
+/// Hotkeys saved per widget id.
+#[tauri::command]
+fn get_hotkeys() -> std::collections::BTreeMap<String, String> {
+    hotkeys::list()
+}
+
+/// Bind a global hotkey that triggers a widget's animation; `null` clears it.
+#[tauri::command]
+fn set_hotkey(app: tauri::AppHandle, widget_id: String, accelerator: Option<String>) -> Result<(), CommandError> {
+    hotkeys::set(&app, &widget_id, accelerator.as_deref())
+}
+
+#[tauri::command]
+fn get_log_path(app: tauri::AppHandle) -> Result<String, CommandError> {
+    let path = log_dir(&app).map_err(CommandError::other)?.join(format!("{LOG_FILE_NAME}.log"));
+    Ok(path.to_string_lossy().into_owned())
+}
+
+#[tauri::command]
+async fn export_frames(id: String, fps: u32, out_dir: String) -> Result<export::FrameExportReport, CommandError> {
+    tauri::async_runtime::spawn_blocking(move || export::export_frames(&id, fps, &out_dir))
+        .await
+        .map_err(CommandError::other)?
+}
+
+/// Write a widget as one self-contained HTML file for hosting without the app.
+#[tauri::command]
+async fn export_standalone_html(id: String, path: String) -> Result<export::StandaloneExportReport, CommandError> {
+    tauri::async_runtime::spawn_blocking(move || export::export_standalone_html(&id, &path))
+        .await
+        .map_err(CommandError::other)?
+}
+
+/// Bundle the whole library (overlays, settings, local files, fonts) into one
+/// archive for moving to another PC.
+#[tauri::command]
+async fn export_library(path: String) -> Result<library::LibraryExportReport, CommandError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        autosave::flush_all();
+        library::export_library(&path)
+    })
+    .await
+    .map_err(CommandError::other)?
+    .map_err(CommandError::from)
+}
+
+/// Restore an archive from `export_library`, merged into the library or
+/// replacing it (after a backup of the database).
+#[tauri::command]
+async fn import_library(
+    app: tauri::AppHandle,
+    path: String,
+    mode: library::ImportMode,
+) -> Result<library::LibraryImportReport, CommandError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        autosave::flush_all();
+        let library::ImportMode::Replace = mode else {
+            return library::import_library(&path, mode, &import_check(true));
+        };
+        // Hotkeys and the LAN setting come from the replaced settings
+        hotkeys::unregister_all(&app);
+        let report = library::import_library(&path, mode, &import_check(false));
+        hotkeys::register_saved(&app);
+        obs_server::profile_changed();
+        restart_obs_server();
+        report
+    })
+    .await
+    .map_err(CommandError::other)?
+}
+
+/// The save checks for an overlay coming in from a library archive. Slugs are
+/// only checked against the stored library on a merge; a replace drops every
+/// stored overlay, and the import already keeps the archive's slugs unique.
+fn import_check(merge: bool) -> impl Fn(&str, &serde_json::Value) -> Result<(), CommandError> {
+    move |id, config| {
+        let config_str = serde_json::to_string(config).map_err(CommandError::other)?;
+        config_checks(merge.then_some(id), config, &config_str).into_iter().collect()
+    }
+}
+
+/// Delete files restored by library imports that no overlay in any profile
+/// uses any more; `dry_run` only lists them and what they'd free.
+#[tauri::command]
+async fn gc_assets(dry_run: bool) -> Result<library::AssetGcReport, CommandError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        // Pending edits land first, so the scan sees what the editor shows
+        autosave::flush_all();
+        library::gc_assets(dry_run)
+    })
+    .await
+    .map_err(CommandError::other)?
+    .map_err(CommandError::from)
+}
+
+/// Create an overlay from a hand-written overlay HTML file: its absolutely
+/// positioned text, images and boxes become elements of one widget. Best
+/// effort; the report lists what it couldn't convert.
+#[tauri::command]
+async fn import_html(path: String) -> Result<html_import::HtmlImportReport, CommandError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let (config, report) = html_import::import(&path)?;
+        let config_str = checked_config(&report.overlay_id, &config)?;
+        db::upsert_overlay(&report.overlay_id, &report.name, &config_str)?;
+        log::info!(
+            "Imported {path} as overlay {} ({} elements, {} notes)",
+            report.overlay_id,
+            report.elements,
+            report.unconverted.len()
+        );
+        Ok(report)
+    })
+    .await
+    .map_err(CommandError::other)?
+}
+
+/// Make every open page of a widget fetch an image or video element's remote
+/// `src` again, past OBS's cache, and keep that version for later loads.
+#[tauri::command]
+async fn refresh_element_src(widget_id: String, element_id: String) -> Result<src_refresh::SrcRefresh, CommandError> {
+    tauri::async_runtime::spawn_blocking(move || src_refresh::refresh(&widget_id, &element_id))
+        .await
+        .map_err(CommandError::other)?
+}
+
+/// Render widgets into the OBS server's cache ahead of a scene switch.
+#[tauri::command]
+async fn prewarm_widgets(ids: Vec<String>) -> Result<obs_server::PrewarmReport, CommandError> {
+    tauri::async_runtime::spawn_blocking(move || obs_server::prewarm(&ids))
+        .await
+        .map_err(CommandError::other)
 }
 
 // ---------------------------------------------------------------------------
@@ -58,42 +716,181 @@ fn get_obs_url(id: String) -> String {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // Initialize the DB early to ensure the table exists
-    let _ = &*db::DB;
-
     tauri::Builder::default()
+        // Must come first: a second launch hands off to the running app and exits
+        // before it opens the database or binds port 7878. The lock dies with its
+        // process (a dead macOS socket is cleared when nothing answers), so a
+        // crash never blocks the next launch.
+        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+            log::info!("Another instance was launched; focusing this one instead");
+            tray::show_main_window(app);
+        }))
         .setup(|app| {
+            // Log to stdout and a size-rotated file in the app-data dir (release builds too)
+            app.handle().plugin(
+                tauri_plugin_log::Builder::default()
+                    .level(log::LevelFilter::Info)
+                    .clear_targets()
+                    .target(Target::new(TargetKind::Stdout))
+                    .target(Target::new(TargetKind::Folder {
+                        path: log_dir(app.handle())?,
+                        file_name: Some(LOG_FILE_NAME.to_string()),
+                    }))
+                    .max_file_size(LOG_MAX_FILE_SIZE)
+                    .rotation_strategy(RotationStrategy::KeepSome(LOG_KEEP_FILES))
+                    .build(),
+            )?;
+
+            // Resolve the DB location, then initialize it early to ensure the table exists
+            let db_path = db::init_db_path(app.path().app_data_dir().ok())?;
+            log::info!("Using database at {}", db_path.display());
+            if let Err(e) = db::open() {
+                // Nothing works without the database: say why, with the editor closed so it can't try
+                log::error!("{e}");
+                if let Some(window) = app.get_webview_window(tray::MAIN_WINDOW) {
+                    let _ = window.destroy();
+                }
+                app.dialog()
+                    .message(e)
+                    .title("Open Overlay can't open its database")
+                    .kind(MessageDialogKind::Error)
+                    .show(|_| std::process::exit(1));
+                return Ok(());
+            }
+            // Tell the editor about every overlay write, including ones it didn't make (autosave, repair)
+            let handle = app.handle().clone();
+            db::on_change(move |change| {
+                if let Err(e) = handle.emit(OVERLAY_CHANGED_EVENT, change) {
+                    log::warn!("Failed to emit {OVERLAY_CHANGED_EVENT} for {}: {e}", change.id);
+                }
+            });
+            let handle = app.handle().clone();
+            autosave::on_write(move |autosaved| {
+                if let Err(e) = handle.emit(AUTOSAVED_EVENT, autosaved) {
+                    log::warn!("Failed to emit {AUTOSAVED_EVENT}: {e}");
+                }
+            });
+            autosave::start();
+            hotkeys::register_saved(app.handle());
+
             // Start the OBS HTTP server in a background Tokio runtime
             let rt = tokio::runtime::Builder::new_multi_thread()
                 .enable_all()
                 .build()
                 .expect("Failed to build Tokio runtime");
 
-            // Leak the runtime so it lives for the entire app lifetime
-            let rt = Box::leak(Box::new(rt));
-            rt.spawn(async {
-                obs_server::start_obs_server_async().await;
+            let rt = SERVER_RUNTIME.get_or_init(|| rt);
+            if safe_mode_requested() {
+                // Editing and exporting only; the editor can start the server later
+                log::warn!("Safe mode: not starting the OBS HTTP server");
+                obs_server::hold();
+            } else {
+                rt.spawn(async {
+                    obs_server::start_obs_server_async().await;
+                });
+            }
+
+            // Push server starts, stops and bind failures to the editor's status indicator
+            let handle = app.handle().clone();
+            let mut status_rx = obs_server::subscribe_status();
+            tauri::async_runtime::spawn(async move {
+                while status_rx.changed().await.is_ok() {
+                    let status = status_rx.borrow_and_update().clone();
+                    if let Err(e) = handle.emit(SERVER_STATUS_EVENT, status) {
+                        log::warn!("Failed to emit {SERVER_STATUS_EVENT}: {e}");
+                    }
+                }
             });
 
-            if cfg!(debug_assertions) {
-                app.handle().plugin(
-                    tauri_plugin_log::Builder::default()
-                        .level(log::LevelFilter::Info)
-                        .build(),
-                )?;
+            tray::init(app.handle())?;
+            if let Some(window) = app.get_webview_window(tray::MAIN_WINDOW) {
+                window_state::restore(&window);
             }
+
             Ok(())
         })
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::CloseRequested { .. } = event {
+                if let Some(window) = window.app_handle().get_webview_window(window.label()) {
+                    window_state::save(&window);
+                }
+            }
+        })
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().with_handler(hotkeys::handle).build())
         .invoke_handler(tauri::generate_handler![
             list_overlays,
             get_overlay,
             save_overlay,
+            patch_overlay,
+            validate_config,
+            save_overlay_as,
+            queue_autosave,
             delete_overlay,
+            diff_configs,
+            check_database,
+            repair_database,
+            compact_database,
+            check_duplicate_widget_ids,
+            normalize_overlay,
+            check_timeline_consistency,
+            fix_timeline_consistency,
+            test_render,
+            profile_widget,
+            find_asset_usages,
+            find_elements_by_type,
+            find_elements_using_font,
+            get_config_limits,
+            set_config_limits,
+            get_playback_defaults,
+            get_rate_limit,
+            get_last_overlay,
+            set_last_overlay,
+            list_profiles,
+            create_profile,
+            switch_profile,
+            get_inbox_token,
+            regenerate_inbox_token,
+            set_playback_defaults,
+            set_rate_limit,
             get_obs_url,
+            copy_obs_url,
+            get_widget_errors,
+            clear_widget_errors,
+            get_server_status,
+            start_obs_server,
+            get_lan_mode,
+            set_lan_mode,
+            get_hotkeys,
+            set_hotkey,
+            export_frames,
+            export_standalone_html,
+            export_library,
+            import_library,
+            gc_assets,
+            import_html,
+            refresh_element_src,
+            prewarm_widgets,
+            get_log_path,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Quitting from the tray skips CloseRequested; the window still exists here
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                if let Some(window) = app.get_webview_window(tray::MAIN_WINDOW) {
+                    window_state::save(&window);
+                }
+            }
+            if let tauri::RunEvent::Exit = event {
+                // Don't lose edits still waiting on the autosave debounce
+                let flushed = autosave::flush_all();
+                if flushed > 0 {
+                    log::info!("Flushed {flushed} pending autosave(s) on exit");
+                }
+            }
+        });
 }
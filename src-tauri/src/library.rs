@@ -0,0 +1,493 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::error::CommandError;
+use crate::{db, export, obs_server, validation};
+
+// ---------------------------------------------------------------------------
+// Library archives: every overlay, the settings, the local files they use and
+// matching installed fonts in one JSON file, for moving to another PC
+// ---------------------------------------------------------------------------
+
+const FORMAT: &str = "open-overlay-library";
+const FORMAT_VERSION: u32 = 1;
+/// Folders beside the database that restored files are written to.
+const ASSETS_DIR: &str = "library-assets";
+const FONTS_DIR: &str = "library-fonts";
+
+/// Held while restored files are written or collected, so a clean-up can't
+/// delete files an import has written but not yet saved overlays pointing at.
+static RESTORED_FILES: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LibraryArchive {
+    format: String,
+    version: u32,
+    overlays: Vec<ArchivedOverlay>,
+    settings: BTreeMap<String, String>,
+    assets: Vec<ArchivedAsset>,
+    fonts: Vec<ArchivedFont>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchivedOverlay {
+    id: String,
+    name: String,
+    updated_at: String,
+    config: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchivedAsset {
+    /// The reference the configs use (absolute path or `file://` URL).
+    reference: String,
+    /// Base64 of the file.
+    data: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchivedFont {
+    family: String,
+    file_name: String,
+    data: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Add the archive's overlays next to the current ones, re-iding any that collide.
+    Merge,
+    /// Back up the database, then swap its overlays and settings for the archive's.
+    Replace,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LibraryExportReport {
+    pub path: String,
+    pub bytes: usize,
+    pub overlays: usize,
+    /// Local files embedded.
+    pub assets: usize,
+    /// Font files embedded.
+    pub fonts: usize,
+    /// Overlays whose config can't be parsed (see `repair_database`); left out.
+    pub skipped_overlays: Vec<String>,
+    /// Local files referenced but unreadable; the references are kept as they are.
+    pub missing_assets: Vec<String>,
+    /// Families used but with no installed font file found; install them by hand.
+    pub missing_fonts: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RemappedId {
+    pub name: String,
+    /// Empty when it had no id.
+    pub old_id: String,
+    pub new_id: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct LibraryImportReport {
+    pub overlays: usize,
+    /// Overlays given a new id because one with theirs already exists (merge only).
+    pub remapped_overlays: Vec<RemappedId>,
+    /// Widgets given a new id, so a new OBS URL, because theirs was taken.
+    pub remapped_widgets: Vec<RemappedId>,
+    /// Slugs removed because a widget here already uses them.
+    pub dropped_slugs: Vec<String>,
+    /// Files written because the path they were used under doesn't exist here.
+    pub restored_assets: usize,
+    /// Where fonts that aren't installed here were written; install them from there.
+    pub fonts_dir: Option<String>,
+    pub fonts_to_install: Vec<String>,
+    /// Copy of the database taken before a replace.
+    pub backup: Option<String>,
+}
+
+fn encode(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+fn decode(data: &str, what: &str) -> Result<Vec<u8>, String> {
+    base64::engine::general_purpose::STANDARD.decode(data).map_err(|e| format!("Archive has a corrupt {what}: {e}"))
+}
+
+/// Run `f` on every asset reference in an overlay config, the workspace audio track included.
+fn for_each_asset(config: &mut Value, f: &mut dyn FnMut(&mut String) -> Result<(), String>) -> Result<(), String> {
+    if let Some(Value::String(track)) = config.get_mut("audioTrack") {
+        f(track)?;
+    }
+    export::for_each_asset(config, f)
+}
+
+/// The family each `fontFamily` at any depth of `value` asks for first, generic ones left out.
+pub(crate) fn collect_font_families(value: &Value, out: &mut BTreeSet<String>) {
+    match value {
+        Value::Object(obj) => {
+            for (key, v) in obj {
+                match v.as_str().filter(|_| key == "fontFamily").and_then(validation::primary_font) {
+                    Some(family) => {
+                        out.insert(family.to_string());
+                    }
+                    None => collect_font_families(v, out),
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_font_families(item, out)),
+        _ => {}
+    }
+}
+
+/// Font files an import restored to `library-fonts/` for `family`, installed or not.
+pub(crate) fn restored_font_files(family: &str) -> Vec<PathBuf> {
+    let dir = db::data_dir().join(FONTS_DIR);
+    let files = std::fs::read_dir(dir).into_iter().flatten().flatten().map(|entry| entry.path());
+    files.filter(|file| file.is_file() && validation::font_file_matches(family, file)).collect()
+}
+
+/// File name safe to create in a folder of restored files.
+fn safe_file_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    match name.trim_matches('.') {
+        "" => "file".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Export
+// ---------------------------------------------------------------------------
+
+/// Write every overlay in the active profile, its settings, the local files
+/// the overlays use and installed font files for their families to `path`.
+/// Unreadable overlays and files are reported and left out rather than failing
+/// the whole backup.
+pub fn export_library(path: &str) -> Result<LibraryExportReport, String> {
+    let db_err = |e: rusqlite::Error| format!("Cannot read the library: {e}");
+    let mut archive = LibraryArchive {
+        format: FORMAT.to_string(),
+        version: FORMAT_VERSION,
+        overlays: Vec::new(),
+        settings: db::all_settings().map_err(db_err)?,
+        assets: Vec::new(),
+        fonts: Vec::new(),
+    };
+    let mut report = LibraryExportReport {
+        path: path.to_string(),
+        bytes: 0,
+        overlays: 0,
+        assets: 0,
+        fonts: 0,
+        skipped_overlays: Vec::new(),
+        missing_assets: Vec::new(),
+        missing_fonts: Vec::new(),
+    };
+
+    let mut seen_assets = HashSet::new();
+    let mut families = BTreeSet::new();
+    for summary in db::list_overlays().map_err(db_err)? {
+        let Some(row) = db::get_overlay(&summary.id).map_err(db_err)? else { continue };
+        let Ok(mut config) = serde_json::from_str::<Value>(&row.config) else {
+            log::warn!("Library export skipped overlay {} ({}): unreadable config", row.id, row.name);
+            report.skipped_overlays.push(row.name);
+            continue;
+        };
+        for_each_asset(&mut config, &mut |reference| {
+            let Some(file) = export::local_asset_path(reference).filter(|_| seen_assets.insert(reference.clone())) else {
+                return Ok(());
+            };
+            match std::fs::read(&file) {
+                Ok(bytes) => archive.assets.push(ArchivedAsset { reference: reference.clone(), data: encode(&bytes) }),
+                Err(e) => {
+                    log::warn!("Library export could not read {}: {e}", file.display());
+                    report.missing_assets.push(reference.clone());
+                }
+            }
+            Ok(())
+        })?;
+        collect_font_families(&config, &mut families);
+        archive.overlays.push(ArchivedOverlay { id: row.id, name: row.name, updated_at: row.updated_at, config });
+    }
+
+    for family in families {
+        let files = validation::font_files(&family);
+        if files.is_empty() {
+            report.missing_fonts.push(family);
+            continue;
+        }
+        for file in files {
+            let Some(file_name) = file.file_name().and_then(|n| n.to_str()).map(str::to_string) else { continue };
+            match std::fs::read(&file) {
+                Ok(bytes) => archive.fonts.push(ArchivedFont { family: family.clone(), file_name, data: encode(&bytes) }),
+                Err(e) => log::warn!("Library export could not read font {}: {e}", file.display()),
+            }
+        }
+    }
+
+    let json = serde_json::to_vec(&archive).map_err(|e| e.to_string())?;
+    std::fs::write(path, &json).map_err(|e| format!("Cannot write '{path}': {e}"))?;
+    report.bytes = json.len();
+    report.overlays = archive.overlays.len();
+    report.assets = archive.assets.len();
+    report.fonts = archive.fonts.len();
+    Ok(report)
+}
+
+// ---------------------------------------------------------------------------
+// Import
+// ---------------------------------------------------------------------------
+
+fn read_archive(path: &str) -> Result<LibraryArchive, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Cannot read '{path}': {e}"))?;
+    let archive: LibraryArchive =
+        serde_json::from_slice(&bytes).map_err(|e| format!("'{path}' is not an Open Overlay library archive: {e}"))?;
+    if archive.format != FORMAT {
+        return Err(format!("'{path}' is not an Open Overlay library archive"));
+    }
+    if archive.version > FORMAT_VERSION {
+        return Err(format!("'{path}' was written by a newer version of Open Overlay (format {})", archive.version));
+    }
+    Ok(archive)
+}
+
+/// Write the archived files whose path doesn't exist on this PC under
+/// `library-assets/<batch>/`, returning old reference -> new path.
+fn restore_assets(assets: &[ArchivedAsset], report: &mut LibraryImportReport) -> Result<HashMap<String, String>, String> {
+    let dir = db::data_dir().join(ASSETS_DIR).join(&uuid::Uuid::new_v4().to_string()[..8]);
+    let mut moved = HashMap::new();
+    for (i, asset) in assets.iter().enumerate() {
+        if export::local_asset_path(&asset.reference).is_some_and(|p| p.exists()) {
+            continue;
+        }
+        let name = Path::new(&asset.reference.replace('\\', "/")).file_name().and_then(|n| n.to_str()).map(safe_file_name);
+        let file = dir.join(format!("{i}-{}", name.unwrap_or_else(|| "file".to_string())));
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Cannot create '{}': {e}", dir.display()))?;
+        std::fs::write(&file, decode(&asset.data, "asset")?).map_err(|e| format!("Cannot write '{}': {e}", file.display()))?;
+        moved.insert(asset.reference.clone(), file.to_string_lossy().into_owned());
+        report.restored_assets += 1;
+    }
+    Ok(moved)
+}
+
+/// Write fonts whose family isn't installed here to `library-fonts/`.
+fn restore_fonts(fonts: &[ArchivedFont], report: &mut LibraryImportReport) -> Result<(), String> {
+    let dir: PathBuf = db::data_dir().join(FONTS_DIR);
+    let mut installed = HashMap::new();
+    for font in fonts {
+        if *installed.entry(font.family.clone()).or_insert_with(|| validation::is_font_installed(&font.family)) {
+            continue;
+        }
+        let file = dir.join(safe_file_name(&font.file_name));
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Cannot create '{}': {e}", dir.display()))?;
+        std::fs::write(&file, decode(&font.data, "font")?).map_err(|e| format!("Cannot write '{}': {e}", file.display()))?;
+        if !report.fonts_to_install.contains(&font.family) {
+            report.fonts_to_install.push(font.family.clone());
+        }
+        report.fonts_dir = Some(dir.to_string_lossy().into_owned());
+    }
+    Ok(())
+}
+
+/// Ids and slugs already in use, which merged overlays and widgets mustn't reuse.
+#[derive(Default)]
+struct Taken {
+    overlays: HashSet<String>,
+    widgets: HashSet<String>,
+    slugs: HashSet<String>,
+}
+
+fn taken_ids() -> Result<Taken, String> {
+    let mut taken = Taken::default();
+    let overlays = db::list_overlays().map_err(|e| format!("Cannot read the library: {e}"))?;
+    taken.overlays.extend(overlays.into_iter().map(|o| o.id));
+    for (_, widget) in obs_server::all_widgets() {
+        taken.widgets.extend(widget.get("id").and_then(|v| v.as_str()).map(str::to_string));
+        taken.slugs.extend(obs_server::widget_slug(&widget).map(str::to_string));
+    }
+    Ok(taken)
+}
+
+/// Give the overlay and its widgets ids (and slugs) nobody in `taken` has,
+/// recording the overlay's own in `taken` so the next one can't collide with it.
+fn remap_ids(overlay: &mut ArchivedOverlay, taken: &mut Taken, report: &mut LibraryImportReport) {
+    if overlay.id.is_empty() || !taken.overlays.insert(overlay.id.clone()) {
+        let id = uuid::Uuid::new_v4().to_string();
+        taken.overlays.insert(id.clone());
+        report.remapped_overlays.push(RemappedId { name: overlay.name.clone(), old_id: overlay.id.clone(), new_id: id.clone() });
+        overlay.id = id;
+    }
+    let Some(obj) = overlay.config.as_object_mut() else { return };
+    obj.insert("id".to_string(), Value::String(overlay.id.clone()));
+    for widget in obj.get_mut("widgets").and_then(|w| w.as_array_mut()).into_iter().flatten() {
+        let Some(widget) = widget.as_object_mut() else { continue };
+        let old_id = widget.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string();
+        if old_id.is_empty() || !taken.widgets.insert(old_id.clone()) {
+            let id = uuid::Uuid::new_v4().to_string();
+            taken.widgets.insert(id.clone());
+            let name = widget.get("name").and_then(|v| v.as_str()).unwrap_or("unnamed").to_string();
+            report.remapped_widgets.push(RemappedId { name, old_id, new_id: id.clone() });
+            widget.insert("id".to_string(), Value::String(id));
+        }
+        if let Some(slug) = widget.get("slug").and_then(|v| v.as_str()).map(str::to_string) {
+            if !taken.slugs.insert(slug.clone()) {
+                widget.remove("slug");
+                report.dropped_slugs.push(slug);
+            }
+        }
+    }
+}
+
+/// Restore a library archive written by `export_library`. Files whose path
+/// doesn't exist here are written beside the database and the configs pointed
+/// at the copies; fonts this PC lacks are written out for installing.
+///
+/// `Merge` keeps everything here: overlays and widgets whose ids (or slugs)
+/// are already used get new ones, and only settings not set here are taken.
+/// `Replace` backs up the database first, then swaps every overlay and
+/// setting for the archive's in one transaction.
+///
+/// Every overlay must pass `check` (the editor's save checks) once its ids
+/// are settled; if any fails, nothing is imported.
+pub fn import_library(
+    path: &str,
+    mode: ImportMode,
+    check: &dyn Fn(&str, &Value) -> Result<(), CommandError>,
+) -> Result<LibraryImportReport, CommandError> {
+    let _restoring = RESTORED_FILES.lock().unwrap();
+    let mut archive = read_archive(path)?;
+    let mut report = LibraryImportReport::default();
+    let mut taken = match mode {
+        ImportMode::Merge => taken_ids()?,
+        ImportMode::Replace => Taken::default(),
+    };
+
+    let mut rejected = Vec::new();
+    for overlay in &mut archive.overlays {
+        remap_ids(overlay, &mut taken, &mut report);
+        if let Err(e) = check(&overlay.id, &overlay.config) {
+            rejected.push(format!("'{}' ({}): {e}", overlay.name, overlay.id));
+        }
+    }
+    if !rejected.is_empty() {
+        log::warn!("Refused library import of {path}: {}", rejected.join("; "));
+        return Err(CommandError::InvalidConfig {
+            details: format!("Nothing was imported; these overlays wouldn't save: {}", rejected.join("; ")),
+        });
+    }
+
+    let moved = restore_assets(&archive.assets, &mut report)?;
+    restore_fonts(&archive.fonts, &mut report)?;
+
+    let mut rows = Vec::new();
+    for overlay in &mut archive.overlays {
+        for_each_asset(&mut overlay.config, &mut |reference| {
+            if let Some(path) = moved.get(reference.as_str()) {
+                *reference = path.clone();
+            }
+            Ok(())
+        })?;
+        let config = serde_json::to_string(&overlay.config).map_err(|e| e.to_string())?;
+        rows.push((overlay.id.clone(), overlay.name.clone(), config));
+    }
+
+    let replace = matches!(mode, ImportMode::Replace);
+    if replace {
+        let backup = db::backup_database().map_err(|e| format!("Cannot back up the database before replacing it: {e}"))?;
+        log::info!("Backed up the database to {} before a library import", backup.display());
+        report.backup = Some(backup.to_string_lossy().into_owned());
+    }
+    db::import_library(&rows, &archive.settings, replace).map_err(|e| format!("Cannot import the library: {e}"))?;
+    report.overlays = rows.len();
+    Ok(report)
+}
+
+// ---------------------------------------------------------------------------
+// Clean-up of restored files no workspace uses any more
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Default, Serialize)]
+pub struct AssetGcReport {
+    pub dry_run: bool,
+    /// Files under `library-assets/` and `library-fonts/` that no overlay in
+    /// any profile uses; deleted unless this was a dry run.
+    pub unreferenced: Vec<String>,
+    /// Their total size: freed, or that would be.
+    pub bytes: u64,
+    /// Files that couldn't be deleted, with why; left out of the above.
+    pub failed: Vec<String>,
+}
+
+fn files_under(dir: &Path, out: &mut Vec<PathBuf>) {
+    for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files_under(&path, out);
+        } else {
+            out.push(path);
+        }
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Delete the files library imports restored (`library-assets/`,
+/// `library-fonts/`) that no overlay in any profile uses any more, quarantined
+/// ones included; `dry_run` only lists them. Files users picked themselves are
+/// never touched. Saves wait until the scan and deletes are done, and an
+/// overlay that can't be read stops the clean-up, since its files can't be told apart.
+pub fn gc_assets(dry_run: bool) -> Result<AssetGcReport, String> {
+    let _collecting = RESTORED_FILES.lock().unwrap();
+    db::with_every_config_locked(|configs| {
+        let mut referenced = HashSet::new();
+        let mut families = BTreeSet::new();
+        for (profile, id, config) in configs {
+            let mut config: Value = serde_json::from_str(&config).map_err(|e| {
+                format!("Overlay '{id}' in profile '{profile}' can't be read ({e}); run repair_database first")
+            })?;
+            for_each_asset(&mut config, &mut |reference| {
+                referenced.extend(export::local_asset_path(reference).map(|p| canonical(&p)));
+                Ok(())
+            })?;
+            collect_font_families(&config, &mut families);
+        }
+
+        let mut report = AssetGcReport { dry_run, ..Default::default() };
+        let assets_dir = db::data_dir().join(ASSETS_DIR);
+        let fonts_dir = db::data_dir().join(FONTS_DIR);
+        let mut unused = Vec::new();
+        files_under(&assets_dir, &mut unused);
+        unused.retain(|file| !referenced.contains(&canonical(file)));
+        let mut fonts = Vec::new();
+        files_under(&fonts_dir, &mut fonts);
+        unused.extend(fonts.into_iter().filter(|file| !families.iter().any(|f| validation::font_file_matches(f, file))));
+
+        for file in unused {
+            let size = std::fs::metadata(&file).map(|m| m.len()).unwrap_or(0);
+            if !dry_run {
+                if let Err(e) = std::fs::remove_file(&file) {
+                    report.failed.push(format!("{}: {e}", file.display()));
+                    continue;
+                }
+            }
+            report.unreferenced.push(file.to_string_lossy().into_owned());
+            report.bytes += size;
+        }
+        if !dry_run {
+            // Batch folders emptied above; remove_dir leaves any still holding files
+            for entry in std::fs::read_dir(&assets_dir).into_iter().flatten().flatten() {
+                let _ = std::fs::remove_dir(entry.path());
+            }
+            log::info!("Removed {} unused restored file(s), {} bytes", report.unreferenced.len(), report.bytes);
+        }
+        Ok(report)
+    })
+    .map_err(|e| format!("Cannot read the library: {e}"))?
+}
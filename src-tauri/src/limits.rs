@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::CommandError;
+
+// ---------------------------------------------------------------------------
+// Bounds on saved overlay configs, so a runaway editor bug or a hostile
+// import can't store something the DB and the renderer choke on
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfigLimits {
+    /// Serialized config size in bytes (embedded images count).
+    pub max_config_bytes: usize,
+    /// Elements across all widgets, group and mask children included.
+    pub max_elements: usize,
+    /// Depth of group/mask nesting; top-level elements are depth 1.
+    pub max_nesting_depth: usize,
+}
+
+impl Default for ConfigLimits {
+    fn default() -> Self {
+        Self {
+            max_config_bytes: 32 * 1024 * 1024,
+            max_elements: 5_000,
+            max_nesting_depth: 16,
+        }
+    }
+}
+
+impl ConfigLimits {
+    /// Check a config (and its serialized form) against the limits.
+    pub fn check(&self, config: &Value, serialized: &str) -> Result<(), CommandError> {
+        let over = |message: String| Err(CommandError::Limit { message });
+        if serialized.len() > self.max_config_bytes {
+            return over(format!(
+                "config is {} bytes, over the {} byte limit",
+                serialized.len(),
+                self.max_config_bytes
+            ));
+        }
+
+        // Walk with an explicit stack: the input is exactly what might be too deep to recurse
+        let mut stack: Vec<(&Value, usize)> = config
+            .get("widgets")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|widget| widget.get("elements").and_then(Value::as_array))
+            .flatten()
+            .map(|el| (el, 1))
+            .collect();
+        let mut elements = 0;
+        while let Some((el, depth)) = stack.pop() {
+            elements += 1;
+            if elements > self.max_elements {
+                return over(format!("config has more than {} elements", self.max_elements));
+            }
+            if depth > self.max_nesting_depth {
+                return over(format!(
+                    "groups are nested more than {} levels deep",
+                    self.max_nesting_depth
+                ));
+            }
+            if let Some(children) = el.get("children").and_then(Value::as_array) {
+                stack.extend(children.iter().map(|child| (child, depth + 1)));
+            }
+        }
+        Ok(())
+    }
+}
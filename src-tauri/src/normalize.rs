@@ -0,0 +1,124 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+
+use crate::segments;
+
+// ---------------------------------------------------------------------------
+// Re-id an overlay: fresh unique element ids (and widget ids where they're
+// missing or shared), with keyframe states and mask links moved along
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Serialize)]
+pub struct RenamedWidget {
+    pub name: String,
+    /// Empty when the widget had no id.
+    pub old_id: String,
+    pub new_id: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct NormalizeReport {
+    /// Elements given a new id (all of them).
+    pub elements: usize,
+    /// Widgets whose id was missing or also used by another widget; their OBS
+    /// URLs change. Widgets with a unique id keep it so existing sources still work.
+    pub widgets: Vec<RenamedWidget>,
+    /// Keyframe entries moved to their element's new id (one per element when
+    /// several elements shared the old id).
+    pub remapped_states: usize,
+    /// Keyframe entries naming an id no element has; kept as they were.
+    pub orphaned_states: Vec<String>,
+    /// When the normalized overlay was saved.
+    pub updated_at: String,
+}
+
+fn new_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Give every element in the tree a new id, recording old id -> new ids.
+fn reassign_elements(elements: &mut Value, ids: &mut HashMap<String, Vec<String>>, report: &mut NormalizeReport) {
+    for element in elements.as_array_mut().into_iter().flatten() {
+        let Some(obj) = element.as_object_mut() else { continue };
+        let id = new_id();
+        if let Some(old) = obj.get("id").and_then(|v| v.as_str()).filter(|old| !old.is_empty()) {
+            ids.entry(old.to_string()).or_default().push(id.clone());
+        }
+        obj.insert("id".to_string(), Value::String(id));
+        report.elements += 1;
+        if let Some(children) = obj.get_mut("children") {
+            reassign_elements(children, ids, report);
+        }
+    }
+}
+
+/// Point `maskWithLayerId` at the mask's new id; a shared old id goes to the first element that had it.
+fn remap_mask_links(elements: &mut Value, ids: &HashMap<String, Vec<String>>) {
+    for element in elements.as_array_mut().into_iter().flatten() {
+        let Some(obj) = element.as_object_mut() else { continue };
+        let target = obj.get("maskWithLayerId").and_then(|v| v.as_str()).and_then(|old| ids.get(old)?.first());
+        if let Some(target) = target.cloned() {
+            obj.insert("maskWithLayerId".to_string(), Value::String(target));
+        }
+        if let Some(children) = obj.get_mut("children") {
+            remap_mask_links(children, ids);
+        }
+    }
+}
+
+/// Rewrite each keyframe's `elementStates` (main timeline and segments) under
+/// the new ids. Every element that shared an old id gets a copy of its state,
+/// so none of them stops moving.
+fn remap_keyframes(widget: &mut Value, ids: &HashMap<String, Vec<String>>, report: &mut NormalizeReport) {
+    for keyframe in segments::keyframe_lists_mut(widget).into_iter().flatten() {
+        let Some(states) = keyframe.get_mut("elementStates").and_then(|s| s.as_object_mut()) else { continue };
+        let mut remapped = Map::new();
+        for (old, state) in std::mem::take(states) {
+            match ids.get(&old) {
+                Some(new_ids) => {
+                    for id in new_ids {
+                        remapped.insert(id.clone(), state.clone());
+                        report.remapped_states += 1;
+                    }
+                }
+                None => {
+                    if !report.orphaned_states.contains(&old) {
+                        report.orphaned_states.push(old.clone());
+                    }
+                    remapped.insert(old, state);
+                }
+            }
+        }
+        *states = remapped;
+    }
+}
+
+/// Normalize `config` in place. `taken_widget_ids` are ids used by other
+/// overlays, which this one's widgets mustn't share.
+pub fn normalize(config: &mut Value, taken_widget_ids: &HashSet<String>) -> NormalizeReport {
+    let mut report = NormalizeReport::default();
+    let mut widget_ids: HashSet<String> = HashSet::new();
+    for widget in config.get_mut("widgets").and_then(|w| w.as_array_mut()).into_iter().flatten() {
+        let Some(obj) = widget.as_object_mut() else { continue };
+        let old_id = obj.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string();
+        if old_id.is_empty() || taken_widget_ids.contains(&old_id) || !widget_ids.insert(old_id.clone()) {
+            let id = new_id();
+            widget_ids.insert(id.clone());
+            report.widgets.push(RenamedWidget {
+                name: obj.get("name").and_then(|v| v.as_str()).unwrap_or("unnamed").to_string(),
+                old_id,
+                new_id: id.clone(),
+            });
+            obj.insert("id".to_string(), Value::String(id));
+        }
+
+        let mut ids = HashMap::new();
+        if let Some(elements) = obj.get_mut("elements") {
+            reassign_elements(elements, &mut ids, &mut report);
+            remap_mask_links(elements, &ids);
+        }
+        remap_keyframes(widget, &ids, &mut report);
+    }
+    report
+}
@@ -1,171 +1,1562 @@
 use actix_cors::Cors;
-use actix_web::{get, web, App, HttpResponse, HttpServer, Responder};
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServerHandle, ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderValue, AUTHORIZATION, CACHE_CONTROL};
+use actix_web::middleware::{from_fn, Logger, Next};
+use actix_web::{get, post, web, App, HttpRequest, HttpResponse, HttpServer, Responder};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::watch;
 
-use crate::db;
+use crate::canvas::WorkspaceCanvas;
+use crate::rate_limit::{RateLimit, RateLimiter};
+use crate::error::CommandError;
+use crate::{color, control, db, export, inbox, segments, settings, src_refresh, style_presets, validation};
 
 pub const OBS_HTTP_PORT: u16 = 7878;
 
 // ---------------------------------------------------------------------------
 // Find a widget across all saved workspaces
 // ---------------------------------------------------------------------------
-fn find_widget(widget_id: &str) -> Option<Value> {
-    let workspaces = db::list_overlays().ok()?;
+
+/// Every widget of every saved workspace, paired with the workspace it lives in.
+/// Most recently updated workspaces come first. Each widget carries a copy of
+/// its workspace's `stylePresets`. A malformed workspace (unparsable config,
+/// `widgets` that isn't a list, entries that aren't objects) is logged and
+/// skipped as far as it's broken, so it can't hide the widgets of others.
+pub(crate) fn all_widgets() -> Vec<(db::OverlaySummary, Value)> {
+    let workspaces = match db::list_overlays() {
+        Ok(list) => list,
+        Err(e) => {
+            log::error!("Database error while listing widgets: {e}");
+            return Vec::new();
+        }
+    };
+    let playback = settings::playback_defaults();
+    let mut out = Vec::new();
     for summary in workspaces {
-        if let Ok(Some(row)) = db::get_overlay(&summary.id) {
-            if let Ok(ws) = serde_json::from_str::<Value>(&row.config) {
-                if let Some(widgets) = ws.get("widgets").and_then(|w| w.as_array()) {
-                    for widget in widgets {
-                        if widget.get("id").and_then(|id| id.as_str()) == Some(widget_id) {
-                            return Some(widget.clone());
-                        }
-                    }
+        let row = match db::get_overlay(&summary.id) {
+            Ok(Some(row)) => row,
+            Ok(None) => continue,
+            Err(e) => {
+                log::error!("Database error while loading workspace {}: {e}", summary.id);
+                continue;
+            }
+        };
+        let ws = match serde_json::from_str::<Value>(&row.config) {
+            Ok(ws) => ws,
+            Err(e) => {
+                warn_malformed(&summary, &format!("config isn't valid JSON ({e}); none of its widgets can be served"));
+                continue;
+            }
+        };
+        let widgets = match ws.get("widgets") {
+            Some(Value::Array(widgets)) => widgets,
+            other => {
+                warn_malformed(&summary, &format!("`widgets` is {}, not a list; it has no widgets to serve", json_kind(other)));
+                continue;
+            }
+        };
+        for (i, widget) in widgets.iter().enumerate() {
+            if !widget.is_object() {
+                warn_malformed(&summary, &format!("widget #{i} is {}, not an object; skipped", json_kind(Some(widget))));
+                continue;
+            }
+            let mut widget = widget.clone();
+            let id = widget_id(&widget).unwrap_or("").to_string();
+            if id.is_empty() {
+                warn_malformed(&summary, &format!("widget #{i} has no id, so no URL finds it"));
+            }
+            if let Some(elements) = widget.get_mut("elements") {
+                for problem in sanitize_elements(elements) {
+                    warn_malformed(&summary, &format!("widget {id}: {problem}"));
                 }
             }
+            style_presets::attach(&ws, &mut widget);
+            // Workspace-wide stacking mode, rendered per widget
+            if let (Some(flat), Some(obj)) = (ws.get("flatZIndex"), widget.as_object_mut()) {
+                obj.insert("flatZIndex".to_string(), flat.clone());
+            }
+            playback.apply(&mut widget);
+            out.push((summary.clone(), widget));
+        }
+    }
+    out
+}
+
+/// Malformed-workspace problems already logged, by workspace, save time and
+/// problem: widgets are looked up on every request, the log needs each once.
+static WARNED_MALFORMED: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+fn warn_malformed(summary: &db::OverlaySummary, problem: &str) {
+    let key = format!("{} {} {problem}", summary.id, summary.updated_at);
+    if WARNED_MALFORMED.lock().unwrap().insert(key) {
+        log::warn!("Workspace '{}' ({}): {problem}", summary.name, summary.id);
+    }
+}
+
+/// What a JSON value is, for messages about the wrong one.
+fn json_kind(value: Option<&Value>) -> &'static str {
+    match value {
+        None => "missing",
+        Some(Value::Null) => "null",
+        Some(Value::Bool(_)) => "a boolean",
+        Some(Value::Number(_)) => "a number",
+        Some(Value::String(_)) => "a string",
+        Some(Value::Array(_)) => "a list",
+        Some(Value::Object(_)) => "an object",
+    }
+}
+
+/// Make `elements` a list of element objects, groups' `children` included: a
+/// non-list becomes empty and entries that aren't objects are dropped. Returns
+/// what was fixed, for the caller to log with the workspace or widget it's in.
+fn sanitize_elements(elements: &mut Value) -> Vec<String> {
+    fn sanitize(elements: &mut Value, what: &str, problems: &mut Vec<String>) {
+        let Value::Array(list) = elements else {
+            problems.push(format!("{what} is {}, not a list; rendering it empty", json_kind(Some(elements))));
+            *elements = Value::Array(Vec::new());
+            return;
+        };
+        let before = list.len();
+        list.retain(Value::is_object);
+        if list.len() < before {
+            let dropped = before - list.len();
+            problems.push(format!("{what} has {dropped} non-object {}; skipped", if dropped == 1 { "entry" } else { "entries" }));
+        }
+        for element in list {
+            let id = element.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string();
+            match element.get_mut("children") {
+                None | Some(Value::Null) => {}
+                Some(children) => sanitize(children, &format!("`children` of element {id}"), problems),
+            }
+        }
+    }
+    let mut problems = Vec::new();
+    sanitize(elements, "`elements`", &mut problems);
+    problems
+}
+
+fn widget_id(widget: &Value) -> Option<&str> {
+    widget.get("id").and_then(|id| id.as_str())
+}
+
+/// Design size of a widget page (400×300 when unset).
+fn widget_size(widget: &Value) -> (u64, u64) {
+    let w = widget.get("width").and_then(|v| v.as_u64()).unwrap_or(400);
+    let h = widget.get("height").and_then(|v| v.as_u64()).unwrap_or(300);
+    (w, h)
+}
+
+/// Seconds between a page's checks for edits, unless the widget sets `pollInterval`.
+const DEFAULT_POLL_SECS: f64 = 2.0;
+/// `pollInterval` bounds; polling faster than once a second just adds load.
+const POLL_RANGE_SECS: (f64, f64) = (1.0, 300.0);
+
+/// How often the widget's pages poll for edits, in milliseconds.
+fn poll_ms(widget: &Value) -> u64 {
+    let secs = widget.get("pollInterval").and_then(|v| v.as_f64()).filter(|s| s.is_finite()).unwrap_or(DEFAULT_POLL_SECS);
+    (secs.clamp(POLL_RANGE_SECS.0, POLL_RANGE_SECS.1) * 1000.0) as u64
+}
+
+/// Bounds on device pixels per design pixel (`pixelRatio`, `?dpr=`).
+const PIXEL_RATIO_RANGE: (f64, f64) = (1.0, 4.0);
+
+/// The widget's `pixelRatio`, or 1 (pages render at design resolution).
+fn pixel_ratio(widget: &Value) -> f64 {
+    let ratio = widget.get("pixelRatio").and_then(|v| v.as_f64()).filter(|r| r.is_finite()).unwrap_or(1.0);
+    ratio.clamp(PIXEL_RATIO_RANGE.0, PIXEL_RATIO_RANGE.1)
+}
+
+/// Element sizes past this (px) are clamped; no browser source lays out more.
+const MAX_ELEMENT_SIZE: f64 = 16_384.0;
+/// Element x/y are clamped to ± this many px.
+const MAX_ELEMENT_OFFSET: f64 = 100_000.0;
+/// Width/height given to an element whose own isn't a number.
+const FALLBACK_ELEMENT_SIZE: f64 = 100.0;
+
+/// Coerce element geometry the page can't lay out (missing, non-numeric,
+/// negative or huge) to something it can, logging each fix. Saves reject most
+/// of this, but imports and older databases never went through them.
+fn clamp_geometry(elements: &mut Value, widget_id: &Value) {
+    let bounds = [
+        ("x", -MAX_ELEMENT_OFFSET, MAX_ELEMENT_OFFSET, 0.0),
+        ("y", -MAX_ELEMENT_OFFSET, MAX_ELEMENT_OFFSET, 0.0),
+        ("width", 0.0, MAX_ELEMENT_SIZE, FALLBACK_ELEMENT_SIZE),
+        ("height", 0.0, MAX_ELEMENT_SIZE, FALLBACK_ELEMENT_SIZE),
+    ];
+    for element in elements.as_array_mut().into_iter().flatten() {
+        let Some(element) = element.as_object_mut() else { continue };
+        for (key, min, max, fallback) in bounds {
+            let raw = element.get(key).and_then(|v| v.as_f64());
+            let fixed = raw.filter(|v| v.is_finite()).map_or(fallback, |v| v.clamp(min, max));
+            if raw != Some(fixed) {
+                let id = element.get("id").unwrap_or(&Value::Null);
+                let was = element.get(key).unwrap_or(&Value::Null);
+                log::warn!("Widget {widget_id}: element {id} has {key} {was}; rendering it as {fixed}");
+                element.insert(key.to_string(), Value::from(fixed));
+            }
+        }
+        if let Some(children) = element.get_mut("children") {
+            clamp_geometry(children, widget_id);
+        }
+    }
+}
+
+/// Give `autoContrast` text black or white, whichever reads better on its
+/// `backdropColor` or else the widget's `backdrop` (its background colour when
+/// that's opaque and has no image over it). Text with neither keeps its `color`.
+fn apply_auto_contrast(elements: &mut Value, backdrop: Option<&str>) {
+    for element in elements.as_array_mut().into_iter().flatten() {
+        let wanted = element.get("autoContrast").and_then(|v| v.as_bool()) == Some(true)
+            && element.get("type").and_then(|v| v.as_str()) == Some("text");
+        if wanted {
+            let own = element.get("backdropColor").and_then(|v| v.as_str()).filter(|c| !c.trim().is_empty());
+            if let Some(color) = own.or(backdrop).and_then(color::contrasting_text) {
+                element["color"] = Value::from(color);
+            }
+        }
+        if let Some(children) = element.get_mut("children") {
+            apply_auto_contrast(children, backdrop);
+        }
+    }
+}
+
+/// Stretch the geometry keys of `target` (an element or keyframe state) by `sx` horizontally and `sy` vertically.
+fn stretch_geometry(target: &mut Value, sx: f64, sy: f64) {
+    for (key, factor) in [("x", sx), ("width", sx), ("y", sy), ("height", sy)] {
+        if let Some(v) = target.get(key).and_then(|v| v.as_f64()) {
+            target[key] = Value::from(v * factor);
+        }
+    }
+}
+
+fn stretch_elements(elements: &mut Value, sx: f64, sy: f64) {
+    for element in elements.as_array_mut().into_iter().flatten() {
+        stretch_geometry(element, sx, sy);
+        if let Some(children) = element.get_mut("children") {
+            stretch_elements(children, sx, sy);
+        }
+    }
+}
+
+/// A copy of `widget` laid out at `width`×`height` (`?fit=layout`): element
+/// and keyframed positions and sizes, group children included, stretch with
+/// the canvas; everything else keeps its pixel size.
+fn relayout(widget: &Value, width: u64, height: u64) -> Value {
+    let (w, h) = widget_size(widget);
+    let (sx, sy) = (width as f64 / w.max(1) as f64, height as f64 / h.max(1) as f64);
+    let mut widget = widget.clone();
+    widget["width"] = Value::from(width);
+    widget["height"] = Value::from(height);
+    if let Some(elements) = widget.get_mut("elements") {
+        stretch_elements(elements, sx, sy);
+    }
+    for keyframe in segments::keyframe_lists_mut(&mut widget).into_iter().flatten() {
+        for state in keyframe.get_mut("elementStates").and_then(|s| s.as_object_mut()).into_iter().flat_map(|s| s.values_mut()) {
+            stretch_geometry(state, sx, sy);
+        }
+    }
+    widget
+}
+
+/// Widget ids already warned about being in several workspaces (once per run).
+static WARNED_DUPLICATES: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// The widget with this id. If several workspaces contain it (e.g. after
+/// importing the same file twice) the most recently updated one wins.
+pub(crate) fn find_widget(widget_id: &str) -> Option<Value> {
+    let mut matches = all_widgets().into_iter().filter(|(_, widget)| self::widget_id(widget) == Some(widget_id));
+    let (summary, widget) = matches.next()?;
+    if matches.next().is_some() && WARNED_DUPLICATES.lock().unwrap().insert(widget_id.to_string()) {
+        log::warn!("Widget id {widget_id} is in several workspaces; serving the one in '{}'", summary.name);
+    }
+    Some(widget)
+}
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateWidget {
+    pub widget_id: String,
+    /// Workspaces containing the id, most recently updated (the one served) first.
+    pub overlays: Vec<db::OverlaySummary>,
+}
+
+/// Widget ids that appear in more than one workspace (or twice in one).
+pub fn duplicate_widget_ids() -> Vec<DuplicateWidget> {
+    let mut by_id: Vec<DuplicateWidget> = Vec::new();
+    for (summary, widget) in all_widgets() {
+        let Some(id) = widget_id(&widget) else { continue };
+        match by_id.iter_mut().find(|d| d.widget_id == id) {
+            Some(entry) => entry.overlays.push(summary),
+            None => by_id.push(DuplicateWidget { widget_id: id.to_string(), overlays: vec![summary] }),
+        }
+    }
+    by_id.retain(|d| d.overlays.len() > 1);
+    by_id
+}
+
+#[derive(Debug, Serialize)]
+pub struct AssetUsage {
+    pub overlay_id: String,
+    pub overlay_name: String,
+    pub widget_id: String,
+    pub element_id: String,
+    /// Property holding the reference: `src`, `poster`, `maskImageSrc`,
+    /// `frames`, `fontFamily` or `segments`, `background` (with an empty
+    /// `element_id`) for the widget's background image, or `audioTrack` (with
+    /// empty `widget_id` and `element_id` too) for the workspace's audio track.
+    pub field: String,
+}
+
+/// Whether `reference` points at `asset`; `file://` URLs match their plain path.
+fn is_same_asset(reference: &str, asset: &str) -> bool {
+    let plain = |r: &str| r.strip_prefix("file://").unwrap_or(r).to_string();
+    reference == asset || plain(reference) == plain(asset)
+}
+
+/// Fields of an element naming `asset` as an image, video or font, each once.
+fn element_asset_fields(element: &Value, asset: &str) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    for (field, reference) in export::element_assets(element).into_iter().chain(export::element_fonts(element)) {
+        if is_same_asset(reference, asset) && !fields.contains(&field) {
+            fields.push(field);
+        }
+    }
+    fields
+}
+
+fn collect_asset_usages(elements: &Value, asset: &str, widget_id: &str, summary: &db::OverlaySummary, out: &mut Vec<AssetUsage>) {
+    for element in elements.as_array().into_iter().flatten() {
+        let element_id = element.get("id").and_then(|v| v.as_str()).unwrap_or("");
+        for field in element_asset_fields(element, asset) {
+            out.push(AssetUsage {
+                overlay_id: summary.id.clone(),
+                overlay_name: summary.name.clone(),
+                widget_id: widget_id.to_string(),
+                element_id: element_id.to_string(),
+                field: field.to_string(),
+            });
+        }
+        if let Some(children) = element.get("children") {
+            collect_asset_usages(children, asset, widget_id, summary, out);
+        }
+    }
+}
+
+/// The workspace's audio track, if it plays `asset`.
+fn audio_track_usage(summary: &db::OverlaySummary, config: &Value, asset: &str) -> Option<AssetUsage> {
+    audio_track(config).filter(|(src, _)| is_same_asset(src, asset)).map(|_| AssetUsage {
+        overlay_id: summary.id.clone(),
+        overlay_name: summary.name.clone(),
+        widget_id: String::new(),
+        element_id: String::new(),
+        field: "audioTrack".to_string(),
+    })
+}
+
+/// Every element, in every workspace, that references `asset` (a URL, file
+/// path or font family): what breaks if it's deleted. References picked up
+/// through a style preset count for each element using the preset, and a
+/// workspace audio track playing it counts too.
+pub fn find_asset_usages(asset: &str) -> Vec<AssetUsage> {
+    let mut usages = Vec::new();
+    for summary in db::list_overlays().unwrap_or_default() {
+        let Ok(Some(row)) = db::get_overlay(&summary.id) else { continue };
+        let Ok(config) = serde_json::from_str::<Value>(&row.config) else { continue };
+        usages.extend(audio_track_usage(&summary, &config, asset));
+    }
+    for (summary, widget) in all_widgets() {
+        let widget_id = widget_id(&widget).unwrap_or("");
+        // The widget's own background image, reported without an element
+        if widget.pointer("/background/src").and_then(|v| v.as_str()).is_some_and(|r| is_same_asset(r, asset)) {
+            usages.push(AssetUsage {
+                overlay_id: summary.id.clone(),
+                overlay_name: summary.name.clone(),
+                widget_id: widget_id.to_string(),
+                element_id: String::new(),
+                field: "background".to_string(),
+            });
+        }
+        let elements = style_presets::resolve(widget.get("elements").unwrap_or(&Value::Null), widget.get("stylePresets"));
+        collect_asset_usages(&elements, asset, widget_id, &summary, &mut usages);
+    }
+    usages
+}
+
+/// One element a search matched.
+#[derive(Debug, Serialize)]
+pub struct ElementHit {
+    pub widget_id: String,
+    pub element_id: String,
+    pub element_name: String,
+}
+
+/// A workspace's matches for an element search.
+#[derive(Debug, Serialize)]
+pub struct OverlayElementHits {
+    pub overlay_id: String,
+    pub overlay_name: String,
+    pub elements: Vec<ElementHit>,
+}
+
+fn collect_elements(elements: &Value, widget_id: &str, predicate: &dyn Fn(&Value) -> bool, out: &mut Vec<ElementHit>) {
+    for element in elements.as_array().into_iter().flatten() {
+        if predicate(element) {
+            out.push(ElementHit {
+                widget_id: widget_id.to_string(),
+                element_id: element.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                element_name: element.get("name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            });
+        }
+        if let Some(children) = element.get("children") {
+            collect_elements(children, widget_id, predicate, out);
+        }
+    }
+}
+
+/// Every element, in every workspace, that `predicate` accepts (group and
+/// mask children included, style presets applied), grouped by workspace.
+/// Workspaces without a match are left out.
+pub fn find_elements(predicate: impl Fn(&Value) -> bool) -> Vec<OverlayElementHits> {
+    let mut found: Vec<OverlayElementHits> = Vec::new();
+    for (summary, widget) in all_widgets() {
+        let elements = style_presets::resolve(widget.get("elements").unwrap_or(&Value::Null), widget.get("stylePresets"));
+        let mut hits = Vec::new();
+        collect_elements(&elements, widget_id(&widget).unwrap_or(""), &predicate, &mut hits);
+        if hits.is_empty() {
+            continue;
+        }
+        match found.iter_mut().find(|o| o.overlay_id == summary.id) {
+            Some(overlay) => overlay.elements.extend(hits),
+            None => found.push(OverlayElementHits { overlay_id: summary.id, overlay_name: summary.name, elements: hits }),
+        }
+    }
+    found
+}
+
+/// Elements of `kind` (`path`, `sprite`, ...), e.g. before retiring a type.
+pub fn find_elements_by_type(kind: &str) -> Vec<OverlayElementHits> {
+    find_elements(|element| element.get("type").and_then(|v| v.as_str()) == Some(kind))
+}
+
+/// Elements whose font stack, or one of whose rich text runs, names `family`
+/// (case-insensitive, quotes ignored).
+pub fn find_elements_using_font(family: &str) -> Vec<OverlayElementHits> {
+    let family = family.trim().trim_matches(['"', '\'']).to_lowercase();
+    let names_family = |v: &Value| {
+        v.get("fontFamily").and_then(|v| v.as_str()).is_some_and(|stack| {
+            stack.split(',').any(|f| f.trim().trim_matches(['"', '\'']).to_lowercase() == family)
+        })
+    };
+    find_elements(|element| {
+        names_family(element) || element.get("segments").and_then(|v| v.as_array()).is_some_and(|runs| runs.iter().any(names_family))
+    })
+}
+
+/// A widget's human-friendly URL name (`/w/{slug}`), if it has one.
+pub(crate) fn widget_slug(widget: &Value) -> Option<&str> {
+    widget.get("slug").and_then(|v| v.as_str()).filter(|s| !s.is_empty())
+}
+
+pub(crate) fn find_widget_by_slug(slug: &str) -> Option<Value> {
+    all_widgets().into_iter().map(|(_, widget)| widget).find(|widget| widget_slug(widget) == Some(slug))
+}
+
+/// Slugs are 1-64 characters of `a-z`, `0-9` and inner `-`, e.g. `follower-alert`.
+pub fn validate_slug(slug: &str) -> Result<(), String> {
+    let charset_ok = slug.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-');
+    if (1..=64).contains(&slug.len()) && charset_ok && !slug.starts_with('-') && !slug.ends_with('-') {
+        Ok(())
+    } else {
+        Err(format!("'{slug}' is not a valid slug; use 1-64 lowercase letters, digits and inner hyphens"))
+    }
+}
+
+/// Browser-source URL of a widget on the given `host[:port]`; the slug form
+/// when the widget has one.
+pub fn widget_url(host: &str, widget_id: &str, slug: Option<&str>) -> String {
+    match slug {
+        Some(slug) => format!("http://{host}/w/{slug}"),
+        None => format!("http://{host}/widget/{widget_id}"),
+    }
+}
+
+/// [`widget_url`] for a saved widget, looking up its slug.
+pub fn saved_widget_url(host: &str, widget_id: &str) -> String {
+    let widget = find_widget(widget_id);
+    widget_url(host, widget_id, widget.as_ref().and_then(widget_slug))
+}
+
+/// This machine's address on the local network, if it has one. Asks the OS
+/// which interface routes to a public address; a UDP `connect` sends nothing.
+pub fn lan_ip() -> Option<std::net::IpAddr> {
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_loopback() && !ip.is_unspecified()).then_some(ip)
+}
+
+/// `host:port` that other machines (LAN mode) or this machine should use.
+pub fn public_host() -> String {
+    let host = if settings::lan_enabled() {
+        lan_ip().map(|ip| ip.to_string()).unwrap_or_else(|| "localhost".to_string())
+    } else {
+        "localhost".to_string()
+    };
+    format!("{host}:{OBS_HTTP_PORT}")
+}
+
+// ---------------------------------------------------------------------------
+// Supported CSS blend modes
+// ---------------------------------------------------------------------------
+/// Every `blendMode` value the renderer honors (the full CSS `mix-blend-mode`
+/// set). Anything else renders as `normal`. Keep in sync with `BLEND_MODES` in
+/// `src/types.ts`, which backs the editor's dropdown.
+pub const BLEND_MODES: &[&str] = &[
+    "normal", "multiply", "screen", "overlay", "darken", "lighten", "color-dodge", "color-burn",
+    "hard-light", "soft-light", "difference", "exclusion", "hue", "saturation", "color", "luminosity",
+];
+
+// ---------------------------------------------------------------------------
+// Render options (query parameters on /widget/{id})
+// ---------------------------------------------------------------------------
+/// What `w`/`h` do to a widget page.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FitMode {
+    /// Scale the whole widget uniformly to fit.
+    #[default]
+    Scale,
+    /// Lay it out again at that size: positions and boxes stretch with the
+    /// canvas, while text, strokes and corner radii keep their pixel size.
+    Layout,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RenderOptions {
+    /// Freeze the timeline at this time in seconds (e.g. `?t=2.5`) instead of autoplaying.
+    pub t: Option<f64>,
+    /// Draw element bounding boxes, ids/zIndex and an FPS counter (`?debug=1`).
+    #[serde(default, deserialize_with = "query_flag")]
+    pub debug: bool,
+    /// Reduced-motion / weak-hardware mode (`?lite=1`): no looping timeline,
+    /// particles, filters or preset animations, just the content and one quick
+    /// fade. Also on when the browser prefers reduced motion.
+    #[serde(default, deserialize_with = "query_flag")]
+    pub lite: bool,
+    /// Uniformly scale the whole composition (e.g. `?scale=1.5`).
+    pub scale: Option<f64>,
+    /// Target width in px; the widget is scaled to fit it (with `h`, to fit both).
+    pub w: Option<f64>,
+    /// Target height in px.
+    pub h: Option<f64>,
+    /// `?fit=layout` re-lays the widget out at `w`×`h` instead of scaling it.
+    #[serde(default)]
+    pub fit: FitMode,
+    /// Render this many device pixels per design pixel (e.g. `?dpr=2`) so text
+    /// stays sharp when the source is scaled up in the scene; the page grows
+    /// to match. Overrides the widget's `pixelRatio`.
+    pub dpr: Option<f64>,
+    /// Longest wait in seconds between attempts to reach the app again after
+    /// it went away (e.g. `?retry=10`); the wait doubles up to this. Default 30.
+    pub retry: Option<f64>,
+    /// Self-contained page for hosting elsewhere: no control socket, reload
+    /// polling or error reporting. Not settable from the URL.
+    #[serde(skip)]
+    pub standalone: bool,
+}
+
+const MIN_SCALE: f64 = 0.05;
+const MAX_SCALE: f64 = 8.0;
+const DEFAULT_RETRY_MAX_SECS: f64 = 30.0;
+const RETRY_MAX_RANGE_SECS: (f64, f64) = (1.0, 300.0);
+
+impl RenderOptions {
+    /// Scale factor for a `width`×`height` widget: an explicit `scale` wins,
+    /// then the largest uniform scale fitting `w`/`h`, else 1.
+    /// With `fit=layout`, `w`/`h` resize the layout instead and only `scale` applies.
+    fn scale_for(&self, width: u64, height: u64) -> f64 {
+        let positive = |v: Option<f64>| v.filter(|v| v.is_finite() && *v > 0.0);
+        let fitting = self.fit == FitMode::Scale;
+        let fit_w = positive(self.w).filter(|_| fitting).map(|w| w / width.max(1) as f64);
+        let fit_h = positive(self.h).filter(|_| fitting).map(|h| h / height.max(1) as f64);
+        let scale = positive(self.scale).or(match (fit_w, fit_h) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        });
+        scale.unwrap_or(1.0).clamp(MIN_SCALE, MAX_SCALE)
+    }
+
+    /// The size to lay a `width`×`height` widget out at for `fit=layout`; an
+    /// axis without `w`/`h` keeps its size. `None` when there's nothing to change.
+    fn layout_size(&self, width: u64, height: u64) -> Option<(u64, u64)> {
+        if self.fit != FitMode::Layout {
+            return None;
+        }
+        let size = |v: Option<f64>, stored: u64| {
+            v.filter(|v| v.is_finite() && *v >= 1.0).map_or(stored, |v| v.min(MAX_ELEMENT_SIZE).round() as u64)
+        };
+        let resized = (size(self.w, width), size(self.h, height));
+        (resized != (width, height)).then_some(resized)
+    }
+
+    /// Device pixels per design pixel: `dpr` when given, else the widget's `pixelRatio`.
+    fn pixel_ratio_for(&self, widget: &Value) -> f64 {
+        match self.dpr.filter(|r| r.is_finite()) {
+            Some(ratio) => ratio.clamp(PIXEL_RATIO_RANGE.0, PIXEL_RATIO_RANGE.1),
+            None => pixel_ratio(widget),
         }
     }
-    None
+
+    /// Reconnect backoff cap in milliseconds.
+    fn retry_max_ms(&self) -> u64 {
+        let secs = self.retry.filter(|s| s.is_finite()).unwrap_or(DEFAULT_RETRY_MAX_SECS);
+        (secs.clamp(RETRY_MAX_RANGE_SECS.0, RETRY_MAX_RANGE_SECS.1) * 1000.0) as u64
+    }
+}
+
+/// Query flags are on when present as `1`, `true`, `yes` or `on`.
+fn query_flag<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    Ok(matches!(value.to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on"))
 }
 
 // ---------------------------------------------------------------------------
 // HTML renderer for a single widget
 // ---------------------------------------------------------------------------
-fn render_widget_html(widget: &Value) -> String {
+fn widget_hash(widget: &Value) -> u64 {
     let mut hasher = DefaultHasher::new();
     widget.to_string().hash(&mut hasher);
-    let hash = hasher.finish();
+    hasher.finish()
+}
 
-    let w = widget.get("width").and_then(|v| v.as_u64()).unwrap_or(400);
-    let h = widget.get("height").and_then(|v| v.as_u64()).unwrap_or(300);
-    let bg = widget.get("background").and_then(|v| v.as_str()).unwrap_or("transparent");
-    let elements_json = widget.get("elements").map(|e| e.to_string()).unwrap_or_else(|| "[]".to_string());
+/// Reset and preset `@keyframes` every widget page shares, served once at
+/// `/static/widget.css` (inlined into standalone exports).
+const WIDGET_CSS: &str = r#"* { margin:0; padding:0; box-sizing:border-box; }
+@keyframes fadeIn { from{opacity:0} to{opacity:1} }
+@keyframes slideInLeft { from{transform:translateX(-100%);opacity:0} to{transform:translateX(0);opacity:1} }
+@keyframes slideInRight { from{transform:translateX(100%);opacity:0} to{transform:translateX(0);opacity:1} }
+@keyframes bounceIn {
+  0%{transform:scale(0.3);opacity:0} 50%{transform:scale(1.05);opacity:1}
+  70%{transform:scale(0.9)} 100%{transform:scale(1)}
+}
+@keyframes pulse-slow { 0%,100%{opacity:1} 50%{opacity:0.5} }
+@keyframes slideInTop { from{transform:translateY(-100%);opacity:0} to{transform:translateY(0);opacity:1} }
+@keyframes slideInBottom { from{transform:translateY(100%);opacity:0} to{transform:translateY(0);opacity:1} }
+@keyframes zoomIn { from{transform:scale(0.3);opacity:0} 50%{opacity:1} to{transform:scale(1)} }
+@keyframes flipInX {
+  0%{transform:perspective(400px) rotateX(90deg);opacity:0} 40%{transform:perspective(400px) rotateX(-20deg)}
+  60%{transform:perspective(400px) rotateX(10deg);opacity:1} 100%{transform:perspective(400px) rotateX(0)}
+}
+@keyframes rotateIn { from{transform:rotate(-200deg) scale(0.5);opacity:0} to{transform:rotate(0) scale(1);opacity:1} }
+@keyframes shake {
+  0%,100%{transform:translateX(0)} 10%,30%,50%,70%,90%{transform:translateX(-10px)} 20%,40%,60%,80%{transform:translateX(10px)}
+}
+@keyframes fadeOut { from{opacity:1} to{opacity:0} }
+@keyframes slideOutLeft { from{transform:translateX(0);opacity:1} to{transform:translateX(-100%);opacity:0} }
+@keyframes slideOutRight { from{transform:translateX(0);opacity:1} to{transform:translateX(100%);opacity:0} }
+@keyframes bounceOut {
+  0%{transform:scale(1)} 30%{transform:scale(1.05);opacity:1} 100%{transform:scale(0.3);opacity:0}
+}
+@keyframes slideOutTop { from{transform:translateY(0);opacity:1} to{transform:translateY(-100%);opacity:0} }
+@keyframes slideOutBottom { from{transform:translateY(0);opacity:1} to{transform:translateY(100%);opacity:0} }
+@keyframes zoomOut { from{transform:scale(1);opacity:1} 50%{opacity:0} to{transform:scale(0.3);opacity:0} }
+@keyframes flipOutX {
+  0%{transform:perspective(400px) rotateX(0)} 30%{transform:perspective(400px) rotateX(-20deg);opacity:1}
+  100%{transform:perspective(400px) rotateX(90deg);opacity:0}
+}
+@keyframes rotateOut { from{transform:rotate(0) scale(1);opacity:1} to{transform:rotate(200deg) scale(0.5);opacity:0} }
+"#;
+
+/// Changes whenever `WIDGET_CSS` does, so pages can cache the stylesheet for good.
+static WIDGET_CSS_VERSION: Lazy<String> = Lazy::new(|| {
+    let mut hasher = DefaultHasher::new();
+    WIDGET_CSS.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+});
+
+/// Page code that talks back to this server (control socket, hash-poll
+/// reload); left out of standalone exports.
+const LIVE_JS: &str = r#"  // --- Control socket: reconnects with jittered exponential backoff ---
+  // While the app is away (restart, hidden source) nothing on screen changes;
+  // `reconnecting` only shows in the ?debug=1 HUD.
+  const RETRY_MIN_MS = 500;
+  let retryMs = RETRY_MIN_MS;
+  let retryTimer = null;
+  // Jitter keeps every source of a restarted app from reconnecting in lockstep
+  function nextRetryMs() {
+    const ms = Math.min(retryMs * (0.75 + Math.random() * 0.5), RETRY_MAX_MS);
+    retryMs = Math.min(retryMs * 2, RETRY_MAX_MS);
+    return ms;
+  }
+  function scheduleReconnect() {
+    if (retryTimer === null) retryTimer = setTimeout(connectControl, nextRetryMs());
+  }
+  function connectControl() {
+    retryTimer = null;
+    let ws;
+    try {
+      ws = new WebSocket(`${location.protocol === 'https:' ? 'wss' : 'ws'}://${location.host}/widget/${encodeURIComponent(WIDGET_ID)}/ws`);
+    } catch(e) { scheduleReconnect(); return; }
+    controlWs = ws;
+    ws.onopen = () => {
+      retryMs = RETRY_MIN_MS;
+      reportQueue();
+      // Back after a gap: the server re-sends its clock on open; catch up on edits right away
+      if (reconnecting) { reconnecting = false; checkHash(); }
+    };
+    ws.onmessage = (ev) => {
+      let msg;
+      try { msg = JSON.parse(ev.data); } catch(e) { return; }
+      handleControl(msg);
+    };
+    ws.onclose = () => {
+      timeUpdates = false;
+      reconnecting = true;
+      scheduleReconnect();
+    };
+  }
+  // A source shown again shouldn't sit out the rest of a long backoff
+  document.addEventListener('visibilitychange', () => {
+    if (document.visibilityState !== 'visible' || !reconnecting || retryTimer === null) return;
+    clearTimeout(retryTimer);
+    retryMs = RETRY_MIN_MS;
+    connectControl();
+  });
+  if (SEEK_T === null && WIDGET_ID) connectControl();
+
+  // Hash-based smart reload (polls instead of blind reloading). A new hash must
+  // be seen on two polls in a row, and reloads are at least RELOAD_MIN_MS apart,
+  // so rapid autosaves can't leave the source flashing through reloads.
+  const RELOAD_MIN_MS = 10000;
+  const RELOAD_KEY = 'oo-last-reload:' + WIDGET_ID;
+  let pendingHash = null;
+  let pollTimer = null, polling = false, pollFailures = 0;
+  function lastReload() {
+    try { return +sessionStorage.getItem(RELOAD_KEY) || 0; } catch(e) { return 0; }
+  }
+  function onPageHash(hash) {
+    if (!hash || hash === CURRENT_HASH) { pendingHash = null; return; }
+    if (hash !== pendingHash) { pendingHash = hash; return; }
+    if (Date.now() - lastReload() < RELOAD_MIN_MS) return;
+    try { sessionStorage.setItem(RELOAD_KEY, String(Date.now())); } catch(e) {}
+    location.reload();
+  }
+  async function checkHash() {
+    if (polling) return;
+    polling = true;
+    clearTimeout(pollTimer);
+    let delay = POLL_MS;
+    try {
+      const r = await fetch(location.href);
+      pollFailures = 0;
+      // e.g. 429 while rate limited: keep what's on screen
+      if (r.ok) {
+        const match = (await r.text()).match(/#HASH_(\d+)/);
+        onPageHash(match && match[1]);
+      }
+    } catch(e) {
+      // App unreachable: back off like the socket does
+      delay = Math.min(POLL_MS * 2 ** ++pollFailures, RETRY_MAX_MS);
+    }
+    polling = false;
+    pollTimer = setTimeout(checkHash, delay);
+  }
+  pollTimer = setTimeout(checkHash, POLL_MS);
+"#;
+
+/// Early script reporting uncaught errors to `/api/widget/{id}/error`;
+/// `__WIDGET_ID__` is replaced with the widget id as JSON.
+const ERROR_REPORTER_JS: &str = r#"<script>
+// Report uncaught errors to the app (a few per page load) so a blank source isn't a mystery
+(function() {
+  const id = __WIDGET_ID__;
+  if (id === null) return;
+  let reports = 0;
+  function report(message, source, line, column, stack) {
+    if (reports++ >= 5) return;
+    fetch('/api/widget/' + encodeURIComponent(id) + '/error', {
+      method: 'POST', headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ message: String(message), source: source || null, line: line || null, column: column || null, stack: stack || null }),
+    }).catch(() => {});
+  }
+  window.addEventListener('error', e => report(e.message, e.filename, e.lineno, e.colno, e.error && e.error.stack));
+  window.addEventListener('unhandledrejection', e => {
+    const r = e.reason;
+    report('Unhandled rejection: ' + (r && r.message || r), null, null, null, r && r.stack);
+  });
+})();
+</script>
+"#;
+
+fn render_widget_html(widget: &Value, opts: &RenderOptions) -> String {
+    // Hashed as stored, so a re-laid-out page doesn't look edited to its reload check;
+    // src nonces are left out, open pages get those over the control channel
+    let hash = widget_hash(&src_refresh::without_nonces(widget));
+    let (stored_w, stored_h) = widget_size(widget);
+    let relaid = opts.layout_size(stored_w, stored_h).map(|(width, height)| relayout(widget, width, height));
+    let widget = relaid.as_ref().unwrap_or(widget);
+
+    let (w, h) = widget_size(widget);
+    // Garbage renders transparent rather than breaking the page; saves reject it up front
+    let background = match widget.get("background").filter(|v| !v.is_null()) {
+        Some(raw) => color::parse_widget_background(raw).unwrap_or_else(|e| {
+            log::warn!("Widget {}: {e}; rendering it transparent", widget.get("id").unwrap_or(&Value::Null));
+            color::WidgetBackground { color: "transparent".to_string(), image: None }
+        }),
+        None => color::WidgetBackground { color: "transparent".to_string(), image: None },
+    };
+    let bg = background.color;
+    let bg_image_json = serde_json::to_string(&background.image).unwrap_or_else(|_| "null".to_string());
+    let mut elements = widget
+        .get("elements")
+        .map(|e| style_presets::resolve(e, widget.get("stylePresets")))
+        .unwrap_or_else(|| Value::Array(Vec::new()));
+    // Served widgets were already cleaned up (and logged) when they were looked up
+    for problem in sanitize_elements(&mut elements) {
+        log::warn!("Widget {}: {problem}", widget.get("id").unwrap_or(&Value::Null));
+    }
+    clamp_geometry(&mut elements, widget.get("id").unwrap_or(&Value::Null));
+    apply_auto_contrast(&mut elements, Some(bg.as_str()).filter(|_| background.image.is_none()));
+    src_refresh::apply(&mut elements);
+    let elements_json = elements.to_string();
     let timeline_json = widget.get("animationTimeline").map(|e| e.to_string()).unwrap_or_else(|| "null".to_string());
+    let blend_modes_json = serde_json::to_string(BLEND_MODES).unwrap_or_else(|_| "[]".to_string());
+    let widget_id_json = widget.get("id").map(|v| v.to_string()).unwrap_or_else(|| "null".to_string());
+    // Timeline seed wins over a widget-level one; any JSON number/string works
+    let random_seed_json = widget
+        .pointer("/animationTimeline/randomSeed")
+        .or_else(|| widget.get("randomSeed"))
+        .filter(|v| v.is_number() || v.is_string())
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "null".to_string());
+    let debug = opts.debug;
+    let lite = opts.lite;
+    let flat_z = widget.get("flatZIndex").and_then(|v| v.as_bool()).unwrap_or(false);
+    let retry_max_ms = opts.retry_max_ms();
+    let poll_ms = poll_ms(widget);
+    // Layout scale times pixel ratio: the page takes the scaled size, the root
+    // stays at design size and is transformed, so positions never change
+    let scale = (opts.scale_for(w, h) * opts.pixel_ratio_for(widget)).min(MAX_SCALE);
+    let (page_w, page_h) = ((w as f64 * scale).round(), (h as f64 * scale).round());
+    let (live_js, error_reporter, widget_css) = if opts.standalone {
+        (String::new(), String::new(), format!("<style>\n{WIDGET_CSS}</style>\n"))
+    } else {
+        (
+            LIVE_JS.to_string(),
+            ERROR_REPORTER_JS.replace("__WIDGET_ID__", &widget_id_json),
+            format!("<link rel=\"stylesheet\" href=\"/static/widget.css?v={}\">\n", *WIDGET_CSS_VERSION),
+        )
+    };
+    let seek_t = opts.t.filter(|t| t.is_finite()).map(|t| t.max(0.0).to_string()).unwrap_or_else(|| "null".to_string());
 
     format!(r#"<!DOCTYPE html>
 <html>
 <!-- #HASH_{hash} -->
 <head>
 <meta charset="utf-8">
-<style>
-* {{ margin:0; padding:0; box-sizing:border-box; }}
-html {{ width:{w}px; height:{h}px; overflow:hidden; background:transparent; }}
-body {{ width:{w}px; height:{h}px; overflow:hidden; background:transparent; }}
-@keyframes fadeIn {{ from{{opacity:0}} to{{opacity:1}} }}
-@keyframes slideInLeft {{ from{{transform:translateX(-100%);opacity:0}} to{{transform:translateX(0);opacity:1}} }}
-@keyframes slideInRight {{ from{{transform:translateX(100%);opacity:0}} to{{transform:translateX(0);opacity:1}} }}
-@keyframes bounceIn {{
-  0%{{transform:scale(0.3);opacity:0}} 50%{{transform:scale(1.05);opacity:1}}
-  70%{{transform:scale(0.9)}} 100%{{transform:scale(1)}}
-}}
-@keyframes pulse-slow {{ 0%,100%{{opacity:1}} 50%{{opacity:0.5}} }}
+{widget_css}<style>
+html {{ width:{page_w}px; height:{page_h}px; overflow:hidden; background:transparent; }}
+body {{ width:{page_w}px; height:{page_h}px; overflow:hidden; background:transparent; }}
 </style>
-</head>
+{error_reporter}</head>
 <body>
 <div id="root"></div>
 <script>
 const ELEMENTS = {elements_json};
 const TIMELINE = {timeline_json};
 const CURRENT_HASH = "{hash}";
+const WIDGET_ID = {widget_id_json};
+const SEEK_T = {seek_t};
+const RANDOM_SEED = {random_seed_json};
+const DEBUG = {debug};
+const LITE = {lite} || !!(window.matchMedia && matchMedia('(prefers-reduced-motion: reduce)').matches);
+const LITE_FADE_MS = 250;
+// Workspace `flatZIndex`: one global stacking order instead of one per group
+const FLAT_Z = {flat_z};
+const RETRY_MAX_MS = {retry_max_ms};
+// Widget `pollInterval`: how often to check the server for edits
+const POLL_MS = {poll_ms};
+// Bounds for element geometry; the server clamps stored values, the page clamps animated ones
+const MAX_SIZE = {max_size}, MAX_OFFSET = {max_offset}, FALLBACK_SIZE = {fallback_size};
+const BLEND_MODES = {blend_modes_json};
 const BG = "{bg}";
+// Widget background image ({{src, size, position}}), painted over BG and under every element
+const BG_IMAGE = {bg_image_json};
 const W = {w}, H = {h};
+const SCALE = {scale};
 
 (function render() {{
   const root = document.getElementById('root');
-  root.style.cssText = `position:relative;width:${{W}}px;height:${{H}}px;overflow:hidden;background:${{BG === 'transparent' ? 'transparent' : BG}}`;
+  root.style.cssText = `position:relative;width:${{W}}px;height:${{H}}px;overflow:hidden;isolation:isolate;background:${{BG === 'transparent' ? 'transparent' : BG}}`;
+  if (BG_IMAGE) {{
+    root.style.backgroundImage = `url(${{JSON.stringify(BG_IMAGE.src)}})`;
+    root.style.backgroundSize = BG_IMAGE.size;
+    root.style.backgroundPosition = BG_IMAGE.position;
+    root.style.backgroundRepeat = 'no-repeat';
+  }}
+  if (SCALE !== 1) {{
+    root.style.transformOrigin = '0 0';
+    root.style.transform = `scale(${{SCALE}})`;
+  }}
+
+  // --- Preset enter/exit animations: the enter (`enterAnimation`, or the older
+  // `animationName`) replays on every play, the exit runs before the widget hides ---
+  const presetAnimated = [];
+  const enterName = d => d.enterAnimation || d.animationName;
+  const hasPreset = name => !!name && name !== 'none';
+  const exitMs = d => (d.exitDuration ?? d.animationDuration ?? 1) * 1000;
+
+  function runPreset(el, name, seconds, delay, fill, iterations) {{
+    // Clearing first and forcing a style flush restarts a preset that's already set
+    el.style.animationName = 'none';
+    void el.offsetWidth;
+    el.style.animationName = name;
+    el.style.animationDuration = seconds + 's';
+    el.style.animationDelay = delay + 's';
+    el.style.animationFillMode = fill;
+    el.style.animationIterationCount = iterations;
+  }}
+
+  function playEnter({{ el, data }}) {{
+    if (hasPreset(enterName(data))) runPreset(el, enterName(data), data.animationDuration || 1, data.animationDelay || 0, 'both', data.animationIterationCount || '1');
+    else el.style.animationName = 'none'; // drop a finished exit
+  }}
 
   function applyAnim(el, data) {{
-    if (!data.animationName || data.animationName === 'none') return;
-    el.style.animationName = data.animationName;
-    el.style.animationDuration = (data.animationDuration || 1) + 's';
-    el.style.animationDelay = (data.animationDelay || 0) + 's';
-    el.style.animationFillMode = 'both';
-    el.style.animationIterationCount = data.animationIterationCount || '1';
+    if (LITE || !(hasPreset(enterName(data)) || hasPreset(data.exitAnimation))) return;
+    presetAnimated.push({{ el, data }});
+    if (!hasPreset(enterName(data))) return;
+    playEnter({{ el, data }});
+    if (SEEK_T !== null) {{
+      // Frozen render: park the preset animation at the requested time
+      el.style.animationDelay = ((data.animationDelay || 0) - SEEK_T) + 's';
+      el.style.animationPlayState = 'paused';
+    }}
   }}
 
+  // Linear directions as angles, where a timeline starts animating `gradientAngle` from
+  const GRADIENT_ANGLES = {{'to top': 0, 'to right': 90, 'to bottom right': 135, 'to bottom': 180, 'to left': 270}};
+  // Gradient mask; `gradientAngle` (deg) overrides the direction, and the stop positions
+  // (`gradientStart`/`gradientEnd`, %) and opacities all animate
   function gradStr(d) {{
     const start = d.gradientStartOpacity ?? 1, end = d.gradientEndOpacity ?? 0;
-    const dirs = {{'to right':'to right','to left':'to left','to bottom':'to bottom','to top':'to top','to bottom right':'to bottom right'}};
-    if(d.gradientDir === 'radial') return `radial-gradient(circle,rgba(0,0,0,${{start}}) 0%,rgba(0,0,0,${{end}}) 100%)`;
-    return `linear-gradient(${{dirs[d.gradientDir]||'to bottom'}},rgba(0,0,0,${{start}}) 0%,rgba(0,0,0,${{end}}) 100%)`;
+    const stops = `rgba(0,0,0,${{start}}) ${{d.gradientStart ?? 0}}%,rgba(0,0,0,${{end}}) ${{d.gradientEnd ?? 100}}%`;
+    if(d.gradientDir === 'radial') return `radial-gradient(circle,${{stops}})`;
+    const dir = typeof d.gradientAngle === 'number' && isFinite(d.gradientAngle) ? d.gradientAngle + 'deg'
+      : d.gradientDir in GRADIENT_ANGLES ? d.gradientDir : 'to bottom';
+    return `linear-gradient(${{dir}},${{stops}})`;
+  }}
+
+  function applyImageMask(el, d) {{
+    const url = `url(${{JSON.stringify(d.maskImageSrc)}})`;
+    const size = d.maskSize || 'cover', pos = d.maskPosition || 'center';
+    el.style.webkitMaskImage = url; el.style.maskImage = url;
+    el.style.webkitMaskSize = size; el.style.maskSize = size;
+    el.style.webkitMaskPosition = pos; el.style.maskPosition = pos;
+    el.style.webkitMaskRepeat = 'no-repeat'; el.style.maskRepeat = 'no-repeat';
+    // A mask image that fails to load would hide the whole group; drop the mask instead
+    const probe = new Image();
+    probe.onerror = () => {{ el.style.webkitMaskImage = 'none'; el.style.maskImage = 'none'; }};
+    probe.src = d.maskImageSrc;
+  }}
+
+  // Unknown blend modes (typos, stale values) fall back to normal
+  function applyBlend(el, data) {{
+    if (!data.blendMode || data.blendMode === 'normal') return;
+    if (!BLEND_MODES.includes(data.blendMode)) {{
+      console.warn(`Unknown blendMode "${{data.blendMode}}" on element ${{data.id}}; using normal`);
+      return;
+    }}
+    el.style.mixBlendMode = data.blendMode;
+  }}
+
+  // --- Tint: a colour layer over an image or video (`tint`, `tintBlend`,
+  // `tintStrength` %), blended with the media only; the element's filters apply on top ---
+  const tintLayers = new WeakMap();
+  const TINT_MASK_SIZES = {{ contain: 'contain', cover: 'cover', fill: '100% 100%' }};
+  function applyTint(el, data) {{
+    if (!data.tint) return;
+    const layer = document.createElement('div');
+    layer.style.cssText = 'position:absolute;inset:0;pointer-events:none';
+    const blend = data.tintBlend || 'color';
+    if (BLEND_MODES.includes(blend)) layer.style.mixBlendMode = blend;
+    else console.warn(`Unknown tintBlend "${{blend}}" on element ${{data.id}}; using normal`);
+    if (data.type === 'image') {{
+      // Masked by the image itself, laid out like it, so transparent areas stay transparent
+      const url = `url(${{JSON.stringify(data.src)}})`;
+      const size = TINT_MASK_SIZES[data.objectFit] || 'contain', pos = data.objectPosition || 'center';
+      layer.style.webkitMaskImage = url; layer.style.maskImage = url;
+      layer.style.webkitMaskSize = size; layer.style.maskSize = size;
+      layer.style.webkitMaskPosition = pos; layer.style.maskPosition = pos;
+      layer.style.webkitMaskRepeat = 'no-repeat'; layer.style.maskRepeat = 'no-repeat';
+    }}
+    // Blend with the media, not whatever is behind the element
+    el.style.isolation = 'isolate';
+    el.appendChild(layer);
+    tintLayers.set(el, layer);
+  }}
+
+  // --- Fit children: a group with `fitChildren` lays its children out in a box of its
+  // stored size and scales that box to the group's animated width/height ---
+  const fitLayers = new WeakMap();
+  function fitLayer(el, data) {{
+    const w = Number(data.width) || 0, h = Number(data.height) || 0;
+    if (!data.fitChildren || w <= 0 || h <= 0) return el;
+    const layer = document.createElement('div');
+    layer.style.cssText = `position:absolute;left:0;top:0;width:${{w}}px;height:${{h}}px;transform-origin:0 0`;
+    el.appendChild(layer);
+    fitLayers.set(el, {{ layer, w, h }});
+    return layer;
+  }}
+
+  // --- Numeric counters: `content` numbers interpolate, keeping any prefix/suffix ("$", " subs") ---
+  const COUNTER_RE = /^([^\d-]*)(-?\d(?:[\d,\s]*\d)?(?:\.\d+)?)(.*)$/s;
+  function parseCounter(s) {{
+    const m = COUNTER_RE.exec(String(s ?? ''));
+    if (!m) return null;
+    const digits = m[2].replace(/[,\s]/g, '');
+    return {{ prefix: m[1], value: parseFloat(digits), decimals: (digits.split('.')[1] || '').length, suffix: m[3] }};
+  }}
+
+  // Decimals default to as many as the (interpolated) content carries
+  function formatCounter(d, content) {{
+    const p = parseCounter(content);
+    if (!p) return String(content ?? '');
+    const decimals = Math.min(20, Math.max(0, d.decimals ?? p.decimals));
+    const opts = {{ minimumFractionDigits: decimals, maximumFractionDigits: decimals, useGrouping: !!d.thousandsSeparator }};
+    let fmt;
+    try {{ fmt = new Intl.NumberFormat(d.locale || undefined, opts); }}
+    catch(e) {{ fmt = new Intl.NumberFormat(undefined, opts); }}
+    return p.prefix + fmt.format(p.value) + p.suffix;
+  }}
+
+  function lerpCounter(a, b, t) {{
+    const pa = parseCounter(a), pb = parseCounter(b);
+    // Non-numeric content can't count; it switches when the next keyframe is reached
+    if (!pa || !pb) return t < 1 ? a : b;
+    // Keep the precision of the more precise end so '0' -> '9.99' counts in cents
+    const value = pa.value + (pb.value - pa.value) * t;
+    return pb.prefix + value.toFixed(Math.max(pa.decimals, pb.decimals)) + pb.suffix;
+  }}
+
+  function opacityKeyframed(id) {{
+    return ((TIMELINE && TIMELINE.keyframes) || []).some(kf => kf.elementStates && kf.elementStates[id] && kf.elementStates[id].opacity !== undefined);
+  }}
+
+  // Last value written to each node's styles/attributes, so a frame only touches
+  // what changed since the previous one instead of re-setting every property
+  const writeCache = new WeakMap();
+  function cacheFor(node) {{
+    let last = writeCache.get(node);
+    if (!last) writeCache.set(node, last = {{}});
+    return last;
+  }}
+  function setStyle(node, prop, value) {{
+    const last = cacheFor(node), v = String(value);
+    if (last[prop] === v) return;
+    last[prop] = v;
+    node.style[prop] = v;
+  }}
+  function setAttr(node, name, value) {{
+    const last = cacheFor(node), key = '@' + name, v = String(value);
+    if (last[key] === v) return;
+    last[key] = v;
+    node.setAttribute(name, v);
+  }}
+  function removeAttr(node, name) {{
+    delete cacheFor(node)['@' + name];
+    node.removeAttribute(name);
+  }}
+
+  // An interpolated NaN or runaway size mustn't take the layout with it; warn once per element and key
+  const geometryWarned = new Set();
+  function safeGeometry(d, key, min, max, fallback) {{
+    const v = Number(d[key]);
+    const fixed = Number.isFinite(v) ? Math.min(max, Math.max(min, v)) : fallback;
+    if (fixed !== v && !geometryWarned.has(d.id + '/' + key)) {{
+      geometryWarned.add(d.id + '/' + key);
+      console.warn(`Element ${{d.id}} has ${{key}} ${{d[key]}}; using ${{fixed}}`);
+    }}
+    return fixed;
   }}
 
   function applyAnimProps(el, merged) {{
     if (!el) return;
-    el.style.left = merged.x + 'px';
-    el.style.top = merged.y + 'px';
-    el.style.width = merged.width + 'px';
-    el.style.height = merged.height + 'px';
-    el.style.opacity = merged.opacity ?? 1;
+    setStyle(el, 'left', safeGeometry(merged, 'x', -MAX_OFFSET, MAX_OFFSET, 0) + 'px');
+    setStyle(el, 'top', safeGeometry(merged, 'y', -MAX_OFFSET, MAX_OFFSET, 0) + 'px');
+    setStyle(el, 'width', safeGeometry(merged, 'width', 0, MAX_SIZE, FALLBACK_SIZE) + 'px');
+    setStyle(el, 'height', safeGeometry(merged, 'height', 0, MAX_SIZE, FALLBACK_SIZE) + 'px');
+    const clip = clipPathOf(merged);
+    if (clip !== null) setStyle(el, 'clipPath', clip);
 
-    let filter = `blur(${{merged.blur||0}}px) brightness(${{merged.brightness||100}}%) contrast(${{merged.contrast||100}}%) hue-rotate(${{merged.hueRotate||0}}deg) saturate(${{merged.saturate||100}}%)`;
+    let opacity = merged.opacity ?? 1;
+    let transform = `scale(${{merged.scaleX??1}}, ${{merged.scaleY??1}}) rotate(${{merged.rotation||0}}deg)`;
+    let filter = LITE ? 'none' : `blur(${{merged.blur||0}}px) brightness(${{merged.brightness||100}}%) contrast(${{merged.contrast||100}}%) hue-rotate(${{merged.hueRotate||0}}deg) saturate(${{merged.saturate||100}}%)`;
 
     if (merged.type === 'group' || merged.type === 'mask') {{
-      el.style.transform = `scale(${{merged.scaleX??1}}, ${{merged.scaleY??1}}) rotate(${{merged.rotation||0}}deg)`;
-      el.style.filter = filter;
-    }} else {{
-      el.style.transform = `scale(${{merged.scaleX??1}}, ${{merged.scaleY??1}}) rotate(${{merged.rotation||0}}deg)`;
-      el.style.filter = filter;
-
-      if (merged.type === 'shape') {{
-        if (merged.shapeType !== 'triangle' && merged.shapeType !== 'star') {{
-          el.style.backgroundColor = merged.fill || 'transparent'; // instead of background to keep structure
-          el.style.borderRadius = (merged.borderRadius || 0) + 'px';
-          if (merged.strokeWidth) el.style.border = merged.strokeWidth + 'px solid ' + (merged.strokeColor || 'transparent');
-          else el.style.border = 'none';
-        }} else {{
-          const i = el.firstChild;
-          if (i) i.style.background = merged.fill || '#3b82f6';
-        }}
-      }} else if (merged.type === 'path' && merged.pathData) {{
-        const svg = el.firstChild;
-        if (svg && svg.firstChild) {{
-           svg.firstChild.setAttribute('fill', merged.fill || 'none');
-           svg.firstChild.setAttribute('stroke', merged.strokeColor || '#3b82f6');
-           svg.firstChild.setAttribute('stroke-width', merged.strokeWidth || 4);
-        }}
-      }} else if (merged.type === 'text') {{
-        el.style.fontSize = (merged.fontSize || 48) + 'px';
-        el.style.color = merged.color || '#fff';
-        if (merged.letterSpacing !== undefined) el.style.letterSpacing = merged.letterSpacing + 'px';
-        if (merged.lineHeight !== undefined) el.style.lineHeight = merged.lineHeight;
+      // Group opacity is one alpha for the composited group; children keep their own on top of it
+      if (merged.maskType === 'opacity' && merged.maskInvert) opacity = 1 - (merged.opacity ?? 1);
+      if (merged.maskType === 'gradient') {{
+        const gs = gradStr(merged);
+        setStyle(el, 'webkitMaskImage', gs);
+        setStyle(el, 'maskImage', gs);
+      }}
+      // Flat z-index: an untransformed, unfiltered group mustn't become a stacking context
+      if (FLAT_Z) {{
+        if ((merged.scaleX ?? 1) === 1 && (merged.scaleY ?? 1) === 1 && !merged.rotation) transform = 'none';
+        if (!merged.blur && !merged.hueRotate && [merged.brightness, merged.contrast, merged.saturate].every(v => !v || v === 100)) filter = 'none';
+      }}
+      setStyle(el, 'opacity', opacity);
+      setStyle(el, 'transform', transform);
+      setStyle(el, 'filter', filter);
+      const fit = fitLayers.get(el);
+      if (fit) {{
+        const sx = safeGeometry(merged, 'width', 0, MAX_SIZE, fit.w) / fit.w, sy = safeGeometry(merged, 'height', 0, MAX_SIZE, fit.h) / fit.h;
+        setStyle(fit.layer, 'transform', sx === 1 && sy === 1 ? 'none' : `scale(${{sx}}, ${{sy}})`);
+      }}
+      return;
+    }}
+    setStyle(el, 'opacity', opacity);
+    setStyle(el, 'transform', transform);
+    setStyle(el, 'filter', filter);
+    const tint = tintLayers.get(el);
+    if (tint) {{
+      setStyle(tint, 'backgroundColor', merged.tint || 'transparent');
+      setStyle(tint, 'opacity', Math.min(100, Math.max(0, merged.tintStrength ?? 100)) / 100);
+    }}
+
+    if (merged.type === 'shape') {{
+      if (merged.shapeType !== 'triangle' && merged.shapeType !== 'star') {{
+        setStyle(el, 'backgroundColor', merged.fill || 'transparent'); // instead of background to keep structure
+        setStyle(el, 'borderRadius', (merged.borderRadius || 0) + 'px');
+        setStyle(el, 'border', merged.strokeWidth ? merged.strokeWidth + 'px solid ' + (merged.strokeColor || 'transparent') : 'none');
+      }} else {{
+        const i = el.firstChild;
+        if (i) setStyle(i, 'background', merged.fill || '#3b82f6');
+      }}
+    }} else if (merged.type === 'path' && merged.pathData) {{
+      const svg = el.firstChild;
+      if (svg && svg.firstChild) {{
+         const paths = [svg.firstChild];
+         setAttr(svg.firstChild, 'd', merged.pathData);
+         // Incompatible morph: fade the target path in over the source
+         const fade = merged.pathCrossfade;
+         let second = svg.childNodes[1];
+         if (fade) {{
+           if (!second) {{ second = svg.firstChild.cloneNode(false); svg.appendChild(second); }}
+           setAttr(second, 'd', fade.to);
+           setAttr(svg.firstChild, 'opacity', 1 - fade.t);
+           setAttr(second, 'opacity', fade.t);
+           paths.push(second);
+         }} else if (second) {{
+           second.remove();
+           removeAttr(svg.firstChild, 'opacity');
+         }}
+         for (const p of paths) {{
+           setAttr(p, 'fill', merged.fill || 'none');
+           setAttr(p, 'stroke', merged.strokeColor || '#3b82f6');
+           setAttr(p, 'stroke-width', merged.strokeWidth || 4);
+         }}
+      }}
+    }} else if (merged.type === 'text') {{
+      setStyle(el, 'fontSize', (merged.fontSize || 48) + 'px');
+      setStyle(el, 'color', merged.color || '#fff');
+      if (merged.letterSpacing !== undefined) setStyle(el, 'letterSpacing', merged.letterSpacing + 'px');
+      if (merged.lineHeight !== undefined) setStyle(el, 'lineHeight', merged.lineHeight);
+      if (merged.numericContent && !merged.dynamic && !(merged.segments && merged.segments.length)) {{
+        const text = formatCounter(merged, merged.content), last = cacheFor(el);
+        if (last.text !== text) {{ last.text = text; el.textContent = text; }}
       }}
     }}
   }}
 
-  function buildEl(data, parentEl) {{
-    if (data.visible === false) return;
+  // --- Randomness: a seeded PRNG (mulberry32) when the widget sets randomSeed ---
+  function seedFrom(v) {{
+    if (typeof v === 'number') return v >>> 0;
+    let h = 2166136261; // FNV-1a over the string
+    for (let i = 0; i < v.length; i++) {{ h ^= v.charCodeAt(i); h = Math.imul(h, 16777619); }}
+    return h >>> 0;
+  }}
 
-    let el = document.createElement('div');
-    el.id = 'el_' + data.id;
+  function mulberry32(a) {{
+    return () => {{
+      a = (a + 0x6D2B79F5) | 0;
+      let t = Math.imul(a ^ (a >>> 15), 1 | a);
+      t = (t + Math.imul(t ^ (t >>> 7), 61 | t)) ^ t;
+      return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+    }};
+  }}
 
-    if (data.type === 'group' || data.type === 'mask') {{
-      el.style.cssText = `position:absolute;z-index:${{data.zIndex}};isolation:isolate;transform-origin:center center;`;
-      if (data.blendMode && data.blendMode !== 'normal') el.style.mixBlendMode = data.blendMode;
-      if (data.maskType === 'clip') {{
-        const r = data.clipRadius !== undefined ? data.clipRadius : 0;
-        el.style.overflow = 'hidden';
-        el.style.borderRadius = r + 'px';
-      }} else if (data.maskType === 'gradient') {{
-        const gs = gradStr(data);
-        el.style.webkitMaskImage = gs; el.style.maskImage = gs;
-      }} else if (data.maskType === 'opacity') {{
-        // opacity applied in applyAnimProps
-      }}
-      (data.children || []).filter(c => c.visible !== false).sort((a,b) => a.zIndex - b.zIndex).forEach(c => buildEl(c, el));
+  let random = Math.random;
+  // Restart the sequence so every play of the timeline comes out identical
+  function reseed() {{
+    if (RANDOM_SEED !== null) random = mulberry32(seedFrom(RANDOM_SEED));
+  }}
+  reseed();
+
+  // --- Particle emitters (confetti bursts on a canvas) ---
+  const emitters = [];
+  function createEmitter(el, d) {{
+    const canvas = document.createElement('canvas');
+    // Backing store at the page's scale so particles stay as sharp as the rest
+    canvas.width = Math.max(1, Math.round(d.width * SCALE)); canvas.height = Math.max(1, Math.round(d.height * SCALE));
+    canvas.style.cssText = 'width:100%;height:100%;pointer-events:none';
+    el.appendChild(canvas);
+    emitters.push({{ data: d, canvas, ctx: canvas.getContext('2d'), particles: [], frame: null, last: 0 }});
+  }}
+
+  function burst(em) {{
+    const d = em.data;
+    const colors = Array.isArray(d.colors) && d.colors.length ? d.colors : ['#f59e0b','#ef4444','#3b82f6','#10b981','#ec4899'];
+    const spread = (d.spread ?? 90) * Math.PI / 180;
+    const power = d.velocity ?? 600;
+    for (let i = 0; i < (d.count ?? 80); i++) {{
+      const angle = -Math.PI / 2 + (random() - 0.5) * spread;
+      const speed = power * (0.5 + random() * 0.5);
+      em.particles.push({{
+        x: d.width / 2, y: d.height / 2,
+        vx: Math.cos(angle) * speed, vy: Math.sin(angle) * speed,
+        size: 4 + random() * 6, rot: random() * Math.PI, vr: (random() - 0.5) * 12,
+        color: colors[i % colors.length], life: d.lifetime ?? 3,
+      }});
+    }}
+    if (em.frame === null) {{ em.last = performance.now(); em.frame = requestAnimationFrame(now => stepEmitter(em, now)); }}
+  }}
+
+  function stepEmitter(em, now) {{
+    const dt = Math.min(0.05, (now - em.last) / 1000);
+    em.last = now;
+    const gravity = em.data.gravity ?? 900, W = Math.max(1, em.data.width), H = Math.max(1, em.data.height);
+    em.ctx.setTransform(em.canvas.width / W, 0, 0, em.canvas.height / H, 0, 0);
+    em.ctx.clearRect(0, 0, W, H);
+    em.particles = em.particles.filter(p => {{
+      p.vy += gravity * dt; p.x += p.vx * dt; p.y += p.vy * dt; p.rot += p.vr * dt; p.life -= dt;
+      if (p.life <= 0 || p.y > H + p.size || p.x < -p.size || p.x > W + p.size) return false;
+      em.ctx.save(); em.ctx.translate(p.x, p.y); em.ctx.rotate(p.rot);
+      em.ctx.fillStyle = p.color; em.ctx.fillRect(-p.size / 2, -p.size / 4, p.size, p.size / 2);
+      em.ctx.restore();
+      return true;
+    }});
+    // Stop scheduling frames once every particle is gone
+    em.frame = em.particles.length ? requestAnimationFrame(n => stepEmitter(em, n)) : null;
+  }}
+
+  function fireEmitters(kind) {{
+    for (const em of emitters) if ((em.data.emitOn || 'start') === kind) burst(em);
+  }}
+
+  // --- Sprites (frame lists or sprite sheets stepped with requestAnimationFrame) ---
+  const sprites = [];
+  let spriteFrame = null;
+  const spriteFps = d => d.fps > 0 ? d.fps : 12;
+  const spriteMs = sp => sp.count * 1000 / spriteFps(sp.data);
+  function createSprite(el, d) {{
+    const frames = Array.isArray(d.frames) ? d.frames.filter(f => typeof f === 'string' && f) : [];
+    const rows = Math.max(1, Math.floor(d.spriteRows || 1)), cols = Math.max(1, Math.floor(d.spriteCols || 1));
+    const count = frames.length || Math.max(1, Math.min(rows * cols, Math.floor(d.frameCount || rows * cols)));
+    if (!frames.length && !d.src) return;
+    let node;
+    if (frames.length) {{
+      node = document.createElement('img');
+      node.style.objectFit = d.objectFit || 'contain';
+      if (d.objectPosition) node.style.objectPosition = d.objectPosition;
+      // Decode every frame up front so stepping never waits on the network
+      for (const f of frames) new Image().src = f;
     }} else {{
-      el.style.cssText = `position:absolute;z-index:${{data.zIndex}};transform-origin:center center;display:flex;align-items:center;justify-content:center;overflow:hidden;`;
-      if (data.blendMode && data.blendMode !== 'normal') el.style.mixBlendMode = data.blendMode;
-      applyAnim(el, data);
+      // Each sheet cell is stretched over the element
+      node = document.createElement('div');
+      node.style.backgroundImage = `url(${{JSON.stringify(d.src)}})`;
+      node.style.backgroundSize = `${{cols * 100}}% ${{rows * 100}}%`;
+      node.style.backgroundRepeat = 'no-repeat';
+    }}
+    node.style.width = '100%'; node.style.height = '100%';
+    el.appendChild(node);
+    const sp = {{ data: d, node, frames, rows, cols, count, start: null, shown: -1 }};
+    sprites.push(sp);
+    showSpriteFrame(sp, 0);
+  }}
 
-      if (data.type === 'shape') {{
-        if (data.shapeType === 'triangle') {{
-          const i = document.createElement('div');
-          i.style.cssText = `width:100%;height:100%;background:${{data.fill||'#3b82f6'}};clip-path:polygon(50% 0%,0% 100%,100% 100%)`;
-          el.appendChild(i);
-        }} else if (data.shapeType === 'star') {{
-          const i = document.createElement('div');
+  function showSpriteFrame(sp, i) {{
+    if (i === sp.shown) return;
+    sp.shown = i;
+    if (sp.frames.length) {{ sp.node.src = sp.frames[i]; return; }}
+    const col = i % sp.cols, row = Math.floor(i / sp.cols);
+    sp.node.style.backgroundPosition =
+      `${{sp.cols > 1 ? col / (sp.cols - 1) * 100 : 0}}% ${{sp.rows > 1 ? row / (sp.rows - 1) * 100 : 0}}%`;
+  }}
+
+  // Frame `ms` into a sprite: wrapped when it loops, held on the last one otherwise
+  function spriteFrameAt(sp, ms) {{
+    const i = Math.floor(ms * spriteFps(sp.data) / 1000);
+    return sp.data.loop ? i % sp.count : Math.min(i, sp.count - 1);
+  }}
+
+  function stepSprites(now) {{
+    let running = false;
+    for (const sp of sprites) {{
+      if (sp.start === null) continue;
+      const ms = now - sp.start;
+      showSpriteFrame(sp, spriteFrameAt(sp, ms));
+      if (sp.data.loop || ms < spriteMs(sp)) running = true;
+      else sp.start = null;
+    }}
+    // Stop scheduling frames once every one-shot sprite has finished
+    spriteFrame = running ? requestAnimationFrame(stepSprites) : null;
+  }}
+
+  // (Re)start sprites from their first frame, or `lateMs` in; `onlyLoops` starts the ambient ones at load
+  function playSprites(lateMs = 0, onlyLoops = false) {{
+    const now = performance.now() - lateMs;
+    for (const sp of sprites) {{
+      if (onlyLoops && !sp.data.loop) continue;
+      if (LITE) {{ showSpriteFrame(sp, sp.data.loop ? 0 : sp.count - 1); continue; }}
+      sp.start = now;
+      sp.shown = -1;
+    }}
+    if (!LITE && spriteFrame === null && sprites.some(sp => sp.start !== null)) spriteFrame = requestAnimationFrame(stepSprites);
+  }}
+
+  // --- Dynamic text (clock / date), formatted per locale via Intl ---
+  const DYNAMIC_DEFAULTS = {{
+    time: {{ hour: 'numeric', minute: '2-digit' }},
+    date: {{ dateStyle: 'long' }},
+    datetime: {{ dateStyle: 'medium', timeStyle: 'short' }},
+  }};
+  const dynamicTexts = [];
+
+  function makeFormatter(d) {{
+    const opts = {{ ...(DYNAMIC_DEFAULTS[d.dynamic] || DYNAMIC_DEFAULTS.time), ...(d.dateTimeOptions || {{}}) }};
+    if (d.hour12 !== undefined) opts.hour12 = d.hour12;
+    if (d.timeZone) opts.timeZone = d.timeZone;
+    // An empty/absent locale means the system locale; a bad locale, then bad options, degrade in turn
+    for (const [locale, o] of [[d.locale || undefined, opts], [undefined, opts], [undefined, DYNAMIC_DEFAULTS.time]]) {{
+      try {{ return new Intl.DateTimeFormat(locale, o); }}
+      catch(e) {{ console.warn(`Invalid locale/format on element ${{d.id}}: ${{e}}`); }}
+    }}
+  }}
+
+  function registerDynamicText(el, d) {{
+    const fmt = makeFormatter(d);
+    el.textContent = fmt.format(new Date());
+    dynamicTexts.push({{ el, fmt }});
+  }}
+
+  // Text direction / writing mode. left/right alignment means start/end, so it
+  // follows the reading direction (identical to before for LTR text)
+  const TEXT_ALIGN = {{ left: 'start', center: 'center', right: 'end' }};
+  function applyTextFlow(el, d) {{
+    if (d.direction === 'rtl' || d.direction === 'auto') el.dir = d.direction;
+    el.style.direction = d.direction === 'rtl' ? 'rtl' : 'ltr';
+    const vertical = d.writingMode === 'vertical-rl' || d.writingMode === 'vertical-lr';
+    if (vertical) el.style.writingMode = d.writingMode;
+    el.style.textAlign = TEXT_ALIGN[d.textAlign || 'center'] || 'center';
+    el.style.padding = vertical ? '8px 0' : '0 8px';
+  }}
+
+  // Inline image run (emote, platform icon): one line tall, centred on the text.
+  // A missing image collapses to its alt text (or nothing) instead of a broken icon
+  function inlineImage(seg, lineHeight) {{
+    const img = document.createElement('img');
+    img.alt = seg.text ?? '';
+    img.style.height = (seg.height ?? lineHeight ?? 1.2) + 'em';
+    img.style.width = 'auto';
+    img.style.verticalAlign = 'middle';
+    img.style.display = 'inline-block';
+    img.onerror = () => {{
+      const fallback = document.createElement('span');
+      fallback.textContent = img.alt;
+      img.replaceWith(fallback);
+    }};
+    img.src = seg.src;
+    return img;
+  }}
+
+  // --- Draw-on paths: the stroke reveals itself as the timeline plays ---
+  const drawPaths = [];
+  function applyDrawOn(t) {{
+    const total = TIMELINE ? TIMELINE.duration : 0;
+    for (const {{ svg, data }} of drawPaths) {{
+      const start = data.drawStart ?? 0;
+      const dur = data.drawDuration ?? Math.max(0, total - start);
+      const progress = dur > 0 ? Math.min(1, Math.max(0, (t - start) / dur)) : (t >= start ? 1 : 0);
+      // Measured every time: a morphing path changes length as it goes
+      for (const p of svg.childNodes) {{
+        const len = p.getTotalLength ? p.getTotalLength() : 0;
+        p.style.strokeDasharray = `${{len}} ${{len}}`;
+        p.style.strokeDashoffset = String(len * (1 - progress));
+      }}
+    }}
+  }}
+
+  // Rich text: each segment is a styled run; unset properties inherit from the element.
+  // Runs share one wrapper so they flow inline instead of becoming separate flex items
+  function renderSegments(el, segments, lineHeight) {{
+    const line = document.createElement('span');
+    line.style.width = '100%';
+    for (const seg of segments) {{
+      if (seg.src) {{ line.appendChild(inlineImage(seg, lineHeight)); continue; }}
+      const span = document.createElement('span');
+      span.textContent = seg.text ?? '';
+      if (seg.color) span.style.color = seg.color;
+      if (seg.fontWeight) span.style.fontWeight = seg.fontWeight;
+      if (seg.italic) span.style.fontStyle = 'italic';
+      if (seg.underline) span.style.textDecoration = 'underline';
+      if (seg.fontSize) span.style.fontSize = seg.fontSize + 'px';
+      if (seg.fontFamily) span.style.fontFamily = seg.fontFamily;
+      line.appendChild(span);
+    }}
+    el.textContent = '';
+    el.appendChild(line);
+  }}
+
+  // --- Trigger values: `{{name}}` tokens in text are filled from a trigger's `values`
+  // (`{{name|someone}}` falls back to "someone", other unfilled tokens render blank) ---
+  const TOKEN_RE = /\{{([A-Za-z_][\w.-]*)(?:\|([^{{}}]*))?\}}/g;
+  const templatedTexts = [];
+  function hasTokens(s) {{
+    return typeof s === 'string' && s.search(TOKEN_RE) >= 0;
+  }}
+  function fillTokens(s, values) {{
+    return String(s ?? '').replace(TOKEN_RE, (_, key, fallback) => {{
+      const v = Object.prototype.hasOwnProperty.call(values, key) ? values[key] : undefined;
+      return ['string', 'number', 'boolean'].includes(typeof v) ? String(v) : (fallback ?? '');
+    }});
+  }}
+  function renderTemplated(t, values) {{
+    const {{ el, data }} = t;
+    if (Array.isArray(data.segments) && data.segments.length > 0) {{
+      renderSegments(el, data.segments.map(seg => hasTokens(seg.text) ? {{ ...seg, text: fillTokens(seg.text, values) }} : seg), data.lineHeight);
+    }} else el.textContent = fillTokens(data.content, values);
+  }}
+  function registerTemplated(el, data) {{
+    const segmented = Array.isArray(data.segments) && data.segments.length > 0;
+    if (!(segmented ? data.segments.some(seg => hasTokens(seg.text)) : hasTokens(data.content))) return;
+    const t = {{ el, data }};
+    templatedTexts.push(t);
+    renderTemplated(t, {{}});
+  }}
+  // Each trigger's values replace the last one's, so tokens it leaves out go back to their fallbacks
+  function applyValues(values) {{
+    const v = values && typeof values === 'object' && !Array.isArray(values) ? values : {{}};
+    for (const t of templatedTexts) renderTemplated(t, v);
+  }}
+
+  // --- Scheduled visibility (daily time windows) ---
+  const scheduled = [];
+
+  function parseHM(s) {{
+    const m = /^(\d{{1,2}}):(\d{{2}})$/.exec(String(s || '').trim());
+    if (!m || +m[1] > 23 || +m[2] > 59) return null;
+    return +m[1] * 60 + +m[2];
+  }}
+
+  function registerScheduled(el, d) {{
+    const w = d.visibleWindow;
+    const start = parseHM(w.start), end = parseHM(w.end);
+    if (start === null || end === null) {{
+      console.warn(`Invalid visibleWindow on element ${{d.id}}; showing it always`);
+      return;
+    }}
+    const opts = {{ hour: 'numeric', minute: 'numeric', hourCycle: 'h23' }};
+    let fmt;
+    try {{ fmt = new Intl.DateTimeFormat('en-GB', {{ ...opts, timeZone: w.timeZone || undefined }}); }}
+    catch(e) {{
+      console.warn(`Invalid visibleWindow timeZone on element ${{d.id}}; using local time`);
+      fmt = new Intl.DateTimeFormat('en-GB', opts);
+    }}
+    // Restore the element's own display value when the window opens
+    scheduled.push({{ el, start, end, fmt, display: el.style.display }});
+  }}
+
+  function updateScheduled(now) {{
+    for (const s of scheduled) {{
+      const parts = s.fmt.formatToParts(now);
+      const get = type => +(parts.find(p => p.type === type) || {{ value: 0 }}).value;
+      const mins = (get('hour') % 24) * 60 + get('minute');
+      const open = s.start === s.end || (s.start < s.end
+        ? mins >= s.start && mins < s.end
+        : mins >= s.start || mins < s.end);
+      s.el.style.display = open ? s.display : 'none';
+    }}
+  }}
+
+  // Rotation pivot: keywords or `x% y%` / px lengths; anything else (or unset) is the center
+  const ORIGIN_RE = /^\s*(?:left|center|right|top|bottom|0|-?\d+(?:\.\d+)?(?:%|px))(?:\s+(?:left|center|right|top|bottom|0|-?\d+(?:\.\d+)?(?:%|px)))?\s*$/i;
+  // `clipShape` polygons: three or more `x y` points, lengths as `0`, % or px
+  const CLIP_LEN = '(?:0|-?\\d+(?:\\.\\d+)?(?:%|px))';
+  const CLIP_POINTS_RE = new RegExp(`^\\s*${{CLIP_LEN}}\\s+${{CLIP_LEN}}(?:\\s*,\\s*${{CLIP_LEN}}\\s+${{CLIP_LEN}}){{2,}}\\s*$`, 'i');
+  // Clip any element to an inset, circle, ellipse or polygon; null when it has no clip.
+  // `clipSize` (inset: % off each edge; circle/ellipse: radius %) and the `clipX`/`clipY`
+  // centre animate like any other number, so a growing circle makes a reveal.
+  function clipPathOf(d) {{
+    const num = (v, fallback) => typeof v === 'number' && isFinite(v) ? v : fallback;
+    const at = `at ${{num(d.clipX, 50)}}% ${{num(d.clipY, 50)}}%`;
+    switch (d.clipShape) {{
+      case 'inset': return `inset(${{Math.min(50, Math.max(0, num(d.clipSize, 10)))}}%)`;
+      case 'circle': return `circle(${{Math.max(0, num(d.clipSize, 50))}}% ${{at}})`;
+      case 'ellipse': {{ const r = Math.max(0, num(d.clipSize, 50)); return `ellipse(${{r}}% ${{r}}% ${{at}})`; }}
+      case 'polygon': return typeof d.clipPoints === 'string' && CLIP_POINTS_RE.test(d.clipPoints) ? `polygon(${{d.clipPoints.trim()}})` : 'none';
+      default: return null;
+    }}
+  }}
+  function originOf(data) {{
+    return typeof data.transformOrigin === 'string' && ORIGIN_RE.test(data.transformOrigin) ? data.transformOrigin.trim() : 'center center';
+  }}
+
+  function buildEl(data, parentEl) {{
+    if (data.visible === false) return;
+
+    let el = document.createElement('div');
+    el.id = 'el_' + data.id;
+
+    if (data.type === 'group' || data.type === 'mask') {{
+      if (FLAT_Z) {{
+        // No z-index or isolation, so children stack against everything else on the page
+        el.style.cssText = `position:absolute;transform-origin:${{originOf(data)}};`;
+      }} else {{
+        // Groups isolate so their children blend with each other, not the scene behind
+        el.style.cssText = `position:absolute;z-index:${{data.zIndex}};isolation:isolate;transform-origin:${{originOf(data)}};`;
+        // A group whose opacity animates gets its own compositing layer up front, so the
+        // fade dims the flattened group uniformly instead of re-rasterizing mid-animation
+        if (opacityKeyframed(data.id)) el.style.willChange = 'opacity';
+      }}
+      applyBlend(el, data);
+      if (data.maskType === 'clip') {{
+        const r = data.clipRadius !== undefined ? data.clipRadius : 0;
+        el.style.overflow = 'hidden';
+        el.style.borderRadius = r + 'px';
+      }} else if (data.maskType === 'gradient') {{
+        const gs = gradStr(data);
+        el.style.webkitMaskImage = gs; el.style.maskImage = gs;
+      }} else if (data.maskType === 'image' && data.maskImageSrc) {{
+        applyImageMask(el, data);
+      }} else if (data.maskType === 'opacity') {{
+        // opacity applied in applyAnimProps
+      }}
+      const inner = fitLayer(el, data);
+      (data.children || []).filter(c => c.visible !== false).sort((a,b) => a.zIndex - b.zIndex).forEach(c => buildEl(c, inner));
+    }} else {{
+      // Plain elements don't isolate, so they blend with whatever their parent has painted
+      el.style.cssText = `position:absolute;z-index:${{data.zIndex}};transform-origin:${{originOf(data)}};display:flex;align-items:center;justify-content:center;overflow:hidden;`;
+      applyBlend(el, data);
+      applyAnim(el, data);
+
+      if (data.type === 'shape') {{
+        if (data.shapeType === 'triangle') {{
+          const i = document.createElement('div');
+          i.style.cssText = `width:100%;height:100%;background:${{data.fill||'#3b82f6'}};clip-path:polygon(50% 0%,0% 100%,100% 100%)`;
+          el.appendChild(i);
+        }} else if (data.shapeType === 'star') {{
+          const i = document.createElement('div');
           i.style.cssText = `width:100%;height:100%;background:${{data.fill||'#3b82f6'}};clip-path:polygon(50% 0%,61% 35%,98% 35%,68% 57%,79% 91%,50% 70%,21% 91%,32% 57%,2% 35%,39% 35%)`;
           el.appendChild(i);
         }} else {{
@@ -178,24 +1569,53 @@ const W = {w}, H = {h};
         const p = document.createElementNS('http://www.w3.org/2000/svg','path');
         p.setAttribute('d', data.pathData);
         s.appendChild(p); el.appendChild(s);
+        if (data.drawOn) drawPaths.push({{ svg: s, data }});
       }} else if (data.type === 'text') {{
         el.style.fontFamily = data.fontFamily || 'Inter,sans-serif';
-        el.style.textAlign = data.textAlign || 'center';
+        applyTextFlow(el, data);
         el.style.fontWeight = data.fontWeight || '600';
         if (data.textShadow) el.style.textShadow = data.textShadow;
         el.style.wordBreak = 'break-word';
         el.style.width = '100%';
-        el.style.padding = '0 8px';
-        el.textContent = data.content || '';
+        if (Array.isArray(data.segments) && data.segments.length > 0) renderSegments(el, data.segments, data.lineHeight);
+        else el.textContent = data.numericContent ? formatCounter(data, data.content) : (data.content || '');
+        if (data.dynamic) registerDynamicText(el, data);
+        else if (!data.numericContent) registerTemplated(el, data);
+      }} else if (data.type === 'particles') {{
+        if (!LITE) createEmitter(el, data);
+      }} else if (data.type === 'sprite') {{
+        createSprite(el, data);
       }} else if (data.type === 'image' && data.src) {{
         const img = document.createElement('img');
         img.src = data.src; img.style.width = '100%'; img.style.height = '100%';
         img.style.objectFit = data.objectFit || 'contain';
+        if (data.objectPosition) img.style.objectPosition = data.objectPosition;
         el.appendChild(img);
+        applyTint(el, data);
+      }} else if (data.type === 'video' && data.src) {{
+        const video = document.createElement('video');
+        video.src = data.src; video.style.width = '100%'; video.style.height = '100%';
+        video.style.objectFit = data.objectFit || 'contain';
+        // The poster is laid out like the video itself, so fit and focal point apply to it too
+        if (data.objectPosition) video.style.objectPosition = data.objectPosition;
+        video.style.background = 'transparent';
+        video.autoplay = true; video.playsInline = true;
+        video.loop = !!data.loop; video.muted = !!data.muted;
+        if (data.poster) {{
+          video.poster = data.poster;
+        }} else {{
+          // No poster: stay transparent until the first frame instead of flashing black
+          video.style.visibility = 'hidden';
+          video.addEventListener('loadeddata', () => {{ video.style.visibility = ''; }}, {{ once: true }});
+        }}
+        el.appendChild(video);
+        applyTint(el, data);
       }}
     }}
 
     applyAnimProps(el, data);
+    // `visible: false` (handled above) always wins; a window only hides what's otherwise shown
+    if (data.visibleWindow) registerScheduled(el, data);
     parentEl.appendChild(el);
   }}
 
@@ -203,6 +1623,15 @@ const W = {w}, H = {h};
     .sort((a,b) => a.zIndex - b.zIndex)
     .forEach(e => buildEl(e, root));
 
+  updateScheduled(new Date());
+  if (dynamicTexts.length > 0 || scheduled.length > 0) {{
+    setInterval(() => {{
+      const now = new Date();
+      for (const d of dynamicTexts) d.el.textContent = d.fmt.format(now);
+      updateScheduled(now);
+    }}, 1000);
+  }}
+
   // --- Animation Engine ---
   const allElementsMap = {{}};
   function flatten(els) {{
@@ -213,8 +1642,14 @@ const W = {w}, H = {h};
   }}
   flatten(ELEMENTS);
 
-  const NUMERIC_PROPS = ['x','y','width','height','rotation','opacity','strokeWidth','borderRadius','fontSize','letterSpacing','lineHeight','blur','brightness','contrast','hueRotate','saturate','scaleX','scaleY'];
-  const COLOR_PROPS = ['fill','strokeColor','color'];
+  const NUMERIC_PROPS = ['x','y','width','height','rotation','opacity','strokeWidth','borderRadius','fontSize','letterSpacing','lineHeight','blur','brightness','contrast','hueRotate','saturate','scaleX','scaleY','clipSize','clipX','clipY','gradientAngle','gradientStart','gradientEnd','gradientStartOpacity','gradientEndOpacity','tintStrength'];
+  // What a numeric prop an element doesn't set animates from
+  function numericBase(el, prop) {{
+    if (el[prop] !== undefined) return el[prop];
+    if (prop === 'gradientAngle') return GRADIENT_ANGLES[el.gradientDir] ?? 180;
+    return {{ gradientEnd: 100, gradientStartOpacity: 1, tintStrength: 100 }}[prop] ?? 0;
+  }}
+  const COLOR_PROPS = ['fill','strokeColor','color','tint'];
 
   function easingFn(t, type) {{
     switch (type) {{
@@ -249,6 +1684,27 @@ const W = {w}, H = {h};
     return `#${{r.toString(16).padStart(2,'0')}}${{g.toString(16).padStart(2,'0')}}${{bl.toString(16).padStart(2,'0')}}`;
   }}
 
+  // Path morphing: paths with the same command structure interpolate number by
+  // number; anything else returns null and the caller crossfades instead
+  const PATH_TOKEN = /[a-df-z]|[-+]?(?:\d*\.\d+|\d+\.?)(?:e[-+]?\d+)?/gi;
+  function lerpPath(a, b, t) {{
+    const ta = String(a).match(PATH_TOKEN) || [], tb = String(b).match(PATH_TOKEN) || [];
+    if (ta.length !== tb.length || ta.length === 0) return null;
+    const out = [];
+    let cmd = '', idx = 0;
+    for (let i = 0; i < ta.length; i++) {{
+      const isCmd = /^[a-z]$/i.test(ta[i]);
+      if (isCmd !== /^[a-z]$/i.test(tb[i]) || (isCmd && ta[i] !== tb[i])) return null;
+      if (isCmd) {{ cmd = ta[i]; idx = 0; out.push(cmd); continue; }}
+      let v = +ta[i] + (+tb[i] - +ta[i]) * t;
+      // Arc flags (4th/5th arc parameter) must stay 0 or 1
+      if ((cmd === 'A' || cmd === 'a') && (idx % 7 === 3 || idx % 7 === 4)) v = Math.round(v);
+      out.push(+v.toFixed(3));
+      idx++;
+    }}
+    return out.join(' ');
+  }}
+
   function interpolate(keyframes, elId, el, time) {{
     if (!keyframes || keyframes.length === 0) return {{}};
     const sorted = [...keyframes].sort((a,b) => a.time - b.time);
@@ -275,99 +1731,1820 @@ const W = {w}, H = {h};
       const nv = nextState[prop];
       if (pv === undefined && nv === undefined) continue;
       if (NUMERIC_PROPS.includes(prop)) {{
-        const a = pv !== undefined ? pv : (el[prop] ?? 0);
-        const b = nv !== undefined ? nv : (el[prop] ?? 0);
+        const a = pv !== undefined ? pv : numericBase(el, prop);
+        const b = nv !== undefined ? nv : numericBase(el, prop);
         result[prop] = a + (b - a) * t;
       }} else if (COLOR_PROPS.includes(prop)) {{
         const a = pv !== undefined ? pv : (el[prop] ?? '#000000');
         const b = nv !== undefined ? nv : (el[prop] ?? '#000000');
         result[prop] = lerpColor(a, b, t);
+      }} else if (prop === 'content' && el.numericContent) {{
+        result.content = lerpCounter(pv ?? el.content, nv ?? el.content, t);
+      }} else if (prop === 'pathData') {{
+        const a = pv ?? el.pathData ?? '', b = nv ?? el.pathData ?? '';
+        const morphed = a === b ? a : lerpPath(a, b, t);
+        if (morphed !== null) result.pathData = morphed;
+        else {{ result.pathData = a; result.pathCrossfade = {{ to: b, t }}; }}
       }}
     }}
     return result;
   }}
 
-  let startT = performance.now();
-  function tick() {{
-    if (!TIMELINE || !TIMELINE.keyframes || TIMELINE.keyframes.length === 0 || !TIMELINE.autoplay) return;
-    const elapsed = (performance.now() - startT) / 1000 * (TIMELINE.speed || 1);
-    let t = elapsed;
+  // --- Aspect lock (`lockAspect`): when keyframes set only one of width/height (or of
+  // scaleX/scaleY), the other follows at the element's own ratio ---
+  function lockAspect(data, o) {{
+    if (!data.lockAspect) return o;
+    for (const [a, b, baseA, baseB] of [['width', 'height', Number(data.width), Number(data.height)], ['scaleX', 'scaleY', data.scaleX ?? 1, data.scaleY ?? 1]]) {{
+      if (!(baseA > 0 && baseB > 0)) continue;
+      if (o[a] !== undefined && o[b] === undefined) o[b] = o[a] * baseB / baseA;
+      else if (o[b] !== undefined && o[a] === undefined) o[a] = o[b] * baseA / baseB;
+    }}
+    return o;
+  }}
+
+  function clampTime(t) {{
     if (t >= TIMELINE.duration) {{
-      if (TIMELINE.loop) t = t % TIMELINE.duration;
-      else t = TIMELINE.duration;
+      if (TIMELINE.loop && TIMELINE.duration > 0) return t % TIMELINE.duration;
+      return TIMELINE.duration;
     }}
-    
+    return t;
+  }}
+
+  // --- Segments: named sub-timelines (intro, idle loop, outro) on clocks of their
+  // own, driven over the control channel and layered over the main timeline in
+  // list order. A segment that ends holds its last pose until it's stopped.
+  const SEGMENTS = new Map();
+  for (const seg of (TIMELINE && Array.isArray(TIMELINE.segments)) ? TIMELINE.segments : []) {{
+    if (!seg || typeof seg.name !== 'string' || SEGMENTS.has(seg.name)) continue;
+    const keyframes = Array.isArray(seg.keyframes) ? seg.keyframes : [];
+    const ids = new Set(keyframes.flatMap(kf => Object.keys(kf.elementStates || {{}})));
+    SEGMENTS.set(seg.name, {{ seg, keyframes, ids, active: false, playing: false, startT: 0, t: 0 }});
+  }}
+  const segmentDuration = s => Math.max(0, s.seg.duration || 0);
+
+  function segmentTime(s, now) {{
+    if (!s.playing) return s.t;
+    const t = Math.max(0, (now - s.startT) / 1000 * (s.seg.speed || 1));
+    const d = segmentDuration(s);
+    if (t < d) return t;
+    return s.seg.loop && d > 0 ? t % d : d;
+  }}
+
+  // Main timeline time last applied; null until it first runs, so segments alone don't pose it
+  let mainT = null;
+  // Elements a segment posed last frame, rewritten once it stops so they fall back
+  let segmentPosed = new Set();
+
+  function applyTimelineAt(t) {{
+    mainT = t;
+    if (t !== null) applyDrawOn(t);
+    const now = performance.now();
+    const layers = [];
+    for (const s of SEGMENTS.values()) if (s.active) layers.push([s, segmentTime(s, now)]);
+    const posed = new Set();
     for (const [id, originalData] of Object.entries(allElementsMap)) {{
       const elNode = document.getElementById('el_' + id);
       if(!elNode) continue;
-      const overrides = interpolate(TIMELINE.keyframes, id, originalData, t);
-      if (Object.keys(overrides).length > 0) {{
+      const overrides = t === null ? {{}} : interpolate(TIMELINE.keyframes, id, originalData, t);
+      for (const [s, st] of layers) {{
+        if (!s.ids.has(id)) continue;
+        Object.assign(overrides, interpolate(s.keyframes, id, originalData, st));
+        posed.add(id);
+      }}
+      lockAspect(originalData, overrides);
+      if (Object.keys(overrides).length > 0 || segmentPosed.has(id)) {{
         applyAnimProps(elNode, {{ ...originalData, ...overrides }});
       }}
     }}
-    
-    if (t < TIMELINE.duration || TIMELINE.loop) {{
-      requestAnimationFrame(tick);
+    segmentPosed = posed;
+    for (const [s, st] of layers) {{
+      if (s.playing && !s.seg.loop && st >= segmentDuration(s)) endSegment(s);
     }}
   }}
 
-  if (TIMELINE && TIMELINE.autoplay && TIMELINE.keyframes && TIMELINE.keyframes.length > 0) {{
-     requestAnimationFrame(tick);
+  const HAS_KEYFRAMES = !!(TIMELINE && TIMELINE.keyframes && TIMELINE.keyframes.length > 0);
+  // Draw-on paths run the timeline even without keyframes
+  const ANIMATED = HAS_KEYFRAMES || !!(TIMELINE && drawPaths.length > 0);
+
+  // Fire emitters whose keyframe was crossed between two timeline times
+  function fireKeyframeEmitters(from, to) {{
+    for (const kf of TIMELINE.keyframes || []) {{
+      const crossed = from <= to ? (kf.time > from && kf.time <= to) : (kf.time > from || kf.time <= to);
+      if (crossed) fireEmitters(kf.id);
+    }}
   }}
 
-  // Hash-based smart reload (polls instead of blind reloading)
-  setInterval(async () => {{
-    try {{
-      const r = await fetch(location.href);
-      const text = await r.text();
-      const match = text.match(/#HASH_(\d+)/);
-      if (match && match[1] !== CURRENT_HASH) {{
-        location.reload();
+  let startT = performance.now();
+  let playing = false;
+  let lastT = 0;
+  let tickCount = 0;
+
+  // Time events for external sync; only sent while the server says someone listens
+  const TIME_EVENT_MS = Math.max(16, ((TIMELINE && TIMELINE.timeEventInterval) ?? 0.25) * 1000);
+  let timeUpdates = false;
+  let lastTimeEmit = 0;
+  function emitTime(now) {{
+    lastTimeEmit = now;
+    if (!timeUpdates || !controlWs || controlWs.readyState !== WebSocket.OPEN) return;
+    controlWs.send(JSON.stringify({{ event: 'time', t: +Math.max(lastT, 0).toFixed(3), playing }}));
+  }}
+
+  // Optional frame-rate cap: animation frames that arrive early are skipped
+  const FRAME_MS = TIMELINE && TIMELINE.maxFps > 0 ? 1000 / TIMELINE.maxFps : 0;
+  let lastFrame = null;
+
+  function tick() {{
+    if (!playing) return;
+    const now = performance.now();
+    if (FRAME_MS && lastFrame !== null) {{
+      const since = now - lastFrame;
+      if (since < FRAME_MS) {{ requestAnimationFrame(tick); return; }}
+      // Keep the remainder so a 30fps cap on a 60Hz display doesn't drift to 20fps
+      lastFrame = now - (since % FRAME_MS);
+    }} else {{
+      lastFrame = now;
+    }}
+    tickCount++;
+    const elapsed = (now - startT) / 1000 * (TIMELINE.speed || 1);
+    if (elapsed >= TIMELINE.duration && !TIMELINE.loop) {{ finishTimeline(now); return; }}
+    const t = clampTime(elapsed);
+
+    applyTimelineAt(t);
+    fireKeyframeEmitters(lastT, t);
+    lastT = t;
+
+    if (timeUpdates && now - lastTimeEmit >= TIME_EVENT_MS) emitTime(now);
+    requestAnimationFrame(tick);
+  }}
+
+  // A one-shot timeline always ends on one apply at exactly `duration`, so it rests on
+  // the final keyframe's values. A timer backs up the frame loop: OBS stops sending
+  // animation frames to a hidden source, which would otherwise stay mid-animation
+  let endTimer = null;
+  function finishTimeline(now) {{
+    clearTimeout(endTimer);
+    endTimer = null;
+    if (!playing) return;
+    playing = false;
+    applyTimelineAt(TIMELINE.duration);
+    fireKeyframeEmitters(lastT, TIMELINE.duration);
+    lastT = TIMELINE.duration;
+    if (timeUpdates) emitTime(now);
+  }}
+
+  // Segments keep their own frame loop; while the main timeline plays, its tick draws them too
+  let segmentFrame = null;
+  function segmentTick() {{
+    segmentFrame = null;
+    if (!playing) applyTimelineAt(mainT);
+    for (const s of SEGMENTS.values()) if (s.playing) {{ segmentFrame = requestAnimationFrame(segmentTick); break; }}
+  }}
+  function redrawSegments() {{
+    if (segmentFrame === null) segmentFrame = requestAnimationFrame(segmentTick);
+  }}
+
+  function segmentEvent(s, state) {{
+    if (controlWs && controlWs.readyState === WebSocket.OPEN) {{
+      controlWs.send(JSON.stringify({{ event: 'segment', name: s.seg.name, state }}));
+    }}
+  }}
+
+  function playSegment(s, fromT = 0, lateMs = 0) {{
+    s.active = true;
+    if (LITE) {{
+      // Lite: straight to the resting pose, like the main timeline
+      s.playing = false;
+      s.t = s.seg.loop ? 0 : segmentDuration(s);
+    }} else {{
+      s.playing = true;
+      s.startT = performance.now() - lateMs - fromT * 1000 / (s.seg.speed || 1);
+    }}
+    segmentEvent(s, 'playing');
+    redrawSegments();
+  }}
+
+  function pauseSegment(s) {{
+    s.t = segmentTime(s, performance.now());
+    s.playing = false;
+  }}
+
+  function stopSegment(s) {{
+    if (!s.active) return;
+    s.active = s.playing = false;
+    s.t = 0;
+    segmentEvent(s, 'stopped');
+  }}
+
+  // A one-shot segment reached its end: hold the pose and start its `then` segment
+  function endSegment(s) {{
+    s.playing = false;
+    s.t = segmentDuration(s);
+    segmentEvent(s, 'ended');
+    const next = typeof s.seg.then === 'string' && SEGMENTS.get(s.seg.then);
+    if (next && next !== s) playSegment(next);
+  }}
+
+  function handleSegment(msg) {{
+    const s = SEGMENTS.get(msg.name);
+    if (!s) return;
+    const t = typeof msg.t === 'number' && isFinite(msg.t) ? Math.max(0, msg.t) : 0;
+    if (msg.op === 'play') {{
+      const wait = typeof msg.at === 'number' ? msg.at - serverNow() : 0;
+      if (wait > 0) {{ setTimeout(() => handleSegment({{ ...msg, at: null }}), wait); return; }}
+      if (msg.exclusive) for (const other of SEGMENTS.values()) if (other !== s) stopSegment(other);
+      playSegment(s, t, wait > -MAX_LATE_MS ? -wait : 0);
+    }} else if (msg.op === 'pause') {{
+      pauseSegment(s);
+    }} else if (msg.op === 'stop') {{
+      stopSegment(s);
+    }} else if (msg.op === 'seek') {{
+      const at = Math.min(t, segmentDuration(s));
+      s.active = true;
+      if (s.playing) s.startT = performance.now() - at * 1000 / (s.seg.speed || 1);
+      else s.t = at;
+    }}
+    redrawSegments();
+  }}
+
+  // Auto-hide (timeline.hideAfter): hold the end of a pass this many seconds, then hide
+  const HIDE_AFTER_MS = TIMELINE && typeof TIMELINE.hideAfter === 'number' && TIMELINE.hideAfter >= 0
+    ? TIMELINE.hideAfter * 1000 : null;
+  let hideTimer = null;
+  function scheduleHide(playMs) {{
+    if (HIDE_AFTER_MS === null) return;
+    clearTimeout(hideTimer);
+    hideTimer = setTimeout(hide, playMs + HIDE_AFTER_MS);
+  }}
+
+  const exiting = () => presetAnimated.filter(a => hasPreset(a.data.exitAnimation));
+  // Longest exit animation, which a hide waits out
+  function exitDurationMs() {{
+    return Math.max(0, ...exiting().map(a => exitMs(a.data)));
+  }}
+
+  // Play exit animations, then take the widget off screen (auto-hide or a `hide` message)
+  function hide() {{
+    clearTimeout(hideTimer);
+    for (const {{ el, data }} of exiting()) runPreset(el, data.exitAnimation, exitMs(data) / 1000, 0, 'forwards', '1');
+    const wait = exitDurationMs();
+    if (wait > 0) hideTimer = setTimeout(() => {{ root.style.display = 'none'; }}, wait);
+    else root.style.display = 'none';
+  }}
+
+  // Shared clock: trigger start times (`at`) are server Unix ms, and the control
+  // channel's `clock` message tells us how far this machine's clock is off
+  let clockOffset = 0;
+  const serverNow = () => Date.now() + clockOffset;
+  // A shared start further behind than this is played from the top instead (e.g. a wrong clock)
+  const MAX_LATE_MS = 2000;
+  // timeline.syncClock: loops are phased to the shared clock, so sources with the same loop line up
+  const SYNC_LOOP = !!(TIMELINE && TIMELINE.syncClock && TIMELINE.loop && TIMELINE.duration > 0);
+  const loopMs = () => TIMELINE.duration * 1000 / (TIMELINE.speed || 1);
+
+  // (Re)start the timeline from zero, or `lateMs` into it; used by autoplay and triggers
+  function play(lateMs = 0) {{
+    clearTimeout(hideTimer);
+    root.style.display = '';
+    presetAnimated.forEach(playEnter);
+    reseed();
+    fireEmitters('start');
+    playSprites(lateMs);
+    if (LITE) {{
+      // Lite: the timeline's resting frame (start of a loop, end of a one-shot) and one quick fade
+      if (ANIMATED) applyTimelineAt(TIMELINE.loop ? 0 : TIMELINE.duration);
+      root.animate([{{ opacity: 0 }}, {{ opacity: 1 }}], {{ duration: LITE_FADE_MS, easing: 'ease-out' }});
+      return;
+    }}
+    if (!ANIMATED) return;
+    startT = performance.now() - lateMs;
+    lastT = -1;
+    lastFrame = null;
+    if (!playing) {{ playing = true; requestAnimationFrame(tick); }}
+    if (!TIMELINE.loop) {{
+      clearTimeout(endTimer);
+      endTimer = setTimeout(() => finishTimeline(performance.now()), Math.max(0, TIMELINE.duration * 1000 / (TIMELINE.speed || 1) - lateMs));
+    }}
+  }}
+
+  if (SEEK_T !== null) {{
+    // ?t= seek: render the interpolated state at that exact time, no autoplay
+    if (ANIMATED) applyTimelineAt(clampTime(SEEK_T));
+    for (const sp of sprites) showSpriteFrame(sp, spriteFrameAt(sp, Math.max(0, SEEK_T) * 1000));
+  }} else if (HIDE_AFTER_MS !== null && !TIMELINE.autoplay) {{
+    // Auto-hiding alert: off screen until the first trigger
+    root.style.display = 'none';
+    applyDrawOn(0);
+    playSprites(0, true);
+  }} else if (!ANIMATED || TIMELINE.autoplay) {{
+    play(SYNC_LOOP ? serverNow() % loopMs() : 0);
+    scheduleHide(playMs());
+  }} else {{
+    // Waiting for a trigger: draw-on paths start undrawn, looping sprites already run
+    applyDrawOn(0);
+    playSprites(0, true);
+  }}
+
+  // --- Debug overlay (?debug=1): bounding boxes, ids/zIndex, animation FPS ---
+  // Purely additive: a separate top layer, element styles are never touched
+  function startDebugOverlay() {{
+    const layer = document.createElement('div');
+    layer.style.cssText = 'position:absolute;left:0;top:0;width:100%;height:100%;pointer-events:none;z-index:2147483647;font:10px/1.2 monospace;color:#fff';
+    const hud = document.createElement('div');
+    hud.style.cssText = 'position:absolute;right:2px;top:2px;padding:2px 4px;background:rgba(0,0,0,.7);white-space:pre;text-align:right';
+    const boxes = Object.entries(allElementsMap).map(([id, data]) => {{
+      const box = document.createElement('div');
+      box.style.cssText = 'position:absolute;border:1px dashed #f0f;box-sizing:border-box';
+      const label = document.createElement('span');
+      label.style.cssText = 'position:absolute;left:0;top:0;padding:0 2px;background:rgba(255,0,255,.75);white-space:nowrap';
+      label.textContent = `${{id}} z:${{data.zIndex ?? 0}}`;
+      box.appendChild(label);
+      layer.appendChild(box);
+      return {{ id, box }};
+    }});
+    layer.appendChild(hud);
+    root.appendChild(layer);
+
+    let fps = 0;
+    setInterval(() => {{ fps = tickCount; tickCount = 0; }}, 1000);
+    function update() {{
+      const origin = root.getBoundingClientRect();
+      const offCanvas = [];
+      for (const {{ id, box }} of boxes) {{
+        const node = document.getElementById('el_' + id);
+        const r = node && node.getClientRects().length ? node.getBoundingClientRect() : null;
+        if (!r) {{ box.style.display = 'none'; continue; }}
+        box.style.display = '';
+        // Rects are in page pixels; the layer lives inside the scaled root
+        box.style.left = (r.left - origin.left) / SCALE + 'px';
+        box.style.top = (r.top - origin.top) / SCALE + 'px';
+        box.style.width = r.width / SCALE + 'px';
+        box.style.height = r.height / SCALE + 'px';
+        if (r.right <= origin.left || r.bottom <= origin.top || r.left >= origin.right || r.top >= origin.bottom) offCanvas.push(id);
       }}
-    }} catch(e) {{}}
-  }}, 2000);
-}})();
+      hud.textContent = `FPS ${{playing ? fps : 0}}  t=${{lastT >= 0 ? lastT.toFixed(2) : '0.00'}}s  ${{boxes.length}} elements`
+        + (reconnecting ? '  reconnecting' : '')
+        + (offCanvas.length ? `\noff-canvas: ${{offCanvas.join(', ')}}` : '');
+      requestAnimationFrame(update);
+    }}
+    requestAnimationFrame(update);
+  }}
+  if (DEBUG) startDebugOverlay();
+
+  // --- Alert queue: overlapping triggers play one at a time, in order ---
+  const alertQueue = [];
+  let alertActive = false;
+  let controlWs = null;
+  // The control socket dropped and hasn't come back yet
+  let reconnecting = false;
+
+  // One pass of the timeline (even when it loops), or until trigger particles and one-shot sprites are done
+  function playMs() {{
+    const timeline = ANIMATED ? TIMELINE.duration / (TIMELINE.speed || 1) : 0;
+    const effects = emitters.filter(em => em.data.emitOn === 'trigger').map(em => em.data.lifetime ?? 3);
+    const oneShots = sprites.filter(sp => !sp.data.loop).map(sp => spriteMs(sp) / 1000);
+    return Math.max(timeline, ...effects, ...oneShots, 0) * 1000;
+  }}
+
+  // The next queued alert waits out the auto-hide hold and exit animations too
+  function alertDurationMs() {{
+    return playMs() + (HIDE_AFTER_MS === null ? 0 : HIDE_AFTER_MS + exitDurationMs());
+  }}
+
+  function reportQueue() {{
+    if (controlWs && controlWs.readyState === WebSocket.OPEN) {{
+      controlWs.send(JSON.stringify({{ type: 'queue', length: alertQueue.length }}));
+    }}
+  }}
+
+  // `at`: shared start time from the server, so widgets triggered together start together
+  function startAlert(msg, at) {{
+    alertActive = true;
+    const wait = typeof at === 'number' ? at - serverNow() : 0;
+    if (wait > 0) {{ setTimeout(() => startAlert(msg), wait); return; }}
+    const late = wait > -MAX_LATE_MS ? -wait : 0;
+    applyValues(msg.values);
+    play(late);
+    fireEmitters('trigger');
+    scheduleHide(playMs() - late);
+    setTimeout(finishAlert, alertDurationMs() - late);
+    reportQueue();
+  }}
+
+  function finishAlert() {{
+    alertActive = false;
+    const next = alertQueue.shift();
+    if (next) startAlert(next);
+    else reportQueue();
+  }}
+
+  // --- Control channel (HTTP triggers etc.) ---
+  function handleControl(msg) {{
+    if (msg.action === 'trigger') {{
+      // Queued alerts play when their turn comes, not at their original start time
+      if (alertActive) {{ alertQueue.push(msg); reportQueue(); }}
+      else startAlert(msg, msg.at);
+    }} else if (msg.action === 'clock') {{
+      clockOffset = msg.now - Date.now();
+      if (SYNC_LOOP && TIMELINE.autoplay && playing && !alertActive) startT = performance.now() - serverNow() % loopMs();
+    }} else if (msg.action === 'time_updates') {{
+      timeUpdates = !!msg.enabled;
+      if (timeUpdates) emitTime(performance.now());
+    }} else if (msg.action === 'hide') {{
+      hide();
+    }} else if (msg.action === 'clear_queue') {{
+      // Emergency stop for a backlog; the alert already on screen finishes normally
+      alertQueue.length = 0;
+      reportQueue();
+    }} else if (msg.action === 'segment') {{
+      handleSegment(msg);
+    }} else if (msg.action === 'refresh_src') {{
+      refreshSrc(msg.element, msg.src);
+    }}
+  }}
+
+  // Same URL, new content (e.g. album art): load the cache-busted src in place.
+  // An image keeps showing the old picture until the new one has loaded
+  function refreshSrc(id, src) {{
+    const data = allElementsMap[id];
+    const el = document.getElementById('el_' + id);
+    if (!data || !el || typeof src !== 'string') return;
+    data.src = src;
+    const media = el.querySelector(data.type === 'video' ? 'video' : 'img');
+    if (!media) return;
+    if (data.type === 'video') {{ media.src = src; return; }}
+    const next = new Image();
+    next.onload = () => {{ media.src = src; }};
+    next.src = src;
+  }}
+
+  // Segments marked autoplay start with the page (not when seeking with ?t=)
+  if (SEEK_T === null) for (const s of SEGMENTS.values()) if (s.seg.autoplay) playSegment(s);
+
+{live_js}}})();
 </script>
 </body>
 </html>"#,
-        hash = hash, w = w, h = h, bg = bg,
+        live_js = live_js, error_reporter = error_reporter,
+        hash = hash, w = w, h = h, page_w = page_w, page_h = page_h, scale = scale, bg = bg, seek_t = seek_t, widget_id_json = widget_id_json,
+        blend_modes_json = blend_modes_json,
+        max_size = MAX_ELEMENT_SIZE, max_offset = MAX_ELEMENT_OFFSET, fallback_size = FALLBACK_ELEMENT_SIZE,
         elements_json = elements_json,
         timeline_json = timeline_json
     )
 }
 
+/// The page OBS gets for a widget's plain URL (no query options).
+pub(crate) fn render_plain_html(widget: &Value) -> String {
+    render_widget_html(widget, &RenderOptions::default())
+}
+
+/// A widget's page with everything that needs this server stripped out, for
+/// hosting it elsewhere.
+pub(crate) fn render_standalone_html(widget: &Value) -> String {
+    render_widget_html(widget, &RenderOptions { standalone: true, ..Default::default() })
+}
+
+// ---------------------------------------------------------------------------
+// Render cache + request metrics
+// ---------------------------------------------------------------------------
+const RENDER_CACHE_MAX_ENTRIES: usize = 256;
+
+type RenderCache = HashMap<(String, String), (u64, String)>;
+
+/// Rendered HTML keyed by (widget id, raw query string), tagged with the widget
+/// hash it was rendered from so edits invalidate it naturally.
+static RENDER_CACHE: Lazy<Mutex<RenderCache>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Default)]
+struct WidgetStats {
+    requests: u64,
+    renders: u64,
+    render_micros: u128,
+}
+
+#[derive(Debug, Default)]
+struct Metrics {
+    widgets: HashMap<String, WidgetStats>,
+    cache_hits: u64,
+    cache_misses: u64,
+    rate_limited: u64,
+}
+
+static METRICS: Lazy<Mutex<Metrics>> = Lazy::new(|| Mutex::new(Metrics::default()));
+
+#[derive(Debug, Serialize)]
+struct WidgetMetrics {
+    id: String,
+    requests: u64,
+    renders: u64,
+    avg_render_ms: f64,
+    connections: usize,
+    /// Triggers waiting in the alert queue (longest queue among open pages).
+    queued: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct CacheMetrics {
+    hits: u64,
+    misses: u64,
+    hit_rate: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct MetricsReport {
+    widgets: Vec<WidgetMetrics>,
+    cache: CacheMetrics,
+    open_connections: usize,
+    /// Widget requests refused with 429 since the app started.
+    rate_limited: u64,
+}
+
+/// Render a widget through the cache, recording request/render metrics.
+fn render_cached(id: &str, widget: &Value, opts: &RenderOptions, query: &str) -> String {
+    let (html, hit) = render_through_cache(id, widget, opts, query);
+    let mut metrics = METRICS.lock().unwrap();
+    metrics.widgets.entry(id.to_string()).or_default().requests += 1;
+    if hit {
+        metrics.cache_hits += 1;
+    } else {
+        metrics.cache_misses += 1;
+    }
+    html
+}
+
+/// The cached HTML when it's still current for the widget (`true`), else a
+/// fresh render that replaces it (`false`).
+fn render_through_cache(id: &str, widget: &Value, opts: &RenderOptions, query: &str) -> (String, bool) {
+    let hash = widget_hash(widget);
+    let key = (id.to_string(), query.to_string());
+
+    let cached = RENDER_CACHE
+        .lock()
+        .unwrap()
+        .get(&key)
+        .filter(|(h, _)| *h == hash)
+        .map(|(_, html)| html.clone());
+    if let Some(html) = cached {
+        return (html, true);
+    }
+
+    let started = Instant::now();
+    let html = render_widget_html(widget, opts);
+    let elapsed = started.elapsed().as_micros();
+
+    let mut metrics = METRICS.lock().unwrap();
+    let stats = metrics.widgets.entry(id.to_string()).or_default();
+    stats.renders += 1;
+    stats.render_micros += elapsed;
+    drop(metrics);
+
+    let mut cache = RENDER_CACHE.lock().unwrap();
+    if cache.len() >= RENDER_CACHE_MAX_ENTRIES {
+        cache.clear();
+    }
+    cache.insert(key, (hash, html.clone()));
+    (html, false)
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct PrewarmReport {
+    /// Rendered now, so their first page load is a cache hit.
+    pub rendered: Vec<String>,
+    /// Already cached and unchanged; nothing was done.
+    pub cached: Vec<String>,
+    /// No saved widget has this id.
+    pub missing: Vec<String>,
+}
+
+/// Render the given widgets into the cache ahead of a scene switch, so ten
+/// browser sources loading at once don't all render at once. Warms the plain
+/// URL (`/widget/{id}` or `/w/{slug}` without options), which is what OBS
+/// sources normally load; prewarming doesn't count as a page request.
+pub fn prewarm(ids: &[String]) -> PrewarmReport {
+    let mut served: HashMap<String, Value> = HashMap::new();
+    for (_, widget) in all_widgets() {
+        if let Some(id) = widget_id(&widget) {
+            served.entry(id.to_string()).or_insert(widget);
+        }
+    }
+    let mut report = PrewarmReport::default();
+    for id in ids {
+        match served.get(id) {
+            Some(widget) => {
+                let (_, hit) = render_through_cache(id, widget, &RenderOptions::default(), "");
+                if hit { &mut report.cached } else { &mut report.rendered }.push(id.clone());
+            }
+            None => report.missing.push(id.clone()),
+        }
+    }
+    report
+}
+
+/// Forget everything cached from the previous profile's database.
+pub fn profile_changed() {
+    RENDER_CACHE.lock().unwrap().clear();
+    MISSING_ASSETS.lock().unwrap().clear();
+    WARNED_DUPLICATES.lock().unwrap().clear();
+}
+
+fn metrics_report() -> MetricsReport {
+    let metrics = METRICS.lock().unwrap();
+    let mut widgets: Vec<WidgetMetrics> = metrics
+        .widgets
+        .iter()
+        .map(|(id, s)| WidgetMetrics {
+            id: id.clone(),
+            requests: s.requests,
+            renders: s.renders,
+            avg_render_ms: if s.renders > 0 { s.render_micros as f64 / s.renders as f64 / 1000.0 } else { 0.0 },
+            connections: control::connection_count(id),
+            queued: control::queue_length(id),
+        })
+        .collect();
+    widgets.sort_by_key(|w| std::cmp::Reverse(w.requests));
+
+    let total = metrics.cache_hits + metrics.cache_misses;
+    MetricsReport {
+        widgets,
+        cache: CacheMetrics {
+            hits: metrics.cache_hits,
+            misses: metrics.cache_misses,
+            hit_rate: if total > 0 { metrics.cache_hits as f64 / total as f64 } else { 0.0 },
+        },
+        open_connections: control::total_connections(),
+        rate_limited: metrics.rate_limited,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Rate limiting (widget routes only; the API and metrics stay unthrottled)
+// ---------------------------------------------------------------------------
+static RATE_LIMITER: Lazy<Mutex<RateLimiter>> = Lazy::new(|| Mutex::new(RateLimiter::new(RateLimit::default())));
+
+/// Swap in a new limit, resetting every client's count.
+pub fn configure_rate_limit(limit: RateLimit) {
+    *RATE_LIMITER.lock().unwrap() = RateLimiter::new(limit);
+}
+
+fn is_widget_route(path: &str) -> bool {
+    path.starts_with("/widget/") || path.starts_with("/w/")
+}
+
+async fn rate_limit_widgets<B: MessageBody + 'static>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, actix_web::Error> {
+    let ip = req.peer_addr().map(|addr| addr.ip());
+    if let (Some(ip), true) = (ip, is_widget_route(req.path())) {
+        let checked = RATE_LIMITER.lock().unwrap().check(ip, Instant::now());
+        if let Err(refused) = checked {
+            METRICS.lock().unwrap().rate_limited += 1;
+            if refused.first {
+                log::warn!(target: "obs_server", "Rate limiting {ip} (first refused: {})", req.path());
+            }
+            let res = HttpResponse::TooManyRequests()
+                .insert_header(("Retry-After", refused.retry_after.to_string()))
+                .body("Too many requests from this address; see the rate limit setting in Open Overlay.");
+            return Ok(req.into_response(res).map_into_right_body());
+        }
+    }
+    next.call(req).await.map(ServiceResponse::map_into_left_body)
+}
+
+// ---------------------------------------------------------------------------
+// Cache-Control per route: widget pages are never cached so edits show on the
+// next load; the widget stylesheet linked with its current `?v=` hash can be
+// kept forever, since a change to it changes the link
+// ---------------------------------------------------------------------------
+const NO_STORE: &str = "no-store";
+const NO_CACHE: &str = "no-cache";
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+fn cache_control_for(path: &str, query: &str, success: bool) -> Option<&'static str> {
+    if is_widget_route(path) {
+        Some(NO_STORE)
+    } else if path.starts_with("/static/") {
+        let current = path == "/static/widget.css"
+            && query.split('&').any(|pair| pair.strip_prefix("v=") == Some(WIDGET_CSS_VERSION.as_str()));
+        // An old or missing `v` (or a 404) must be checked again, not kept for a year
+        Some(if current && success { IMMUTABLE } else { NO_CACHE })
+    } else {
+        None
+    }
+}
+
+/// Apply the route's cache policy unless the handler set its own header.
+async fn cache_headers(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let mut res = next.call(req).await?;
+    let policy = cache_control_for(res.request().path(), res.request().query_string(), res.status().is_success());
+    if let Some(policy) = policy {
+        if !res.headers().contains_key(CACHE_CONTROL) {
+            res.headers_mut().insert(CACHE_CONTROL, HeaderValue::from_static(policy));
+        }
+    }
+    Ok(res)
+}
+
 // ---------------------------------------------------------------------------
 // Routes
 // ---------------------------------------------------------------------------
+/// Most missing assets listed in the `X-Missing-Assets` header; the count header has the full number.
+const MISSING_ASSETS_HEADER_MAX: usize = 20;
+
+/// JSON with everything outside printable ASCII escaped, so it fits in a header value.
+fn ascii_json(value: &Value) -> String {
+    let mut out = String::new();
+    for c in value.to_string().chars() {
+        if c.is_ascii() && !c.is_ascii_control() {
+            out.push(c);
+        } else {
+            for unit in c.encode_utf16(&mut [0; 2]) {
+                out.push_str(&format!("\\u{unit:04x}"));
+            }
+        }
+    }
+    out
+}
+
+type MissingAssetCache = HashMap<String, (u64, Arc<Vec<validation::MissingAsset>>)>;
+
+/// `validation::missing_assets` per widget id, tagged with the widget hash it
+/// was checked for, so page loads of an unchanged widget don't touch the disk.
+static MISSING_ASSETS: Lazy<Mutex<MissingAssetCache>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Check the files and fonts `widget` references and note the missing ones in
+/// its error log (each once).
+fn check_assets(id: &str, widget: &Value) -> Vec<validation::MissingAsset> {
+    let missing = validation::missing_assets(widget);
+    if !missing.is_empty() {
+        let known: HashSet<String> = control::errors(id).into_iter().map(|e| e.message).collect();
+        for asset in &missing {
+            let message = match (asset.kind, &asset.element_id) {
+                ("font", Some(element)) => format!("Font '{}' used by element '{element}' isn't installed", asset.reference),
+                ("font", None) => format!("Font '{}' isn't installed", asset.reference),
+                (_, Some(element)) => format!("Asset '{}' used by element '{element}' is missing", asset.reference),
+                (_, None) => format!("Background image '{}' is missing", asset.reference),
+            };
+            if !known.contains(&message) {
+                control::record_error(id, control::PageError { message, source: None, line: None, column: None, stack: None, at: 0 });
+            }
+        }
+    }
+    missing
+}
+
+/// The widget's missing assets, checked (off the worker thread) only when
+/// the widget changed since the last check.
+async fn missing_assets_cached(id: &str, widget: &Value) -> Arc<Vec<validation::MissingAsset>> {
+    let hash = widget_hash(widget);
+    let cached = MISSING_ASSETS.lock().unwrap().get(id).filter(|(h, _)| *h == hash).map(|(_, m)| m.clone());
+    if let Some(missing) = cached {
+        return missing;
+    }
+    let (owned_id, owned_widget) = (id.to_string(), widget.clone());
+    let missing = Arc::new(web::block(move || check_assets(&owned_id, &owned_widget)).await.unwrap_or_default());
+    let mut cache = MISSING_ASSETS.lock().unwrap();
+    if cache.len() >= RENDER_CACHE_MAX_ENTRIES {
+        cache.clear();
+    }
+    cache.insert(id.to_string(), (hash, missing.clone()));
+    missing
+}
+
+/// Check the widget's assets again on its next page load.
+pub fn forget_asset_check(id: &str) {
+    MISSING_ASSETS.lock().unwrap().remove(id);
+}
+
+/// A widget's page. Files and fonts it references that won't load are listed
+/// in `X-Missing-Assets` (a JSON list of `{kind, reference, element_id}`) and
+/// noted once each in the widget's error log; the page renders regardless.
+async fn widget_page(id: &str, widget: &Value, opts: &RenderOptions, query: &str) -> HttpResponse {
+    let missing = missing_assets_cached(id, widget).await;
+    let mut response = HttpResponse::Ok();
+    response.content_type("text/html; charset=utf-8");
+    if !missing.is_empty() {
+        let listed = serde_json::to_value(&missing[..missing.len().min(MISSING_ASSETS_HEADER_MAX)]).unwrap_or_default();
+        response.insert_header(("X-Missing-Assets", ascii_json(&listed)));
+        response.insert_header(("X-Missing-Assets-Count", missing.len().to_string()));
+    }
+    response.body(render_cached(id, widget, opts, query))
+}
+
 #[get("/widget/{id}")]
-async fn serve_widget(path: web::Path<String>) -> impl Responder {
+async fn serve_widget(req: HttpRequest, path: web::Path<String>, query: web::Query<RenderOptions>) -> impl Responder {
     let id = path.into_inner();
     match find_widget(&id) {
-        Some(widget) => HttpResponse::Ok()
-            .content_type("text/html; charset=utf-8")
-            .body(render_widget_html(&widget)),
+        Some(widget) => widget_page(&id, &widget, &query, req.query_string()).await,
         None => HttpResponse::NotFound().body(format!("Widget '{id}' not found")),
     }
 }
 
+/// The stylesheet every widget page links (`?v=` changes with its content).
+#[get("/static/widget.css")]
+async fn serve_widget_css() -> impl Responder {
+    HttpResponse::Ok().content_type("text/css; charset=utf-8").body(WIDGET_CSS)
+}
+
+/// Same page as `/widget/{id}`, addressed by the widget's slug.
+#[get("/w/{slug}")]
+async fn serve_widget_by_slug(req: HttpRequest, path: web::Path<String>, query: web::Query<RenderOptions>) -> impl Responder {
+    let slug = path.into_inner();
+    let found = find_widget_by_slug(&slug)
+        .and_then(|widget| Some((widget.get("id")?.as_str()?.to_string(), widget)));
+    match found {
+        Some((id, widget)) => widget_page(&id, &widget, &query, req.query_string()).await,
+        None => HttpResponse::NotFound().body(format!("No widget with slug '{slug}'")),
+    }
+}
+
+/// What tooling needs to set up a browser source for a widget without parsing its config.
+#[get("/widget/{id}/manifest.json")]
+async fn serve_widget_manifest(req: HttpRequest, path: web::Path<String>) -> impl Responder {
+    let id = path.into_inner();
+    match find_widget(&id) {
+        Some(widget) => HttpResponse::Ok().json(widget_manifest(req.connection_info().host(), &id, &widget)),
+        None => HttpResponse::NotFound().body(format!("Widget '{id}' not found")),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum PageReport {
+    Queue { length: usize },
+}
+
+/// Handle a message a widget page sends over its control socket: status
+/// reports (`type`) are recorded, events (`event`) go out to listeners.
+fn report_from_page(widget_id: &str, conn: u64, text: &str) {
+    let Ok(value) = serde_json::from_str::<Value>(text) else {
+        log::debug!("Ignoring non-JSON page message");
+        return;
+    };
+    if value.get("event").is_some() {
+        control::publish_event(widget_id, value);
+        return;
+    }
+    match serde_json::from_value::<PageReport>(value) {
+        Ok(PageReport::Queue { length }) => control::set_queue_length(conn, length),
+        Err(e) => log::debug!("Ignoring unknown page report: {e}"),
+    }
+}
+
+/// Control WebSocket for a widget page: forwards control messages (triggers
+/// etc.) addressed to this widget.
+#[get("/widget/{id}/ws")]
+async fn widget_ws(req: HttpRequest, body: web::Payload, path: web::Path<String>) -> actix_web::Result<HttpResponse> {
+    let (response, mut session, mut incoming) = actix_ws::handle(&req, body)?;
+    let id = path.into_inner();
+    let mut rx = control::subscribe();
+
+    actix_web::rt::spawn(async move {
+        let conn = control::connection_opened(&id);
+        let _ = session.text(control::clock_message().to_string()).await;
+        if control::listener_count(&id) > 0 {
+            let _ = session.text(control::time_updates_message(true).to_string()).await;
+        }
+        loop {
+            tokio::select! {
+                msg = rx.recv() => match msg {
+                    Ok(msg) if msg.widget_id == id => {
+                        if session.text(msg.payload.to_string()).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => break,
+                },
+                frame = incoming.recv() => match frame {
+                    Some(Ok(actix_ws::Message::Ping(bytes))) => {
+                        if session.pong(&bytes).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Ok(actix_ws::Message::Text(text))) => report_from_page(&id, conn, &text),
+                    Some(Ok(actix_ws::Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+        control::connection_closed(&id, conn);
+        let _ = session.close(None).await;
+    });
+
+    Ok(response)
+}
+
+/// Event WebSocket for integrators: relays what a widget's open pages publish,
+/// e.g. `{"event":"time","t":1.23,"playing":true}`. Pages only emit while at
+/// least one listener is connected.
+#[get("/widget/{id}/events")]
+async fn widget_events_ws(req: HttpRequest, body: web::Payload, path: web::Path<String>) -> actix_web::Result<HttpResponse> {
+    let (response, mut session, mut incoming) = actix_ws::handle(&req, body)?;
+    let id = path.into_inner();
+    let mut rx = control::subscribe_events();
+
+    actix_web::rt::spawn(async move {
+        control::listener_opened(&id);
+        loop {
+            tokio::select! {
+                event = rx.recv() => match event {
+                    Ok(event) if event.widget_id == id => {
+                        if session.text(event.payload.to_string()).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => break,
+                },
+                frame = incoming.recv() => match frame {
+                    Some(Ok(actix_ws::Message::Ping(bytes))) => {
+                        if session.pong(&bytes).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Ok(actix_ws::Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+        control::listener_closed(&id);
+        let _ = session.close(None).await;
+    });
+
+    Ok(response)
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+/// What `/ws/events` sends for a stored change.
+fn change_event(change: &db::OverlayChange) -> Value {
+    let kind = if change.kind == db::ChangeKind::Deleted { "deleted" } else { "saved" };
+    serde_json::json!({ "type": kind, "id": change.id, "updated_at": change.updated_at })
+}
+
+/// Database change feed for dashboards and companion tools:
+/// `{"type": "saved" | "deleted", "id": "<overlay-id>", "updated_at": ...}`
+/// for every overlay write, whoever made it. Needs the inbox token (header or
+/// `?token=`). A subscriber that falls behind gets `{"type": "resync"}` and
+/// should reload `/api/workspaces`.
+#[get("/ws/events")]
+async fn db_events_ws(
+    req: HttpRequest,
+    body: web::Payload,
+    query: web::Query<TokenQuery>,
+) -> actix_web::Result<HttpResponse> {
+    if let Err(response) = check_token(&req, query.token.as_deref()) {
+        return Ok(response);
+    }
+    let (response, mut session, mut incoming) = actix_ws::handle(&req, body)?;
+    let mut rx = db::subscribe_changes();
+    let peer = req.peer_addr().map(|a| a.to_string()).unwrap_or_default();
+    log::info!(target: "obs_server", "{peer} subscribed to database changes");
+
+    actix_web::rt::spawn(async move {
+        loop {
+            tokio::select! {
+                change = rx.recv() => {
+                    let event = match change {
+                        Ok(change) => change_event(&change),
+                        Err(RecvError::Lagged(_)) => serde_json::json!({ "type": "resync" }),
+                        Err(RecvError::Closed) => break,
+                    };
+                    if session.text(event.to_string()).await.is_err() {
+                        break;
+                    }
+                }
+                frame = incoming.recv() => match frame {
+                    Some(Ok(actix_ws::Message::Ping(bytes))) => {
+                        if session.pong(&bytes).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Ok(actix_ws::Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+        let _ = session.close(None).await;
+    });
+
+    Ok(response)
+}
+
+/// A trigger's `values` fill `{name}` tokens in the widget's text, so they
+/// must be an object of strings, numbers or booleans.
+fn check_values(payload: &Value) -> Result<(), &'static str> {
+    match payload.get("values") {
+        None | Some(Value::Null) => Ok(()),
+        Some(Value::Object(values)) if values.values().all(|v| v.is_string() || v.is_number() || v.is_boolean()) => Ok(()),
+        Some(_) => Err("values must be a JSON object of strings, numbers or booleans"),
+    }
+}
+
+/// Restart a widget's timeline (and fire `emitOn: "trigger"` effects) on every
+/// open page. An optional JSON object body is forwarded with the trigger; its
+/// `values` fill the widget's `{name}` text tokens.
+#[post("/api/widget/{id}/trigger")]
+async fn api_trigger_widget(path: web::Path<String>, body: web::Bytes) -> impl Responder {
+    let id = path.into_inner();
+    let payload = if body.is_empty() {
+        serde_json::json!({})
+    } else {
+        match serde_json::from_slice::<Value>(&body) {
+            Ok(v @ Value::Object(_)) => v,
+            _ => return HttpResponse::BadRequest().body("Trigger body must be a JSON object"),
+        }
+    };
+    if let Err(e) = check_values(&payload) {
+        return HttpResponse::BadRequest().body(e);
+    }
+    let delivered = control::trigger(&id, payload);
+    HttpResponse::Ok().json(serde_json::json!({ "delivered": delivered, "queued": control::queue_length(&id) }))
+}
+
+/// Trigger several widgets to start at the same moment:
+/// `{"ids": ["<widget-id>", ...]}`; other fields are forwarded like a single trigger's.
+#[post("/api/trigger")]
+async fn api_trigger_widgets(body: web::Json<Value>) -> impl Responder {
+    let mut payload = body.into_inner();
+    let ids: Vec<String> = match payload.as_object_mut().and_then(|obj| obj.remove("ids")) {
+        Some(ids) => match serde_json::from_value(ids) {
+            Ok(ids) => ids,
+            Err(_) => return HttpResponse::BadRequest().body("ids must be a list of widget ids"),
+        },
+        None => return HttpResponse::BadRequest().body("Trigger body must be a JSON object with ids"),
+    };
+    if let Err(e) = check_values(&payload) {
+        return HttpResponse::BadRequest().body(e);
+    }
+    let at = control::start_epoch();
+    payload["at"] = Value::from(at);
+    let delivered: BTreeMap<&str, usize> = ids.iter().map(|id| (id.as_str(), control::trigger(id, payload.clone()))).collect();
+    HttpResponse::Ok().json(serde_json::json!({ "at": at, "delivered": delivered }))
+}
+
+/// Drop every trigger still waiting in a widget's alert queue; the alert
+/// currently playing finishes.
+#[post("/api/widget/{id}/queue/clear")]
+async fn api_clear_queue(path: web::Path<String>) -> impl Responder {
+    let delivered = control::send(&path, serde_json::json!({ "action": "clear_queue" }));
+    HttpResponse::Ok().json(serde_json::json!({ "delivered": delivered }))
+}
+
+/// Fetch an image or video element's remote `src` again on every open page,
+/// past OBS's cache, and keep the fresh version for the next load (the
+/// element's saved `srcNonce` is bumped). It writes to the database, so like
+/// `/inbox` it needs the inbox token (header or `?token=`).
+#[post("/api/widget/{id}/element/{element}/refresh")]
+async fn api_refresh_src(
+    req: HttpRequest,
+    path: web::Path<(String, String)>,
+    query: web::Query<TokenQuery>,
+) -> impl Responder {
+    if let Err(response) = check_token(&req, query.token.as_deref()) {
+        return response;
+    }
+    let (id, element) = path.into_inner();
+    match web::block(move || src_refresh::refresh(&id, &element)).await {
+        Ok(Ok(refresh)) => HttpResponse::Ok().json(refresh),
+        Ok(Err(e @ CommandError::NotFound { .. })) => HttpResponse::NotFound().body(e.to_string()),
+        Ok(Err(e @ CommandError::InvalidConfig { .. })) => HttpResponse::BadRequest().body(e.to_string()),
+        Ok(Err(e @ (CommandError::Conflict { .. } | CommandError::Busy { .. }))) => HttpResponse::Conflict().body(e.to_string()),
+        Ok(Err(e)) => HttpResponse::InternalServerError().body(e.to_string()),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+/// Hide a widget on every open page after its elements' exit animations;
+/// the next trigger shows it again.
+#[post("/api/widget/{id}/hide")]
+async fn api_hide_widget(path: web::Path<String>) -> impl Responder {
+    let delivered = control::send(&path, control::hide_message());
+    HttpResponse::Ok().json(serde_json::json!({ "delivered": delivered }))
+}
+
+/// Play, pause, stop or seek one of a widget's timeline segments on every open
+/// page. Optional JSON body: `{"t": <seconds>}` for play and seek, and
+/// `{"exclusive": true}` to stop the widget's other segments when this one plays.
+#[post("/api/widget/{id}/segment/{name}/{op}")]
+async fn api_widget_segment(path: web::Path<(String, String, String)>, body: web::Bytes) -> impl Responder {
+    let (id, name, op) = path.into_inner();
+    let Some(op) = segments::SegmentOp::parse(&op) else {
+        return HttpResponse::BadRequest().body(format!("Unknown segment action '{op}'; use play, pause, stop or seek"));
+    };
+    let args: segments::SegmentArgs = if body.is_empty() {
+        Default::default()
+    } else {
+        match serde_json::from_slice(&body) {
+            Ok(args) => args,
+            Err(e) => return HttpResponse::BadRequest().body(format!("Invalid segment body: {e}")),
+        }
+    };
+    let widget = find_widget(&id);
+    if let Some(widget) = &widget {
+        if !segments::names(widget).contains(&name) {
+            return HttpResponse::NotFound().body(format!("Widget '{id}' has no timeline segment '{name}'"));
+        }
+    }
+    let duration = widget.as_ref().and_then(|w| segments::duration(w, &name));
+    let delivered = control::send(&id, segments::message(&name, op, &args, duration));
+    HttpResponse::Ok().json(serde_json::json!({ "delivered": delivered }))
+}
+
+/// Check the inbox token, sent as `Authorization: Bearer <token>` or, where
+/// the client can't set headers (a browser WebSocket), as `presented`.
+fn check_token(req: &HttpRequest, presented: Option<&str>) -> Result<(), HttpResponse> {
+    let expected = match settings::inbox_token() {
+        Ok(token) => token,
+        Err(e) => {
+            log::error!("Database error while reading the inbox token: {e}");
+            return Err(HttpResponse::InternalServerError().body(e.to_string()));
+        }
+    };
+    let presented = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or(presented);
+    if !presented.is_some_and(|token| inbox::token_matches(token.trim(), &expected)) {
+        return Err(HttpResponse::Unauthorized().body("Missing or wrong inbox token (send Authorization: Bearer <token>)"));
+    }
+    Ok(())
+}
+
+/// Generic webhook for chat bots: `{"type": "raid", "payload": {...}}` with
+/// `Authorization: Bearer <inbox token>`. Widgets whose `inbox` rules name the
+/// type are triggered (or have their queue cleared).
+#[post("/inbox")]
+async fn api_inbox(req: HttpRequest, body: web::Bytes) -> impl Responder {
+    if let Err(response) = check_token(&req, None) {
+        return response;
+    }
+    let event: inbox::InboxEvent = match serde_json::from_slice(&body) {
+        Ok(event) => event,
+        Err(e) => {
+            return HttpResponse::BadRequest()
+                .body(format!(r#"Inbox events are JSON objects like {{"type": "raid", "payload": {{}}}}: {e}"#))
+        }
+    };
+    let deliveries = inbox::deliver(&event);
+    log::info!(target: "obs_server", "Inbox event '{}' matched {} widget action(s)", event.kind, deliveries.len());
+    HttpResponse::Ok().json(serde_json::json!({ "matched": deliveries.len(), "deliveries": deliveries }))
+}
+
+/// Uncaught script error from a widget page; shown in the editor so a blank
+/// browser source has an explanation.
+#[post("/api/widget/{id}/error")]
+async fn api_widget_error(path: web::Path<String>, body: web::Json<control::PageError>) -> impl Responder {
+    let id = path.into_inner();
+    // Only keep reports for real widgets so stray posts can't grow the buffer
+    if find_widget(&id).is_none() {
+        return HttpResponse::NotFound().body(format!("Widget '{id}' not found"));
+    }
+    control::record_error(&id, body.into_inner());
+    HttpResponse::NoContent().finish()
+}
+
+#[derive(Debug, Serialize)]
+struct WidgetListing {
+    id: String,
+    name: String,
+    width: u64,
+    height: u64,
+    url: String,
+    overlay_id: String,
+    overlay_name: String,
+}
+
+/// Every widget across all workspaces with its URL on `host`.
+fn widget_listings(host: &str) -> Vec<WidgetListing> {
+    all_widgets()
+        .into_iter()
+        .filter_map(|(summary, widget)| {
+            let id = widget.get("id")?.as_str()?.to_string();
+            let (width, height) = widget_size(&widget);
+            Some(WidgetListing {
+                url: widget_url(host, &id, widget_slug(&widget)),
+                name: widget.get("name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                width,
+                height,
+                id,
+                overlay_id: summary.id,
+                overlay_name: summary.name,
+            })
+        })
+        .collect()
+}
+
+/// Flat list of every widget across all workspaces with its ready-to-paste URL.
+#[get("/api/widgets")]
+async fn api_list_widgets(req: HttpRequest) -> impl Responder {
+    HttpResponse::Ok().json(widget_listings(req.connection_info().host()))
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Copies a row's URL. `navigator.clipboard` needs a secure context, which a
+/// LAN address isn't, so it falls back to copying from a selected text field.
+const INDEX_COPY_JS: &str = r#"<script>
+document.addEventListener('click', async e => {
+  const button = e.target.closest('button[data-url]');
+  if (!button) return;
+  const url = button.dataset.url;
+  try { await navigator.clipboard.writeText(url); }
+  catch (err) {
+    const field = document.createElement('textarea');
+    field.value = url; document.body.appendChild(field); field.select();
+    document.execCommand('copy'); field.remove();
+  }
+  button.textContent = 'Copied';
+  setTimeout(() => { button.textContent = 'Copy'; }, 1500);
+});
+</script>"#;
+
+/// Plain page listing every widget with its URL, for grabbing OBS URLs
+/// without the editor (and checking the server is reachable from another PC).
+#[get("/")]
+async fn serve_index(req: HttpRequest) -> impl Responder {
+    let listings = widget_listings(req.connection_info().host());
+    let rows: String = listings
+        .iter()
+        .map(|w| {
+            let url = escape_html(&w.url);
+            format!(
+                "<tr><td>{}</td><td>{}</td><td>{}×{}</td><td><a href=\"{url}\">{url}</a></td>\
+                 <td><button data-url=\"{url}\">Copy</button></td></tr>\n",
+                escape_html(&w.overlay_name),
+                escape_html(if w.name.is_empty() { &w.id } else { &w.name }),
+                w.width,
+                w.height,
+            )
+        })
+        .collect();
+    let body = if listings.is_empty() {
+        "<p>No widgets yet. Create one in the Open Overlay editor.</p>".to_string()
+    } else {
+        format!("<table>\n<tr><th>Workspace</th><th>Widget</th><th>Size</th><th>URL</th><th></th></tr>\n{rows}</table>")
+    };
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Open Overlay widgets</title>
+<style>
+body {{ font: 14px/1.4 system-ui, sans-serif; margin: 2em; color: #222; }}
+table {{ border-collapse: collapse; }}
+th, td {{ text-align: left; padding: 4px 12px 4px 0; border-bottom: 1px solid #ddd; }}
+</style>
+</head>
+<body>
+<h1>Open Overlay widgets</h1>
+<p>Add a widget to OBS as a Browser source with its URL and size.</p>
+{body}
+{INDEX_COPY_JS}
+</body>
+</html>"#
+    );
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .insert_header((CACHE_CONTROL, NO_STORE))
+        .body(html)
+}
+
+#[derive(Debug, Serialize)]
+struct TriggerManifest {
+    /// `POST` here to play the widget.
+    url: String,
+    clear_queue_url: String,
+    hide_url: String,
+    /// Inbox event types the widget reacts to.
+    inbox_events: Vec<String>,
+    /// `POST` to `.../segment/{name}/{play|pause|stop|seek}` to drive a timeline segment.
+    segment_url: String,
+    /// The widget's timeline segments, in layering order.
+    segments: Vec<String>,
+    /// `{name}` tokens in the widget's text, filled from a trigger's `values`.
+    values: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct WidgetManifest {
+    id: String,
+    name: String,
+    widget_type: String,
+    url: String,
+    /// Browser source size: the design size times `pixel_ratio`.
+    width: u64,
+    height: u64,
+    /// Device pixels per design pixel the page renders at (the widget's `pixelRatio`).
+    pixel_ratio: f64,
+    /// Keyframes or draw-on paths; the page animates rather than sitting still.
+    has_timeline: bool,
+    autoplay: bool,
+    /// Font families the elements name, which must be installed where OBS runs.
+    fonts: Vec<String>,
+    /// Image, video, poster, mask and background image URLs the page loads.
+    assets: Vec<String>,
+    triggers: TriggerManifest,
+}
+
+fn push_unique(list: &mut Vec<String>, value: &str) {
+    let value = value.trim();
+    if !value.is_empty() && !list.iter().any(|v| v == value) {
+        list.push(value.to_string());
+    }
+}
+
+/// Fonts and assets referenced anywhere in an element tree (including group children and text runs).
+fn collect_resources(elements: &Value, fonts: &mut Vec<String>, assets: &mut Vec<String>, has_draw_on: &mut bool) {
+    for element in elements.as_array().into_iter().flatten() {
+        for (_, font) in export::element_fonts(element) {
+            push_unique(fonts, font);
+        }
+        for (_, asset) in export::element_assets(element) {
+            push_unique(assets, asset);
+        }
+        *has_draw_on |= element.get("drawOn").is_some_and(|v| v.as_bool() == Some(true));
+        if let Some(children) = element.get("children") {
+            collect_resources(children, fonts, assets, has_draw_on);
+        }
+    }
+}
+
+/// Names of the `{name}` / `{name|fallback}` tokens in `text`, as the page matches them.
+fn text_tokens(text: &str, out: &mut Vec<String>) {
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start + 1..];
+        let Some(end) = rest.find(['{', '}']) else { break };
+        if !rest[end..].starts_with('}') {
+            continue;
+        }
+        let name = rest[..end].split('|').next().unwrap_or("");
+        let valid = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+        if valid && !out.iter().any(|v| v == name) {
+            out.push(name.to_string());
+        }
+        rest = &rest[end + 1..];
+    }
+}
+
+/// Every token the text elements in an element tree use; counters and clocks don't fill them.
+fn collect_tokens(elements: &Value, out: &mut Vec<String>) {
+    for element in elements.as_array().into_iter().flatten() {
+        let templated = element.get("type").and_then(|v| v.as_str()) == Some("text")
+            && !element.get("numericContent").and_then(|v| v.as_bool()).unwrap_or(false)
+            && !element.get("dynamic").and_then(|v| v.as_str()).is_some_and(|d| !d.is_empty());
+        if templated {
+            match element.get("segments").and_then(|v| v.as_array()).filter(|s| !s.is_empty()) {
+                Some(segments) => segments.iter().filter_map(|s| s.get("text")?.as_str()).for_each(|t| text_tokens(t, out)),
+                None => text_tokens(element.get("content").and_then(|v| v.as_str()).unwrap_or(""), out),
+            }
+        }
+        if let Some(children) = element.get("children") {
+            collect_tokens(children, out);
+        }
+    }
+}
+
+fn widget_manifest(host: &str, id: &str, widget: &Value) -> WidgetManifest {
+    let (width, height) = widget_size(widget);
+    let ratio = pixel_ratio(widget);
+    let (mut fonts, mut assets, mut has_draw_on) = (Vec::new(), Vec::new(), false);
+    let elements = style_presets::resolve(widget.get("elements").unwrap_or(&Value::Null), widget.get("stylePresets"));
+    collect_resources(&elements, &mut fonts, &mut assets, &mut has_draw_on);
+    let mut values = Vec::new();
+    collect_tokens(&elements, &mut values);
+    if let Some(background) = widget.pointer("/background/src").and_then(|v| v.as_str()) {
+        push_unique(&mut assets, background);
+    }
+    // Same test the page uses to decide whether it animates
+    let timeline = widget.get("animationTimeline").filter(|t| t.is_object());
+    let has_keyframes = timeline
+        .and_then(|t| t.get("keyframes"))
+        .and_then(|k| k.as_array())
+        .is_some_and(|k| !k.is_empty());
+    WidgetManifest {
+        id: id.to_string(),
+        name: widget.get("name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+        widget_type: widget.get("widgetType").and_then(|v| v.as_str()).unwrap_or("custom").to_string(),
+        url: widget_url(host, id, widget_slug(widget)),
+        width: (width as f64 * ratio).round() as u64,
+        height: (height as f64 * ratio).round() as u64,
+        pixel_ratio: ratio,
+        has_timeline: has_keyframes || (timeline.is_some() && has_draw_on),
+        autoplay: timeline.and_then(|t| t.get("autoplay")).and_then(|v| v.as_bool()).unwrap_or(false),
+        fonts,
+        assets,
+        triggers: TriggerManifest {
+            url: format!("http://{host}/api/widget/{id}/trigger"),
+            clear_queue_url: format!("http://{host}/api/widget/{id}/queue/clear"),
+            hide_url: format!("http://{host}/api/widget/{id}/hide"),
+            inbox_events: inbox::event_types(widget),
+            segment_url: format!("http://{host}/api/widget/{id}/segment"),
+            segments: segments::names(widget),
+            values,
+        },
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Workspace audio: the config's `audioTrack` looped on a page of its own,
+// added to OBS as one more (invisible) browser source
+// ---------------------------------------------------------------------------
+#[derive(Debug, Default, Deserialize)]
+struct AudioOptions {
+    /// Keep the track silent (`?mute=1`), for previewing in a normal browser.
+    #[serde(default, deserialize_with = "query_flag")]
+    mute: bool,
+}
+
+/// The workspace's track as a URL, with its volume clamped to 0-1 (default 1).
+fn audio_track(config: &Value) -> Option<(&str, f64)> {
+    let src = config.get("audioTrack").and_then(|v| v.as_str()).map(str::trim).filter(|s| !s.is_empty())?;
+    let volume = config.get("audioVolume").and_then(|v| v.as_f64()).filter(|v| v.is_finite()).unwrap_or(1.0);
+    Some((src, volume.clamp(0.0, 1.0)))
+}
+
+/// `audioTrack` must be a string and `audioVolume` a number from 0 to 1.
+pub fn validate_audio_track(config: &Value) -> Result<(), String> {
+    if config.get("audioTrack").is_some_and(|v| !v.is_null() && !v.is_string()) {
+        return Err("audioTrack must be a URL string".to_string());
+    }
+    match config.get("audioVolume").filter(|v| !v.is_null()) {
+        Some(v) if !v.as_f64().is_some_and(|v| (0.0..=1.0).contains(&v)) => {
+            Err("audioVolume must be a number from 0 to 1".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Browsers (unlike OBS) refuse to start sound before the page is clicked, so
+/// a refused `play()` starts the track muted and unmutes it on the first gesture.
+const AUDIO_JS: &str = r#"<script>
+const audio = document.getElementById('track');
+audio.volume = VOLUME;
+audio.muted = MUTE;
+audio.play().catch(() => {
+  audio.muted = true;
+  audio.play().catch(() => {});
+  if (MUTE) return;
+  const unmute = () => { audio.muted = false; audio.play().catch(() => {}); };
+  addEventListener('pointerdown', unmute, { once: true });
+  addEventListener('keydown', unmute, { once: true });
+});
+</script>"#;
+
+fn render_audio_html(config: &Value, mute: bool) -> String {
+    // A workspace without a track still gets a (silent) page, so a source set up early shows nothing
+    let Some((src, volume)) = audio_track(config) else {
+        return "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"></head><body></body></html>".to_string();
+    };
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<style>html, body {{ margin:0; background:transparent; }}</style>
+</head>
+<body>
+<audio id="track" src="{src}" loop preload="auto"></audio>
+<script>const VOLUME = {volume}; const MUTE = {mute};</script>
+{AUDIO_JS}
+</body>
+</html>"#,
+        src = escape_html(src),
+    )
+}
+
+#[get("/workspace/{id}/audio")]
+async fn serve_workspace_audio(path: web::Path<String>, query: web::Query<AudioOptions>) -> impl Responder {
+    let id = path.into_inner();
+    let config = match db::get_overlay(&id) {
+        Ok(Some(row)) => serde_json::from_str::<Value>(&row.config).unwrap_or(Value::Null),
+        Ok(None) => return HttpResponse::NotFound().body(format!("Workspace '{id}' not found")),
+        Err(e) => {
+            log::error!("Database error while loading workspace {id}: {e}");
+            return HttpResponse::InternalServerError().body(e.to_string());
+        }
+    };
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .insert_header((CACHE_CONTROL, NO_STORE))
+        .body(render_audio_html(&config, query.mute))
+}
+
+/// Entry in `/api/workspaces`: the summary plus the canvas it's designed for
+/// and, when it has a track, its audio page.
+#[derive(Debug, Serialize)]
+struct WorkspaceListing {
+    #[serde(flatten)]
+    summary: db::OverlaySummary,
+    canvas: WorkspaceCanvas,
+    audio_url: Option<String>,
+}
+
 #[get("/api/workspaces")]
-async fn api_list_workspaces() -> impl Responder {
-    match db::list_overlays() {
+async fn api_list_workspaces(req: HttpRequest) -> impl Responder {
+    let host = req.connection_info().host().to_string();
+    let listed = db::list_overlays().map(|list| {
+        list.into_iter()
+            .map(|summary| {
+                let config = db::get_overlay(&summary.id)
+                    .ok()
+                    .flatten()
+                    .and_then(|row| serde_json::from_str::<Value>(&row.config).ok());
+                let canvas = config.as_ref().and_then(|c| WorkspaceCanvas::from_config(c).ok()).unwrap_or_default();
+                let audio_url = config
+                    .as_ref()
+                    .and_then(audio_track)
+                    .map(|_| format!("http://{host}/workspace/{}/audio", summary.id));
+                WorkspaceListing { summary, canvas, audio_url }
+            })
+            .collect::<Vec<_>>()
+    });
+    match listed {
         Ok(list) => HttpResponse::Ok().json(list),
+        Err(e) => {
+            log::error!("Database error while listing workspaces: {e}");
+            HttpResponse::InternalServerError().body(e.to_string())
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PrewarmRequest {
+    ids: Vec<String>,
+}
+
+/// Pre-render widgets before a scene switch: `{"ids": ["<widget-id>", ...]}`.
+#[post("/api/prewarm")]
+async fn api_prewarm(body: web::Json<PrewarmRequest>) -> impl Responder {
+    let ids = body.into_inner().ids;
+    match web::block(move || prewarm(&ids)).await {
+        Ok(report) => HttpResponse::Ok().json(report),
         Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
     }
 }
 
-pub async fn start_obs_server_async() {
+#[get("/metrics")]
+async fn api_metrics() -> impl Responder {
+    HttpResponse::Ok().json(metrics_report())
+}
+
+// ---------------------------------------------------------------------------
+// Server lifecycle + status
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerPhase {
+    #[default]
+    Stopped,
+    /// Binding the port.
+    Starting,
+    Running,
+    /// The previous server is shutting down for a restart.
+    Stopping,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ServerStatus {
+    /// `phase` is `Running`.
+    pub running: bool,
+    pub phase: ServerPhase,
+    /// Address actually bound, e.g. `127.0.0.1:7878`.
+    pub bound_addr: Option<String>,
+    pub port: u16,
+    pub lan_enabled: bool,
+    /// Unix time in ms the running server started listening.
+    pub started_at: Option<u64>,
+    /// Why the last start failed (e.g. the port is already in use).
+    pub last_error: Option<String>,
+    /// Launched in safe mode and not started by hand since: the server stays down.
+    pub safe_mode: bool,
+}
+
+static STATUS: Lazy<watch::Sender<ServerStatus>> =
+    Lazy::new(|| watch::channel(ServerStatus { port: OBS_HTTP_PORT, ..Default::default() }).0);
+
+static HANDLE: Lazy<Mutex<Option<ServerHandle>>> = Lazy::new(|| Mutex::new(None));
+
+/// Held for a whole start or restart, so a quick second restart can't race
+/// the first for the port.
+static RESTARTS: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Bumped by every start; a server that stops only reports it while no newer one was started.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Keep the server down (a safe-mode launch) until `start_by_hand`; restarts
+/// after a LAN mode or profile change are skipped meanwhile.
+pub fn hold() {
+    STATUS.send_modify(|status| status.safe_mode = true);
+}
+
+fn held() -> bool {
+    STATUS.borrow().safe_mode
+}
+
+pub fn status() -> ServerStatus {
+    STATUS.borrow().clone()
+}
+
+/// Receive a notification whenever the server status changes.
+pub fn subscribe_status() -> watch::Receiver<ServerStatus> {
+    STATUS.subscribe()
+}
+
+/// Stop the running server (if any) and start it again, picking up the
+/// current LAN mode setting.
+pub async fn restart_obs_server() {
+    let _restarting = RESTARTS.lock().await;
+    if held() {
+        log::info!("Safe mode: OBS HTTP server not started");
+        return;
+    }
+    let handle = HANDLE.lock().unwrap().take();
+    if let Some(handle) = handle {
+        STATUS.send_modify(|status| {
+            status.running = false;
+            status.phase = ServerPhase::Stopping;
+        });
+        // Not graceful: open widget sockets would hold the old server for the
+        // whole shutdown timeout, and pages reconnect on their own
+        handle.stop(false).await;
+    }
+    start();
+}
+
+/// Start (or restart) the server on request, ending safe mode's hold.
+pub async fn start_by_hand() {
+    STATUS.send_modify(|status| status.safe_mode = false);
+    restart_obs_server().await;
+}
+
+/// Build and bind the server. Kept synchronous so the (non-Send) `HttpServer`
+/// builder never lives across an await in `start_obs_server_async`.
+fn bind_server(host: &str) -> std::io::Result<actix_web::dev::Server> {
     let server = HttpServer::new(|| {
         let cors = Cors::default().allow_any_origin().allow_any_method().allow_any_header();
-        App::new().wrap(cors).service(serve_widget).service(api_list_workspaces)
+        // Request log: method/path, status, widget id (when routed to one) and timing.
+        // The change feed and src refreshes log themselves, since their URLs may carry the token
+        let logger = Logger::new(r#"%a "%r" %s widget=%{widget}xo %Dms"#)
+            .log_target("obs_server")
+            .exclude("/ws/events")
+            .exclude_regex("^/api/widget/[^/]+/element/[^/]+/refresh$")
+            .custom_response_replace("widget", |res| {
+                res.request().match_info().get("id").unwrap_or("-").to_string()
+            });
+        App::new()
+            .wrap(from_fn(rate_limit_widgets))
+            .wrap(from_fn(cache_headers))
+            .wrap(cors)
+            .wrap(logger)
+            .service(serve_index)
+            .service(serve_workspace_audio)
+            .service(serve_widget)
+            .service(serve_widget_by_slug)
+            .service(serve_widget_css)
+            .service(serve_widget_manifest)
+            .service(widget_ws)
+            .service(widget_events_ws)
+            .service(db_events_ws)
+            .service(api_trigger_widget)
+            .service(api_trigger_widgets)
+            .service(api_clear_queue)
+            .service(api_refresh_src)
+            .service(api_hide_widget)
+            .service(api_widget_segment)
+            .service(api_inbox)
+            .service(api_widget_error)
+            .service(api_list_widgets)
+            .service(api_list_workspaces)
+            .service(api_prewarm)
+            .service(api_metrics)
     })
-    .bind(("127.0.0.1", OBS_HTTP_PORT))
-    .expect("Failed to bind OBS HTTP server")
-    .run();
+    .bind((host, OBS_HTTP_PORT))?;
+    Ok(server.run())
+}
+
+pub async fn start_obs_server_async() {
+    let _starting = RESTARTS.lock().await;
+    start();
+}
+
+/// Bind the server and run it in the background, publishing each step.
+fn start() {
+    let lan_enabled = settings::lan_enabled();
+    let host = if lan_enabled { "0.0.0.0" } else { "127.0.0.1" };
+    configure_rate_limit(settings::rate_limit());
+    STATUS.send_modify(|status| status.phase = ServerPhase::Starting);
+
+    let server = match bind_server(host) {
+        Ok(server) => server,
+        Err(e) => {
+            log::error!("Failed to bind OBS HTTP server on {host}:{OBS_HTTP_PORT}: {e}");
+            STATUS.send_replace(ServerStatus {
+                running: false,
+                phase: ServerPhase::Stopped,
+                bound_addr: None,
+                port: OBS_HTTP_PORT,
+                lan_enabled,
+                started_at: None,
+                last_error: Some(format!("Could not bind {host}:{OBS_HTTP_PORT}: {e}")),
+                safe_mode: false,
+            });
+            return;
+        }
+    };
+
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    *HANDLE.lock().unwrap() = Some(server.handle());
+    log::info!("OBS HTTP server listening on {host}:{OBS_HTTP_PORT}");
+    STATUS.send_replace(ServerStatus {
+        running: true,
+        phase: ServerPhase::Running,
+        bound_addr: Some(format!("{host}:{OBS_HTTP_PORT}")),
+        port: OBS_HTTP_PORT,
+        lan_enabled,
+        started_at: Some(control::now_ms()),
+        last_error: None,
+        safe_mode: false,
+    });
+
+    tokio::spawn(async move {
+        let result = server.await;
+        if let Err(e) = &result {
+            log::error!("OBS HTTP server error: {e}");
+        }
+        // Stopped for a restart: the newer server's status stands
+        if GENERATION.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        STATUS.send_modify(|status| {
+            status.running = false;
+            status.phase = ServerPhase::Stopped;
+            status.bound_addr = None;
+            status.started_at = None;
+            if let Err(e) = &result {
+                status.last_error = Some(e.to_string());
+            }
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn widget_stylesheet_is_immutable_only_at_its_current_version() {
+        let current = format!("v={}", *WIDGET_CSS_VERSION);
+        assert_eq!(cache_control_for("/static/widget.css", &current, true), Some(IMMUTABLE));
+        assert_eq!(cache_control_for("/static/widget.css", &format!("x=1&{current}"), true), Some(IMMUTABLE));
+        assert_eq!(cache_control_for("/static/widget.css", "v=0123456789abcdef", true), Some(NO_CACHE));
+        assert_eq!(cache_control_for("/static/widget.css", "", true), Some(NO_CACHE));
+        assert_eq!(cache_control_for("/static/widget.css", &current, false), Some(NO_CACHE));
+        assert_eq!(cache_control_for("/static/other.css", &current, false), Some(NO_CACHE));
+    }
+
+    #[test]
+    fn workspace_audio_track_counts_as_a_usage() {
+        let summary = db::OverlaySummary { id: "o".into(), name: "Stream".into(), updated_at: String::new() };
+        let config = serde_json::json!({ "audioTrack": " file:///music/loop.mp3 ", "widgets": [] });
+        let usage = audio_track_usage(&summary, &config, "/music/loop.mp3").expect("the track plays it");
+        assert_eq!((usage.overlay_id.as_str(), usage.field.as_str(), usage.widget_id.as_str()), ("o", "audioTrack", ""));
+        assert!(audio_track_usage(&summary, &config, "/music/other.mp3").is_none());
+        assert!(audio_track_usage(&summary, &serde_json::json!({ "widgets": [] }), "/music/loop.mp3").is_none());
+    }
 
-    if let Err(e) = server.await {
-        eprintln!("OBS HTTP server error: {e}");
+    #[test]
+    fn widget_pages_are_never_stored() {
+        assert_eq!(cache_control_for("/widget/abc", "", true), Some(NO_STORE));
+        assert_eq!(cache_control_for("/w/scoreboard", "v=1", true), Some(NO_STORE));
+        assert_eq!(cache_control_for("/api/widgets", "", true), None);
     }
 }
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// ---------------------------------------------------------------------------
+// Server-wide `autoplay`/`loop` defaults for timelines that don't set their own
+// ---------------------------------------------------------------------------
+
+/// `None` leaves a timeline that omits the flag at the page's own default (off).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlaybackDefaults {
+    pub autoplay: Option<bool>,
+    #[serde(rename = "loop")]
+    pub looping: Option<bool>,
+}
+
+impl PlaybackDefaults {
+    /// Fill in the flags the widget's timeline leaves out; values it sets (even
+    /// `false`) win. Widgets without a timeline are left alone.
+    pub fn apply(&self, widget: &mut Value) {
+        let Some(timeline) = widget.get_mut("animationTimeline").and_then(|t| t.as_object_mut()) else {
+            return;
+        };
+        for (key, default) in [("autoplay", self.autoplay), ("loop", self.looping)] {
+            if let Some(default) = default {
+                if timeline.get(key).map_or(true, Value::is_null) {
+                    timeline.insert(key.to_string(), Value::Bool(default));
+                }
+            }
+        }
+    }
+}
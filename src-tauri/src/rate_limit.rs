@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+// ---------------------------------------------------------------------------
+// Per-client-IP limit on widget requests (page loads, reload polls, sockets),
+// so a source stuck in a reload loop can't swamp the server on weak hardware
+// ---------------------------------------------------------------------------
+
+const WINDOW: Duration = Duration::from_secs(60);
+/// Forget idle clients once this many are tracked.
+const MAX_TRACKED_CLIENTS: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimit {
+    /// Widget requests allowed per client IP per minute; `0` turns the limit off.
+    /// Every OBS source on one PC shares an IP and polls every 2s (30/min), so
+    /// the default leaves room for about 40 sources.
+    pub requests_per_minute: u32,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self { requests_per_minute: 1_200 }
+    }
+}
+
+struct Window {
+    started: Instant,
+    count: u32,
+    refused: bool,
+}
+
+/// A request over the limit.
+pub struct Refused {
+    /// Seconds until the client's window resets (for `Retry-After`).
+    pub retry_after: u64,
+    /// First refusal in this window, so it's logged once rather than per request.
+    pub first: bool,
+}
+
+/// Fixed one-minute windows per client IP.
+pub struct RateLimiter {
+    limit: RateLimit,
+    windows: HashMap<IpAddr, Window>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        Self { limit, windows: HashMap::new() }
+    }
+
+    /// Count a request from `ip`, refusing it once the client is over the limit.
+    pub fn check(&mut self, ip: IpAddr, now: Instant) -> Result<(), Refused> {
+        if self.limit.requests_per_minute == 0 {
+            return Ok(());
+        }
+        if self.windows.len() >= MAX_TRACKED_CLIENTS && !self.windows.contains_key(&ip) {
+            self.windows.retain(|_, w| now.duration_since(w.started) < WINDOW);
+        }
+
+        let window = self.windows.entry(ip).or_insert(Window { started: now, count: 0, refused: false });
+        if now.duration_since(window.started) >= WINDOW {
+            *window = Window { started: now, count: 0, refused: false };
+        }
+        if window.count >= self.limit.requests_per_minute {
+            let remaining = WINDOW.saturating_sub(now.duration_since(window.started));
+            let first = !window.refused;
+            window.refused = true;
+            return Err(Refused { retry_after: remaining.as_secs().max(1), first });
+        }
+        window.count += 1;
+        Ok(())
+    }
+}
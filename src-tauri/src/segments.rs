@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+
+use crate::control;
+
+// ---------------------------------------------------------------------------
+// Timeline segments: named sub-timelines (`animationTimeline.segments`) with
+// their own keyframes, duration and looping, each on its own clock in the page
+// ---------------------------------------------------------------------------
+
+const MAX_NAME_LEN: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentOp {
+    /// Start from `t` (default 0); `exclusive` stops every other segment first.
+    Play,
+    /// Hold the current pose.
+    Pause,
+    /// Drop the segment's pose, so its elements fall back to the main timeline.
+    Stop,
+    /// Jump to `t`, playing on if it was playing.
+    Seek,
+}
+
+impl SegmentOp {
+    pub fn parse(op: &str) -> Option<Self> {
+        serde_json::from_value(Value::from(op)).ok()
+    }
+}
+
+/// Optional body of a segment request.
+#[derive(Debug, Default, Deserialize)]
+pub struct SegmentArgs {
+    /// Seconds into the segment.
+    #[serde(default)]
+    pub t: Option<f64>,
+    #[serde(default)]
+    pub exclusive: bool,
+}
+
+/// Control message for a widget's pages. Plays get a shared start time, so
+/// pages (and widgets) told together start together. `t` is clamped to
+/// `0..=duration` when the segment's duration is known.
+pub fn message(name: &str, op: SegmentOp, args: &SegmentArgs, duration: Option<f64>) -> Value {
+    let mut msg = serde_json::json!({ "action": "segment", "name": name, "op": op });
+    if let Some(t) = args.t.filter(|t| t.is_finite()) {
+        let end = duration.filter(|d| d.is_finite()).unwrap_or(f64::INFINITY).max(0.0);
+        msg["t"] = Value::from(t.clamp(0.0, end));
+    }
+    if op == SegmentOp::Play {
+        msg["at"] = Value::from(control::start_epoch());
+        msg["exclusive"] = Value::from(args.exclusive);
+    }
+    msg
+}
+
+fn segments(widget: &Value) -> impl Iterator<Item = &Value> {
+    widget.pointer("/animationTimeline/segments").and_then(|s| s.as_array()).into_iter().flatten()
+}
+
+/// Names of a widget's segments, in layering order.
+pub fn names(widget: &Value) -> Vec<String> {
+    segments(widget).filter_map(|s| s.get("name")?.as_str()).map(str::to_string).collect()
+}
+
+/// Length in seconds of the segment called `name`.
+pub fn duration(widget: &Value, name: &str) -> Option<f64> {
+    segments(widget).find(|s| s.get("name").and_then(|v| v.as_str()) == Some(name))?.get("duration")?.as_f64()
+}
+
+/// The main timeline's keyframes (segment `None`) followed by every
+/// segment's, with its name.
+pub fn keyframe_lists(widget: &Value) -> Vec<(Option<&str>, &Vec<Value>)> {
+    let main = widget.pointer("/animationTimeline/keyframes").and_then(|k| k.as_array());
+    let segments = segments(widget).filter_map(|s| {
+        let name = s.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        Some((Some(name), s.get("keyframes")?.as_array()?))
+    });
+    main.map(|k| (None, k)).into_iter().chain(segments).collect()
+}
+
+pub fn keyframe_lists_mut(widget: &mut Value) -> Vec<&mut Vec<Value>> {
+    let Some(timeline) = widget.get_mut("animationTimeline").and_then(|t| t.as_object_mut()) else {
+        return Vec::new();
+    };
+    let mut lists = Vec::new();
+    for (key, value) in timeline.iter_mut() {
+        match (key.as_str(), value) {
+            ("keyframes", Value::Array(keyframes)) => lists.push(keyframes),
+            ("segments", Value::Array(segments)) => {
+                lists.extend(segments.iter_mut().filter_map(|s| s.get_mut("keyframes")?.as_array_mut()))
+            }
+            _ => {}
+        }
+    }
+    lists
+}
+
+/// Every widget's segments need a unique 1-64 character name, a non-negative
+/// duration and a keyframe list; `then` must name another of its segments.
+pub fn validate(config: &Value) -> Result<(), String> {
+    let widgets = config.get("widgets").and_then(|w| w.as_array()).into_iter().flatten();
+    for widget in widgets {
+        let widget_name = widget.get("name").and_then(|v| v.as_str()).unwrap_or("unnamed");
+        let Some(list) = widget.pointer("/animationTimeline/segments").filter(|v| !v.is_null()) else { continue };
+        let Some(list) = list.as_array() else {
+            return Err(format!("Widget '{widget_name}': timeline segments must be a list"));
+        };
+        let mut seen = HashSet::new();
+        for segment in list {
+            let name = segment.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            if name.trim().is_empty() || name.chars().count() > MAX_NAME_LEN {
+                return Err(format!("Widget '{widget_name}': segment names must be 1-{MAX_NAME_LEN} characters"));
+            }
+            if !seen.insert(name) {
+                return Err(format!("Widget '{widget_name}': segment '{name}' is defined twice"));
+            }
+            if !segment.get("duration").and_then(|v| v.as_f64()).is_some_and(|d| d.is_finite() && d >= 0.0) {
+                return Err(format!("Widget '{widget_name}': segment '{name}' needs a duration of 0 seconds or more"));
+            }
+            if !segment.get("keyframes").is_some_and(|k| k.is_array()) {
+                return Err(format!("Widget '{widget_name}': segment '{name}' needs a keyframes list"));
+            }
+        }
+        for segment in list {
+            let name = segment.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            match segment.get("then").filter(|v| !v.is_null()) {
+                None => {}
+                Some(Value::String(next)) if next != name && seen.contains(next.as_str()) => {}
+                Some(next) => {
+                    return Err(format!("Widget '{widget_name}': segment '{name}' is followed by {next}, which isn't another of its segments"));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn widget() -> Value {
+        serde_json::json!({ "animationTimeline": { "segments": [{ "name": "intro", "duration": 2.5, "keyframes": [] }] } })
+    }
+
+    #[test]
+    fn out_of_range_times_are_clamped_to_the_segment() {
+        let duration = duration(&widget(), "intro");
+        assert_eq!(duration, Some(2.5));
+        for (t, want) in [(-5.0, 0.0), (999.0, 2.5), (1.0, 1.0)] {
+            let args = SegmentArgs { t: Some(t), exclusive: false };
+            assert_eq!(message("intro", SegmentOp::Seek, &args, duration)["t"], Value::from(want));
+            assert_eq!(message("intro", SegmentOp::Play, &args, duration)["t"], Value::from(want));
+        }
+        let args = SegmentArgs { t: Some(-1.0), exclusive: false };
+        assert_eq!(message("intro", SegmentOp::Seek, &args, None)["t"], Value::from(0.0));
+    }
+}
@@ -0,0 +1,134 @@
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+
+use crate::db;
+use crate::limits::ConfigLimits;
+use crate::playback::PlaybackDefaults;
+use crate::rate_limit::RateLimit;
+use crate::window_state::WindowState;
+
+// ---------------------------------------------------------------------------
+// Typed accessors over the `settings` table
+// ---------------------------------------------------------------------------
+
+const LAN_ENABLED: &str = "lan_enabled";
+const HOTKEYS: &str = "hotkeys";
+const CONFIG_LIMITS: &str = "config_limits";
+const RATE_LIMIT: &str = "rate_limit";
+const INBOX_TOKEN: &str = "inbox_token";
+const LAST_OVERLAY: &str = "last_overlay";
+const WINDOW_STATE: &str = "window_state";
+const PLAYBACK_DEFAULTS: &str = "playback_defaults";
+
+fn get_bool(key: &str, default: bool) -> bool {
+    match db::get_setting(key) {
+        Ok(Some(v)) => v == "true",
+        Ok(None) => default,
+        Err(e) => {
+            log::error!("Failed to read setting {key}: {e}");
+            default
+        }
+    }
+}
+
+/// LAN mode: the OBS server listens on all interfaces and OBS URLs use this
+/// machine's LAN address, so a second PC can load the browser sources.
+pub fn lan_enabled() -> bool {
+    get_bool(LAN_ENABLED, false)
+}
+
+pub fn set_lan_enabled(enabled: bool) -> rusqlite::Result<()> {
+    db::set_setting(LAN_ENABLED, if enabled { "true" } else { "false" })
+}
+
+/// A JSON-valued setting; missing or malformed values fall back to the default.
+fn get_json<T: DeserializeOwned + Default>(key: &str) -> T {
+    match db::get_setting(key) {
+        Ok(Some(v)) => serde_json::from_str(&v).unwrap_or_else(|e| {
+            log::error!("Ignoring malformed {key} setting: {e}");
+            T::default()
+        }),
+        Ok(None) => T::default(),
+        Err(e) => {
+            log::error!("Failed to read setting {key}: {e}");
+            T::default()
+        }
+    }
+}
+
+/// Global hotkeys: widget id -> accelerator (e.g. `"CmdOrCtrl+Shift+F1"`).
+pub fn hotkeys() -> BTreeMap<String, String> {
+    get_json(HOTKEYS)
+}
+
+pub fn set_hotkeys(hotkeys: &BTreeMap<String, String>) -> rusqlite::Result<()> {
+    let json = serde_json::to_string(hotkeys).expect("string map always serializes");
+    db::set_setting(HOTKEYS, &json)
+}
+
+/// Size/element/nesting bounds enforced when saving overlays.
+pub fn config_limits() -> ConfigLimits {
+    get_json(CONFIG_LIMITS)
+}
+
+pub fn set_config_limits(limits: &ConfigLimits) -> rusqlite::Result<()> {
+    let json = serde_json::to_string(limits).expect("limits always serialize");
+    db::set_setting(CONFIG_LIMITS, &json)
+}
+
+/// Per-client-IP request limit on the OBS server's widget routes.
+pub fn rate_limit() -> RateLimit {
+    get_json(RATE_LIMIT)
+}
+
+pub fn set_rate_limit(limit: &RateLimit) -> rusqlite::Result<()> {
+    let json = serde_json::to_string(limit).expect("rate limit always serializes");
+    db::set_setting(RATE_LIMIT, &json)
+}
+
+/// `autoplay`/`loop` for widget timelines that leave them out.
+pub fn playback_defaults() -> PlaybackDefaults {
+    get_json(PLAYBACK_DEFAULTS)
+}
+
+pub fn set_playback_defaults(defaults: &PlaybackDefaults) -> rusqlite::Result<()> {
+    let json = serde_json::to_string(defaults).expect("playback defaults always serialize");
+    db::set_setting(PLAYBACK_DEFAULTS, &json)
+}
+
+/// Shared secret chat bots send to `POST /inbox`; created on first use.
+pub fn inbox_token() -> rusqlite::Result<String> {
+    match db::get_setting(INBOX_TOKEN)? {
+        Some(token) => Ok(token),
+        None => regenerate_inbox_token(),
+    }
+}
+
+/// Replace the inbox token, locking out every bot holding the old one.
+pub fn regenerate_inbox_token() -> rusqlite::Result<String> {
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    db::set_setting(INBOX_TOKEN, &token)?;
+    Ok(token)
+}
+
+/// The overlay that was open in the editor, which may have been deleted since.
+pub fn last_overlay() -> Option<String> {
+    db::get_setting(LAST_OVERLAY).unwrap_or_else(|e| {
+        log::error!("Failed to read setting {LAST_OVERLAY}: {e}");
+        None
+    })
+}
+
+pub fn set_last_overlay(id: &str) -> rusqlite::Result<()> {
+    db::set_setting(LAST_OVERLAY, id)
+}
+
+/// Editor window geometry from the last run.
+pub fn window_state() -> Option<WindowState> {
+    get_json(WINDOW_STATE)
+}
+
+pub fn set_window_state(state: &WindowState) -> rusqlite::Result<()> {
+    let json = serde_json::to_string(state).expect("window state always serializes");
+    db::set_setting(WINDOW_STATE, &json)
+}
@@ -0,0 +1,236 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::error::CommandError;
+use crate::{autosave, control, db};
+
+// ---------------------------------------------------------------------------
+// Forced refresh of one element's remote image or video. A `srcNonce` saved on
+// the element is appended to its `src` as `?v=`, so OBS fetches the URL again
+// (e.g. "now playing" album art served at a fixed address) without a page reload
+// ---------------------------------------------------------------------------
+
+/// Tries at bumping a nonce while other saves keep landing on the workspace.
+const SAVE_ATTEMPTS: usize = 3;
+
+#[derive(Debug, Serialize)]
+pub struct SrcRefresh {
+    pub overlay_id: String,
+    pub nonce: u64,
+    /// The `src` open pages were sent, nonce included.
+    pub src: String,
+    /// Open pages of the widget that got it.
+    pub delivered: usize,
+}
+
+fn is_remote(src: &str) -> bool {
+    src.starts_with("http://") || src.starts_with("https://")
+}
+
+/// `src` with `v=<nonce>` added to its query (before any `#fragment`).
+pub fn with_nonce(src: &str, nonce: u64) -> String {
+    let (url, fragment) = src.split_once('#').map_or((src, None), |(u, f)| (u, Some(f)));
+    let separator = if url.contains('?') { '&' } else { '?' };
+    match fragment {
+        Some(fragment) => format!("{url}{separator}v={nonce}#{fragment}"),
+        None => format!("{url}{separator}v={nonce}"),
+    }
+}
+
+fn nonce_of(element: &Value) -> Option<u64> {
+    element.get("srcNonce").and_then(|n| n.as_u64()).filter(|n| *n > 0)
+}
+
+/// Give every image and video with a remote `src` and a `srcNonce` its
+/// cache-busted URL, group children included. Other sources are left alone.
+pub fn apply(elements: &mut Value) {
+    for element in elements.as_array_mut().into_iter().flatten() {
+        let media = matches!(element.get("type").and_then(|t| t.as_str()), Some("image" | "video"));
+        if let (true, Some(nonce)) = (media, nonce_of(element)) {
+            if let Some(Value::String(src)) = element.get_mut("src").filter(|s| s.as_str().is_some_and(is_remote)) {
+                *src = with_nonce(src, nonce);
+            }
+        }
+        if let Some(children) = element.get_mut("children") {
+            apply(children);
+        }
+    }
+}
+
+/// A copy of `widget` without its elements' nonces, for the hash open pages
+/// compare: a bump swaps the asset in place instead of reloading the page.
+pub fn without_nonces(widget: &Value) -> Value {
+    fn strip(elements: &mut Value) {
+        for element in elements.as_array_mut().into_iter().flatten() {
+            if let Some(obj) = element.as_object_mut() {
+                obj.remove("srcNonce");
+            }
+            if let Some(children) = element.get_mut("children") {
+                strip(children);
+            }
+        }
+    }
+    let mut widget = widget.clone();
+    if let Some(elements) = widget.get_mut("elements") {
+        strip(elements);
+    }
+    widget
+}
+
+/// `incoming` with each element's `srcNonce` raised to the one stored for it
+/// (same widget and element id), so a save from a copy loaded before a bump
+/// doesn't bring the stale asset back. `None` when nothing needed raising.
+pub fn keep_nonces(stored: &str, incoming: &str) -> Option<String> {
+    fn collect(widget_id: &str, elements: &Value, nonces: &mut HashMap<(String, String), u64>) {
+        for element in elements.as_array().into_iter().flatten() {
+            if let (Some(id), Some(nonce)) = (element.get("id").and_then(|v| v.as_str()), nonce_of(element)) {
+                nonces.insert((widget_id.to_string(), id.to_string()), nonce);
+            }
+            if let Some(children) = element.get("children") {
+                collect(widget_id, children, nonces);
+            }
+        }
+    }
+    fn raise(widget_id: &str, elements: &mut Value, nonces: &HashMap<(String, String), u64>) -> bool {
+        let mut raised = false;
+        for element in elements.as_array_mut().into_iter().flatten() {
+            let id = element.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string();
+            if let Some(&nonce) = nonces.get(&(widget_id.to_string(), id)) {
+                if nonce_of(element).unwrap_or(0) < nonce {
+                    element["srcNonce"] = nonce.into();
+                    raised = true;
+                }
+            }
+            if let Some(children) = element.get_mut("children") {
+                raised |= raise(widget_id, children, nonces);
+            }
+        }
+        raised
+    }
+    fn widgets(config: &mut Value) -> impl Iterator<Item = &mut Value> {
+        config.get_mut("widgets").and_then(|w| w.as_array_mut()).into_iter().flatten()
+    }
+
+    if !stored.contains("srcNonce") {
+        return None;
+    }
+    let mut stored: Value = serde_json::from_str(stored).ok()?;
+    let mut nonces = HashMap::new();
+    for widget in widgets(&mut stored) {
+        let widget_id = widget.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string();
+        if let Some(elements) = widget.get("elements") {
+            collect(&widget_id, elements, &mut nonces);
+        }
+    }
+    if nonces.is_empty() {
+        return None;
+    }
+    let mut incoming: Value = serde_json::from_str(incoming).ok()?;
+    let mut raised = false;
+    for widget in widgets(&mut incoming) {
+        let widget_id = widget.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string();
+        if let Some(elements) = widget.get_mut("elements") {
+            raised |= raise(&widget_id, elements, &nonces);
+        }
+    }
+    raised.then(|| incoming.to_string())
+}
+
+fn find_element<'a>(elements: &'a mut Value, id: &str) -> Option<&'a mut Value> {
+    for element in elements.as_array_mut()? {
+        if element.get("id").and_then(|v| v.as_str()) == Some(id) {
+            return Some(element);
+        }
+        if let Some(found) = element.get_mut("children").and_then(|c| find_element(c, id)) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+/// Bump the `srcNonce` of element `element_id` in widget `widget_id`, save it
+/// and send the widget's open pages the new `src` to load in place. Like
+/// serving, a widget in several workspaces means the most recently updated one.
+/// The bump keeps the workspace's `updated_at`, and later saves carry the
+/// nonce over, so editors with the workspace open can keep saving.
+pub fn refresh(widget_id: &str, element_id: &str) -> Result<SrcRefresh, CommandError> {
+    for _ in 0..SAVE_ATTEMPTS {
+        let (row, mut config) = workspace_with(widget_id)?;
+        // A pending autosave lands first, so the bump builds on it
+        if autosave::flush(&row.id) > 0 {
+            continue;
+        }
+        let widget = config
+            .get_mut("widgets")
+            .and_then(|w| w.as_array_mut())
+            .and_then(|widgets| widgets.iter_mut().find(|w| w.get("id").and_then(|v| v.as_str()) == Some(widget_id)))
+            .ok_or_else(|| CommandError::not_found(format!("Widget '{widget_id}' not found")))?;
+        let element = widget
+            .get_mut("elements")
+            .and_then(|e| find_element(e, element_id))
+            .ok_or_else(|| CommandError::not_found(format!("Element '{element_id}' not found in widget '{widget_id}'")))?;
+        let media = matches!(element.get("type").and_then(|t| t.as_str()), Some("image" | "video"));
+        let src = element.get("src").and_then(|s| s.as_str()).unwrap_or("").to_string();
+        if !media || !is_remote(&src) {
+            return Err(CommandError::InvalidConfig {
+                details: format!("Element '{element_id}' isn't an image or video with an http(s) src"),
+            });
+        }
+        let nonce = nonce_of(element).unwrap_or(0) + 1;
+        element["srcNonce"] = nonce.into();
+
+        let config_str = serde_json::to_string(&config).map_err(CommandError::other)?;
+        match db::refresh_config_if_unchanged(&row.id, &row.config, &config_str)? {
+            Some(db::SaveOutcome::Saved { .. }) => {
+                let src = with_nonce(&src, nonce);
+                let delivered = control::send(
+                    widget_id,
+                    serde_json::json!({ "action": "refresh_src", "element": element_id, "src": src }),
+                );
+                log::info!("Refreshed the src of element {element_id} in widget {widget_id} (v={nonce}, {delivered} page(s))");
+                return Ok(SrcRefresh { overlay_id: row.id, nonce, src, delivered });
+            }
+            Some(db::SaveOutcome::Conflict { .. }) | None => continue,
+        }
+    }
+    Err(CommandError::Conflict { message: format!("the workspace holding widget '{widget_id}' kept changing; try again") })
+}
+
+/// The stored workspace containing the widget, most recently updated first.
+fn workspace_with(widget_id: &str) -> Result<(db::OverlayRow, Value), CommandError> {
+    for summary in db::list_overlays()? {
+        let Some(row) = db::get_overlay(&summary.id)? else { continue };
+        let Ok(config) = serde_json::from_str::<Value>(&row.config) else { continue };
+        let has_widget = config
+            .get("widgets")
+            .and_then(|w| w.as_array())
+            .is_some_and(|widgets| widgets.iter().any(|w| w.get("id").and_then(|v| v.as_str()) == Some(widget_id)));
+        if has_widget {
+            return Ok((row, config));
+        }
+    }
+    Err(CommandError::not_found(format!("Widget '{widget_id}' not found")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(nonce: Option<u64>) -> String {
+        let mut art = serde_json::json!({ "id": "art", "type": "image", "src": "https://example.com/art.png" });
+        if let Some(nonce) = nonce {
+            art["srcNonce"] = nonce.into();
+        }
+        serde_json::json!({ "widgets": [{ "id": "w", "elements": [{ "id": "g", "type": "group", "children": [art] }] }] }).to_string()
+    }
+
+    #[test]
+    fn saves_keep_a_newer_stored_nonce() {
+        let kept = keep_nonces(&config(Some(3)), &config(None)).unwrap();
+        assert_eq!(kept, config(Some(3)));
+        assert_eq!(keep_nonces(&config(Some(3)), &config(Some(1))).unwrap(), config(Some(3)));
+        assert_eq!(keep_nonces(&config(Some(3)), &config(Some(4))), None);
+        assert_eq!(keep_nonces(&config(None), &config(None)), None);
+    }
+}
@@ -0,0 +1,83 @@
+use serde_json::{Map, Value};
+
+// ---------------------------------------------------------------------------
+// Style presets: named property bundles on the workspace (`stylePresets`)
+// that elements pick up through `stylePreset`; the element's own properties win
+// ---------------------------------------------------------------------------
+
+/// Properties a preset can't carry: they identify an element or its place in the tree.
+const STRUCTURAL_KEYS: &[&str] = &["id", "type", "children", "stylePreset"];
+const MAX_NAME_LEN: usize = 64;
+
+fn presets(config: &Value) -> Option<&Map<String, Value>> {
+    config.get("stylePresets").and_then(|v| v.as_object())
+}
+
+/// `stylePresets` must map 1-64 character names to property objects.
+pub fn validate(config: &Value) -> Result<(), String> {
+    let Some(value) = config.get("stylePresets").filter(|v| !v.is_null()) else {
+        return Ok(());
+    };
+    let Some(presets) = value.as_object() else {
+        return Err("stylePresets must be an object of named presets".to_string());
+    };
+    for (name, preset) in presets {
+        if name.trim().is_empty() || name.chars().count() > MAX_NAME_LEN {
+            return Err(format!("Style preset names must be 1-{MAX_NAME_LEN} characters ('{name}')"));
+        }
+        if !preset.is_object() {
+            return Err(format!("Style preset '{name}' must be an object of element properties"));
+        }
+    }
+    Ok(())
+}
+
+/// Copy the workspace's presets onto one of its widgets, so the widget alone
+/// renders (and hashes) with them.
+pub fn attach(config: &Value, widget: &mut Value) {
+    if let (Some(presets), Some(widget)) = (presets(config), widget.as_object_mut()) {
+        widget.insert("stylePresets".to_string(), Value::Object(presets.clone()));
+    }
+}
+
+/// The element tree with every `stylePreset` reference merged in, preset first.
+/// Unknown presets are ignored so deleting one never breaks a page.
+pub fn resolve(elements: &Value, presets: Option<&Value>) -> Value {
+    let Some(presets) = presets.and_then(|p| p.as_object()).filter(|p| !p.is_empty()) else {
+        return elements.clone();
+    };
+    resolve_with(elements, presets)
+}
+
+fn resolve_with(elements: &Value, presets: &Map<String, Value>) -> Value {
+    let Some(list) = elements.as_array() else {
+        return elements.clone();
+    };
+    let resolved = list
+        .iter()
+        .map(|element| {
+            let Some(own) = element.as_object() else {
+                return element.clone();
+            };
+            let preset = own.get("stylePreset").and_then(|v| v.as_str()).and_then(|name| presets.get(name));
+            let mut merged = Map::new();
+            if let Some(preset) = preset.and_then(|p| p.as_object()) {
+                for (key, value) in preset {
+                    if !STRUCTURAL_KEYS.contains(&key.as_str()) {
+                        merged.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+            for (key, value) in own {
+                // A cleared property (null) falls back to the preset
+                if value.is_null() && merged.contains_key(key) {
+                    continue;
+                }
+                let value = if key == "children" { resolve_with(value, presets) } else { value.clone() };
+                merged.insert(key.clone(), value);
+            }
+            Value::Object(merged)
+        })
+        .collect();
+    Value::Array(resolved)
+}
@@ -0,0 +1,115 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+
+use crate::{segments, validation};
+
+// ---------------------------------------------------------------------------
+// Timeline consistency: keyframe states must name elements that exist. The
+// page looks states up by element id, so a state left behind by a deleted
+// element (or an id that changed) animates nothing and hides the mistake
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Serialize)]
+pub struct OrphanedState {
+    /// The segment whose keyframe it is; `None` for the main timeline.
+    pub segment: Option<String>,
+    pub keyframe_id: String,
+    pub element_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WidgetTimelineReport {
+    pub widget_id: String,
+    pub widget_name: String,
+    /// Keyframe states naming an id no element in the widget has.
+    pub orphaned_states: Vec<OrphanedState>,
+    /// Elements (group children included) that no keyframe of a widget with
+    /// keyframes mentions; they sit still at their base state.
+    pub unanimated_elements: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct TimelineReport {
+    /// Widgets with orphaned states or unanimated elements; consistent ones are left out.
+    pub widgets: Vec<WidgetTimelineReport>,
+    /// Orphaned states removed by a fix; 0 for a check.
+    pub pruned: usize,
+    /// When the fixed overlay was saved; `None` when nothing needed saving.
+    pub updated_at: Option<String>,
+}
+
+/// Non-empty element ids of a widget, group children included, in document order.
+fn element_ids(widget: &Value) -> Vec<&str> {
+    let mut ids = Vec::new();
+    validation::element_ids(widget.get("elements").unwrap_or(&Value::Null), &mut ids);
+    ids.retain(|id| !id.is_empty());
+    ids
+}
+
+fn check_widget(widget: &Value) -> WidgetTimelineReport {
+    let elements = element_ids(widget);
+    let known: HashSet<&str> = elements.iter().copied().collect();
+    let mut animated = HashSet::new();
+    let mut orphaned_states = Vec::new();
+    let mut any_keyframes = false;
+    for (segment, keyframes) in segments::keyframe_lists(widget) {
+        for keyframe in keyframes {
+            any_keyframes = true;
+            let keyframe_id = keyframe.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string();
+            for id in keyframe.get("elementStates").and_then(|s| s.as_object()).into_iter().flat_map(|s| s.keys()) {
+                if known.contains(id.as_str()) {
+                    animated.insert(id.as_str());
+                } else {
+                    orphaned_states.push(OrphanedState {
+                        segment: segment.map(str::to_string),
+                        keyframe_id: keyframe_id.clone(),
+                        element_id: id.clone(),
+                    });
+                }
+            }
+        }
+    }
+    let unanimated_elements = if any_keyframes {
+        let mut seen = HashSet::new();
+        elements.iter().filter(|id| !animated.contains(*id) && seen.insert(**id)).map(|id| id.to_string()).collect()
+    } else {
+        Vec::new()
+    };
+    WidgetTimelineReport {
+        widget_id: widget.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+        widget_name: widget.get("name").and_then(|v| v.as_str()).unwrap_or("unnamed").to_string(),
+        orphaned_states,
+        unanimated_elements,
+    }
+}
+
+/// Report every widget of `config` whose timeline names missing elements or
+/// leaves elements out.
+pub fn check(config: &Value) -> TimelineReport {
+    let widgets = config.get("widgets").and_then(|w| w.as_array()).into_iter().flatten();
+    TimelineReport {
+        widgets: widgets
+            .map(check_widget)
+            .filter(|w| !w.orphaned_states.is_empty() || !w.unanimated_elements.is_empty())
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// Remove the orphaned states [`check`] finds, in place. Keyframes left with
+/// no states stay, so the timeline keeps its timing. Unanimated elements are
+/// only reported; sitting still is often what's meant.
+pub fn prune(config: &mut Value) -> TimelineReport {
+    let mut report = check(config);
+    for widget in config.get_mut("widgets").and_then(|w| w.as_array_mut()).into_iter().flatten() {
+        let known: HashSet<String> = element_ids(widget).into_iter().map(str::to_string).collect();
+        for keyframe in segments::keyframe_lists_mut(widget).into_iter().flatten() {
+            let Some(states) = keyframe.get_mut("elementStates").and_then(|s| s.as_object_mut()) else { continue };
+            let before = states.len();
+            states.retain(|id, _| known.contains(id));
+            report.pruned += before - states.len();
+        }
+    }
+    report
+}
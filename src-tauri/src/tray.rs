@@ -0,0 +1,160 @@
+use tauri::image::Image;
+use tauri::menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::{TrayIcon, TrayIconBuilder};
+use tauri::{AppHandle, Manager, Wry};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::obs_server::{self, ServerPhase, ServerStatus};
+use crate::settings;
+
+const TRAY_ID: &str = "main";
+pub(crate) const MAIN_WINDOW: &str = "main";
+
+const MENU_STATUS: &str = "status";
+const MENU_OPEN: &str = "open";
+const MENU_COPY_URL: &str = "copy_url";
+const MENU_LAN: &str = "lan";
+const MENU_QUIT: &str = "quit";
+
+const COLOR_OK: [u8; 3] = [0x22, 0xc5, 0x5e];
+const COLOR_ERROR: [u8; 3] = [0xef, 0x44, 0x44];
+const COLOR_STOPPED: [u8; 3] = [0x9c, 0xa3, 0xaf];
+
+// ---------------------------------------------------------------------------
+// Status presentation
+// ---------------------------------------------------------------------------
+
+fn status_text(status: &ServerStatus) -> String {
+    let lan = if status.lan_enabled { "LAN on" } else { "LAN off" };
+    match (&status.bound_addr, &status.last_error) {
+        (Some(addr), _) if status.running => format!("Server running on {addr} ({lan})"),
+        _ if matches!(status.phase, ServerPhase::Starting | ServerPhase::Stopping) => format!("Server restarting ({lan})"),
+        (_, Some(err)) => format!("Server failed: {err}"),
+        _ => format!("Server stopped (port {}, {lan})", status.port),
+    }
+}
+
+fn status_color(status: &ServerStatus) -> [u8; 3] {
+    if status.running {
+        COLOR_OK
+    } else if status.last_error.is_some() {
+        COLOR_ERROR
+    } else {
+        COLOR_STOPPED
+    }
+}
+
+/// The app icon with a coloured status dot in the bottom-right corner.
+fn status_icon(base: &Image<'_>, color: [u8; 3]) -> Image<'static> {
+    let (w, h) = (base.width(), base.height());
+    let mut rgba = base.rgba().to_vec();
+    let radius = (w.min(h) as f32) * 0.22;
+    let (cx, cy) = (w as f32 - radius - 1.0, h as f32 - radius - 1.0);
+    for y in 0..h {
+        for x in 0..w {
+            let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+            let dist = (dx * dx + dy * dy).sqrt();
+            if dist > radius {
+                continue;
+            }
+            let i = ((y * w + x) * 4) as usize;
+            // Thin dark ring so the dot reads on both light and dark taskbars
+            let px = if dist > radius - 1.5 { [0x11, 0x11, 0x11] } else { color };
+            rgba[i..i + 4].copy_from_slice(&[px[0], px[1], px[2], 0xff]);
+        }
+    }
+    Image::new_owned(rgba, w, h)
+}
+
+// ---------------------------------------------------------------------------
+// Menu actions
+// ---------------------------------------------------------------------------
+
+/// Bring the editor window back from the tray, minimized or behind other windows.
+pub(crate) fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn copy_base_url(app: &AppHandle) {
+    let url = format!("http://{}/", obs_server::public_host());
+    if let Err(e) = app.clipboard().write_text(url) {
+        log::error!("Failed to copy base URL: {e}");
+    }
+}
+
+fn toggle_lan(lan_item: &CheckMenuItem<Wry>) {
+    let enabled = !settings::lan_enabled();
+    if let Err(e) = settings::set_lan_enabled(enabled) {
+        log::error!("Failed to save LAN mode: {e}");
+        let _ = lan_item.set_checked(!enabled);
+        return;
+    }
+    crate::restart_obs_server();
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent, lan_item: &CheckMenuItem<Wry>) {
+    match event.id().as_ref() {
+        MENU_OPEN => show_main_window(app),
+        MENU_COPY_URL => copy_base_url(app),
+        MENU_LAN => toggle_lan(lan_item),
+        MENU_QUIT => app.exit(0),
+        _ => {}
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Setup
+// ---------------------------------------------------------------------------
+
+fn apply_status(tray: &TrayIcon, status_item: &MenuItem<Wry>, lan_item: &CheckMenuItem<Wry>, base: Option<&Image<'_>>, status: &ServerStatus) {
+    let text = status_text(status);
+    let _ = status_item.set_text(&text);
+    let _ = lan_item.set_checked(status.lan_enabled);
+    let _ = tray.set_tooltip(Some(format!("Open Overlay: {text}")));
+    if let Some(base) = base {
+        let _ = tray.set_icon(Some(status_icon(base, status_color(status))));
+    }
+}
+
+/// Create the tray icon and keep it in sync with the OBS server status.
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let status = obs_server::status();
+    let status_item = MenuItem::with_id(app, MENU_STATUS, status_text(&status), false, None::<&str>)?;
+    let lan_item = CheckMenuItem::with_id(app, MENU_LAN, "LAN mode", true, settings::lan_enabled(), None::<&str>)?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &status_item,
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(app, MENU_OPEN, "Open Open Overlay", true, None::<&str>)?,
+            &MenuItem::with_id(app, MENU_COPY_URL, "Copy base URL", true, None::<&str>)?,
+            &lan_item,
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(app, MENU_QUIT, "Quit", true, None::<&str>)?,
+        ],
+    )?;
+
+    let base_icon = app.default_window_icon().map(|icon| icon.clone().to_owned());
+    let menu_lan_item = lan_item.clone();
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .on_menu_event(move |app, event| on_menu_event(app, event, &menu_lan_item));
+    if let Some(base) = &base_icon {
+        builder = builder.icon(status_icon(base, status_color(&status)));
+    }
+    let tray = builder.build(app)?;
+    apply_status(&tray, &status_item, &lan_item, base_icon.as_ref(), &status);
+
+    let mut rx = obs_server::subscribe_status();
+    tauri::async_runtime::spawn(async move {
+        while rx.changed().await.is_ok() {
+            let status = rx.borrow_and_update().clone();
+            apply_status(&tray, &status_item, &lan_item, base_icon.as_ref(), &status);
+        }
+    });
+    Ok(())
+}
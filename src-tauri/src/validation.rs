@@ -0,0 +1,597 @@
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::{export, segments};
+
+// ---------------------------------------------------------------------------
+// Preflight report for a config before it's imported or saved: every
+// save-time check, plus element problems the page would quietly render around
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Serialize)]
+pub struct Issue {
+    /// The widget and element the issue is about, when it's about one.
+    pub widget_id: Option<String>,
+    pub element_id: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidationReport {
+    /// No errors: the config saves and its widgets render.
+    pub ok: bool,
+    /// Problems a save rejects or that break a widget's page.
+    pub errors: Vec<Issue>,
+    /// Things that render, but probably not as intended (a missing file, an unknown preset).
+    pub warnings: Vec<Issue>,
+}
+
+const ELEMENT_TYPES: &[&str] = &["shape", "text", "image", "video", "path", "group", "mask", "particles", "sprite"];
+const GEOMETRY_KEYS: &[&str] = &["x", "y", "width", "height"];
+
+#[derive(Default)]
+struct Collector {
+    errors: Vec<Issue>,
+    warnings: Vec<Issue>,
+}
+
+impl Collector {
+    fn issue(widget_id: &str, element_id: Option<&str>, message: String) -> Issue {
+        Issue {
+            widget_id: Some(widget_id.to_string()),
+            element_id: element_id.filter(|id| !id.is_empty()).map(str::to_string),
+            message,
+        }
+    }
+
+    fn error(&mut self, widget_id: &str, element_id: Option<&str>, message: String) {
+        self.errors.push(Self::issue(widget_id, element_id, message));
+    }
+
+    fn warn(&mut self, widget_id: &str, element_id: Option<&str>, message: String) {
+        self.warnings.push(Self::issue(widget_id, element_id, message));
+    }
+}
+
+/// Why the page can't load `reference`, if it can't: a local file that doesn't
+/// exist, or something that's neither a URL nor an absolute path.
+fn unresolvable_asset(reference: &str) -> Option<String> {
+    if reference.is_empty() || ["http://", "https://", "data:"].iter().any(|p| reference.starts_with(p)) {
+        return None;
+    }
+    match export::local_asset_path(reference) {
+        Some(path) if path.is_file() => None,
+        Some(path) => Some(format!("File '{}' doesn't exist", path.display())),
+        None => Some(format!("'{reference}' is neither a URL nor an absolute file path")),
+    }
+}
+
+fn asset_references(element: &Value) -> Vec<&str> {
+    export::element_assets(element).into_iter().map(|(_, reference)| reference).collect()
+}
+
+/// `0`, or a decimal number of `%` or `px`: the lengths the page's pivot and
+/// clip polygon checks accept.
+fn is_css_length(value: &str) -> bool {
+    let digits = |d: &str| !d.is_empty() && d.chars().all(|c| c.is_ascii_digit());
+    let number = |n: &str| {
+        let n = n.strip_prefix('-').unwrap_or(n);
+        let (int, frac) = n.split_once('.').unwrap_or((n, "0"));
+        digits(int) && digits(frac)
+    };
+    let value = value.to_ascii_lowercase();
+    value == "0" || value.strip_suffix('%').or_else(|| value.strip_suffix("px")).is_some_and(number)
+}
+
+/// What the page accepts as a rotation pivot: one or two keywords, `x% y%` or
+/// px lengths. Anything else renders around the center.
+fn valid_transform_origin(value: &str) -> bool {
+    let part = |p: &str| ["left", "center", "right", "top", "bottom"].contains(&p.to_ascii_lowercase().as_str()) || is_css_length(p);
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    (1..=2).contains(&parts.len()) && parts.iter().all(|p| part(p))
+}
+
+/// `clipPoints` for a polygon clip: three or more comma-separated `x y` pairs
+/// of lengths, e.g. `0% 0%, 100% 0%, 50% 100%`.
+fn valid_clip_points(value: &str) -> bool {
+    let points: Vec<&str> = value.split(',').collect();
+    points.len() >= 3
+        && points.iter().all(|point| {
+            let parts: Vec<&str> = point.split_whitespace().collect();
+            parts.len() == 2 && parts.iter().all(|p| is_css_length(p))
+        })
+}
+
+fn check_elements<'a>(
+    elements: &'a Value,
+    widget_id: &str,
+    presets: Option<&Value>,
+    seen: &mut HashSet<&'a str>,
+    out: &mut Collector,
+) {
+    for element in elements.as_array().into_iter().flatten() {
+        let id = element.get("id").and_then(|v| v.as_str()).filter(|id| !id.is_empty());
+        let name = element.get("name").and_then(|v| v.as_str()).unwrap_or("unnamed");
+        match id {
+            None => out.error(widget_id, None, format!("Element '{name}' has no id")),
+            Some(id) if !seen.insert(id) => out.error(widget_id, Some(id), format!("Element id '{id}' is used twice")),
+            Some(_) => {}
+        }
+        for key in GEOMETRY_KEYS {
+            if !element.get(*key).and_then(|v| v.as_f64()).is_some_and(f64::is_finite) {
+                out.error(widget_id, id, format!("Element '{name}' needs a numeric {key}"));
+            }
+        }
+        if ["width", "height"].iter().any(|key| element.get(*key).and_then(|v| v.as_f64()).is_some_and(|v| v < 0.0)) {
+            out.warn(widget_id, id, format!("Element '{name}' has a negative size and won't show"));
+        }
+        match element.get("type").and_then(|v| v.as_str()) {
+            Some(kind) if ELEMENT_TYPES.contains(&kind) => {}
+            Some(kind) => out.warn(widget_id, id, format!("Element '{name}' has unknown type '{kind}' and renders empty")),
+            None => out.warn(widget_id, id, format!("Element '{name}' has no type and renders empty")),
+        }
+        if let Some(preset) = element.get("stylePreset").and_then(|v| v.as_str()) {
+            if presets.and_then(|p| p.get(preset)).is_none() {
+                out.warn(widget_id, id, format!("Element '{name}' uses style preset '{preset}', which doesn't exist"));
+            }
+        }
+        if let Some(origin) = element.get("transformOrigin").filter(|v| !v.is_null()) {
+            if !origin.as_str().is_some_and(valid_transform_origin) {
+                out.warn(widget_id, id, format!("Element '{name}' has transformOrigin {origin}, which isn't keywords or `x% y%`; it rotates around its center"));
+            }
+        }
+        if let Some(shape) = element.get("clipShape").filter(|v| !v.is_null()) {
+            match shape.as_str() {
+                Some("inset" | "circle" | "ellipse") => {}
+                Some("polygon") if element.get("clipPoints").and_then(|v| v.as_str()).is_some_and(valid_clip_points) => {}
+                Some("polygon") => out.warn(widget_id, id, format!("Element '{name}' has a polygon clip without three or more `x y` clipPoints; it isn't clipped")),
+                _ => out.warn(widget_id, id, format!("Element '{name}' has unknown clipShape {shape}; it isn't clipped")),
+            }
+        }
+        if element.get("tint").is_some_and(|v| !v.is_null()) {
+            if !matches!(element.get("type").and_then(|v| v.as_str()), Some("image" | "video")) {
+                out.warn(widget_id, id, format!("Element '{name}' has a tint, which only images and videos show"));
+            }
+            if let Some(blend) = element.get("tintBlend").filter(|v| !v.is_null()) {
+                if !blend.as_str().is_some_and(|b| crate::obs_server::BLEND_MODES.contains(&b)) {
+                    out.warn(widget_id, id, format!("Element '{name}' has unknown tintBlend {blend}; the tint covers it unblended"));
+                }
+            }
+        }
+        if element.get("autoContrast").and_then(|v| v.as_bool()) == Some(true)
+            && element.get("type").and_then(|v| v.as_str()) != Some("text")
+        {
+            out.warn(widget_id, id, format!("Element '{name}' has autoContrast, which only text uses"));
+        }
+        if let Some(backdrop) = element.get("backdropColor").and_then(|v| v.as_str()).filter(|c| !c.trim().is_empty()) {
+            if let Err(e) = crate::color::parse_background(backdrop) {
+                out.warn(widget_id, id, format!("Element '{name}' backdropColor: {e}"));
+            }
+        }
+        if element.get("fitChildren").and_then(|v| v.as_bool()) == Some(true)
+            && !matches!(element.get("type").and_then(|v| v.as_str()), Some("group" | "mask"))
+        {
+            out.warn(widget_id, id, format!("Element '{name}' has fitChildren, which only groups use"));
+        }
+        for reference in asset_references(element) {
+            if let Some(problem) = unresolvable_asset(reference) {
+                out.warn(widget_id, id, format!("Element '{name}': {problem}"));
+            }
+        }
+        if let Some(children) = element.get("children") {
+            check_elements(children, widget_id, presets, seen, out);
+        }
+    }
+}
+
+/// Build the report from the save-time check failures (`save_errors`) and
+/// this module's element checks. Reads asset files' existence, writes nothing.
+pub fn report(config: &Value, save_errors: Vec<String>) -> ValidationReport {
+    let mut out = Collector {
+        errors: save_errors
+            .into_iter()
+            .map(|message| Issue { widget_id: None, element_id: None, message })
+            .collect(),
+        ..Default::default()
+    };
+    if !config.get("widgets").is_some_and(|w| w.is_array()) {
+        out.errors.push(Issue { widget_id: None, element_id: None, message: "Config has no widgets list".to_string() });
+    }
+    if let Some(problem) = config.get("audioTrack").and_then(|v| v.as_str()).and_then(unresolvable_asset) {
+        out.warnings.push(Issue { widget_id: None, element_id: None, message: format!("Audio track: {problem}") });
+    }
+    let presets = config.get("stylePresets");
+    let mut widget_ids = HashSet::new();
+    for widget in config.get("widgets").and_then(|w| w.as_array()).into_iter().flatten() {
+        let name = widget.get("name").and_then(|v| v.as_str()).unwrap_or("unnamed");
+        let Some(widget_id) = widget.get("id").and_then(|v| v.as_str()).filter(|id| !id.is_empty()) else {
+            out.errors.push(Issue { widget_id: None, element_id: None, message: format!("Widget '{name}' has no id") });
+            continue;
+        };
+        if !widget_ids.insert(widget_id) {
+            out.error(widget_id, None, format!("Widget id '{widget_id}' is used twice"));
+        }
+        if let Some(problem) = widget.pointer("/background/src").and_then(|v| v.as_str()).and_then(unresolvable_asset) {
+            out.warn(widget_id, None, format!("Widget '{name}' background image: {problem}"));
+        }
+        let mut seen = HashSet::new();
+        check_elements(widget.get("elements").unwrap_or(&Value::Null), widget_id, presets, &mut seen, &mut out);
+    }
+    ValidationReport { ok: out.errors.is_empty(), errors: out.errors, warnings: out.warnings }
+}
+
+// ---------------------------------------------------------------------------
+// Test render: build a widget's page here and look for the usual reasons it
+// comes out blank on stream, without a browser
+// ---------------------------------------------------------------------------
+
+/// Pages past this are slow for OBS to load and parse (usually big inlined data URIs).
+const MAX_HTML_BYTES: usize = 5 * 1024 * 1024;
+const GENERIC_FONTS: &[&str] =
+    &["serif", "sans-serif", "monospace", "cursive", "fantasy", "system-ui", "ui-sans-serif", "ui-serif", "ui-monospace"];
+
+#[derive(Debug, Serialize)]
+pub struct RenderReport {
+    pub widget_id: String,
+    /// Size of the rendered page.
+    pub html_bytes: usize,
+    pub render_ms: f64,
+    /// No errors: the page builds and has something to show.
+    pub ok: bool,
+    pub errors: Vec<Issue>,
+    /// Likely causes of a blank or wrong-looking source (missing files or fonts, ...).
+    pub warnings: Vec<Issue>,
+}
+
+pub(crate) fn font_key(name: &str) -> String {
+    name.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_ascii_lowercase()
+}
+
+/// Folders this OS keeps installed fonts in.
+fn font_dirs() -> Vec<std::path::PathBuf> {
+    let env = |key: &str| std::env::var_os(key).map(std::path::PathBuf::from);
+    let mut dirs = Vec::new();
+    if cfg!(windows) {
+        dirs.extend(env("WINDIR").map(|d| d.join("Fonts")));
+        dirs.extend(env("LOCALAPPDATA").map(|d| d.join("Microsoft").join("Windows").join("Fonts")));
+    } else if cfg!(target_os = "macos") {
+        dirs.extend(["/System/Library/Fonts", "/Library/Fonts"].map(std::path::PathBuf::from));
+        dirs.extend(env("HOME").map(|d| d.join("Library").join("Fonts")));
+    } else {
+        dirs.extend(["/usr/share/fonts", "/usr/local/share/fonts"].map(std::path::PathBuf::from));
+        dirs.extend(env("HOME").into_iter().flat_map(|d| [d.join(".local/share/fonts"), d.join(".fonts")]));
+    }
+    dirs
+}
+
+/// How long a scan of the font folders is reused: pages check their fonts on
+/// every render, and fonts are rarely installed.
+const FONT_SCAN_TTL: Duration = Duration::from_secs(60);
+
+type FontScan = (Instant, Arc<Vec<std::path::PathBuf>>);
+
+static INSTALLED_FONTS: Lazy<Mutex<Option<FontScan>>> = Lazy::new(|| Mutex::new(None));
+
+/// The installed font files, from a scan at most `FONT_SCAN_TTL` old.
+fn installed_font_paths() -> Arc<Vec<std::path::PathBuf>> {
+    let mut cached = INSTALLED_FONTS.lock().unwrap();
+    match cached.as_ref() {
+        Some((scanned, paths)) if scanned.elapsed() < FONT_SCAN_TTL => paths.clone(),
+        _ => {
+            let paths = Arc::new(scan_font_dirs());
+            *cached = Some((Instant::now(), paths.clone()));
+            paths
+        }
+    }
+}
+
+/// Scan the font folders again on next use, for checks right after the user
+/// may have installed or removed fonts.
+pub(crate) fn forget_installed_fonts() {
+    *INSTALLED_FONTS.lock().unwrap() = None;
+}
+
+fn scan_font_dirs() -> Vec<std::path::PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = font_dirs();
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files
+}
+
+fn font_file_key(path: &std::path::Path) -> Option<String> {
+    path.file_name().and_then(|n| n.to_str()).map(font_key)
+}
+
+/// File names of the installed fonts, as `font_key`s (`Inter-Bold.ttf` -> `interboldttf`).
+fn installed_font_files() -> Vec<String> {
+    installed_font_paths().iter().filter_map(|p| font_file_key(p)).collect()
+}
+
+/// A best guess from font file names (families aren't read out of the files),
+/// so it can miss a font installed under an unusual file name.
+fn font_installed(family: &str, installed: &[String]) -> bool {
+    let key = font_key(family);
+    key.is_empty() || installed.iter().any(|file| file.starts_with(&key))
+}
+
+/// The family a `fontFamily` value asks for first, unless it's a generic one.
+pub(crate) fn primary_font(font_family: &str) -> Option<&str> {
+    let first = font_family.split(',').next().unwrap_or("").trim().trim_matches(['"', '\'']);
+    (!first.is_empty() && !GENERIC_FONTS.contains(&first.to_ascii_lowercase().as_str())).then_some(first)
+}
+
+/// Installed files that look like `family` by the same guess as `font_installed`.
+pub(crate) fn font_files(family: &str) -> Vec<std::path::PathBuf> {
+    let key = font_key(family);
+    if key.is_empty() {
+        return Vec::new();
+    }
+    installed_font_paths().iter().filter(|p| font_file_key(p).is_some_and(|file| file.starts_with(&key))).cloned().collect()
+}
+
+/// Whether a font file's name looks like `family`, by the same guess as `font_installed`.
+pub(crate) fn font_file_matches(family: &str, path: &std::path::Path) -> bool {
+    let key = font_key(family);
+    !key.is_empty() && font_file_key(path).is_some_and(|file| file.starts_with(&key))
+}
+
+pub(crate) fn is_font_installed(family: &str) -> bool {
+    font_installed(family, &installed_font_files())
+}
+
+fn collect_fonts<'a>(elements: &'a Value, out: &mut Vec<(&'a str, Option<&'a str>)>) {
+    for element in elements.as_array().into_iter().flatten() {
+        let id = element.get("id").and_then(|v| v.as_str());
+        let segment_fonts = element.get("segments").and_then(|v| v.as_array()).into_iter().flatten();
+        for font in std::iter::once(element).chain(segment_fonts).filter_map(|v| v.get("fontFamily")?.as_str()) {
+            out.push((font, id));
+        }
+        if let Some(children) = element.get("children") {
+            collect_fonts(children, out);
+        }
+    }
+}
+
+/// Something a widget's page references that it won't be able to load.
+#[derive(Debug, Clone, Serialize)]
+pub struct MissingAsset {
+    /// `file` for images, videos, sprite frames and the background, `font` for a font family.
+    pub kind: &'static str,
+    /// As the widget has it: a path or `file://` URL, or a family name.
+    pub reference: String,
+    /// The element that uses it; none for the widget background.
+    pub element_id: Option<String>,
+}
+
+fn collect_missing_files(elements: &Value, out: &mut Vec<MissingAsset>) {
+    for element in elements.as_array().into_iter().flatten() {
+        let element_id = element.get("id").and_then(|v| v.as_str()).map(str::to_string);
+        for reference in asset_references(element).into_iter().filter(|r| unresolvable_asset(r).is_some()) {
+            out.push(MissingAsset { kind: "file", reference: reference.to_string(), element_id: element_id.clone() });
+        }
+        if let Some(children) = element.get("children") {
+            collect_missing_files(children, out);
+        }
+    }
+}
+
+/// What `widget`'s page refers to but won't load: local files that don't
+/// exist (or references that are neither URLs nor absolute paths) and fonts
+/// asked for first that don't seem to be installed. Each reference is listed
+/// once, with the first element using it. URLs aren't fetched.
+pub(crate) fn missing_assets(widget: &Value) -> Vec<MissingAsset> {
+    let elements = crate::style_presets::resolve(widget.get("elements").unwrap_or(&Value::Null), widget.get("stylePresets"));
+    let mut missing = Vec::new();
+    let background = widget.get("background").and_then(|b| b.get("src")).and_then(|v| v.as_str()).map(str::trim);
+    if let Some(src) = background.filter(|src| unresolvable_asset(src).is_some()) {
+        missing.push(MissingAsset { kind: "file", reference: src.to_string(), element_id: None });
+    }
+    collect_missing_files(&elements, &mut missing);
+
+    let mut fonts = Vec::new();
+    collect_fonts(&elements, &mut fonts);
+    if !fonts.is_empty() {
+        let installed = installed_font_files();
+        for (font, id) in fonts {
+            let Some(first) = primary_font(font).filter(|first| !font_installed(first, &installed)) else { continue };
+            missing.push(MissingAsset { kind: "font", reference: first.to_string(), element_id: id.map(str::to_string) });
+        }
+    }
+
+    let mut seen = HashSet::new();
+    missing.retain(|asset| seen.insert((asset.kind, asset.reference.clone())));
+    missing
+}
+
+/// Ids of every element in a tree, group children included, in document order.
+pub(crate) fn element_ids<'a>(elements: &'a Value, out: &mut impl Extend<&'a str>) {
+    for element in elements.as_array().into_iter().flatten() {
+        out.extend(element.get("id").and_then(|v| v.as_str()));
+        if let Some(children) = element.get("children") {
+            element_ids(children, out);
+        }
+    }
+}
+
+/// Render `widget` (as served, presets attached) and check what the page
+/// depends on: element problems and asset files as in `report`, installed
+/// fonts, keyframes naming elements that don't exist, elements entirely off
+/// the canvas, the page size and whether it starts hidden.
+pub fn render_report(widget: &Value) -> RenderReport {
+    let widget_id = widget.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string();
+    let started = std::time::Instant::now();
+    let html = crate::obs_server::render_plain_html(widget);
+    let render_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    let mut out = Collector::default();
+    let presets = widget.get("stylePresets");
+    let elements = crate::style_presets::resolve(widget.get("elements").unwrap_or(&Value::Null), presets);
+    let mut seen = HashSet::new();
+    check_elements(&elements, &widget_id, presets, &mut seen, &mut out);
+
+    let top_level = elements.as_array().map(Vec::as_slice).unwrap_or_default();
+    if top_level.is_empty() {
+        out.error(&widget_id, None, "The widget has no elements, so its page is empty".to_string());
+    }
+    if let Some(Err(e)) = widget.get("background").filter(|v| !v.is_null()).map(crate::color::parse_widget_background) {
+        out.warn(&widget_id, None, format!("{e}; the page renders it transparent"));
+    }
+
+    let bounds = |key: &str, default: f64| widget.get(key).and_then(|v| v.as_f64()).unwrap_or(default);
+    let (w, h) = (bounds("width", 400.0), bounds("height", 300.0));
+    for element in top_level {
+        let geometry: Option<Vec<f64>> = GEOMETRY_KEYS.iter().map(|key| element.get(*key)?.as_f64()).collect();
+        if let Some([x, y, width, height]) = geometry.as_deref() {
+            if x >= &w || y >= &h || x + width <= 0.0 || y + height <= 0.0 {
+                let name = element.get("name").and_then(|v| v.as_str()).unwrap_or("unnamed");
+                let id = element.get("id").and_then(|v| v.as_str());
+                out.warn(&widget_id, id, format!("Element '{name}' is entirely outside the {w}×{h} widget"));
+            }
+        }
+    }
+
+    let installed = installed_font_files();
+    let mut fonts = Vec::new();
+    collect_fonts(&elements, &mut fonts);
+    let mut checked = HashSet::new();
+    for (font, id) in fonts {
+        let Some(first) = primary_font(font).filter(|first| checked.insert(*first)) else { continue };
+        if !font_installed(first, &installed) {
+            out.warn(&widget_id, id, format!("Font '{first}' doesn't seem to be installed on this PC; text falls back to another font"));
+        }
+    }
+
+    let mut ids = HashSet::new();
+    element_ids(&elements, &mut ids);
+    let keyframes = segments::keyframe_lists(widget).into_iter().flat_map(|(_, keyframes)| keyframes);
+    let mut missing = HashSet::new();
+    for keyframe in keyframes {
+        for id in keyframe.get("elementStates").and_then(|s| s.as_object()).into_iter().flat_map(|s| s.keys()) {
+            if !ids.contains(id.as_str()) && missing.insert(id.as_str()) {
+                out.warn(&widget_id, Some(id), format!("Keyframes animate element '{id}', which doesn't exist"));
+            }
+        }
+    }
+
+    if let Some(timeline) = widget.get("animationTimeline").filter(|t| t.is_object()) {
+        let hides = timeline.get("hideAfter").and_then(|v| v.as_f64()).is_some_and(|v| v >= 0.0);
+        if hides && !timeline.get("autoplay").and_then(|v| v.as_bool()).unwrap_or(false) {
+            out.warn(&widget_id, None, "The widget starts hidden and only shows when it's triggered".to_string());
+        }
+    }
+    if html.len() > MAX_HTML_BYTES {
+        out.warn(
+            &widget_id,
+            None,
+            format!("The page is {:.1} MB; OBS may take a while to load it (large embedded images?)", html.len() as f64 / 1e6),
+        );
+    }
+
+    RenderReport {
+        widget_id,
+        html_bytes: html.len(),
+        render_ms,
+        ok: out.errors.is_empty(),
+        errors: out.errors,
+        warnings: out.warnings,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Widget profile: the numbers behind a heavy page (element and filter counts,
+// nesting, keyframes, render time) to optimize against
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Default, Serialize)]
+pub struct WidgetProfile {
+    pub widget_id: String,
+    /// Every element, group children included.
+    pub elements: usize,
+    /// Element count per type.
+    pub element_types: BTreeMap<String, usize>,
+    /// Deepest group nesting; 0 when no element is inside a group.
+    pub max_depth: usize,
+    /// Elements with a CSS filter (blur, brightness, contrast, hue or saturation)
+    /// set or animated. GPU-heavy in OBS.
+    pub filtered_elements: usize,
+    /// Of those, the ones that blur: the most expensive filter.
+    pub blurred_elements: usize,
+    /// Keyframes on the main timeline and in every segment.
+    pub keyframes: usize,
+    /// Elements some keyframe animates.
+    pub animated_elements: usize,
+    pub html_bytes: usize,
+    /// Time taken to build the page's HTML.
+    pub render_ms: f64,
+}
+
+const FILTER_KEYS: &[(&str, f64)] = &[("blur", 0.0), ("hueRotate", 0.0), ("brightness", 100.0), ("contrast", 100.0), ("saturate", 100.0)];
+
+/// Filter keys `props` sets to something other than no effect.
+fn filters_set(props: &Value) -> impl Iterator<Item = &'static str> + '_ {
+    FILTER_KEYS.iter().filter(move |(key, none)| props.get(*key).and_then(|v| v.as_f64()).is_some_and(|v| v != *none)).map(|(key, _)| *key)
+}
+
+fn profile_elements(elements: &Value, depth: usize, animated: &HashMap<&str, HashSet<&'static str>>, out: &mut WidgetProfile) {
+    for element in elements.as_array().into_iter().flatten() {
+        out.elements += 1;
+        out.max_depth = out.max_depth.max(depth);
+        let kind = element.get("type").and_then(|v| v.as_str()).unwrap_or("unknown");
+        *out.element_types.entry(kind.to_string()).or_default() += 1;
+        let id = element.get("id").and_then(|v| v.as_str()).unwrap_or("");
+        let mut filters: HashSet<&str> = filters_set(element).collect();
+        filters.extend(animated.get(id).into_iter().flatten());
+        if !filters.is_empty() {
+            out.filtered_elements += 1;
+        }
+        if filters.contains("blur") {
+            out.blurred_elements += 1;
+        }
+        if let Some(children) = element.get("children") {
+            profile_elements(children, depth + 1, animated, out);
+        }
+    }
+}
+
+/// Measure `widget` as served (presets attached): how many elements, filters
+/// and keyframes its page carries, and how long its HTML takes to build.
+pub fn profile_widget(widget: &Value) -> WidgetProfile {
+    let started = std::time::Instant::now();
+    let html = crate::obs_server::render_plain_html(widget);
+    let render_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    // Filters keyframes animate, per element id
+    let mut animated: HashMap<&str, HashSet<&'static str>> = HashMap::new();
+    let mut keyframes = 0;
+    for keyframe in segments::keyframe_lists(widget).into_iter().flat_map(|(_, keyframes)| keyframes) {
+        keyframes += 1;
+        for (id, state) in keyframe.get("elementStates").and_then(|s| s.as_object()).into_iter().flatten() {
+            animated.entry(id.as_str()).or_default().extend(filters_set(state));
+        }
+    }
+
+    let mut out = WidgetProfile {
+        widget_id: widget.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+        keyframes,
+        animated_elements: animated.len(),
+        html_bytes: html.len(),
+        render_ms,
+        ..WidgetProfile::default()
+    };
+    let elements = crate::style_presets::resolve(widget.get("elements").unwrap_or(&Value::Null), widget.get("stylePresets"));
+    profile_elements(&elements, 0, &animated, &mut out);
+    out
+}
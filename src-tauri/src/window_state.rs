@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+use tauri::{PhysicalPosition, PhysicalSize, WebviewWindow};
+
+use crate::settings;
+
+// ---------------------------------------------------------------------------
+// Editor window size/position, saved when it closes and restored on launch
+// ---------------------------------------------------------------------------
+
+/// Physical pixels; the size is the unmaximized inner size.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowState {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub maximized: bool,
+}
+
+/// Remember where the window is. A minimized window keeps the last saved state,
+/// and a maximized one keeps its previous size so unmaximizing still works.
+pub fn save(window: &WebviewWindow) {
+    if window.is_minimized().unwrap_or(false) {
+        return;
+    }
+    let maximized = window.is_maximized().unwrap_or(false);
+    let state = match (maximized, settings::window_state()) {
+        (true, Some(previous)) => WindowState { maximized, ..previous },
+        _ => match (window.outer_position(), window.inner_size()) {
+            (Ok(pos), Ok(size)) => WindowState { x: pos.x, y: pos.y, width: size.width, height: size.height, maximized },
+            (Err(e), _) | (_, Err(e)) => {
+                log::warn!("Couldn't read the window geometry: {e}");
+                return;
+            }
+        },
+    };
+    if let Err(e) = settings::set_window_state(&state) {
+        log::error!("Failed to save window state: {e}");
+    }
+}
+
+/// Put the window back where it was, unless that spot is on a monitor that's
+/// no longer connected (then it stays centred at its saved size).
+pub fn restore(window: &WebviewWindow) {
+    let Some(state) = settings::window_state() else { return };
+    if let Err(e) = window.set_size(PhysicalSize::new(state.width, state.height)) {
+        log::warn!("Couldn't restore the window size: {e}");
+    }
+    let on_screen = window.available_monitors().unwrap_or_default().iter().any(|monitor| {
+        let (pos, size) = (monitor.position(), monitor.size());
+        (pos.x..pos.x + size.width as i32).contains(&state.x) && (pos.y..pos.y + size.height as i32).contains(&state.y)
+    });
+    if on_screen {
+        let _ = window.set_position(PhysicalPosition::new(state.x, state.y));
+    } else {
+        let _ = window.center();
+    }
+    if state.maximized {
+        let _ = window.maximize();
+    }
+}
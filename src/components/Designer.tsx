@@ -6,18 +6,19 @@ import {
   Circle, Triangle, Star, CheckCheck, Copy, Link, Wifi, Layers,
   ChevronRight, ChevronDown, GripVertical, X, Scissors, Monitor,
   Blend, LayoutTemplate, RotateCw, Play, Pause, SkipBack, SkipForward,
-  Repeat, Diamond, Clock, Zap, ChevronsRight, Eraser, Pencil, Hexagon, Octagon
+  Repeat, Diamond, Clock, Zap, ChevronsRight, Eraser, Pencil, Hexagon, Octagon, Film, Scan, Clapperboard, Music
 } from 'lucide-react';
 import { v4 as uuidv4 } from 'uuid';
 import { Rnd } from 'react-rnd';
 import { AnimatePresence, motion } from 'motion/react';
 import {
   WorkspaceConfig, Widget, OverlayElement, ElementType, MaskType,
-  BlendMode, WIDGET_PRESETS, WIDGET_COLORS, WidgetType, GradientDir,
-  GlobalKeyframe, KeyframeProperty, EasingType, AnimationTimeline
+  BlendMode, BLEND_MODES, WIDGET_PRESETS, WIDGET_COLORS, WidgetType, GradientDir,
+  GlobalKeyframe, KeyframeProperty, ClipShape, EasingType, AnimationTimeline, TimelineSegment, DEFAULT_CANVAS,
+  StylePreset, PRESET_STYLE_KEYS, WidgetBackground
 } from '../types';
-import { cn } from '../utils';
-import { listWorkspaces, getWorkspace, saveWorkspace, getWidgetObsUrl } from '../tauriApi';
+import { cn, resolveStylePreset } from '../utils';
+import { listWorkspaces, getWorkspace, getLastWorkspace, setLastWorkspace, saveWorkspace, saveWorkspaceAs, getWidgetObsUrl, getWidgetErrors, clearWidgetErrors, onOverlayChanged, getInboxToken, validateConfig, errorMessage, getServerStatus, onServerStatus, startObsServer, type WidgetError, type ServerStatus } from '../tauriApi';
 import ColorPicker, { buildColor, parseColor } from './ColorPicker';
 
 // ---------------------------------------------------------------------------
@@ -81,6 +82,61 @@ function LiveTextArea({ syncKey, value, onChange, className }: {
   );
 }
 
+// ---------------------------------------------------------------------------
+// Widget backgrounds: a colour string, or a colour with an image over it
+// ---------------------------------------------------------------------------
+const BG_SIZE_CSS: Record<NonNullable<WidgetBackground['size']>, string> = { cover: 'cover', contain: 'contain', stretch: '100% 100%', auto: 'auto' };
+
+function bgObject(bg: Widget['background']): WidgetBackground {
+  return typeof bg === 'string' ? { color: bg } : bg;
+}
+
+/** Back to a plain colour string once there's no image, so older builds still read it */
+function withBg(bg: Widget['background'], up: Partial<WidgetBackground>): Widget['background'] {
+  const next = { ...bgObject(bg), ...up };
+  return next.src ? next : (next.color || 'transparent');
+}
+
+const CHECKERBOARD = 'linear-gradient(45deg,#111 25%,transparent 25%),linear-gradient(-45deg,#111 25%,transparent 25%),linear-gradient(45deg,transparent 75%,#111 75%),linear-gradient(-45deg,transparent 75%,#111 75%)';
+
+/** Canvas styles for a background: its image over the colour, or over a checkerboard when that's transparent */
+function canvasBackground(bg: WidgetBackground): React.CSSProperties {
+  const transparent = !bg.color || bg.color === 'transparent';
+  const image = bg.src ? [`url(${JSON.stringify(bg.src)}) ${bg.position||'center'} / ${BG_SIZE_CSS[bg.size||'cover']} no-repeat`] : [];
+  if (!transparent) return { background: [...image, bg.color].join(',') };
+  return {
+    background: [...image, CHECKERBOARD].join(','),
+    backgroundSize: [...image.map(() => BG_SIZE_CSS[bg.size||'cover']), '24px 24px'].join(','),
+    backgroundPosition: [...image.map(() => bg.position||'center'), '0 0,0 12px,12px -12px,-12px 0'].join(','),
+  };
+}
+
+/** Let the user pick an image file and return it as a data URI, or null if they cancel */
+async function pickImageFile(): Promise<string | null> {
+  const { open: openDialog } = await import('@tauri-apps/plugin-dialog');
+  const path = await openDialog({
+    filters: [{ name: 'Images', extensions: ['png','jpg','jpeg','gif','webp','svg','bmp','avif'] }],
+    multiple: false,
+  });
+  if (!path || typeof path !== 'string') return null;
+  // Use plugin-fs to read the file with the temporary permission granted by plugin-dialog
+  const { readFile } = await import('@tauri-apps/plugin-fs');
+  const bytes = await readFile(path);
+
+  // Convert bytes to base64 (handled smoothly in chunks to prevent Maximum Call Stack Size Exceeded logic)
+  let binary = '';
+  const chunkSize = 8192;
+  for (let i = 0; i < bytes.length; i += chunkSize) {
+    binary += String.fromCharCode.apply(null, Array.from(bytes.subarray(i, i + chunkSize)));
+  }
+  const base64 = btoa(binary);
+
+  // Determine mime type
+  const ext = path.split('.').pop()?.toLowerCase() || 'png';
+  const typeMap: Record<string, string> = { svg: 'svg+xml', png: 'png', webp: 'webp', gif: 'gif', jpg: 'jpeg', jpeg: 'jpeg', bmp: 'bmp', avif: 'avif' };
+  return `data:image/${typeMap[ext] || 'png'};base64,${base64}`;
+}
+
 // ---------------------------------------------------------------------------
 // Defaults
 // ---------------------------------------------------------------------------
@@ -141,9 +197,73 @@ function newMask(maskType: MaskType = 'clip'): OverlayElement {
 const NUMERIC_KEYFRAME_PROPS: KeyframeProperty[] = [
   'x','y','width','height','rotation','opacity',
   'strokeWidth','borderRadius','fontSize','letterSpacing','lineHeight',
-  'blur','brightness','contrast','hueRotate','saturate','scaleX','scaleY'
+  'blur','brightness','contrast','hueRotate','saturate','scaleX','scaleY',
+  'clipSize','clipX','clipY',
+  'gradientAngle','gradientStart','gradientEnd','gradientStartOpacity','gradientEndOpacity',
+  'tintStrength'
 ];
-const COLOR_KEYFRAME_PROPS: KeyframeProperty[] = ['fill','strokeColor','color'];
+/** Linear gradient directions as angles, where animating `gradientAngle` starts from */
+const GRADIENT_ANGLES: Partial<Record<GradientDir, number>> = { 'to top': 0, 'to right': 90, 'to bottom right': 135, 'to bottom': 180, 'to left': 270 };
+
+/** What a numeric keyframe prop the element doesn't set animates from (same as the OBS page) */
+function numericBase(el: OverlayElement, prop: KeyframeProperty): number {
+  const own = (el as any)[prop];
+  if (own !== undefined) return own;
+  if (prop === 'gradientAngle') return GRADIENT_ANGLES[el.gradientDir ?? 'to bottom'] ?? 180;
+  return prop === 'gradientEnd' || prop === 'tintStrength' ? 100 : prop === 'gradientStartOpacity' ? 1 : 0;
+}
+const COLOR_KEYFRAME_PROPS: KeyframeProperty[] = ['fill','strokeColor','color','tint'];
+
+const PATH_TOKEN = /[a-df-z]|[-+]?(?:\d*\.\d+|\d+\.?)(?:e[-+]?\d+)?/gi;
+
+/** Morph between two SVG paths with the same command structure; null if they differ */
+function lerpPath(a: string, b: string, t: number): string | null {
+  const ta = a.match(PATH_TOKEN) ?? [], tb = b.match(PATH_TOKEN) ?? [];
+  if (ta.length !== tb.length || ta.length === 0) return null;
+  const out: (string|number)[] = [];
+  let cmd = '', idx = 0;
+  for (let i = 0; i < ta.length; i++) {
+    const isCmd = /^[a-z]$/i.test(ta[i]);
+    if (isCmd !== /^[a-z]$/i.test(tb[i]) || (isCmd && ta[i] !== tb[i])) return null;
+    if (isCmd) { cmd = ta[i]; idx = 0; out.push(cmd); continue; }
+    let v = +ta[i] + (+tb[i] - +ta[i]) * t;
+    // Arc flags (4th/5th arc parameter) must stay 0 or 1
+    if ((cmd === 'A' || cmd === 'a') && (idx % 7 === 3 || idx % 7 === 4)) v = Math.round(v);
+    out.push(+v.toFixed(3));
+    idx++;
+  }
+  return out.join(' ');
+}
+
+// Numeric counters: numbers in `content` interpolate, keeping any prefix/suffix ("$", " subs")
+const COUNTER_RE = /^([^\d-]*)(-?\d(?:[\d,\s]*\d)?(?:\.\d+)?)(.*)$/s;
+
+function parseCounter(s: string | undefined) {
+  const m = COUNTER_RE.exec(s ?? '');
+  if (!m) return null;
+  const digits = m[2].replace(/[,\s]/g, '');
+  return { prefix: m[1], value: parseFloat(digits), decimals: (digits.split('.')[1] || '').length, suffix: m[3] };
+}
+
+function lerpCounter(a: string, b: string, t: number): string {
+  const pa = parseCounter(a), pb = parseCounter(b);
+  // Non-numeric content can't count; it switches when the next keyframe is reached
+  if (!pa || !pb) return t < 1 ? a : b;
+  const value = pa.value + (pb.value - pa.value) * t;
+  return pb.prefix + value.toFixed(Math.max(pa.decimals, pb.decimals)) + pb.suffix;
+}
+
+/** Counter text with the element's decimals/grouping; mirrors `formatCounter` in the OBS renderer */
+function formatCounter(el: OverlayElement, content: string | undefined): string {
+  const p = parseCounter(content);
+  if (!p) return content ?? '';
+  const decimals = Math.min(20, Math.max(0, el.decimals ?? p.decimals));
+  const opts = { minimumFractionDigits: decimals, maximumFractionDigits: decimals, useGrouping: !!el.thousandsSeparator };
+  let fmt: Intl.NumberFormat;
+  try { fmt = new Intl.NumberFormat(el.locale || undefined, opts); }
+  catch { fmt = new Intl.NumberFormat(undefined, opts); }
+  return p.prefix + fmt.format(p.value) + p.suffix;
+}
 
 function easingFn(t: number, type: EasingType): number {
   switch (type) {
@@ -235,18 +355,66 @@ function interpolateElementFromGlobal(
     const nv = nextState[prop];
     if (pv === undefined && nv === undefined) continue;
     if (NUMERIC_KEYFRAME_PROPS.includes(prop)) {
-      const a = (pv as number) ?? (el as any)[prop] ?? 0;
-      const b = (nv as number) ?? (el as any)[prop] ?? 0;
+      const a = (pv as number) ?? numericBase(el, prop);
+      const b = (nv as number) ?? numericBase(el, prop);
       result[prop] = a + (b - a) * t;
     } else if (COLOR_KEYFRAME_PROPS.includes(prop)) {
       const a = (pv as string) ?? (el as any)[prop] ?? '#000000';
       const b = (nv as string) ?? (el as any)[prop] ?? '#000000';
       result[prop] = lerpColor(a, b, t);
+    } else if (prop === 'content' && el.numericContent) {
+      result.content = lerpCounter((pv as string) ?? el.content ?? '', (nv as string) ?? el.content ?? '', t);
+    } else if (prop === 'pathData') {
+      const a = (pv as string) ?? el.pathData ?? '', b = (nv as string) ?? el.pathData ?? '';
+      // OBS crossfades incompatible paths; the editor preview switches halfway
+      result.pathData = a === b ? a : lerpPath(a, b, t) ?? (t < 0.5 ? a : b);
     }
   }
   return result;
 }
 
+/** Black or white text, whichever contrasts more with an opaque hex/rgb() colour
+ *  (as the OBS page picks it for `autoContrast`); undefined for anything see-through */
+function contrastingText(bg: string | undefined): string | undefined {
+  const c = (bg ?? '').trim().toLowerCase();
+  let rgb: number[] | undefined, alpha = 1;
+  if (/^#([0-9a-f]{3,4}|[0-9a-f]{6}|[0-9a-f]{8})$/.test(c)) {
+    const hex = c.length <= 5 ? c.slice(1).split('').map(ch => ch + ch).join('') : c.slice(1);
+    rgb = [0, 2, 4].map(i => parseInt(hex.slice(i, i + 2), 16));
+    if (hex.length === 8) alpha = parseInt(hex.slice(6, 8), 16) / 255;
+  } else if (/^rgba?\(/.test(c)) {
+    const parts = c.replace(/^rgba?\(|\)$/g, '').split(/[\s,/]+/).filter(Boolean);
+    rgb = parts.slice(0, 3).map(Number);
+    if (parts[3] !== undefined) alpha = parts[3].endsWith('%') ? parseFloat(parts[3]) / 100 : Number(parts[3]);
+  } else if (c === 'black' || c === 'white') {
+    rgb = c === 'black' ? [0, 0, 0] : [255, 255, 255];
+  }
+  if (!rgb || rgb.some(v => !Number.isFinite(v)) || alpha < 1) return undefined;
+  const [r, g, b] = rgb.map(v => { const x = v / 255; return x <= 0.03928 ? x / 12.92 : ((x + 0.055) / 1.055) ** 2.4; });
+  const l = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+  return 1.05 / (l + 0.05) >= (l + 0.05) / 0.05 ? '#ffffff' : '#000000';
+}
+
+/** With `lockAspect`, a keyframed width/height (or scaleX/scaleY) brings the other one along at the element's ratio */
+function lockAspect(el: OverlayElement, o: Partial<Record<KeyframeProperty, number|string>>) {
+  if (!el.lockAspect) return o;
+  for (const [a, b, baseA, baseB] of [['width', 'height', el.width, el.height], ['scaleX', 'scaleY', el.scaleX ?? 1, el.scaleY ?? 1]] as [KeyframeProperty, KeyframeProperty, number, number][]) {
+    if (!(baseA > 0 && baseB > 0)) continue;
+    if (o[a] !== undefined && o[b] === undefined) o[b] = (o[a] as number) * baseB / baseA;
+    else if (o[b] !== undefined && o[a] === undefined) o[a] = (o[b] as number) * baseA / baseB;
+  }
+  return o;
+}
+
+/** Offered `pollInterval`s in seconds; 2 is the server's default */
+const POLL_CHOICES = [1, 2, 5, 10, 30, 60];
+
+/** Offered `pixelRatio`s; 1 is design resolution */
+const PIXEL_RATIO_CHOICES = [1, 2, 3];
+
+/** Segment select value that creates a new segment instead of opening one */
+const NEW_SEGMENT = '\u0000new';
+
 /** Default timeline config */
 function defaultTimeline(): AnimationTimeline {
   return { duration: 5, loop: false, autoplay: false, speed: 1, keyframes: [] };
@@ -271,13 +439,53 @@ function ElementContent({ el }: { el: OverlayElement }) {
     return <svg viewBox={`0 0 ${el.width} ${el.height}`} style={{width:'100%',height:'100%',overflow:'visible'}}>
       <path d={el.pathData} fill={el.fill||'none'} stroke={el.strokeColor||'#3b82f6'} strokeWidth={el.strokeWidth||4} />
     </svg>;
-  if (el.type === 'text')
-    return <div style={{ fontSize:`${el.fontSize}px`,color:el.color,fontFamily:el.fontFamily,textAlign:el.textAlign,fontWeight:el.fontWeight,textShadow:el.textShadow,lineHeight:el.lineHeight,letterSpacing:el.letterSpacing?`${el.letterSpacing}px`:undefined,width:'100%',padding:'0 8px',wordBreak:'break-word' }}>{el.content}</div>;
+  if (el.type === 'text') {
+    const vertical = el.writingMode==='vertical-rl'||el.writingMode==='vertical-lr';
+    const align = ({left:'start',center:'center',right:'end'} as const)[el.textAlign??'center'];
+    return <div dir={el.direction==='rtl'||el.direction==='auto'?el.direction:undefined} style={{ fontSize:`${el.fontSize}px`,color:el.color,fontFamily:el.fontFamily,textAlign:align,direction:el.direction==='rtl'?'rtl':'ltr',writingMode:vertical?el.writingMode:undefined,fontWeight:el.fontWeight,textShadow:el.textShadow,lineHeight:el.lineHeight,letterSpacing:el.letterSpacing?`${el.letterSpacing}px`:undefined,width:'100%',padding:vertical?'8px 0':'0 8px',wordBreak:'break-word' }}>
+      {el.segments?.length ? el.segments.map((seg,i)=>seg.src ? (
+        <img key={i} src={seg.src} alt={seg.text} draggable={false}
+          onError={e=>{ e.currentTarget.style.display='none'; }}
+          style={{ display:'inline-block',height:`${seg.height??el.lineHeight??1.2}em`,width:'auto',verticalAlign:'middle' }}/>
+      ) : (
+        <span key={i} style={{ color:seg.color,fontWeight:seg.fontWeight,fontStyle:seg.italic?'italic':undefined,textDecoration:seg.underline?'underline':undefined,fontSize:seg.fontSize?`${seg.fontSize}px`:undefined,fontFamily:seg.fontFamily }}>{seg.text}</span>
+      )) : el.numericContent ? formatCounter(el, el.content) : el.content}
+    </div>;
+  }
   if (el.type === 'image' && el.src)
-    return <img src={el.src} style={{width:'100%',height:'100%',objectFit:el.objectFit||'contain'}} draggable={false} />;
+    return <Tinted el={el}>
+      <img src={el.src} style={{width:'100%',height:'100%',objectFit:el.objectFit||'contain',objectPosition:el.objectPosition}} draggable={false} />
+    </Tinted>;
+  if (el.type === 'video' && (el.src || el.poster))
+    return <Tinted el={el}>
+      <video src={el.src} poster={el.poster} muted loop playsInline
+        style={{width:'100%',height:'100%',objectFit:el.objectFit||'contain',objectPosition:el.objectPosition,background:'transparent',pointerEvents:'none'}} />
+    </Tinted>;
+  // Sprites preview their first frame; the OBS page steps through the rest
+  if (el.type === 'sprite' && el.frames?.length)
+    return <img src={el.frames[0]} style={{width:'100%',height:'100%',objectFit:el.objectFit||'contain',objectPosition:el.objectPosition}} draggable={false} />;
+  if (el.type === 'sprite' && el.src)
+    return <div style={{width:'100%',height:'100%',backgroundImage:`url(${JSON.stringify(el.src)})`,backgroundRepeat:'no-repeat',backgroundPosition:'0 0',
+      backgroundSize:`${Math.max(1,el.spriteCols??1)*100}% ${Math.max(1,el.spriteRows??1)*100}%`}} />;
   return null;
 }
 
+const TINT_MASK_SIZES = { contain: 'contain', cover: 'cover', fill: '100% 100%' } as const;
+
+/** Media with its `tint` layer over it, blended with the media only (as on the OBS page) */
+function Tinted({ el, children }: { el: OverlayElement; children: React.ReactNode }) {
+  if (!el.tint) return <>{children}</>;
+  // Images mask the layer by their own alpha, so transparent areas stay untinted
+  const mask = el.type === 'image' && el.src ? `url(${JSON.stringify(el.src)})` : undefined;
+  const size = TINT_MASK_SIZES[el.objectFit ?? 'contain'], pos = el.objectPosition || 'center';
+  return <div style={{ position:'relative', width:'100%', height:'100%', isolation:'isolate' }}>
+    {children}
+    <div style={{ position:'absolute', inset:0, pointerEvents:'none', background:el.tint,
+      mixBlendMode:(el.tintBlend ?? 'color') as any, opacity:Math.min(100, Math.max(0, el.tintStrength ?? 100)) / 100,
+      ...(mask ? { maskImage:mask, WebkitMaskImage:mask, maskSize:size, WebkitMaskSize:size, maskPosition:pos, WebkitMaskPosition:pos, maskRepeat:'no-repeat', WebkitMaskRepeat:'no-repeat' } : {}) }}/>
+  </div>;
+}
+
 function getMaskCss(el: OverlayElement): React.CSSProperties {
   if (el.maskType === 'clip') {
     return { overflow:'hidden', borderRadius:`${el.clipRadius??0}px` };
@@ -285,14 +493,16 @@ function getMaskCss(el: OverlayElement): React.CSSProperties {
   if (el.maskType === 'gradient') {
     const dir = el.gradientDir ?? 'to bottom';
     const s = el.gradientStartOpacity ?? 1, e = el.gradientEndOpacity ?? 0;
+    const stops = `rgba(0,0,0,${el.maskInvert ? 1 - s : s}) ${el.gradientStart ?? 0}%,rgba(0,0,0,${el.maskInvert ? 1 - e : e}) ${el.gradientEnd ?? 100}%`;
     const grad = dir === 'radial'
-      ? `radial-gradient(circle,rgba(0,0,0,${el.maskInvert ? 1 - s : s}) 0%,rgba(0,0,0,${el.maskInvert ? 1 - e : e}) 100%)`
-      : `linear-gradient(${dir},rgba(0,0,0,${el.maskInvert ? 1 - s : s}) 0%,rgba(0,0,0,${el.maskInvert ? 1 - e : e}) 100%)`;
+      ? `radial-gradient(circle,${stops})`
+      : `linear-gradient(${el.gradientAngle !== undefined ? `${el.gradientAngle}deg` : dir},${stops})`;
     return { WebkitMaskImage: grad, maskImage: grad } as React.CSSProperties;
   }
   if (el.maskType === 'image' && el.maskImageSrc) {
     const imgGrad = `url(${el.maskImageSrc})`;
-    return { WebkitMaskImage: imgGrad, maskImage: imgGrad, WebkitMaskSize: 'cover', maskSize: 'cover', WebkitMaskPosition: 'center', maskPosition: 'center' } as React.CSSProperties;
+    const size = el.maskSize || 'cover', pos = el.maskPosition || 'center';
+    return { WebkitMaskImage: imgGrad, maskImage: imgGrad, WebkitMaskSize: size, maskSize: size, WebkitMaskPosition: pos, maskPosition: pos, WebkitMaskRepeat: 'no-repeat', maskRepeat: 'no-repeat' } as React.CSSProperties;
   }
   if (el.maskType === 'opacity') {
     return { opacity: el.maskInvert ? 1 - (el.opacity ?? 1) : el.opacity };
@@ -300,6 +510,50 @@ function getMaskCss(el: OverlayElement): React.CSSProperties {
   return {};
 }
 
+// ---------------------------------------------------------------------------
+// Rotation pivot (`transformOrigin`), checked the same way as the OBS page
+// ---------------------------------------------------------------------------
+const ORIGIN_PART = '(?:left|center|right|top|bottom|0|-?\\d+(?:\\.\\d+)?(?:%|px))';
+const ORIGIN_RE = new RegExp(`^\\s*${ORIGIN_PART}(?:\\s+${ORIGIN_PART})?\\s*$`, 'i');
+const ORIGIN_PRESETS = ['center center','left top','center top','right top','left center','right center','left bottom','center bottom','right bottom'];
+
+/** The element's pivot as CSS; anything the page wouldn't accept pivots on the center */
+function transformOriginOf(el: { transformOrigin?: string }): string {
+  return el.transformOrigin && ORIGIN_RE.test(el.transformOrigin) ? el.transformOrigin.trim().toLowerCase() : 'center center';
+}
+
+/** Pivot point in the element's own pixels */
+function originPoint(el: { width: number; height: number; transformOrigin?: string }): { x: number; y: number } {
+  let parts = transformOriginOf(el).split(/\s+/);
+  // One value, or keywords written vertical-first (`top left`), put y first
+  if (parts.length === 1) parts = parts[0] === 'top' || parts[0] === 'bottom' ? ['center', parts[0]] : [parts[0], 'center'];
+  else if (['top','bottom'].includes(parts[0]) || ['left','right'].includes(parts[1])) parts = [parts[1], parts[0]];
+  const KEYWORD: Record<string, number> = { left: 0, top: 0, center: 0.5, right: 1, bottom: 1 };
+  const resolve = (part: string, size: number) =>
+    part in KEYWORD ? KEYWORD[part] * size : part.endsWith('%') ? parseFloat(part) / 100 * size : parseFloat(part) || 0;
+  return { x: resolve(parts[0], el.width), y: resolve(parts[1], el.height) };
+}
+
+// ---------------------------------------------------------------------------
+// Clip shapes (`clipShape`), built the same way as the OBS page
+// ---------------------------------------------------------------------------
+const CLIP_LEN = '(?:0|-?\\d+(?:\\.\\d+)?(?:%|px))';
+const CLIP_POINTS_RE = new RegExp(`^\\s*${CLIP_LEN}\\s+${CLIP_LEN}(?:\\s*,\\s*${CLIP_LEN}\\s+${CLIP_LEN}){2,}\\s*$`, 'i');
+const CLIP_SHAPES: ClipShape[] = ['inset', 'circle', 'ellipse', 'polygon'];
+
+/** The element's `clip-path`, or undefined when it isn't clipped */
+function clipPathOf(el: OverlayElement): string | undefined {
+  const num = (v: number | undefined, fallback: number) => typeof v === 'number' && isFinite(v) ? v : fallback;
+  const at = `at ${num(el.clipX, 50)}% ${num(el.clipY, 50)}%`;
+  switch (el.clipShape) {
+    case 'inset': return `inset(${Math.min(50, Math.max(0, num(el.clipSize, 10)))}%)`;
+    case 'circle': return `circle(${Math.max(0, num(el.clipSize, 50))}% ${at})`;
+    case 'ellipse': { const r = Math.max(0, num(el.clipSize, 50)); return `ellipse(${r}% ${r}% ${at})`; }
+    case 'polygon': return el.clipPoints && CLIP_POINTS_RE.test(el.clipPoints) ? `polygon(${el.clipPoints.trim()})` : 'none';
+    default: return undefined;
+  }
+}
+
 // ---------------------------------------------------------------------------
 // TransformBox (Custom rotation & bounds manager replacing react-rnd)
 // ---------------------------------------------------------------------------
@@ -366,6 +620,12 @@ function TransformBox({ el, isSelected, scale, containerW, containerH, onUpdate,
 
       let newW = Math.max(4, startW + dw);
       let newH = Math.max(4, startH + dh);
+      if (el.lockAspect && startW > 0 && startH > 0) {
+        // The dragged side leads (on a corner, whichever changed more); the other keeps the ratio
+        const byWidth = dir.length === 2 ? Math.abs(newW / startW - 1) >= Math.abs(newH / startH - 1) : /[ew]/.test(dir);
+        if (byWidth) newH = Math.max(4, newW * startH / startW);
+        else newW = Math.max(4, newH * startW / startH);
+      }
 
       let actual_dw = newW - startW;
       let actual_dh = newH - startH;
@@ -413,7 +673,7 @@ function TransformBox({ el, isSelected, scale, containerW, containerH, onUpdate,
       style={{
         width: el.width, height: el.height, left: el.x, top: el.y,
         transform: `rotate(${el.rotation || 0}deg)`,
-        transformOrigin: 'center center',
+        transformOrigin: transformOriginOf(el),
         cursor: el.locked || isDrawing ? 'default' : 'move',
         ...style
       }}
@@ -455,13 +715,21 @@ export default function Designer() {
   const [activeTab, setActiveTab] = useState<'layers'|'properties'>('layers');
   const [showObsPanel, setShowObsPanel] = useState(false);
   const [widgetObsUrl, setWidgetObsUrl] = useState('');
+  const [serverStatus, setServerStatus] = useState<ServerStatus|null>(null);
+  const [widgetError, setWidgetError] = useState<WidgetError | null>(null);
   const [urlCopied, setUrlCopied] = useState(false);
+  const [tokenCopied, setTokenCopied] = useState(false);
   const [saveStatus, setSaveStatus] = useState<'idle'|'saving'|'saved'|'error'>('idle');
   const [showLoadModal, setShowLoadModal] = useState(false);
+  /** Set when the open workspace was saved or deleted by something other than this editor */
+  const [externalChange, setExternalChange] = useState<'updated'|'deleted'|null>(null);
+  /** Our own save in flight / the `updated_at` it produced, so its change event is ignored */
+  const ownSaveRef = useRef<{ saving: boolean; updatedAt?: string }>({ saving: false });
   const [workspaceList, setWorkspaceList] = useState<{id:string;name:string}[]>([]);
   const [activeTool, setActiveTool] = useState<'select'|'curvature'|'pencil'|'eraser'>('select');
   const isDrawing = activeTool !== 'select';
   const [showGrid, setShowGrid] = useState(false);
+  const [showSafeArea, setShowSafeArea] = useState(false);
   const [currentPath, setCurrentPath] = useState<{x:number;y:number}[]>([]);
   const [previewPoint, setPreviewPoint] = useState<{x:number;y:number} | null>(null);
   const [expandedGroups, setExpandedGroups] = useState<Set<string>>(new Set());
@@ -476,12 +744,29 @@ export default function Designer() {
   const [currentTime, setCurrentTime] = useState(0);
   const [isScrubbing, setIsScrubbing] = useState(false);
   const [selectedKeyframeId, setSelectedKeyframeId] = useState<string|null>(null);
+  // Name of the timeline segment being edited; null edits the main timeline
+  const [editingSegment, setEditingSegment] = useState<string|null>(null);
   const playStartRef = useRef<number>(0);
   const playTimeOffsetRef = useRef<number>(0);
   const animFrameRef = useRef<number>(0);
 
   const activeWidget = workspace.widgets.find(w => w.id === activeWidgetId) ?? workspace.widgets[0];
-  const timeline = activeWidget.animationTimeline ?? defaultTimeline();
+  const activeBg = bgObject(activeWidget.background);
+  const canvasWidth = workspace.canvasWidth ?? DEFAULT_CANVAS.width;
+  const canvasHeight = workspace.canvasHeight ?? DEFAULT_CANVAS.height;
+  const safeArea = workspace.safeArea;
+  const safePreset = !safeArea ? '0'
+    : (['5', '10'].find(p => {
+        const x = Math.round(canvasWidth * +p / 100), y = Math.round(canvasHeight * +p / 100);
+        return safeArea.left === x && safeArea.right === x && safeArea.top === y && safeArea.bottom === y;
+      }) ?? 'custom');
+  const mainTimeline = activeWidget.animationTimeline ?? defaultTimeline();
+  const segment = editingSegment ? mainTimeline.segments?.find(s => s.name === editingSegment) : undefined;
+  // While a segment is open the timeline panel edits it through the same fields
+  const timeline: AnimationTimeline = segment
+    ? { ...mainTimeline, duration: segment.duration, loop: !!segment.loop, speed: segment.speed ?? 1,
+        autoplay: !!segment.autoplay, keyframes: segment.keyframes }
+    : mainTimeline;
 
   // Update canvas size → update scale
   useEffect(() => {
@@ -497,7 +782,51 @@ export default function Designer() {
 
   useEffect(() => {
     getWidgetObsUrl(activeWidgetId).then(setWidgetObsUrl).catch(console.error);
-  }, [activeWidgetId]);
+  }, [activeWidgetId, saveStatus]);
+
+  // Reopen the last workspace at launch; only then start remembering whichever one is open
+  const [restoredLast, setRestoredLast] = useState(false);
+  useEffect(() => {
+    getLastWorkspace()
+      .then(async id => {
+        const saved = id ? await getWorkspace(id) : null;
+        if (saved) { setWorkspace(saved.config); setActiveWidgetId(saved.config.widgets[0]?.id ?? ''); }
+      })
+      .catch(console.error)
+      .finally(() => setRestoredLast(true));
+  }, []);
+  useEffect(() => {
+    if (restoredLast) setLastWorkspace(workspace.id).catch(console.error);
+  }, [restoredLast, workspace.id]);
+
+  // Follow writes made outside the editor: refresh the saved list and flag the open workspace
+  useEffect(() => { setExternalChange(null); }, [workspace.id]);
+  useEffect(() => {
+    const unlisten = onOverlayChanged(change => {
+      if (showLoadModal) listWorkspaces().then(setWorkspaceList).catch(console.error);
+      if (change.id !== workspace.id || change.kind === 'created' || change.kind === 'refreshed') return;
+      const own = ownSaveRef.current;
+      if (own.saving || (change.updated_at !== null && change.updated_at === own.updatedAt)) return;
+      setExternalChange(change.kind);
+    });
+    return () => { unlisten.then(stop => stop()).catch(console.error); };
+  }, [workspace.id, showLoadModal]);
+
+  // Whether the OBS server actually came up, for the OBS panel's indicator
+  useEffect(() => {
+    getServerStatus().then(setServerStatus).catch(console.error);
+    const unlisten = onServerStatus(setServerStatus);
+    return () => { unlisten.then(stop => stop()).catch(console.error); };
+  }, []);
+
+  // Poll for script errors the widget's OBS pages reported while the OBS panel is open
+  useEffect(() => {
+    if (!showObsPanel) return;
+    const poll = () => getWidgetErrors(activeWidgetId).then(errs => setWidgetError(errs[errs.length - 1] ?? null)).catch(console.error);
+    poll();
+    const timer = setInterval(poll, 5000);
+    return () => clearInterval(timer);
+  }, [activeWidgetId, showObsPanel]);
 
   // ── Workspace mutation helpers ──────────────────────────────────────────
   const updateWidget = (wid: string, up: Partial<Widget>) =>
@@ -511,6 +840,13 @@ export default function Designer() {
       return el;
     });
   }
+  /** Apply `fn` to every element of a tree, parents before their (new) children */
+  function mapElements(elements: OverlayElement[], fn: (el: OverlayElement) => OverlayElement): OverlayElement[] {
+    return elements.map(el => {
+      const out = fn(el);
+      return out.children ? { ...out, children: mapElements(out.children, fn) } : out;
+    });
+  }
   function removeFromElements(elements: OverlayElement[], id: string): OverlayElement[] {
     return elements
       .filter(el => el.id !== id)
@@ -531,7 +867,7 @@ export default function Designer() {
 
       if (selectedKeyframeId) {
         const tl = activeW.animationTimeline || { duration: 5, loop: false, autoplay: false, speed: 1, keyframes: [] };
-        const newKfs = tl.keyframes.map(kf => {
+        const patchKf = (kf: GlobalKeyframe) => {
           if (kf.id === selectedKeyframeId) {
             return {
               ...kf,
@@ -542,12 +878,15 @@ export default function Designer() {
             };
           }
           return kf;
-        });
+        };
+        const timeline = editingSegment && tl.segments?.some(s => s.name === editingSegment)
+          ? { ...tl, segments: tl.segments.map(s => s.name === editingSegment ? { ...s, keyframes: s.keyframes.map(patchKf) } : s) }
+          : { ...tl, keyframes: tl.keyframes.map(patchKf) };
         return {
           ...ws,
           widgets: ws.widgets.map(w => w.id === activeWidgetId ? {
             ...w,
-            animationTimeline: { ...tl, keyframes: newKfs }
+            animationTimeline: timeline
           } : w)
         };
       }
@@ -560,7 +899,7 @@ export default function Designer() {
         } : w)
       };
     });
-  }, [activeWidgetId, selectedKeyframeId]);
+  }, [activeWidgetId, selectedKeyframeId, editingSegment]);
 
   const deleteEl = useCallback((id: string) => {
     setWorkspace(ws => ({
@@ -589,6 +928,7 @@ export default function Designer() {
 
   const selectedId = selectedPath[selectedPath.length - 1] ?? null;
   let selected = selectedId ? findElement(activeWidget.elements, selectedId) : null;
+  if (selected) selected = resolveStylePreset(selected, workspace.stylePresets);
 
   if (selected && shouldAnimate && timeline.keyframes.length >= 1) {
     const overrides = interpolateElementFromGlobal(timeline.keyframes, selected.id, selected, currentTime);
@@ -722,7 +1062,44 @@ export default function Designer() {
   useEffect(() => () => cancelAnimationFrame(animFrameRef.current), []);
 
   const setTimelineProp = (up: Partial<AnimationTimeline>) => {
-    updateWidget(activeWidgetId, { animationTimeline: { ...timeline, ...up } });
+    if (!segment) {
+      updateWidget(activeWidgetId, { animationTimeline: { ...mainTimeline, ...up } });
+      return;
+    }
+    const { duration, loop, speed, autoplay, keyframes } = up;
+    setSegmentProp({
+      ...(duration !== undefined ? { duration } : {}),
+      ...(loop !== undefined ? { loop: loop || undefined } : {}),
+      ...(speed !== undefined ? { speed: speed === 1 ? undefined : speed } : {}),
+      ...(autoplay !== undefined ? { autoplay: autoplay || undefined } : {}),
+      ...(keyframes !== undefined ? { keyframes } : {}),
+    });
+  };
+
+  const setSegmentProp = (up: Partial<TimelineSegment>) => {
+    if (!segment) return;
+    const segments = (mainTimeline.segments ?? []).map(s => s.name === segment.name ? { ...s, ...up } : s);
+    updateWidget(activeWidgetId, { animationTimeline: { ...mainTimeline, segments } });
+  };
+
+  const addSegment = () => {
+    const name = window.prompt('Segment name (used in /api/widget/…/segment/{name}/play)', 'intro')?.trim();
+    if (!name) return;
+    if (mainTimeline.segments?.some(s => s.name === name)) { setEditingSegment(name); return; }
+    const created: TimelineSegment = { name: name.slice(0, 64), duration: 2, keyframes: [] };
+    updateWidget(activeWidgetId, { animationTimeline: { ...mainTimeline, segments: [...(mainTimeline.segments ?? []), created] } });
+    setEditingSegment(created.name);
+    setSelectedKeyframeId(null);
+  };
+
+  const deleteSegment = () => {
+    if (!segment || !window.confirm(`Delete segment "${segment.name}"?`)) return;
+    const segments = (mainTimeline.segments ?? [])
+      .filter(s => s.name !== segment.name)
+      .map(s => s.then === segment.name ? { ...s, then: undefined } : s);
+    updateWidget(activeWidgetId, { animationTimeline: { ...mainTimeline, segments: segments.length ? segments : undefined } });
+    setEditingSegment(null);
+    setSelectedKeyframeId(null);
   };
 
   // ── Keyframe management (global) ──────────────────────────────────────────
@@ -747,8 +1124,20 @@ export default function Designer() {
           ...(stateEl.color ? { color: stateEl.color } : {}),
           ...(stateEl.borderRadius !== undefined ? { borderRadius: stateEl.borderRadius } : {}),
           ...(stateEl.blur ? { blur: stateEl.blur } : {}),
+          ...(stateEl.clipShape && stateEl.clipShape !== 'polygon' ? {
+            clipSize: stateEl.clipSize ?? (stateEl.clipShape === 'inset' ? 10 : 50),
+            ...(stateEl.clipShape !== 'inset' ? { clipX: stateEl.clipX ?? 50, clipY: stateEl.clipY ?? 50 } : {}),
+          } : {}),
+          ...(stateEl.maskType === 'gradient' ? {
+            gradientStart: stateEl.gradientStart ?? 0, gradientEnd: stateEl.gradientEnd ?? 100,
+            gradientStartOpacity: stateEl.gradientStartOpacity ?? 1, gradientEndOpacity: stateEl.gradientEndOpacity ?? 0,
+            ...(stateEl.gradientAngle !== undefined ? { gradientAngle: stateEl.gradientAngle } : {}),
+          } : {}),
+          ...(stateEl.tint ? { tint: stateEl.tint, tintStrength: stateEl.tintStrength ?? 100 } : {}),
           ...(stateEl.brightness ? { brightness: stateEl.brightness } : {}),
           ...(stateEl.fontSize ? { fontSize: stateEl.fontSize } : {}),
+          ...(stateEl.type === 'path' && stateEl.pathData ? { pathData: stateEl.pathData } : {}),
+          ...(stateEl.numericContent && stateEl.content !== undefined ? { content: stateEl.content } : {}),
         };
         if (el.children) flatten(el.children);
       }
@@ -764,6 +1153,12 @@ export default function Designer() {
       easing: 'linear' as EasingType,
       elementStates: snapshotAllElements(activeWidget.elements, time, timeline.keyframes),
     };
+    if (segment) {
+      // Segment keyframes only pose what the segment animates, plus the selected element
+      const posed = new Set(segment.keyframes.flatMap(k => Object.keys(k.elementStates)));
+      if (selectedId) posed.add(selectedId);
+      kf.elementStates = Object.fromEntries(Object.entries(kf.elementStates).filter(([id]) => posed.has(id)));
+    }
     const existing = timeline.keyframes;
     // Replace if keyframe exists at same time
     const filtered = existing.filter(k => Math.abs(k.time - time) > 0.01);
@@ -939,6 +1334,31 @@ export default function Designer() {
     }
   };
 
+  /** Preflight an import; problems are listed and the user decides whether to go ahead */
+  const confirmImport = async (config: WorkspaceConfig): Promise<boolean> => {
+    let report;
+    try { report = await validateConfig(config); } catch(e) { console.warn('Import preflight failed', e); return true; }
+    const lines = [...report.errors.map(i => `✖ ${i.message}`), ...report.warnings.map(i => `⚠ ${i.message}`)];
+    if (lines.length === 0) return true;
+    const shown = lines.length > 12 ? [...lines.slice(0, 12), `…and ${lines.length - 12} more`] : lines;
+    const verdict = report.ok ? 'It will import, with warnings:' : 'It has problems that will stop it saving or rendering:';
+    return window.confirm(`${verdict}\n\n${shown.join('\n')}\n\nImport anyway?`);
+  };
+
+  /** Open an imported workspace, or add an imported widget to this one */
+  const applyImport = async (data: any) => {
+    if (data.widgets && data.id) {
+      if (!await confirmImport(data)) return;
+      setWorkspace(data);
+      setActiveWidgetId(data.widgets[0]?.id || '');
+    } else if (data.widgetType || (data.id && data.elements)) {
+      const newWidget = { ...data, id: uuidv4(), name: data.name + ' (Imported)' };
+      if (!await confirmImport({ ...workspace, widgets: [newWidget] })) return;
+      setWorkspace(ws => ({ ...ws, widgets: [...ws.widgets, newWidget] }));
+      setActiveWidgetId(newWidget.id);
+    }
+  };
+
   const handleImportFile = async () => {
     try {
       const { open } = await import('@tauri-apps/plugin-dialog');
@@ -950,15 +1370,7 @@ export default function Designer() {
       if (!selected) return; // user cancelled
       const filePath = typeof selected === 'string' ? selected : (selected as any)?.path ?? String(selected);
       const content = await readTextFile(filePath);
-      const data = JSON.parse(content);
-      if (data.widgets && data.id) {
-        setWorkspace(data);
-        setActiveWidgetId(data.widgets[0]?.id || '');
-      } else if (data.widgetType || (data.id && data.elements)) {
-        const newWidget = { ...data, id: uuidv4(), name: data.name + ' (Imported)' };
-        setWorkspace(ws => ({ ...ws, widgets: [...ws.widgets, newWidget] }));
-        setActiveWidgetId(newWidget.id);
-      }
+      await applyImport(JSON.parse(content));
     } catch(err) {
       console.warn("Tauri open dialog failed, using browser fallback", err);
       const input = document.createElement('input');
@@ -970,15 +1382,7 @@ export default function Designer() {
         const reader = new FileReader();
         reader.onload = (re) => {
           try {
-            const data = JSON.parse(re.target?.result as string);
-            if (data.widgets && data.id) {
-              setWorkspace(data);
-              setActiveWidgetId(data.widgets[0]?.id || '');
-            } else if (data.widgetType || (data.id && data.elements)) {
-              const newWidget = { ...data, id: uuidv4(), name: data.name + ' (Imported)' };
-              setWorkspace(ws => ({ ...ws, widgets: [...ws.widgets, newWidget] }));
-              setActiveWidgetId(newWidget.id);
-            }
+            applyImport(JSON.parse(re.target?.result as string));
           } catch(e) { console.error("Error parsing JSON", e); }
         };
         reader.readAsText(file);
@@ -987,11 +1391,119 @@ export default function Designer() {
     }
   };
 
+  // ── Canvas ───────────────────────────────────────────────────────────
+  /** Resize the workspace canvas, scaling an existing safe area with it */
+  const setCanvasSize = (width: number, height: number) => setWorkspace(ws => {
+    const sx = width / (ws.canvasWidth ?? DEFAULT_CANVAS.width);
+    const sy = height / (ws.canvasHeight ?? DEFAULT_CANVAS.height);
+    const area = ws.safeArea;
+    return {
+      ...ws, canvasWidth: width, canvasHeight: height,
+      safeArea: area && {
+        top: Math.round(area.top * sy), right: Math.round(area.right * sx),
+        bottom: Math.round(area.bottom * sy), left: Math.round(area.left * sx),
+      },
+    };
+  });
+
+  /** Inset the safe area `percent` of the canvas on every side; 0 removes it */
+  const setSafeAreaPercent = (percent: number) => setWorkspace(ws => {
+    const x = Math.round((ws.canvasWidth ?? DEFAULT_CANVAS.width) * percent / 100);
+    const y = Math.round((ws.canvasHeight ?? DEFAULT_CANVAS.height) * percent / 100);
+    return { ...ws, safeArea: percent > 0 ? { top: y, right: x, bottom: y, left: x } : undefined };
+  });
+
+  // ── Style presets ────────────────────────────────────────────────────
+  /** `ws` with element `id` of the active widget replaced by `patch(el)` (base properties, never a keyframe) */
+  const patchActiveElement = (ws: WorkspaceConfig, id: string, patch: (el: OverlayElement) => OverlayElement): WorkspaceConfig => ({
+    ...ws,
+    widgets: ws.widgets.map(w => w.id === activeWidgetId ? { ...w, elements: mapElements(w.elements, el => el.id === id ? patch(el) : el) } : w),
+  });
+  const omitKeys = (el: OverlayElement, keys: string[]): OverlayElement => {
+    const copy: any = { ...el };
+    for (const k of keys) delete copy[k];
+    return copy;
+  };
+
+  /** Link an element to a preset, dropping its own copies of the preset's properties so the preset shows
+   *  through; `null` unlinks it, keeping the preset's look as the element's own properties */
+  const setElementStylePreset = (id: string, name: string | null) => setWorkspace(ws => patchActiveElement(ws, id, el => {
+    if (name === null) return omitKeys(resolveStylePreset(el, ws.stylePresets), ['stylePreset']);
+    const preset = ws.stylePresets?.[name];
+    return preset ? { ...omitKeys(el, Object.keys(preset)), stylePreset: name } : el;
+  }));
+
+  /** New preset from the element's current style (whichever PRESET_STYLE_KEYS it has), linked to it */
+  const createStylePreset = (id: string, name: string) => setWorkspace(ws => {
+    const raw = findElement(ws.widgets.find(w => w.id === activeWidgetId)?.elements ?? [], id);
+    if (!raw) return ws;
+    const el = resolveStylePreset(raw, ws.stylePresets);
+    const preset: any = {};
+    for (const k of PRESET_STYLE_KEYS) if (el[k] !== undefined) preset[k] = el[k];
+    const next = { ...ws, stylePresets: { ...ws.stylePresets, [name]: preset as StylePreset } };
+    return patchActiveElement(next, id, e => ({ ...omitKeys(e, Object.keys(preset)), stylePreset: name }));
+  });
+
+  /** Move the element's own values for its preset's properties into the preset, updating every element using it */
+  const pushToStylePreset = (id: string) => setWorkspace(ws => {
+    const raw: any = findElement(ws.widgets.find(w => w.id === activeWidgetId)?.elements ?? [], id);
+    const preset = raw?.stylePreset ? ws.stylePresets?.[raw.stylePreset] : undefined;
+    if (!preset) return ws;
+    const keys = Object.keys(preset).filter(k => raw[k] !== undefined);
+    if (!keys.length) return ws;
+    const updated: any = { ...preset };
+    for (const k of keys) updated[k] = raw[k];
+    const next = { ...ws, stylePresets: { ...ws.stylePresets, [raw.stylePreset]: updated } };
+    return patchActiveElement(next, id, e => omitKeys(e, keys));
+  });
+
+  /** Remove a preset; elements in every widget that used it keep its look as their own properties */
+  const deleteStylePreset = (name: string) => setWorkspace(ws => {
+    const { [name]: removed, ...rest } = ws.stylePresets ?? {};
+    if (!removed) return ws;
+    const bake = (el: OverlayElement) => el.stylePreset === name ? omitKeys(resolveStylePreset(el, ws.stylePresets), ['stylePreset']) : el;
+    return { ...ws, stylePresets: rest, widgets: ws.widgets.map(w => ({ ...w, elements: mapElements(w.elements, bake) })) };
+  });
+
   // ── Save ─────────────────────────────────────────────────────────────
   const handleSave = async () => {
     setSaveStatus('saving');
-    try { await saveWorkspace(workspace); setSaveStatus('saved'); setTimeout(()=>setSaveStatus('idle'),2000); }
-    catch { setSaveStatus('error'); setTimeout(()=>setSaveStatus('idle'),3000); }
+    ownSaveRef.current = { saving: true };
+    try {
+      const updatedAt = await saveWorkspace(workspace);
+      ownSaveRef.current = { saving: false, updatedAt };
+      setExternalChange(null);
+      setSaveStatus('saved'); setTimeout(()=>setSaveStatus('idle'),2000);
+    }
+    catch (e) { console.error(errorMessage(e)); ownSaveRef.current = { saving: false }; setSaveStatus('error'); setTimeout(()=>setSaveStatus('idle'),3000); }
+  };
+
+  /** Replace the editor state with the stored copy after an outside change */
+  const handleReloadWorkspace = async () => {
+    const saved = await getWorkspace(workspace.id);
+    setExternalChange(null);
+    if (!saved) return;
+    const widgetIndex = workspace.widgets.findIndex(w => w.id === activeWidgetId);
+    setWorkspace(saved.config);
+    setActiveWidgetId(saved.config.widgets.find(w => w.id === activeWidgetId)?.id ?? saved.config.widgets[Math.max(0, widgetIndex)]?.id ?? '');
+  };
+
+  /** Save the current state as a new workspace and switch to it; the opened one is left as stored */
+  const handleSaveCopy = async () => {
+    const name = window.prompt('Name for the copy', `${workspace.name} (copy)`);
+    if (!name) return;
+    setSaveStatus('saving');
+    try {
+      const id = await saveWorkspaceAs(workspace, name);
+      const saved = await getWorkspace(id);
+      if (saved) {
+        const widgetIndex = workspace.widgets.findIndex(w => w.id === activeWidgetId);
+        setWorkspace(saved.config);
+        setActiveWidgetId(saved.config.widgets[Math.max(0, widgetIndex)]?.id ?? '');
+      }
+      setSaveStatus('saved'); setTimeout(()=>setSaveStatus('idle'),2000);
+    }
+    catch (e) { console.error(errorMessage(e)); setSaveStatus('error'); setTimeout(()=>setSaveStatus('idle'),3000); }
   };
 
   // ── Layer Reordering & Drag/Drop ─────────────────────────────────────
@@ -1090,12 +1602,16 @@ export default function Designer() {
   const renderElements = (elements: OverlayElement[], containerW: number, containerH: number, inGroup = false, overrideTime?: number, isGhost = false): React.ReactNode[] => {
     const timeToUse = overrideTime !== undefined ? overrideTime : currentTime;
     return elements.filter(el => el.visible !== false).sort((a,b)=>a.zIndex-b.zIndex).map(rawEl => {
-      // Apply keyframe interpolation during playback, scrubbing, or keyframe preview
-      let el = rawEl;
+      // Style preset underneath, then keyframe interpolation during playback, scrubbing, or keyframe preview
+      let el = resolveStylePreset(rawEl, workspace.stylePresets);
+      if (el.type === 'text' && el.autoContrast) {
+        const color = contrastingText(el.backdropColor?.trim() || (activeBg.src ? undefined : activeBg.color));
+        if (color) el = { ...el, color };
+      }
       if ((shouldAnimate || overrideTime !== undefined) && timeline.keyframes.length >= 1) {
-        const overrides = interpolateElementFromGlobal(timeline.keyframes, rawEl.id, rawEl, timeToUse);
+        const overrides = lockAspect(el, interpolateElementFromGlobal(timeline.keyframes, el.id, el, timeToUse));
         if (Object.keys(overrides).length > 0) {
-          el = { ...rawEl, ...overrides as any };
+          el = { ...el, ...overrides as any };
         }
       }
 
@@ -1114,6 +1630,7 @@ export default function Designer() {
         filter: `blur(${el.blur||0}px) brightness(${el.brightness||100}%) contrast(${el.contrast||100}%) hue-rotate(${el.hueRotate||0}deg) saturate(${el.saturate||100}%)`,
         mixBlendMode: el.blendMode !== 'normal' ? el.blendMode as any : undefined,
         ...(hasScale ? { transform: `scale(${sx}, ${sy})` } : {}),
+        ...(el.clipShape ? { clipPath: clipPathOf(el) } : {}),
         ...maskParams,
       };
 
@@ -1133,7 +1650,11 @@ export default function Designer() {
               {/* group outline in designer */}
               <div className={cn("absolute inset-0 pointer-events-none border border-dashed rounded-[inherit]", el.type === 'mask' ? "border-teal-500/30" : "border-purple-500/30", isGhost ? "opacity-0" : "")} />
               <div className={cn("absolute top-0 left-0 text-white text-[9px] px-1 rounded-br pointer-events-none z-50", el.type === 'mask' ? "bg-teal-500/70" : "bg-purple-500/70", isGhost ? "opacity-0" : "")}>{el.name}</div>
-              {inGroup ? null : renderElements(el.children ?? [], el.width, el.height, true, overrideTime, isGhost)}
+              {inGroup ? null : el.fitChildren && rawEl.width > 0 && rawEl.height > 0 && (el.width !== rawEl.width || el.height !== rawEl.height)
+                ? <div className="absolute left-0 top-0" style={{ width: rawEl.width, height: rawEl.height, transformOrigin: '0 0', transform: `scale(${el.width / rawEl.width}, ${el.height / rawEl.height})` }}>
+                    {renderElements(el.children ?? [], rawEl.width, rawEl.height, true, overrideTime, isGhost)}
+                  </div>
+                : renderElements(el.children ?? [], el.width, el.height, true, overrideTime, isGhost)}
             </div>
           </TransformBox>
         );
@@ -1170,6 +1691,42 @@ export default function Designer() {
 
       {/* ── Left: Widget List + Toolbar ── */}
       <div className="w-56 bg-[#0E0E11] border-r border-white/5 flex flex-col shrink-0 z-40 shadow-2xl">
+        {/* Workspace canvas */}
+        <div className="p-4 border-b border-white/5">
+          <div className="text-[10px] uppercase font-semibold tracking-widest text-white/40 mb-3">Canvas</div>
+          <div className="flex items-center gap-2 text-sm text-white/40 font-medium">
+            <DimInput value={canvasWidth} min={1} max={7680} onCommit={v => setCanvasSize(v, canvasHeight)} />
+            <span>×</span>
+            <DimInput value={canvasHeight} min={1} max={4320} onCommit={v => setCanvasSize(canvasWidth, v)} />
+          </div>
+          <select value={safePreset} onChange={e => setSafeAreaPercent(+e.target.value)}
+            className="w-full mt-2 bg-white/5 rounded-lg px-2 py-1 text-xs text-white/70 border border-white/5 outline-none focus:border-white/20 transition-colors">
+            <option value="0">No safe area</option>
+            <option value="5">Action-safe (5%)</option>
+            <option value="10">Title-safe (10%)</option>
+            {safePreset === 'custom' && <option value="custom" disabled>Custom</option>}
+          </select>
+          <label className="flex items-center gap-2 mt-2 text-xs text-white/50"
+            title="Stack elements by zIndex across groups on the OBS page. Groups stop isolating blend modes, and transformed, faded or masked groups still stack as one layer. The editor canvas keeps stacking per group.">
+            <input type="checkbox" checked={!!workspace.flatZIndex}
+              onChange={e => setWorkspace(ws => ({ ...ws, flatZIndex: e.target.checked || undefined }))}
+              className="accent-amber-500 w-3 h-3"/>
+            Global z-order
+          </label>
+          <div className="flex items-center gap-2 mt-2" title="Looping music for this workspace, played by its own browser source (the audio URL in the OBS Link panel)">
+            <Music size={12} className="text-white/40 shrink-0"/>
+            <LiveText syncKey={workspace.id} value={workspace.audioTrack||''}
+              onChange={v => setWorkspace(ws => ({ ...ws, audioTrack: v.trim() || undefined }))}
+              placeholder="Audio track URL"
+              className="flex-1 min-w-0 bg-white/5 rounded-lg px-2 py-1 text-xs text-white/70 border border-white/5 outline-none focus:border-white/20 transition-colors"/>
+          </div>
+          {workspace.audioTrack && (
+            <input type="range" min={0} max={1} step={0.05} value={workspace.audioVolume ?? 1} title={`Volume ${Math.round((workspace.audioVolume ?? 1) * 100)}%`}
+              onChange={e => setWorkspace(ws => ({ ...ws, audioVolume: +e.target.value }))}
+              className="w-full mt-2 accent-amber-500"/>
+          )}
+        </div>
+
         {/* Widget tabs */}
         <div className="p-4 border-b border-white/5">
           <div className="text-[10px] uppercase font-semibold tracking-widest text-white/40 mb-3">Widgets</div>
@@ -1222,6 +1779,8 @@ export default function Designer() {
           </div>
           <button onClick={()=>addEl(newElement('text',{name:'Text Element'}))} className="w-full flex items-center gap-2.5 px-3 py-2 rounded-xl hover:bg-white/5 text-sm font-medium text-white/60 hover:text-white transition-colors"><Type size={16}/>Text</button>
           <button onClick={()=>addEl(newElement('image',{name:'Image',fill:'transparent'}))} className="w-full flex items-center gap-2.5 px-3 py-2 rounded-xl hover:bg-white/5 text-sm font-medium text-white/60 hover:text-white transition-colors"><ImageIcon size={16}/>Image</button>
+          <button onClick={()=>addEl(newElement('video',{name:'Video',fill:'transparent',width:320,height:180}))} className="w-full flex items-center gap-2.5 px-3 py-2 rounded-xl hover:bg-white/5 text-sm font-medium text-white/60 hover:text-white transition-colors"><Film size={16}/>Video</button>
+          <button onClick={()=>addEl(newElement('sprite',{name:'Sprite',fill:'transparent',loop:true}))} className="w-full flex items-center gap-2.5 px-3 py-2 rounded-xl hover:bg-white/5 text-sm font-medium text-white/60 hover:text-white transition-colors"><Clapperboard size={16}/>Sprite</button>
           
           <button onClick={()=>{setActiveTool(t=>t==='curvature'?'select':'curvature');}} className={cn("w-full flex items-center gap-2.5 px-3 py-2 rounded-xl text-sm font-medium transition-colors",activeTool==='curvature'?"bg-blue-500/20 text-blue-400":"hover:bg-white/5 text-white/60 hover:text-white")}><PenTool size={16}/>Curvature Pen</button>
           <button onClick={()=>{setActiveTool(t=>t==='pencil'?'select':'pencil');}} className={cn("w-full flex items-center gap-2.5 px-3 py-2 rounded-xl text-sm font-medium transition-colors",activeTool==='pencil'?"bg-blue-500/20 text-blue-400":"hover:bg-white/5 text-white/60 hover:text-white")}><Pencil size={16}/>Pencil</button>
@@ -1301,6 +1860,25 @@ export default function Designer() {
             {saveStatus==='saved'?<CheckCheck size={16}/>:<Save size={16}/>}
             {saveStatus==='saving'?'Saving…':saveStatus==='saved'?'Saved':saveStatus==='error'?'Error':'Save Workspace'}
           </button>
+          <button onClick={handleSaveCopy} disabled={saveStatus==='saving'}
+            className="w-full flex items-center justify-center gap-2 px-3 py-1.5 mt-1 rounded-xl text-xs font-medium text-white/50 hover:text-white hover:bg-white/5 transition-colors">
+            <Copy size={14}/>Save a Copy
+          </button>
+          {externalChange && (
+            <div className="mt-2 px-3 py-2 rounded-xl bg-amber-500/10 border border-amber-500/20 text-xs text-amber-300 space-y-1.5">
+              <div>{externalChange === 'deleted'
+                ? 'This workspace was deleted outside the editor; saving will recreate it.'
+                : 'This workspace was changed outside the editor.'}</div>
+              <div className="flex gap-2">
+                {externalChange === 'updated' && (
+                  <button onClick={()=>handleReloadWorkspace().catch(console.error)}
+                    className="px-2 py-0.5 rounded bg-white/10 hover:bg-white/20 transition-colors">Reload</button>
+                )}
+                <button onClick={()=>setExternalChange(null)}
+                  className="px-2 py-0.5 rounded bg-white/10 hover:bg-white/20 transition-colors">Dismiss</button>
+              </div>
+            </div>
+          )}
         </div>
       </div>
 
@@ -1318,7 +1896,12 @@ export default function Designer() {
           <button onClick={() => setShowGrid(g => !g)} className={cn("flex items-center gap-1.5 px-3 py-1.5 rounded-lg text-sm font-medium transition-colors ml-auto", showGrid ? "bg-blue-500/20 text-blue-400" : "bg-white/5 text-white/40 hover:text-white/80")}>
             <Grid size={14} /> Grid
           </button>
-          <select value={activeWidget.background} onChange={e=>updateWidget(activeWidgetId,{background:e.target.value})}
+          <button onClick={() => setShowSafeArea(v => !v)} disabled={!safeArea}
+            title={safeArea ? `Safe area of the ${canvasWidth}×${canvasHeight} canvas, scaled to this widget` : 'Choose a safe area under Canvas first'}
+            className={cn("flex items-center gap-1.5 px-3 py-1.5 rounded-lg text-sm font-medium transition-colors disabled:opacity-40", showSafeArea && safeArea ? "bg-amber-500/20 text-amber-400" : "bg-white/5 text-white/40 hover:text-white/80")}>
+            <Scan size={14} /> Safe
+          </button>
+          <select value={activeBg.color||'transparent'} onChange={e=>updateWidget(activeWidgetId,{background:withBg(activeWidget.background,{color:e.target.value})})}
             className="bg-white/5 rounded-lg px-3 py-1.5 text-sm font-medium text-white/70 border border-white/5 outline-none focus:border-white/20 transition-colors">
             <option value="transparent">Transparent</option>
             <option value="#00FF00">Green Screen</option>
@@ -1326,6 +1909,31 @@ export default function Designer() {
             <option value="#111111">Dark</option>
             <option value="#ffffff">White</option>
           </select>
+          <button title="Background image (drawn behind every element)"
+            onClick={async () => {
+              try {
+                const src = await pickImageFile();
+                if (src) updateWidget(activeWidgetId,{background:withBg(activeWidget.background,{src})});
+              } catch(err) { console.error('Background pick error:', err); }
+            }}
+            className={cn("flex items-center gap-1.5 px-3 py-1.5 rounded-lg text-sm font-medium transition-colors", activeBg.src ? "bg-blue-500/20 text-blue-400" : "bg-white/5 text-white/40 hover:text-white/80")}>
+            <ImageIcon size={14} /> BG
+          </button>
+          {activeBg.src && <>
+            <select value={activeBg.size||'cover'} onChange={e=>updateWidget(activeWidgetId,{background:withBg(activeWidget.background,{size:e.target.value as WidgetBackground['size']})})}
+              className="bg-white/5 rounded-lg px-2 py-1.5 text-sm font-medium text-white/70 border border-white/5 outline-none focus:border-white/20 transition-colors">
+              <option value="cover">Cover</option>
+              <option value="contain">Contain</option>
+              <option value="stretch">Stretch</option>
+              <option value="auto">Original size</option>
+            </select>
+            <select value={activeBg.position||'center'} onChange={e=>updateWidget(activeWidgetId,{background:withBg(activeWidget.background,{position:e.target.value})})}
+              className="bg-white/5 rounded-lg px-2 py-1.5 text-sm font-medium text-white/70 border border-white/5 outline-none focus:border-white/20 transition-colors">
+              {['center','top','bottom','left','right','top left','top right','bottom left','bottom right'].map(p=><option key={p} value={p}>{p}</option>)}
+            </select>
+            <button title="Remove the background image" onClick={()=>updateWidget(activeWidgetId,{background:withBg(activeWidget.background,{src:undefined})})}
+              className="p-1.5 rounded-lg bg-white/5 text-white/40 hover:text-red-400 transition-colors"><X size={14}/></button>
+          </>}
           <div className="text-[10px] text-white/25 uppercase tracking-wider font-semibold">OBS: {activeWidget.width}w</div>
         </div>
 
@@ -1342,8 +1950,81 @@ export default function Designer() {
                     {urlCopied?<CheckCheck size={14} className="text-emerald-400"/>:<Copy size={14} className="text-white/40 hover:text-white"/>}
                   </button>
                 </div>
-                <span className="text-xs font-medium text-white/30 shrink-0">{activeWidget.width}×{activeWidget.height} native · live reload</span>
+                <label className="flex items-center gap-1 text-xs font-mono text-white/40 shrink-0" title="Readable URL: /w/<slug> (unique, lowercase letters, digits and hyphens)">
+                  /w/
+                  <input value={activeWidget.slug??''} placeholder="slug"
+                    onChange={e=>updateWidget(activeWidgetId,{slug:e.target.value.toLowerCase().replace(/[^a-z0-9-]+/g,'-').slice(0,64)||undefined})}
+                    className="w-32 bg-black/40 rounded-md px-2 py-1 text-white/70 border border-purple-500/20 outline-none focus:border-purple-500/50"/>
+                </label>
+                <span className="text-xs font-medium text-white/30 shrink-0">
+                  {(activeWidget.pixelRatio ?? 1) > 1
+                    ? `${Math.round(activeWidget.width * activeWidget.pixelRatio!)}×${Math.round(activeWidget.height * activeWidget.pixelRatio!)} source`
+                    : `${activeWidget.width}×${activeWidget.height} native`} · live reload
+                </span>
+                <select value={activeWidget.pixelRatio ?? 1}
+                  onChange={e => updateWidget(activeWidgetId, { pixelRatio: +e.target.value === 1 ? undefined : +e.target.value })}
+                  title="Render at a higher resolution so text stays sharp when the source is scaled up; size the browser source to match"
+                  className="bg-black/40 rounded-md px-1.5 py-1 text-xs text-white/70 border border-purple-500/20 outline-none shrink-0">
+                  {PIXEL_RATIO_CHOICES.map(r => <option key={r} value={r}>{r}×</option>)}
+                  {!PIXEL_RATIO_CHOICES.includes(activeWidget.pixelRatio ?? 1) && <option value={activeWidget.pixelRatio}>{activeWidget.pixelRatio}×</option>}
+                </select>
+                <select value={activeWidget.pollInterval ?? 2}
+                  onChange={e => updateWidget(activeWidgetId, { pollInterval: +e.target.value === 2 ? undefined : +e.target.value })}
+                  title="How often OBS checks for edits; a slower check suits overlays that rarely change"
+                  className="bg-black/40 rounded-md px-1.5 py-1 text-xs text-white/70 border border-purple-500/20 outline-none shrink-0">
+                  {POLL_CHOICES.map(s => <option key={s} value={s}>every {s}s</option>)}
+                  {!POLL_CHOICES.includes(activeWidget.pollInterval ?? 2) && <option value={activeWidget.pollInterval}>every {activeWidget.pollInterval}s</option>}
+                </select>
+                {serverStatus && (
+                  <span className={cn("flex items-center gap-1.5 text-xs font-mono shrink-0", serverStatus.running ? "text-emerald-300/70" : "text-red-300")}
+                    title={serverStatus.last_error ?? (serverStatus.bound_addr ? `Listening on ${serverStatus.bound_addr}${serverStatus.lan_enabled ? ' (LAN)' : ''}` : undefined)}>
+                    <span className={cn("w-1.5 h-1.5 rounded-full", serverStatus.running ? "bg-emerald-400" : "bg-red-400")}/>
+                    {serverStatus.running ? `Server: running on :${serverStatus.port}`
+                      : serverStatus.phase === 'starting' || serverStatus.phase === 'stopping' ? 'Server: restarting…'
+                      : serverStatus.safe_mode ? 'Server: off (safe mode)' : 'Server: stopped'}
+                  </span>
+                )}
+                {serverStatus && serverStatus.phase === 'stopped' && (
+                  <button onClick={() => startObsServer().catch(e => console.error(errorMessage(e)))}
+                    title="Start the OBS server on port 7878"
+                    className="px-2 py-1 rounded-md text-xs text-white/70 bg-white/5 hover:bg-white/10 border border-purple-500/20 shrink-0">
+                    Start
+                  </button>
+                )}
+              </div>
+              <div className="px-6 pb-3 flex items-center gap-3 text-xs text-white/40"
+                title="Chat bots POST {&quot;type&quot;: &quot;raid&quot;} to /inbox with the token; this widget plays for the event types listed">
+                <span className="font-mono shrink-0">POST /inbox</span>
+                <LiveText syncKey={activeWidgetId}
+                  value={(activeWidget.inbox ?? []).filter(r => (r.action ?? 'trigger') === 'trigger').map(r => r.event).join(', ')}
+                  onChange={v => {
+                    const kept = (activeWidget.inbox ?? []).filter(r => (r.action ?? 'trigger') !== 'trigger');
+                    const rules = [...kept, ...v.split(',').map(t => t.trim()).filter(Boolean).map(event => ({ event }))];
+                    updateWidget(activeWidgetId, { inbox: rules.length ? rules : undefined });
+                  }}
+                  placeholder="event types that play this widget, e.g. !hydrate, raid"
+                  className="flex-1 bg-black/40 rounded-md px-2 py-1 font-mono text-white/70 border border-purple-500/20 outline-none focus:border-purple-500/50"/>
+                <button onClick={async()=>{await navigator.clipboard.writeText(await getInboxToken());setTokenCopied(true);setTimeout(()=>setTokenCopied(false),2000);}}
+                  className="shrink-0 px-2 py-0.5 rounded bg-white/10 hover:bg-white/20 transition-colors">{tokenCopied?'Copied':'Copy token'}</button>
               </div>
+              {workspace.audioTrack && widgetObsUrl && (
+                <div className="px-6 pb-3 flex items-center gap-3 text-xs text-white/40"
+                  title="Add as its own browser source with 'Control audio via OBS' ticked; append ?mute=1 to preview it silently in a browser">
+                  <Music size={12} className="shrink-0"/>
+                  <span className="font-mono truncate flex-1">{`${new URL(widgetObsUrl).origin}/workspace/${workspace.id}/audio`}</span>
+                  <button onClick={()=>navigator.clipboard.writeText(`${new URL(widgetObsUrl).origin}/workspace/${workspace.id}/audio`).catch(console.error)}
+                    className="shrink-0 px-2 py-0.5 rounded bg-white/10 hover:bg-white/20 transition-colors">Copy audio URL</button>
+                </div>
+              )}
+              {widgetError && (
+                <div className="px-6 pb-3 flex items-center gap-3 text-xs text-red-300">
+                  <span className="truncate flex-1 font-mono" title={widgetError.stack ?? widgetError.message}>
+                    This widget errored in OBS: {widgetError.message}{widgetError.line ? ` (line ${widgetError.line}${widgetError.column ? `:${widgetError.column}` : ''})` : ''}
+                  </span>
+                  <button onClick={()=>clearWidgetErrors(activeWidgetId).then(()=>setWidgetError(null)).catch(console.error)}
+                    className="shrink-0 px-2 py-0.5 rounded bg-white/10 hover:bg-white/20 transition-colors">Dismiss</button>
+                </div>
+              )}
             </motion.div>
           )}
         </AnimatePresence>
@@ -1355,11 +2036,7 @@ export default function Designer() {
             style={{
               width:activeWidget.width, height:activeWidget.height,
               transform:`scale(${scale})`, transformOrigin:'center center',
-              background: activeWidget.background==='transparent'
-                ? 'linear-gradient(45deg,#111 25%,transparent 25%),linear-gradient(-45deg,#111 25%,transparent 25%),linear-gradient(45deg,transparent 75%,#111 75%),linear-gradient(-45deg,transparent 75%,#111 75%)'
-                : activeWidget.background,
-              backgroundSize: activeWidget.background==='transparent' ? '24px 24px' : undefined,
-              backgroundPosition: activeWidget.background==='transparent' ? '0 0,0 12px,12px -12px,-12px 0' : undefined,
+              ...canvasBackground(activeBg),
               cursor: isDrawing ? 'crosshair' : 'default',
               position:'relative',
             }}
@@ -1399,6 +2076,15 @@ export default function Designer() {
                 <div className="absolute top-1/2 left-0 right-0 h-px bg-blue-500/30 -translate-y-px" />
               </div>
             )}
+            {showSafeArea && safeArea && (
+              <div className="absolute pointer-events-none z-0 border border-dashed border-amber-400/60"
+                style={{
+                  left: safeArea.left * activeWidget.width / canvasWidth,
+                  right: safeArea.right * activeWidget.width / canvasWidth,
+                  top: safeArea.top * activeWidget.height / canvasHeight,
+                  bottom: safeArea.bottom * activeWidget.height / canvasHeight,
+                }} />
+            )}
             {/* Explicit SVGs Masks */}
             <svg width="0" height="0" className="absolute pointer-events-none">
               <defs>
@@ -1480,7 +2166,7 @@ export default function Designer() {
                     left: el.x, top: el.y,
                     width: el.width, height: el.height,
                     transform: `rotate(${el.rotation || 0}deg)`,
-                    transformOrigin: 'center center',
+                    transformOrigin: transformOriginOf(el),
                   }}
                 >
                   {/* Stem line */}
@@ -1495,8 +2181,9 @@ export default function Designer() {
                       setRotatingId(el.id);
                       const rect = canvasRef.current?.getBoundingClientRect();
                       if (!rect) return;
-                      const elCx = rect.left + (el.x + el.width / 2) * scale;
-                      const elCy = rect.top + (el.y + el.height / 2) * scale;
+                      const pivot = originPoint(el);
+                      const elCx = rect.left + (el.x + pivot.x) * scale;
+                      const elCy = rect.top + (el.y + pivot.y) * scale;
 
                       const onMove = (ev: MouseEvent) => {
                         const dx = ev.clientX - elCx;
@@ -1568,6 +2255,36 @@ export default function Designer() {
                   <Repeat size={14} />
                 </button>
 
+                <select value={segment?.name ?? ''}
+                  onChange={e => {
+                    if (e.target.value === NEW_SEGMENT) { addSegment(); return; }
+                    stopAnimation();
+                    setSelectedKeyframeId(null);
+                    setEditingSegment(e.target.value || null);
+                  }}
+                  title="Segments are named sub-timelines with their own clock, started over the control API"
+                  className="ml-2 bg-white/5 rounded px-1.5 py-1 text-xs text-white border border-white/10 outline-none max-w-[9rem]">
+                  <option value="">Main timeline</option>
+                  {(mainTimeline.segments ?? []).map(s => <option key={s.name} value={s.name}>{s.name}</option>)}
+                  <option value={NEW_SEGMENT}>+ New segment…</option>
+                </select>
+                {segment && (
+                  <>
+                    <select value={segment.then ?? ''} onChange={e => setSegmentProp({ then: e.target.value || undefined })}
+                      disabled={!!segment.loop}
+                      title="Segment to start when this one ends"
+                      className="bg-white/5 rounded px-1.5 py-1 text-xs text-white border border-white/10 outline-none disabled:opacity-40 max-w-[8rem]">
+                      <option value="">Then: hold</option>
+                      {(mainTimeline.segments ?? []).filter(s => s.name !== segment.name).map(s => (
+                        <option key={s.name} value={s.name}>Then: {s.name}</option>
+                      ))}
+                    </select>
+                    <button onClick={deleteSegment} className="p-1.5 rounded-md hover:bg-red-500/20 text-white/30 hover:text-red-300 transition-colors" title="Delete segment">
+                      <Trash2 size={12} />
+                    </button>
+                  </>
+                )}
+
                 <div className="flex items-center gap-1.5 px-3 py-1 bg-white/5 rounded-lg ml-2">
                   <span className="text-[11px] font-mono text-amber-300 w-12 text-right">{currentTime.toFixed(2)}s</span>
                   <span className="text-[10px] text-white/20">/</span>
@@ -1590,11 +2307,38 @@ export default function Designer() {
                     <option value={1.5}>1.5×</option>
                     <option value={2}>2×</option>
                   </select>
+                  {!segment && <>
+                  <span className="text-white/30 uppercase tracking-wider ml-2">FPS</span>
+                  <select value={timeline.maxFps ?? 0} onChange={e => setTimelineProp({ maxFps: +e.target.value || undefined })}
+                    title="Frame-rate cap in OBS, to save CPU for encoding"
+                    className="bg-white/5 rounded px-1.5 py-1 text-xs text-white border border-white/10 outline-none">
+                    <option value={0}>Max</option>
+                    <option value={60}>60</option>
+                    <option value={30}>30</option>
+                    <option value={24}>24</option>
+                    <option value={15}>15</option>
+                  </select>
+                  <span className="text-white/30 uppercase tracking-wider ml-2">Hide after</span>
+                  <input type="number" min={0} max={600} step={0.5} placeholder="never"
+                    value={timeline.hideAfter ?? ''}
+                    onChange={e => setTimelineProp({ hideAfter: e.target.value === '' ? undefined : Math.max(0, +e.target.value) })}
+                    title="Seconds to hold the end of the animation before the widget hides in OBS (empty = stay on screen)"
+                    className="w-14 bg-white/5 rounded px-1.5 py-1 text-xs text-white border border-white/10 outline-none text-center"
+                  />
+                  </>}
                   <label className="flex items-center gap-1 ml-2 cursor-pointer">
                     <input type="checkbox" checked={timeline.autoplay} onChange={e => setTimelineProp({ autoplay: e.target.checked })}
                       className="accent-amber-500 w-3 h-3" />
                     <span className="text-white/40">Autoplay</span>
                   </label>
+                  {!segment && timeline.autoplay && timeline.loop && (
+                    <label className="flex items-center gap-1 ml-2 cursor-pointer"
+                      title="Keep this loop in step with other widgets' loops in OBS, using the server clock">
+                      <input type="checkbox" checked={!!timeline.syncClock} onChange={e => setTimelineProp({ syncClock: e.target.checked || undefined })}
+                        className="accent-amber-500 w-3 h-3" />
+                      <span className="text-white/40">Sync</span>
+                    </label>
+                  )}
                 </div>
               </div>
 
@@ -1776,6 +2520,11 @@ export default function Designer() {
                 moveLayer={moveLayer} removeFromGroup={removeFromGroup}
               />
             : <PropertiesPanel selected={selected} allElements={allElements} onUpdate={updateEl} onDelete={deleteEl}
+                stylePresets={workspace.stylePresets}
+                onSetStylePreset={setElementStylePreset}
+                onCreateStylePreset={createStylePreset}
+                onPushStylePreset={pushToStylePreset}
+                onDeleteStylePreset={deleteStylePreset}
                 onDuplicate={(id: string)=>{
                   const el=findElement(activeWidget.elements,id);
                   if(el) addEl({...el,id:uuidv4(),name:el.name+' copy',x:el.x+20,y:el.y+20});
@@ -1995,6 +2744,8 @@ function LayerIcon({ type }: { type: ElementType; }) {
   if (type==='text') return <Type size={12} className="text-white/40 shrink-0"/>;
   if (type==='shape') return <Square size={12} className="text-white/40 shrink-0"/>;
   if (type==='image') return <ImageIcon size={12} className="text-white/40 shrink-0"/>;
+  if (type==='video') return <Film size={12} className="text-white/40 shrink-0"/>;
+  if (type==='sprite') return <Clapperboard size={12} className="text-white/40 shrink-0"/>;
   if (type==='path') return <PenTool size={12} className="text-white/40 shrink-0"/>;
   return null;
 }
@@ -2095,7 +2846,10 @@ function Num({el,set,k,label,min,max,step=1}:{el:any;set:(up:any)=>void;k:string
   );
 }
 
-function PropertiesPanel({ selected, allElements, onUpdate, onDelete, onDuplicate, showTimeline, currentTime, timeline, onAddGlobalKeyframe, onDeleteGlobalKeyframe, onUpdateGlobalKeyframeEasing }: any) {
+/** Style select value that starts a new preset instead of picking one */
+const NEW_PRESET = '\u0000new';
+
+function PropertiesPanel({ selected, allElements, onUpdate, onDelete, onDuplicate, stylePresets, onSetStylePreset, onCreateStylePreset, onPushStylePreset, onDeleteStylePreset, showTimeline, currentTime, timeline, onAddGlobalKeyframe, onDeleteGlobalKeyframe, onUpdateGlobalKeyframeEasing }: any) {
   if (!selected) return (
     <div className="flex flex-col items-center justify-center h-full text-center gap-2 opacity-20 p-6">
       <Settings size={32}/><p className="text-xs">Select an element</p>
@@ -2111,6 +2865,31 @@ function PropertiesPanel({ selected, allElements, onUpdate, onDelete, onDuplicat
           <LiveText syncKey={el.id} value={el.name} onChange={v=>set({name:v})}
             className="flex-1 bg-[#222] rounded px-2 py-1 text-xs border-none outline-none"/>
         </Row>
+        <Row label="Style">
+          <select value={el.stylePreset??''} onChange={e=>{
+              const v = e.target.value;
+              if (v !== NEW_PRESET) { onSetStylePreset(el.id, v || null); return; }
+              const name = window.prompt('Name for the style preset', el.name)?.trim().slice(0, 64);
+              if (!name || (stylePresets?.[name] && !window.confirm(`Replace the '${name}' preset?`))) return;
+              onCreateStylePreset(el.id, name);
+            }}
+            title="Shared properties from a workspace style preset; this element's own settings win"
+            className="flex-1 bg-[#222] rounded px-2 py-1 text-xs border-none outline-none">
+            <option value="">None</option>
+            {Object.keys(stylePresets ?? {}).map(n=><option key={n} value={n}>{n}</option>)}
+            <option value={NEW_PRESET}>New preset from this…</option>
+          </select>
+        </Row>
+        {el.stylePreset && stylePresets?.[el.stylePreset] && (
+          <div className="flex gap-1.5">
+            <button onClick={()=>onPushStylePreset(el.id)}
+              title="Copy this element's values for the preset's properties into the preset, restyling every element that uses it"
+              className="flex-1 py-1.5 rounded text-[11px] bg-white/10 hover:bg-white/20">Update preset</button>
+            <button onClick={()=>onDeleteStylePreset(el.stylePreset)}
+              title="Delete the preset; elements using it keep its look"
+              className="flex-1 py-1.5 rounded text-[11px] bg-red-500/20 text-red-400 hover:bg-red-500/30">Delete preset</button>
+          </div>
+        )}
         <div className="flex gap-1.5">
           <button onClick={()=>set({visible:!el.visible})} className={cn("flex-1 py-1.5 rounded text-[11px] flex items-center justify-center gap-1",el.visible?"bg-white/10":"bg-red-500/20 text-red-400")}>
             {el.visible?<Eye size={12}/>:<EyeOff size={12}/>}{el.visible?'Visible':'Hidden'}
@@ -2125,10 +2904,17 @@ function PropertiesPanel({ selected, allElements, onUpdate, onDelete, onDuplicat
         <div className="grid grid-cols-2 gap-2">
           {([['x','X'],['y','Y'],['width','W'],['height','H']] as [keyof OverlayElement,string][]).map(([k,l])=>(
             <div key={k as string}><Row label={l}>
-              <DimInput value={(el as any)[k]??0} min={-9999} max={99999} onCommit={v=>set({[k]:v})}/>
+              <DimInput value={(el as any)[k]??0} min={-9999} max={99999} onCommit={v=>set(
+                el.lockAspect && el.width > 0 && el.height > 0 && k === 'width' ? {width:v,height:Math.round(v*el.height/el.width)}
+                : el.lockAspect && el.width > 0 && el.height > 0 && k === 'height' ? {height:v,width:Math.round(v*el.width/el.height)}
+                : {[k]:v})}/>
             </Row></div>
           ))}
         </div>
+        <Row label="Lock ratio">
+          <input type="checkbox" checked={!!el.lockAspect} onChange={e=>set({lockAspect:e.target.checked||undefined})}
+            className="accent-teal-500 w-3 h-3"/>
+        </Row>
         <Row label="Rotate°">
           <div className="flex items-center gap-2 flex-1">
             <input type="range" min={-180} max={180} step={1} value={el.rotation||0}
@@ -2136,6 +2922,49 @@ function PropertiesPanel({ selected, allElements, onUpdate, onDelete, onDuplicat
             <DimInput value={el.rotation||0} min={-360} max={360} onCommit={v=>set({rotation:v})}/>
           </div>
         </Row>
+        <Row label="Pivot">
+          <div className="flex items-center gap-2 flex-1">
+            <select value={ORIGIN_PRESETS.includes(transformOriginOf(el)) ? transformOriginOf(el) : ''}
+              onChange={e=>set({transformOrigin:e.target.value==='center center'?undefined:e.target.value||el.transformOrigin})}
+              className="flex-1 bg-[#222] rounded px-1 py-1 text-[10px] border-none outline-none">
+              {ORIGIN_PRESETS.map(p=><option key={p} value={p}>{p}</option>)}
+              <option value="">Custom</option>
+            </select>
+            <LiveText syncKey={`${el.id}:${el.transformOrigin??''}`} value={el.transformOrigin||''} onChange={v=>set({transformOrigin:v||undefined})}
+              placeholder="50% 50%"
+              className={cn('w-20 bg-[#222] rounded px-2 py-1 text-[10px] font-mono border-none outline-none',
+                el.transformOrigin && !ORIGIN_RE.test(el.transformOrigin) && 'text-red-400')}/>
+          </div>
+        </Row>
+        <Row label="Clip">
+          <div className="flex items-center gap-2 flex-1">
+            <select value={el.clipShape??''} onChange={e=>set({clipShape:(e.target.value||undefined) as ClipShape|undefined})}
+              className="flex-1 bg-[#222] rounded px-1 py-1 text-[10px] border-none outline-none">
+              <option value="">None</option>
+              {CLIP_SHAPES.map(c=><option key={c} value={c}>{c}</option>)}
+            </select>
+            {el.clipShape && el.clipShape !== 'polygon' && (
+              <DimInput value={el.clipSize ?? (el.clipShape === 'inset' ? 10 : 50)} min={0} max={el.clipShape === 'inset' ? 50 : 200}
+                onCommit={v=>set({clipSize:v})}/>
+            )}
+          </div>
+        </Row>
+        {(el.clipShape === 'circle' || el.clipShape === 'ellipse') && (
+          <Row label="Clip at %">
+            <div className="flex items-center gap-2 flex-1">
+              <DimInput value={el.clipX ?? 50} min={-100} max={200} onCommit={v=>set({clipX:v})}/>
+              <DimInput value={el.clipY ?? 50} min={-100} max={200} onCommit={v=>set({clipY:v})}/>
+            </div>
+          </Row>
+        )}
+        {el.clipShape === 'polygon' && (
+          <Row label="Points">
+            <LiveText syncKey={`${el.id}:${el.clipPoints??''}`} value={el.clipPoints||''} onChange={v=>set({clipPoints:v||undefined})}
+              placeholder="0% 0%, 100% 0%, 50% 100%"
+              className={cn('flex-1 bg-[#222] rounded px-2 py-1 text-[10px] font-mono border-none outline-none',
+                !!el.clipPoints && !CLIP_POINTS_RE.test(el.clipPoints) && 'text-red-400')}/>
+          </Row>
+        )}
         <Row label="Opacity">
           <div className="flex items-center gap-2 flex-1">
             <input type="range" min={0} max={1} step={0.01} value={el.opacity}
@@ -2146,7 +2975,7 @@ function PropertiesPanel({ selected, allElements, onUpdate, onDelete, onDuplicat
         <Row label="Blend">
           <select value={el.blendMode??'normal'} onChange={e=>set({blendMode:e.target.value as BlendMode})}
             className="bg-[#222] rounded px-2 py-1 text-xs border-none outline-none">
-            {['normal','multiply','screen','overlay','darken','lighten','color-dodge','color-burn','hard-light','soft-light','difference','exclusion','hue','saturation','color','luminosity'].map(m=><option key={m} value={m}>{m}</option>)}
+            {BLEND_MODES.map(m=><option key={m} value={m}>{m}</option>)}
           </select>
         </Row>
         <Row label="Explicit Mask">
@@ -2174,6 +3003,16 @@ function PropertiesPanel({ selected, allElements, onUpdate, onDelete, onDuplicat
         </Sec>
       )}
 
+      {(el.type === 'group' || el.type === 'mask') && (
+        <Sec title="Children">
+          <Row label="Fit children">
+            <input type="checkbox" checked={!!el.fitChildren} onChange={e=>set({fitChildren:e.target.checked||undefined})}
+              className="accent-purple-500 w-3 h-3"/>
+          </Row>
+          <p className="text-[10px] text-white/30 mt-1">Scale X/Y always scale the children. Keyframed width and height only resize the box, unless fit children is on: then the children stretch with it.</p>
+        </Sec>
+      )}
+
       {el.type === 'mask' && (
         <Sec title="Mask Properties">
           <Row label="Mask Type">
@@ -2195,10 +3034,23 @@ function PropertiesPanel({ selected, allElements, onUpdate, onDelete, onDuplicat
           {el.maskType==='clip'&&<Num el={el} set={set} k="clipRadius" label="Radius" min={0} max={400}/>}
           {el.maskType==='gradient'&&(<>
             <Row label="Direction">
-              <select value={el.gradientDir??'to bottom'} onChange={e=>set({gradientDir:e.target.value as GradientDir})}
+              <select value={el.gradientAngle!==undefined&&el.gradientDir!=='radial'?'angle':el.gradientDir??'to bottom'}
+                onChange={e=>e.target.value==='angle'
+                  ? set({gradientAngle:GRADIENT_ANGLES[el.gradientDir??'to bottom']??180})
+                  : set({gradientDir:e.target.value as GradientDir,gradientAngle:undefined})}
                 className="bg-[#222] rounded px-2 py-1 text-xs border-none outline-none">
                 {(['to right','to left','to bottom','to top','to bottom right','radial'] as GradientDir[]).map(d=><option key={d} value={d}>{d}</option>)}
+                <option value="angle">angle…</option>
               </select>
+              {el.gradientAngle!==undefined&&el.gradientDir!=='radial'&&(
+                <DimInput value={el.gradientAngle} min={-720} max={720} onCommit={v=>set({gradientAngle:v})}/>
+              )}
+            </Row>
+            <Row label="Stops %">
+              <div className="flex items-center gap-2 flex-1">
+                <DimInput value={el.gradientStart??0} min={-100} max={200} onCommit={v=>set({gradientStart:v||undefined})}/>
+                <DimInput value={el.gradientEnd??100} min={-100} max={200} onCommit={v=>set({gradientEnd:v===100?undefined:v})}/>
+              </div>
             </Row>
             <Row label="Start α">
               <div className="flex items-center gap-2 flex-1">
@@ -2231,6 +3083,14 @@ function PropertiesPanel({ selected, allElements, onUpdate, onDelete, onDuplicat
           <Num el={el} set={set} k="borderRadius" label="Radius" min={0} max={400}/>
           <ColorField label="Stroke" value={el.strokeColor??'transparent'} onChange={v=>set({strokeColor:v})}/>
           <Num el={el} set={set} k="strokeWidth" label="Stroke W" min={0} max={50}/>
+          <Row label="Draw on">
+            <input type="checkbox" checked={!!el.drawOn} onChange={e=>set({drawOn:e.target.checked})}
+              className="accent-amber-500 w-3 h-3"/>
+          </Row>
+          {el.drawOn&&<>
+            <Num el={el} set={set} k="drawStart" label="Draw at" min={0} step={0.1}/>
+            <Num el={el} set={set} k="drawDuration" label="Draw for" min={0} step={0.1}/>
+          </>}
         </Sec>
       )}
 
@@ -2248,7 +3108,25 @@ function PropertiesPanel({ selected, allElements, onUpdate, onDelete, onDuplicat
         <Sec title="Text">
           <LiveTextArea syncKey={el.id} value={el.content??''} onChange={v=>set({content:v})}
             className="w-full bg-[#222] rounded p-2 text-xs h-14 border-none outline-none resize-none"/>
+          {!!el.segments?.length&&(
+            <div className="text-[10px] text-white/30">Shown as {el.segments.length} styled segments instead of this text</div>
+          )}
+          {!el.numericContent&&!el.dynamic&&(
+            <div className="text-[10px] text-white/30">{'{name}'} is filled from the trigger's values; {'{name|fallback}'} shows a default</div>
+          )}
           <ColorField label="Color" value={el.color??'#ffffff'} onChange={v=>set({color:v})}/>
+          <Row label="Auto contrast">
+            <input type="checkbox" checked={!!el.autoContrast} onChange={e=>set(e.target.checked ? {autoContrast:true} : {autoContrast:undefined,backdropColor:undefined})}
+              className="accent-blue-500 w-3 h-3"/>
+          </Row>
+          {el.autoContrast&&<>
+            <Row label="Backdrop">
+              <input type="checkbox" checked={!!el.backdropColor} onChange={e=>set({backdropColor:e.target.checked?'#000000':undefined})}
+                className="accent-blue-500 w-3 h-3"/>
+            </Row>
+            {el.backdropColor&&<ColorField label="Behind" value={el.backdropColor} onChange={v=>set({backdropColor:v})}/>}
+            <div className="text-[10px] text-white/30">Black or white, whichever reads better on {el.backdropColor ? 'this backdrop colour' : "the widget's background"}. On a transparent or image background the colour above is used.</div>
+          </>}
           <Num el={el} set={set} k="fontSize" label="Size" min={6} max={500}/>
           <Row label="Weight">
             <select value={el.fontWeight??'600'} onChange={e=>set({fontWeight:e.target.value})}
@@ -2264,7 +3142,35 @@ function PropertiesPanel({ selected, allElements, onUpdate, onDelete, onDuplicat
               ))}
             </div>
           </Row>
+          <Row label="Direction">
+            <select value={el.direction??'ltr'} onChange={e=>set({direction:e.target.value as OverlayElement['direction']})}
+              className="bg-[#222] rounded px-2 py-1 text-xs border-none outline-none">
+              <option value="ltr">Left to right</option>
+              <option value="rtl">Right to left</option>
+              <option value="auto">Auto</option>
+            </select>
+          </Row>
+          <Row label="Flow">
+            <select value={el.writingMode??'horizontal-tb'} onChange={e=>set({writingMode:e.target.value as OverlayElement['writingMode']})}
+              className="bg-[#222] rounded px-2 py-1 text-xs border-none outline-none">
+              <option value="horizontal-tb">Horizontal</option>
+              <option value="vertical-rl">Vertical (right to left)</option>
+              <option value="vertical-lr">Vertical (left to right)</option>
+            </select>
+          </Row>
           <Num el={el} set={set} k="letterSpacing" label="Spacing" min={-5} max={50} step={0.5}/>
+          <Row label="Counter">
+            <input type="checkbox" checked={!!el.numericContent} onChange={e=>set({numericContent:e.target.checked})}
+              title="Count between the numbers in this text across keyframes"
+              className="accent-amber-500 w-3 h-3"/>
+          </Row>
+          {el.numericContent&&<>
+            <Num el={el} set={set} k="decimals" label="Decimals" min={0} max={6}/>
+            <Row label="1,000s">
+              <input type="checkbox" checked={!!el.thousandsSeparator} onChange={e=>set({thousandsSeparator:e.target.checked})}
+                className="accent-amber-500 w-3 h-3"/>
+            </Row>
+          </>}
         </Sec>
       )}
 
@@ -2274,31 +3180,8 @@ function PropertiesPanel({ selected, allElements, onUpdate, onDelete, onDuplicat
           <button
             onClick={async () => {
               try {
-                const { open: openDialog } = await import('@tauri-apps/plugin-dialog');
-                const path = await openDialog({
-                  filters: [{ name: 'Images', extensions: ['png','jpg','jpeg','gif','webp','svg','bmp','avif'] }],
-                  multiple: false,
-                });
-                if (path && typeof path === 'string') {
-                  // Use plugin-fs to read the file with the temporary permission granted by plugin-dialog
-                  const { readFile } = await import('@tauri-apps/plugin-fs');
-                  const bytes = await readFile(path);
-                  
-                  // Convert bytes to base64 (handled smoothly in chunks to prevent Maximum Call Stack Size Exceeded logic)
-                  let binary = '';
-                  const chunkSize = 8192;
-                  for (let i = 0; i < bytes.length; i += chunkSize) {
-                    binary += String.fromCharCode.apply(null, Array.from(bytes.subarray(i, i + chunkSize)));
-                  }
-                  const base64 = btoa(binary);
-                  
-                  // Determine mime type
-                  const ext = path.split('.').pop()?.toLowerCase() || 'png';
-                  const typeMap: Record<string, string> = { svg: 'svg+xml', png: 'png', webp: 'webp', gif: 'gif', jpg: 'jpeg', jpeg: 'jpeg', bmp: 'bmp', avif: 'avif' };
-                  const mime = `image/${typeMap[ext] || 'png'}`;
-                  
-                  set({ src: `data:${mime};base64,${base64}` });
-                }
+                const src = await pickImageFile();
+                if (src) set({ src });
               } catch(err) { console.error('Image pick error:', err); }
             }}
             className="w-full py-2 rounded-lg bg-blue-500/15 text-blue-400 hover:bg-blue-500/25 text-xs transition-colors flex items-center justify-center gap-1.5">
@@ -2317,6 +3200,98 @@ function PropertiesPanel({ selected, allElements, onUpdate, onDelete, onDuplicat
               <option value="fill">Fill</option>
             </select>
           </Row>
+          <Row label="Focus">
+            <LiveText syncKey={el.id} value={el.objectPosition||''} onChange={v=>set({objectPosition:v||undefined})}
+              placeholder="50% 50%"
+              className="flex-1 bg-[#222] rounded px-2 py-1 text-xs border-none outline-none"/>
+          </Row>
+        </Sec>
+      )}
+
+      {/* Video */}
+      {el.type==='video'&&(
+        <Sec title="Video">
+          <Row label="URL">
+            <LiveText syncKey={el.id} value={el.src||''} onChange={v=>set({src:v})}
+              placeholder="https://…/stinger.webm"
+              className="flex-1 bg-[#222] rounded px-2 py-1 text-xs border-none outline-none"/>
+          </Row>
+          <Row label="Poster">
+            <LiveText syncKey={el.id} value={el.poster||''} onChange={v=>set({poster:v||undefined})}
+              placeholder="https://…/first-frame.png"
+              className="flex-1 bg-[#222] rounded px-2 py-1 text-xs border-none outline-none"/>
+          </Row>
+          <Row label="Fit">
+            <select value={el.objectFit||'contain'} onChange={e=>set({objectFit:e.target.value as any})}
+              className="bg-[#222] rounded px-2 py-1 text-xs border-none outline-none">
+              <option value="contain">Contain</option>
+              <option value="cover">Cover</option>
+              <option value="fill">Fill</option>
+            </select>
+          </Row>
+          <Row label="Focus">
+            <LiveText syncKey={el.id} value={el.objectPosition||''} onChange={v=>set({objectPosition:v||undefined})}
+              placeholder="50% 50%"
+              className="flex-1 bg-[#222] rounded px-2 py-1 text-xs border-none outline-none"/>
+          </Row>
+          <Row label="Loop">
+            <input type="checkbox" checked={!!el.loop} onChange={e=>set({loop:e.target.checked})}
+              className="accent-amber-500 w-3 h-3"/>
+          </Row>
+          <Row label="Muted">
+            <input type="checkbox" checked={!!el.muted} onChange={e=>set({muted:e.target.checked})}
+              className="accent-amber-500 w-3 h-3"/>
+          </Row>
+        </Sec>
+      )}
+
+      {/* Tint (images and videos) */}
+      {(el.type==='image'||el.type==='video')&&(
+        <Sec title="Tint">
+          <Row label="Tint">
+            <input type="checkbox" checked={!!el.tint} onChange={e=>set(e.target.checked ? {tint:'#9146ff'} : {tint:undefined,tintBlend:undefined,tintStrength:undefined})}
+              className="accent-amber-500 w-3 h-3"/>
+          </Row>
+          {el.tint&&<>
+            <ColorField label="Color" value={el.tint} onChange={v=>set({tint:v})}/>
+            <Row label="Blend">
+              <select value={el.tintBlend??'color'} onChange={e=>set({tintBlend:e.target.value==='color'?undefined:e.target.value as BlendMode})}
+                className="bg-[#222] rounded px-2 py-1 text-xs border-none outline-none">
+                {BLEND_MODES.map(m=><option key={m} value={m}>{m}</option>)}
+              </select>
+            </Row>
+            <Row label="Strength">
+              <input type="range" min={0} max={100} step={1} value={el.tintStrength??100} onChange={e=>set({tintStrength:+e.target.value===100?undefined:+e.target.value})} className="flex-1 accent-amber-500"/>
+              <span className="text-[10px] w-7 text-right text-white/40">{el.tintStrength??100}%</span>
+            </Row>
+          </>}
+        </Sec>
+      )}
+
+      {/* Sprite */}
+      {el.type==='sprite'&&(
+        <Sec title="Sprite">
+          <div className="text-[10px] text-white/40 mb-1">Frames, one URL per line</div>
+          <LiveTextArea syncKey={el.id} value={(el.frames??[]).join('\n')}
+            onChange={v=>{ const frames=v.split('\n').map(f=>f.trim()).filter(Boolean); set({frames:frames.length?frames:undefined}); }}
+            className="w-full bg-[#222] rounded p-2 text-xs h-14 border-none outline-none resize-none mb-1"/>
+          {!el.frames?.length&&<>
+            <Row label="Sheet">
+              <LiveText syncKey={el.id} value={el.src||''} onChange={v=>set({src:v})}
+                placeholder="https://…/sheet.png"
+                className="flex-1 bg-[#222] rounded px-2 py-1 text-xs border-none outline-none"/>
+            </Row>
+            <div className="grid grid-cols-2 gap-2">
+              <Num el={{spriteRows:1,spriteCols:1,...el}} set={set} k="spriteRows" label="Rows" min={1}/>
+              <Num el={{spriteRows:1,spriteCols:1,...el}} set={set} k="spriteCols" label="Cols" min={1}/>
+            </div>
+            <Num el={{frameCount:(el.spriteRows??1)*(el.spriteCols??1),...el}} set={set} k="frameCount" label="Frames" min={1}/>
+          </>}
+          <Num el={{fps:12,...el}} set={set} k="fps" label="FPS" min={1} max={120}/>
+          <Row label="Loop">
+            <input type="checkbox" checked={!!el.loop} onChange={e=>set({loop:e.target.checked})}
+              className="accent-amber-500 w-3 h-3"/>
+          </Row>
         </Sec>
       )}
 
@@ -2355,14 +3330,21 @@ function PropertiesPanel({ selected, allElements, onUpdate, onDelete, onDuplicat
       {/* Animation */}
       {el.type!=='group'&&(
         <Sec title="Animation">
-          <Row label="Effect">
-            <select value={el.animationName??'none'} onChange={e=>set({animationName:e.target.value})}
+          <Row label="Enter">
+            <select value={el.enterAnimation??el.animationName??'none'} onChange={e=>set({enterAnimation:e.target.value,animationName:undefined})}
               className="bg-[#222] rounded px-2 py-1 text-xs border-none outline-none">
-              {['none','fadeIn','slideInLeft','slideInRight','bounceIn','pulse-slow'].map(a=><option key={a} value={a}>{a}</option>)}
+              {['none','fadeIn','slideInLeft','slideInRight','slideInTop','slideInBottom','zoomIn','flipInX','rotateIn','bounceIn','shake','pulse-slow'].map(a=><option key={a} value={a}>{a}</option>)}
             </select>
           </Row>
           <Num el={el} set={set} k="animationDuration" label="Duration" min={0.1} max={10} step={0.1}/>
           <Num el={el} set={set} k="animationDelay" label="Delay" min={0} max={10} step={0.1}/>
+          <Row label="Exit">
+            <select value={el.exitAnimation??'none'} onChange={e=>set({exitAnimation:e.target.value==='none'?undefined:e.target.value})}
+              className="bg-[#222] rounded px-2 py-1 text-xs border-none outline-none">
+              {['none','fadeOut','slideOutLeft','slideOutRight','slideOutTop','slideOutBottom','zoomOut','flipOutX','rotateOut','bounceOut'].map(a=><option key={a} value={a}>{a}</option>)}
+            </select>
+          </Row>
+          {el.exitAnimation&&<Num el={{exitDuration:el.animationDuration??1,...el}} set={set} k="exitDuration" label="Exit len" min={0.1} max={10} step={0.1}/>}
         </Sec>
       )}
 
@@ -1,9 +1,29 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { WorkspaceConfig } from './types';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type { WorkspaceConfig, ElementType } from './types';
+
+/**
+ * What every command rejects with. `busy` means another program held the
+ * database locked and is worth retrying; `invalid_config` carries the failed save check.
+ */
+export type CommandError =
+  | { kind: 'not_found' | 'conflict' | 'limit' | 'busy' | 'database' | 'other'; message: string }
+  | { kind: 'invalid_config'; details: string };
+
+function isCommandError(err: unknown): err is CommandError {
+  return typeof err === 'object' && err !== null && typeof (err as { kind?: unknown }).kind === 'string';
+}
+
+/** Human-readable text for an error thrown by any of the wrappers below */
+export function errorMessage(err: unknown): string {
+  if (!isCommandError(err)) return String(err);
+  return err.kind === 'invalid_config' ? err.details : err.message;
+}
 
 export interface WorkspaceSummary {
   id: string;
   name: string;
+  /** UTC, ISO-8601 with milliseconds (`2024-05-01T18:30:00.123Z`), so `new Date(updated_at)` reads it correctly */
   updated_at: string;
 }
 
@@ -17,19 +37,645 @@ export async function getWorkspace(id: string): Promise<{ config: WorkspaceConfi
   return invoke<{ config: WorkspaceConfig } | null>('get_overlay', { id });
 }
 
-/** Save (upsert) a workspace */
-export async function saveWorkspace(ws: WorkspaceConfig): Promise<void> {
-  return invoke('save_overlay', {
-    args: { id: ws.id, name: ws.name, config: ws },
+/** Id of the workspace to reopen at launch: the last one open, else the most recently updated */
+export async function getLastWorkspace(): Promise<string | null> {
+  return invoke<string | null>('get_last_overlay');
+}
+
+/** Remember the workspace open in the editor for the next launch */
+export async function setLastWorkspace(id: string): Promise<void> {
+  return invoke('set_last_overlay', { id });
+}
+
+export interface ValidationIssue {
+  /** The widget and element the issue is about, when it's about one */
+  widget_id: string | null;
+  element_id: string | null;
+  message: string;
+}
+
+export interface ValidationReport {
+  /** No errors: the config saves and its widgets render */
+  ok: boolean;
+  /** Problems a save rejects or that break a widget's page */
+  errors: ValidationIssue[];
+  /** Things that render, but probably not as intended (a missing file, an unknown preset) */
+  warnings: ValidationIssue[];
+}
+
+/**
+ * Dry-run a save without writing anything: every save-time check plus element checks
+ * (ids, numeric geometry, known types, asset files). For a preflight report before importing.
+ */
+export async function validateConfig(config: WorkspaceConfig): Promise<ValidationReport> {
+  return invoke<ValidationReport>('validate_config', { config });
+}
+
+export interface RenderReport {
+  widget_id: string;
+  /** Size of the rendered page */
+  html_bytes: number;
+  render_ms: number;
+  /** No errors: the page builds and has something to show */
+  ok: boolean;
+  errors: ValidationIssue[];
+  /** Likely causes of a blank or wrong-looking source (missing files or fonts, off-canvas elements, ...) */
+  warnings: ValidationIssue[];
+}
+
+/** Render a saved widget's OBS page in the app (no browser) and check it for common "blank on stream" causes */
+export async function testRender(id: string): Promise<RenderReport> {
+  return invoke<RenderReport>('test_render', { id });
+}
+
+export interface WidgetProfile {
+  widget_id: string;
+  /** Every element, group children included */
+  elements: number;
+  element_types: Record<string, number>;
+  /** Deepest group nesting; 0 when nothing is inside a group */
+  max_depth: number;
+  /** Elements with a blur, brightness, contrast, hue or saturation filter set or animated */
+  filtered_elements: number;
+  blurred_elements: number;
+  /** Main timeline and segment keyframes */
+  keyframes: number;
+  animated_elements: number;
+  html_bytes: number;
+  /** Time taken to build the page's HTML */
+  render_ms: number;
+}
+
+/** The numbers behind a heavy widget page: element, filter and keyframe counts, nesting depth and HTML build time */
+export async function profileWidget(id: string): Promise<WidgetProfile> {
+  return invoke<WidgetProfile>('profile_widget', { id });
+}
+
+/**
+ * Save (upsert) a workspace. Pass the `updated_at` it was loaded with to reject
+ * the write (a `conflict` error) if it changed since; omit it to force.
+ * Workspaces over the config limits are rejected with a `limit` error, and
+ * ones failing a save check with `invalid_config`.
+ * Resolves to the new `updated_at`.
+ */
+export async function saveWorkspace(ws: WorkspaceConfig, updatedAt?: string): Promise<string> {
+  return invoke<string>('save_overlay', {
+    args: { id: ws.id, name: ws.name, config: ws, updated_at: updatedAt },
   });
 }
 
+/**
+ * Save the workspace as a brand-new one named `name`; the stored original is never
+ * touched. Widgets get fresh ids (so new OBS URLs). Resolves to the new workspace id.
+ */
+export async function saveWorkspaceAs(ws: WorkspaceConfig, name: string): Promise<string> {
+  return invoke<string>('save_overlay_as', { name, config: ws });
+}
+
+/** One RFC 6902 JSON Patch operation; `path`/`from` are JSON pointers such as `/widgets/0/elements/2/x` */
+export type JsonPatchOperation =
+  | { op: 'add' | 'replace' | 'test'; path: string; value: unknown }
+  | { op: 'remove'; path: string }
+  | { op: 'move' | 'copy'; from: string; path: string };
+
+/**
+ * Apply a JSON Patch to a saved workspace, for small edits without resending the
+ * whole config. All operations apply or none do (an `invalid_config` error); the
+ * result goes through every save check. Add `test` operations to refuse edits
+ * made since it was loaded. Resolves to the new `updated_at`.
+ */
+export async function patchWorkspace(id: string, patch: JsonPatchOperation[]): Promise<string> {
+  return invoke<string>('patch_overlay', { id, patch });
+}
+
+export interface OverlayChange {
+  id: string;
+  /** `refreshed`: only elements' `srcNonce`s were bumped, keeping `updated_at`; saves carry them over */
+  kind: 'created' | 'updated' | 'deleted' | 'refreshed';
+  /** New `updated_at` of the row; null for deletions */
+  updated_at: string | null;
+}
+
+/** Subscribe to `overlay-changed`, sent after every write that actually changed a saved workspace */
+export function onOverlayChanged(handler: (change: OverlayChange) => void): Promise<UnlistenFn> {
+  return listen<OverlayChange>('overlay-changed', event => handler(event.payload));
+}
+
+/**
+ * Queue a debounced autosave; rapid edits are coalesced into one write every ~2s.
+ * With `updatedAt` the write is skipped if the workspace was modified since. The
+ * outcome arrives through `onAutosaved`; queue later edits with its `updated_at`.
+ */
+export async function queueAutosave(ws: WorkspaceConfig, updatedAt?: string): Promise<void> {
+  return invoke('queue_autosave', {
+    args: { id: ws.id, name: ws.name, config: ws, updated_at: updatedAt },
+  });
+}
+
+/** What a queued autosave came to */
+export type Autosaved =
+  | { outcome: 'saved'; id: string; updated_at: string }
+  /** Not written: the workspace was modified after the `updatedAt` it was queued with */
+  | { outcome: 'conflict'; id: string; stored_updated_at: string };
+
+/** Subscribe to `autosaved`, sent after every autosave write or refused write */
+export function onAutosaved(handler: (autosaved: Autosaved) => void): Promise<UnlistenFn> {
+  return listen<Autosaved>('autosaved', event => handler(event.payload));
+}
+
+/** True if an error from saveWorkspace is an optimistic-concurrency conflict */
+export function isSaveConflict(err: unknown): boolean {
+  return isCommandError(err) && err.kind === 'conflict';
+}
+
+/** True if another program held the database locked (antivirus, a backup tool); retrying usually works */
+export function isBusyError(err: unknown): boolean {
+  return isCommandError(err) && err.kind === 'busy';
+}
+
+/** True if a save was rejected for exceeding the config limits */
+export function isLimitError(err: unknown): boolean {
+  return isCommandError(err) && err.kind === 'limit';
+}
+
+export type DiffChange = 'added' | 'removed' | 'changed';
+
+export interface ElementDiff {
+  id: string;
+  name: string;
+  change: DiffChange;
+  /** Differing properties (`parent` = moved in/out of a group); empty unless changed */
+  properties: string[];
+}
+
+export interface WidgetDiff {
+  id: string;
+  name: string;
+  change: DiffChange;
+  properties: string[];
+  elements: ElementDiff[];
+}
+
+export interface ConfigDiff {
+  /** Workspace-level properties that differ */
+  properties: string[];
+  /** Only widgets that were added, removed or changed */
+  widgets: WidgetDiff[];
+  /** Element counts across all widgets, for "3 elements moved, 1 text changed" */
+  summary: {
+    added: number;
+    removed: number;
+    changed: number;
+    moved: number;
+    resized: number;
+    text_changed: number;
+  };
+}
+
+/** Structured diff `from` → `to`, matching widgets and elements by id */
+export async function diffConfigs(from: WorkspaceConfig, to: WorkspaceConfig): Promise<ConfigDiff> {
+  return invoke<ConfigDiff>('diff_configs', { from, to });
+}
+
+export interface ConfigLimits {
+  /** Serialized workspace size in bytes, embedded images included */
+  max_config_bytes: number;
+  /** Elements across all widgets, group children included */
+  max_elements: number;
+  /** Group nesting depth; top-level elements are depth 1 */
+  max_nesting_depth: number;
+}
+
+/** Bounds enforced when saving or autosaving a workspace */
+export async function getConfigLimits(): Promise<ConfigLimits> {
+  return invoke<ConfigLimits>('get_config_limits');
+}
+
+export async function setConfigLimits(limits: ConfigLimits): Promise<void> {
+  return invoke('set_config_limits', { limits });
+}
+
+export interface RateLimit {
+  /** Widget requests allowed per client IP per minute; 0 turns the limit off */
+  requests_per_minute: number;
+}
+
+/** Per-IP limit on widget page loads and reload polls; changes apply immediately */
+export async function getRateLimit(): Promise<RateLimit> {
+  return invoke<RateLimit>('get_rate_limit');
+}
+
+export async function setRateLimit(limit: RateLimit): Promise<void> {
+  return invoke('set_rate_limit', { limit });
+}
+
+export interface PlaybackDefaults {
+  /** Used by timelines that don't set `autoplay`; null leaves them trigger-driven */
+  autoplay: boolean | null;
+  /** Used by timelines that don't set `loop`; null leaves them playing once */
+  loop: boolean | null;
+}
+
+/** Server-wide autoplay/loop for timelines that omit them; a timeline's own values always win */
+export async function getPlaybackDefaults(): Promise<PlaybackDefaults> {
+  return invoke<PlaybackDefaults>('get_playback_defaults');
+}
+
+export async function setPlaybackDefaults(defaults: PlaybackDefaults): Promise<void> {
+  return invoke('set_playback_defaults', { defaults });
+}
+
 /** Delete a workspace */
 export async function deleteWorkspace(id: string): Promise<void> {
   return invoke('delete_overlay', { id });
 }
 
+export interface CorruptRow {
+  id: string;
+  name: string;
+  error: string;
+}
+
+export interface IntegrityReport {
+  ok: boolean;
+  /** SQLite integrity_check output; ["ok"] when the file is healthy */
+  integrity: string[];
+  overlays_checked: number;
+  corrupt_rows: CorruptRow[];
+}
+
+/** Verify the database file and that every saved workspace's config parses */
+export async function checkDatabase(): Promise<IntegrityReport> {
+  return invoke<IntegrityReport>('check_database');
+}
+
+/** Move workspaces whose config can't be parsed into a quarantine table so the rest load */
+export async function repairDatabase(): Promise<{ quarantined: CorruptRow[] }> {
+  return invoke<{ quarantined: CorruptRow[] }>('repair_database');
+}
+
+export interface DuplicateWidget {
+  widget_id: string;
+  /** Workspaces containing the widget id, most recently updated (the one OBS is served) first */
+  overlays: WorkspaceSummary[];
+}
+
+/** Widget ids that appear in more than one workspace, e.g. after importing the same file twice */
+export async function checkDuplicateWidgetIds(): Promise<DuplicateWidget[]> {
+  return invoke<DuplicateWidget[]>('check_duplicate_widget_ids');
+}
+
+export interface AssetUsage {
+  overlay_id: string;
+  overlay_name: string;
+  widget_id: string;
+  element_id: string;
+  /**
+   * Property holding the reference: 'src', 'poster', 'maskImageSrc', 'frames', 'fontFamily' or 'segments';
+   * 'background' for a widget's background image, 'audioTrack' for the workspace's audio track (no widget or element)
+   */
+  field: string;
+}
+
+/**
+ * Elements in any workspace that reference an asset (URL, file path or font family),
+ * so deleting it can be blocked or warned about. Style presets count for each element using them,
+ * and so does a workspace audio track playing it.
+ */
+export async function findAssetUsages(asset: string): Promise<AssetUsage[]> {
+  return invoke<AssetUsage[]>('find_asset_usages', { asset });
+}
+
+/** Elements an element search matched in one workspace */
+export interface OverlayElementHits {
+  overlay_id: string;
+  overlay_name: string;
+  elements: { widget_id: string; element_id: string; element_name: string }[];
+}
+
+/** Elements of one type ('path', 'sprite', …) in every workspace, grouped by workspace */
+export async function findElementsByType(kind: ElementType): Promise<OverlayElementHits[]> {
+  return invoke<OverlayElementHits[]>('find_elements_by_type', { kind });
+}
+
+/** Elements whose font stack (or a rich text run) names a family, grouped by workspace */
+export async function findElementsUsingFont(family: string): Promise<OverlayElementHits[]> {
+  return invoke<OverlayElementHits[]>('find_elements_using_font', { family });
+}
+
+export interface NormalizeReport {
+  /** Elements given a new id (all of them) */
+  elements: number;
+  /** Widgets whose id was missing or shared; their OBS URLs change */
+  widgets: { name: string; old_id: string; new_id: string }[];
+  /** Keyframe entries moved to their element's new id */
+  remapped_states: number;
+  /** Keyframe entries for ids no element has, left untouched */
+  orphaned_states: string[];
+  updated_at: string;
+}
+
+/** Re-id a saved workspace's elements (keeping keyframes and mask links) and re-save it */
+export async function normalizeOverlay(id: string): Promise<NormalizeReport> {
+  return invoke<NormalizeReport>('normalize_overlay', { id });
+}
+
+export interface TimelineReport {
+  /** Widgets with leftover keyframe states or elements no keyframe mentions */
+  widgets: {
+    widget_id: string;
+    widget_name: string;
+    /** Keyframe states for ids no element has; `segment` is null on the main timeline */
+    orphaned_states: { segment: string | null; keyframe_id: string; element_id: string }[];
+    unanimated_elements: string[];
+  }[];
+  /** States removed by a fix; 0 for a check */
+  pruned: number;
+  /** Set when a fix re-saved the workspace */
+  updated_at: string | null;
+}
+
+/** Keyframe states in a saved workspace that point at missing elements, and un-keyframed elements */
+export async function checkTimelineConsistency(id: string): Promise<TimelineReport> {
+  return invoke<TimelineReport>('check_timeline_consistency', { id });
+}
+
+/** Remove keyframe states for missing elements and re-save the workspace */
+export async function fixTimelineConsistency(id: string): Promise<TimelineReport> {
+  return invoke<TimelineReport>('fix_timeline_consistency', { id });
+}
+
+/** VACUUM the database (after flushing pending autosaves) to reclaim space */
+export async function compactDatabase(): Promise<{ before_bytes: number; after_bytes: number }> {
+  return invoke<{ before_bytes: number; after_bytes: number }>('compact_database');
+}
+
+export interface ProfileInfo {
+  name: string;
+  active: boolean;
+  /** Path of the profile's overlays.db */
+  path: string;
+}
+
+/** Overlay libraries, each with its own database; 'default' is always first */
+export async function listProfiles(): Promise<ProfileInfo[]> {
+  return invoke<ProfileInfo[]>('list_profiles');
+}
+
+/** Create an empty profile (letters, digits, '-' and '_'); an existing name is a `conflict` error */
+export async function createProfile(name: string): Promise<ProfileInfo> {
+  return invoke<ProfileInfo>('create_profile', { name });
+}
+
+/** Switch libraries; pending autosaves land in the old one. Reload the workspace list afterwards. */
+export async function switchProfile(name: string): Promise<ProfileInfo> {
+  return invoke<ProfileInfo>('switch_profile', { name });
+}
+
+/** Token bots send as `Authorization: Bearer <token>` to `POST /inbox`; created on first use */
+export async function getInboxToken(): Promise<string> {
+  return invoke<string>('get_inbox_token');
+}
+
+/** Issue a new inbox token; bots using the old one are rejected from then on */
+export async function regenerateInboxToken(): Promise<string> {
+  return invoke<string>('regenerate_inbox_token');
+}
+
+export interface PrewarmReport {
+  rendered: string[];
+  /** Already cached and unchanged */
+  cached: string[];
+  missing: string[];
+}
+
+/** Render widgets into the OBS server's cache so a scene switch doesn't render them all at once */
+export async function prewarmWidgets(ids: string[]): Promise<PrewarmReport> {
+  return invoke<PrewarmReport>('prewarm_widgets', { ids });
+}
+
 /** Get OBS browser source URL for a specific widget */
 export async function getWidgetObsUrl(widgetId: string): Promise<string> {
   return invoke<string>('get_obs_url', { id: widgetId });
 }
+
+/** Copy a widget's OBS URL (LAN URL in LAN mode) to the clipboard; resolves to the copied URL */
+export async function copyWidgetObsUrl(widgetId: string): Promise<string> {
+  return invoke<string>('copy_obs_url', { id: widgetId });
+}
+
+export interface WidgetError {
+  message: string;
+  source: string | null;
+  line: number | null;
+  column: number | null;
+  stack: string | null;
+  /** Unix time in ms the app received it */
+  at: number;
+}
+
+/** Uncaught script errors OBS pages reported for a widget, and assets its page was served without (the last 20, oldest first) */
+export async function getWidgetErrors(widgetId: string): Promise<WidgetError[]> {
+  return invoke<WidgetError[]>('get_widget_errors', { widgetId });
+}
+
+export async function clearWidgetErrors(widgetId: string): Promise<void> {
+  return invoke('clear_widget_errors', { widgetId });
+}
+
+/** State of the local OBS HTTP server */
+export interface ServerStatus {
+  /** `phase` is 'running' */
+  running: boolean;
+  /** 'starting' and 'stopping' while it restarts */
+  phase: 'stopped' | 'starting' | 'running' | 'stopping';
+  /** Address actually bound, e.g. "127.0.0.1:7878" */
+  bound_addr: string | null;
+  port: number;
+  lan_enabled: boolean;
+  /** Unix time in ms the running server started listening */
+  started_at: number | null;
+  /** Why the last start failed (e.g. the port is already in use) */
+  last_error: string | null;
+  /** Launched with `--safe-mode` (or `OPEN_OVERLAY_SAFE_MODE=1`) and not started since */
+  safe_mode: boolean;
+}
+
+/** Whether the OBS server is running and where; rely on this rather than assuming :7878 */
+export async function getServerStatus(): Promise<ServerStatus> {
+  return invoke<ServerStatus>('get_server_status');
+}
+
+/** Start the OBS server (restarting it if it's up), e.g. after a safe-mode launch; watch `onServerStatus` for the outcome */
+export async function startObsServer(): Promise<void> {
+  return invoke('start_obs_server');
+}
+
+/** Subscribe to `server-status`, sent whenever the server starts, stops or fails to bind */
+export function onServerStatus(handler: (status: ServerStatus) => void): Promise<UnlistenFn> {
+  return listen<ServerStatus>('server-status', event => handler(event.payload));
+}
+
+/** Whether LAN mode (serve widgets to other machines) is enabled */
+export async function getLanMode(): Promise<boolean> {
+  return invoke<boolean>('get_lan_mode');
+}
+
+/** Enable/disable LAN mode; the OBS server restarts on the new bind address */
+export async function setLanMode(enabled: boolean): Promise<void> {
+  return invoke('set_lan_mode', { enabled });
+}
+
+/** Global hotkeys, keyed by widget id (values are accelerators like "CmdOrCtrl+Shift+F1") */
+export async function getHotkeys(): Promise<Record<string, string>> {
+  return invoke<Record<string, string>>('get_hotkeys');
+}
+
+/**
+ * Bind a global hotkey that triggers a widget's animation; pass null to clear it.
+ * Rejects with a `conflict` error if the accelerator is already taken (see isSaveConflict).
+ */
+export async function setHotkey(widgetId: string, accelerator: string | null): Promise<void> {
+  return invoke('set_hotkey', { widgetId, accelerator });
+}
+
+export interface FrameExportReport {
+  frames: number;
+  fps: number;
+  out_dir: string;
+}
+
+/** Export a widget's timeline as a transparent PNG sequence (one loop for looping timelines) */
+export async function exportFrames(widgetId: string, fps: number, outDir: string): Promise<FrameExportReport> {
+  return invoke<FrameExportReport>('export_frames', { id: widgetId, fps, outDir });
+}
+
+export interface StandaloneExportReport {
+  path: string;
+  bytes: number;
+  /** Local files embedded as data URIs */
+  inlined: number;
+  /** http(s) assets left as links; the page needs network access for these */
+  remote: string[];
+  /** Font files embedded for the families the widget uses */
+  fonts: number;
+  /** Families no font file was found for; install them wherever the page is shown */
+  missing_fonts: string[];
+  /** Relative paths and links to this PC's own server, left as-is; they won't load elsewhere */
+  unresolved: string[];
+}
+
+/** Write a widget as one self-contained .html file (assets and fonts inlined, no live updates) */
+export async function exportStandaloneHtml(widgetId: string, path: string): Promise<StandaloneExportReport> {
+  return invoke<StandaloneExportReport>('export_standalone_html', { id: widgetId, path });
+}
+
+export interface LibraryExportReport {
+  path: string;
+  bytes: number;
+  overlays: number;
+  /** Local files embedded */
+  assets: number;
+  /** Installed font files embedded */
+  fonts: number;
+  /** Workspaces with an unreadable config, left out (see repairDatabase) */
+  skipped_overlays: string[];
+  /** Local files that couldn't be read; their references are kept as-is */
+  missing_assets: string[];
+  /** Font families with no installed file found */
+  missing_fonts: string[];
+}
+
+export interface RemappedId {
+  name: string;
+  /** Empty when it had no id */
+  old_id: string;
+  new_id: string;
+}
+
+export interface LibraryImportReport {
+  overlays: number;
+  remapped_overlays: RemappedId[];
+  /** These widgets have new OBS URLs */
+  remapped_widgets: RemappedId[];
+  dropped_slugs: string[];
+  /** Files written beside the database because their original path doesn't exist here */
+  restored_assets: number;
+  /** Folder holding fonts this PC doesn't have; install them from there */
+  fonts_dir: string | null;
+  fonts_to_install: string[];
+  /** Copy of the database taken before a replace */
+  backup: string | null;
+}
+
+/** Bundle every workspace, the settings, local assets and fonts into one archive file */
+export async function exportLibrary(path: string): Promise<LibraryExportReport> {
+  return invoke<LibraryExportReport>('export_library', { path });
+}
+
+/**
+ * Restore an exportLibrary archive. `merge` adds it next to the current library (colliding
+ * ids get new ones, current settings win); `replace` backs up the database and swaps it all.
+ */
+export async function importLibrary(path: string, mode: 'merge' | 'replace'): Promise<LibraryImportReport> {
+  return invoke<LibraryImportReport>('import_library', { path, mode });
+}
+
+/** Result of gcAssets */
+export interface AssetGcReport {
+  dry_run: boolean;
+  /** Restored files no workspace in any profile uses; deleted unless this was a dry run */
+  unreferenced: string[];
+  /** Their total size in bytes: freed, or that would be */
+  bytes: number;
+  /** Files that couldn't be deleted, with why */
+  failed: string[];
+}
+
+/** Delete files library imports restored that nothing uses any more; `dryRun` only lists them */
+export async function gcAssets(dryRun: boolean): Promise<AssetGcReport> {
+  return invoke<AssetGcReport>('gc_assets', { dryRun });
+}
+
+/** Result of importHtml */
+export interface HtmlImportReport {
+  overlay_id: string;
+  name: string;
+  /** Elements created in the new overlay's one widget */
+  elements: number;
+  /** What wasn't carried over, or only partly, one line each; finish these in the editor */
+  unconverted: string[];
+}
+
+/**
+ * Create a workspace from a hand-written overlay HTML file. Absolutely positioned text,
+ * images, videos and boxes become elements of one widget; scripts, CSS animations and
+ * anything in the page flow are listed in `unconverted` instead.
+ */
+export async function importHtml(path: string): Promise<HtmlImportReport> {
+  return invoke<HtmlImportReport>('import_html', { path });
+}
+
+/** Result of refreshElementSrc */
+export interface SrcRefresh {
+  overlay_id: string;
+  nonce: number;
+  /** The src open pages were sent, `?v=` included */
+  src: string;
+  /** Open OBS pages of the widget that got it */
+  delivered: number;
+}
+
+/**
+ * Make a widget's open OBS pages fetch an image or video element's http(s) `src` again
+ * (e.g. album art that changed at the same URL) without reloading. Bumps and saves the
+ * element's `srcNonce`, so later loads get the fresh version too.
+ */
+export async function refreshElementSrc(widgetId: string, elementId: string): Promise<SrcRefresh> {
+  return invoke<SrcRefresh>('refresh_element_src', { widgetId, elementId });
+}
+
+/** Path of the current log file, for attaching to bug reports */
+export async function getLogPath(): Promise<string> {
+  return invoke<string>('get_log_path');
+}
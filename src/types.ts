@@ -1,15 +1,21 @@
 // ---------------------------------------------------------------------------
 // Element types
 // ---------------------------------------------------------------------------
-export type ElementType = 'shape' | 'text' | 'image' | 'path' | 'group' | 'mask';
+export type ElementType = 'shape' | 'text' | 'image' | 'video' | 'path' | 'group' | 'mask' | 'particles' | 'sprite';
 export type ShapeType = 'rectangle' | 'circle' | 'triangle' | 'star' | 'hexagon' | 'octagon';
 export type MaskType = 'none' | 'clip' | 'gradient' | 'opacity' | 'image';
 export type GradientDir = 'to right' | 'to left' | 'to bottom' | 'to top' | 'to bottom right' | 'radial';
-export type BlendMode =
-  | 'normal' | 'multiply' | 'screen' | 'overlay'
-  | 'darken' | 'lighten' | 'color-dodge' | 'color-burn'
-  | 'hard-light' | 'soft-light' | 'difference' | 'exclusion'
-  | 'hue' | 'saturation' | 'color' | 'luminosity';
+/**
+ * Blend modes supported by the OBS renderer (the full CSS mix-blend-mode set).
+ * Must match `BLEND_MODES` in `src-tauri/src/obs_server.rs`; unknown values render as 'normal'.
+ */
+export const BLEND_MODES = [
+  'normal', 'multiply', 'screen', 'overlay',
+  'darken', 'lighten', 'color-dodge', 'color-burn',
+  'hard-light', 'soft-light', 'difference', 'exclusion',
+  'hue', 'saturation', 'color', 'luminosity',
+] as const;
+export type BlendMode = typeof BLEND_MODES[number];
 
 // ---------------------------------------------------------------------------
 // Keyframe Animation System
@@ -20,7 +26,16 @@ export type KeyframeProperty =
   | 'fill' | 'strokeColor' | 'strokeWidth' | 'borderRadius'
   | 'fontSize' | 'letterSpacing' | 'lineHeight'
   | 'blur' | 'brightness' | 'contrast' | 'hueRotate' | 'saturate'
-  | 'color' | 'scaleX' | 'scaleY';
+  | 'color' | 'scaleX' | 'scaleY'
+  | 'clipSize' | 'clipX' | 'clipY'
+  | 'gradientAngle' | 'gradientStart' | 'gradientEnd' | 'gradientStartOpacity' | 'gradientEndOpacity'
+  | 'tint' | 'tintStrength'
+  /** SVG path string; morphs when both keyframes share the same command structure */
+  | 'pathData'
+  /** Text content; counts between the numbers in it when `numericContent` is set */
+  | 'content';
+
+export type ClipShape = 'inset' | 'circle' | 'ellipse' | 'polygon';
 
 /** Easing function names */
 export type EasingType = 'linear' | 'ease-in' | 'ease-out' | 'ease-in-out'
@@ -55,6 +70,62 @@ export interface AnimationTimeline {
   speed: number;
   /** Global keyframes — each one captures ALL elements */
   keyframes: GlobalKeyframe[];
+  /** Seed for particle/jitter randomness; the same seed replays identically every time */
+  randomSeed?: number | string;
+  /** Cap timeline updates at this many frames per second in OBS (default uncapped) */
+  maxFps?: number;
+  /** Seconds between `time` events sent to /widget/{id}/events listeners (default 0.25) */
+  timeEventInterval?: number;
+  /** Hide the widget this many seconds after a pass ends (OBS only). Without autoplay it also
+   *  starts hidden, so triggered alerts pop in, hold and disappear. */
+  hideAfter?: number;
+  /** Phase a looping autoplay timeline to the server clock, so sources sharing a loop stay in step */
+  syncClock?: boolean;
+  /** Named sub-timelines (intro, idle loop, outro) played over the control channel, each on
+   *  its own clock and layered over the main timeline in list order */
+  segments?: TimelineSegment[];
+}
+
+/** A named sub-timeline; its keyframes only pose the elements they mention */
+export interface TimelineSegment {
+  name: string;
+  /** Length in seconds */
+  duration: number;
+  loop?: boolean;
+  /** Playback speed multiplier (default 1) */
+  speed?: number;
+  /** Start when the page loads */
+  autoplay?: boolean;
+  /** Segment to start when this one ends (one-shot segments only) */
+  then?: string;
+  keyframes: GlobalKeyframe[];
+}
+
+/** One styled run of a rich text element; unset fields inherit from the element.
+ *  A run with `src` is an inline image (emote/icon) and `text` is its fallback. */
+export interface TextSegment {
+  text: string;
+  /** Inline image URL; drawn one line tall and vertically centred on the text */
+  src?: string;
+  /** Image height in em (default: the element's line height, or 1.2) */
+  height?: number;
+  color?: string;
+  fontWeight?: string;
+  italic?: boolean;
+  underline?: boolean;
+  fontSize?: number;
+  fontFamily?: string;
+}
+
+/** Daily time window, e.g. { start: '18:00', end: '22:00' }. A window whose end
+ *  is before its start wraps past midnight; equal start/end means all day. */
+export interface VisibleWindow {
+  /** 'HH:MM', 24-hour */
+  start: string;
+  /** 'HH:MM', 24-hour, exclusive */
+  end: string;
+  /** IANA time zone the times are in (e.g. 'America/New_York'); defaults to local time */
+  timeZone?: string;
 }
 
 export interface OverlayElement {
@@ -71,21 +142,51 @@ export interface OverlayElement {
   locked: boolean;
   opacity: number;
   rotation: number;
+  /** Pivot for rotation, as CSS `transform-origin`: keywords (`left top`) or `x% y%` / px; center when unset */
+  transformOrigin?: string;
+  /** Clip the element (any type) to a shape, as CSS `clip-path` */
+  clipShape?: ClipShape;
+  /** inset: % cut off each edge (default 10); circle/ellipse: radius in % (default 50). Animatable */
+  clipSize?: number;
+  /** Circle/ellipse centre in % of the element (default 50). Animatable */
+  clipX?: number;
+  clipY?: number;
+  /** Polygon points, e.g. "0% 0%, 100% 0%, 50% 100%" (lengths in %, px or 0) */
+  clipPoints?: string;
   blendMode?: BlendMode;
+  /** Only show during this daily time window (in OBS); still hidden when `visible` is false */
+  visibleWindow?: VisibleWindow;
+  /** Name of a workspace style preset supplying any property this element doesn't set */
+  stylePreset?: string;
+  /** Keep width:height (and scaleX:scaleY) at the element's own ratio: a keyframe that
+   *  sets only one of them brings the other along, and editor resizes keep the shape. */
+  lockAspect?: boolean;
 
   // ── Group / mask container ──────────────────────────────────────────────
   /** If type === 'group', children are rendered inside this container.
    *  Children x/y are relative to the group's top-left corner. */
   children?: OverlayElement[];
+  /** Groups only: keyframed width/height scale the children along with the box
+   *  (against the group's stored size) instead of just resizing the box. */
+  fitChildren?: boolean;
   /** Controls CSS masking applied to the whole group */
   maskType?: MaskType;
   gradientDir?: GradientDir;
+  /** Linear gradient angle in degrees (0 = to top); overrides `gradientDir` when set */
+  gradientAngle?: number;
+  /** Stop positions in % (default 0 and 100) */
+  gradientStart?: number;
+  gradientEnd?: number;
   gradientStartOpacity?: number; // 0-1
   gradientEndOpacity?: number;   // 0-1
   /** For 'clip' mask: border-radius in px (0 = rect, 9999 = circle) */
   clipRadius?: number;
   /** For 'image' mask */
   maskImageSrc?: string;
+  /** CSS mask-size for 'image' mask (default 'cover') */
+  maskSize?: string;
+  /** CSS mask-position for 'image' mask (default 'center') */
+  maskPosition?: string;
   
   // ── Explicit Layer Mask ─────────────────────────────────────────────────
   /** If set, this element is masked by the vector shape/path of the referenced element ID. */
@@ -102,21 +203,100 @@ export interface OverlayElement {
 
   // ── Path ────────────────────────────────────────────────────────────────
   pathData?: string;
+  /** Reveal the stroke as if drawn, following the timeline (OBS only) */
+  drawOn?: boolean;
+  /** Timeline time in seconds the draw starts (default 0) */
+  drawStart?: number;
+  /** Seconds the draw takes (default: until the end of the timeline) */
+  drawDuration?: number;
 
   // ── Text ────────────────────────────────────────────────────────────────
+  /** `{name}` tokens are filled from a trigger's `values` on the OBS page;
+   *  `{name|fallback}` shows the fallback until then, other tokens show blank. */
   content?: string;
   fontSize?: number;
   color?: string;
+  /** Render the text black or white, whichever contrasts more with `backdropColor`, or
+   *  else the widget's background. Transparent, translucent or image backgrounds keep `color`. */
+  autoContrast?: boolean;
+  /** The colour behind this text (e.g. a panel shape), for `autoContrast` */
+  backdropColor?: string;
   fontFamily?: string;
+  /** Alignment relative to `direction`: for RTL text 'left' is the start (right) edge */
   textAlign?: 'left' | 'center' | 'right';
+  /** Reading direction; 'auto' picks it from the content (default 'ltr') */
+  direction?: 'ltr' | 'rtl' | 'auto';
+  /** CSS writing mode, e.g. 'vertical-rl' for vertical Japanese (default 'horizontal-tb') */
+  writingMode?: 'horizontal-tb' | 'vertical-rl' | 'vertical-lr';
   fontWeight?: string;
   textShadow?: string;
   lineHeight?: number;
   letterSpacing?: number;
+  /** Treat the number in `content` as a counter that counts between keyframes ("$0" → "$1,250") */
+  numericContent?: boolean;
+  /** Decimal places for counters (default: as many as the content has) */
+  decimals?: number;
+  /** Group thousands in counters per `locale` (1,234 / 1.234) */
+  thousandsSeparator?: boolean;
+  /** Styled runs rendered in place of `content` (e.g. only a name in bold) */
+  segments?: TextSegment[];
+  /** Live clock/date text that replaces `content` and updates every second */
+  dynamic?: 'time' | 'date' | 'datetime';
+  /** BCP-47 locale for dynamic text (e.g. 'fr-FR'); defaults to the system locale */
+  locale?: string;
+  /** Force 12/24-hour clock for dynamic text; defaults to the locale's convention */
+  hour12?: boolean;
+  /** IANA time zone for dynamic text (e.g. 'Europe/Paris'); defaults to local time */
+  timeZone?: string;
+  /** Extra Intl.DateTimeFormat options merged over the defaults for `dynamic` */
+  dateTimeOptions?: Intl.DateTimeFormatOptions;
 
-  // ── Image ───────────────────────────────────────────────────────────────
+  // ── Image / Video ───────────────────────────────────────────────────────
   src?: string;
   objectFit?: 'contain' | 'cover' | 'fill';
+  /** Focal point kept in view when `objectFit` crops, as CSS object-position (e.g. '50% 20%') */
+  objectPosition?: string;
+  /** Colour laid over the image or video, blended with the media only (e.g. to recolour a grayscale logo) */
+  tint?: string;
+  /** How the tint blends with the media (default 'color': the tint's hue with the media's lightness) */
+  tintBlend?: BlendMode;
+  /** Tint strength, 0–100 % (default 100) */
+  tintStrength?: number;
+  /** Bumped by refreshElementSrc: sent as `?v=` on an http(s) `src` so OBS fetches it again past its cache */
+  srcNonce?: number;
+  /** Video: image shown until the first frame plays; without one the video starts transparent */
+  poster?: string;
+  /** Video / sprite: loop playback */
+  loop?: boolean;
+  /** Video: play without sound */
+  muted?: boolean;
+
+  // ── Sprite (frame list, or a sheet in `src`) ────────────────────────────
+  /** Frame image URLs in order; when set, `src` is ignored */
+  frames?: string[];
+  /** Sheet grid, read left to right then top to bottom (default 1×1) */
+  spriteRows?: number;
+  spriteCols?: number;
+  /** Frames used from the sheet, for a partly filled last row (default rows × cols) */
+  frameCount?: number;
+  /** Frames per second (default 12) */
+  fps?: number;
+
+  // ── Particles ───────────────────────────────────────────────────────────
+  /** Number of particles per burst (default 80) */
+  count?: number;
+  /** Particle colors, cycled (default a confetti palette) */
+  colors?: string[];
+  /** Emission cone in degrees, centred straight up (default 90) */
+  spread?: number;
+  /** Downward acceleration in px/s² (default 900) */
+  gravity?: number;
+  /** Initial particle speed in px/s (default 600) */
+  velocity?: number;
+  /** Seconds a particle lives at most (default 3) */
+  lifetime?: number;
+  /** When to burst: 'start' (timeline start), 'trigger' (HTTP trigger) or a keyframe id */
+  emitOn?: string;
 
   // ── Filters ─────────────────────────────────────────────────────────────
   blur?: number;
@@ -130,10 +310,23 @@ export interface OverlayElement {
   scaleY?: number;
 
   // ── Animation (preset effects) ─────────────────────────────────────────
+  /** Older name for `enterAnimation`, still read when that's unset */
   animationName?: string;
+  /**
+   * Preset played when the page loads and on every trigger: fadeIn, slideInLeft/Right/Top/Bottom,
+   * zoomIn, flipInX, rotateIn, bounceIn, shake or pulse-slow
+   */
+  enterAnimation?: string;
   animationDuration?: number;
   animationDelay?: number;
   animationIterationCount?: string;
+  /**
+   * Preset played before the widget hides (hideAfter or a hide request): fadeOut,
+   * slideOutLeft/Right/Top/Bottom, zoomOut, flipOutX, rotateOut or bounceOut
+   */
+  exitAnimation?: string;
+  /** Exit length in seconds (default: animationDuration) */
+  exitDuration?: number;
 
   // (Keyframe data is stored globally on Widget.animationTimeline.keyframes)
 }
@@ -161,26 +354,94 @@ export const WIDGET_COLORS: Record<WidgetType, string> = {
   custom:      '#6b7280',
 };
 
+/** Reaction to a `POST /inbox` event from a chat bot */
+export interface InboxRule {
+  /** Event type to react to, matched case-insensitively (e.g. '!hydrate', 'raid') */
+  event: string;
+  /** 'trigger' plays the widget (default); 'clear_queue' drops queued alerts; 'hide' hides it */
+  action?: 'trigger' | 'clear_queue' | 'hide';
+}
+
+export interface WidgetBackground {
+  /** Same colour formats as a plain background; defaults to 'transparent' */
+  color?: string;
+  /** Image URL, absolute file path or data URI; PNG transparency shows the colour (or OBS) through */
+  src?: string;
+  /** Defaults to 'cover'; 'stretch' fills the widget ignoring the aspect ratio, 'auto' keeps the image's own size */
+  size?: 'cover' | 'contain' | 'stretch' | 'auto';
+  /** 'center' (default), 'top', 'bottom', 'left', 'right' or a corner like 'top left' */
+  position?: string;
+}
+
 export interface Widget {
   id: string;
   name: string;
   widgetType: WidgetType;
   width: number;
   height: number;
-  background: string;
+  /** 'transparent', #RGB[A] / #RRGGBB[AA], rgb()/rgba() or a basic colour name (anything else is rejected on save),
+   * or a colour plus an image drawn over it and under every element */
+  background: string | WidgetBackground;
   /** x/y on the artboard — only used for designer layout, not OBS */
   artboardX: number;
   artboardY: number;
   elements: OverlayElement[];
   /** Keyframe animation timeline settings */
   animationTimeline?: AnimationTimeline;
+  /** Readable URL name served at /w/{slug} (e.g. 'follower-alert'); unique across all workspaces */
+  slug?: string;
+  /** Inbox events this widget reacts to */
+  inbox?: InboxRule[];
+  /** Seconds between OBS pages' checks for edits (default 2, clamped to 1–300); raise it for overlays that rarely change */
+  pollInterval?: number;
+  /** Device pixels per design pixel OBS pages render at (default 1, up to 4); the browser source is this much larger */
+  pixelRatio?: number;
+  /** Randomness seed used when the timeline doesn't set its own `randomSeed` */
+  randomSeed?: number | string;
 }
 
 // ---------------------------------------------------------------------------
 // Workspace — the top-level save unit containing all widgets
 // ---------------------------------------------------------------------------
+/** Title-safe margins in canvas pixels */
+export interface SafeArea {
+  top: number;
+  right: number;
+  bottom: number;
+  left: number;
+}
+
+/** Canvas size used when a workspace doesn't set one */
+export const DEFAULT_CANVAS = { width: 1920, height: 1080 };
+
+/** Named property bundle elements share through `stylePreset` */
+export type StylePreset = Partial<Omit<OverlayElement, 'id' | 'type' | 'children' | 'stylePreset'>>;
+
+/** Properties "New preset" captures from an element */
+export const PRESET_STYLE_KEYS: (keyof StylePreset)[] = [
+  'fontFamily', 'fontSize', 'fontWeight', 'color', 'textShadow', 'letterSpacing', 'lineHeight',
+  'fill', 'fillOpacity', 'strokeColor', 'strokeWidth', 'borderRadius',
+];
+
 export interface WorkspaceConfig {
   id: string;
   name: string;
   widgets: Widget[];
+  /** Output resolution the workspace is designed against (default 1920×1080, max 7680×4320) */
+  canvasWidth?: number;
+  canvasHeight?: number;
+  /** Margins important content should stay inside; they must leave part of the canvas free */
+  safeArea?: SafeArea;
+  /** Style presets by name (1-64 characters), e.g. a brand title font and colour */
+  stylePresets?: Record<string, StylePreset>;
+  /**
+   * Stack every element by its zIndex across group boundaries on the OBS page, instead of
+   * each group stacking as one layer. Groups no longer isolate their children's blend modes,
+   * and rotated, scaled, filtered, faded, masked or blended groups still stack as one layer.
+   */
+  flatZIndex?: boolean;
+  /** Music looped by the workspace's audio page (`/workspace/{id}/audio`): an http(s) or data: URL */
+  audioTrack?: string;
+  /** 0-1, default 1 */
+  audioVolume?: number;
 }
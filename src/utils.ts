@@ -1,6 +1,16 @@
 import { clsx, type ClassValue } from 'clsx';
 import { twMerge } from 'tailwind-merge';
+import type { OverlayElement, StylePreset } from './types';
 
 export function cn(...inputs: ClassValue[]) {
   return twMerge(clsx(inputs));
 }
+
+/** The element as rendered: its style preset's properties with the element's own set on top */
+export function resolveStylePreset(el: OverlayElement, presets?: Record<string, StylePreset>): OverlayElement {
+  const preset = el.stylePreset ? presets?.[el.stylePreset] : undefined;
+  if (!preset) return el;
+  const merged: any = { ...preset };
+  for (const [k, v] of Object.entries(el)) if (v !== undefined && v !== null) merged[k] = v;
+  return merged;
+}